@@ -0,0 +1,90 @@
+// Generated from the types in `src/fill_schema.rs`; see `typescript_declarations`.
+
+export type FillRequest = { 
+/**
+ * The schema version the request was written for; defaults to the current one.
+ */
+version?: number, 
+/**
+ * A template string with . representing empty cells, # representing blocks, and letters
+ * representing themselves. Letters of either case are locked in unless
+ * `options.soft_uppercase` is set.
+ */
+grid: string, constraints?: FillConstraints, options?: FillOptions, };
+
+export type FillConstraints = { 
+/**
+ * The minimum score for words in the fill [default: `DEFAULT_MIN_SCORE`].
+ */
+min_score?: number, 
+/**
+ * Words that can't appear in the fill.
+ */
+forbidden_words?: Array<string>, 
+/**
+ * Words that must appear somewhere in the fill.
+ */
+required_words?: Array<string>, };
+
+export type FillOptions = { 
+/**
+ * How long to search before giving up, in milliseconds [default: no limit].
+ */
+timeout_ms?: number, 
+/**
+ * Treat uppercase letters in the grid as suggestions, which the fill can override, rather
+ * than locking them in [default: false].
+ */
+soft_uppercase?: boolean, };
+
+export type FillStatus = "filled" | "unfillable" | "timeout" | "aborted" | "exceeded_backtrack_limit" | "invalid_input" | "solver_error";
+
+export type Diagnostics = { states: number, backtracks: number, retries: number, time_ms: number, 
+/**
+ * After a failed search, the crossings that caused the most trouble, most troublesome first;
+ * see `crossing_report.rs`.
+ */
+troublesome_crossings: Array<string>, 
+/**
+ * After a failed search, the slot it backtracked out of most often and how many options that
+ * slot had left, e.g. "17A: 212 backtracks, 3 options left"; see `StuckSlot`.
+ */
+stuck_slot: string | null, };
+
+export type FillResponse = { version: number, status: FillStatus, 
+/**
+ * The filled grid, with # representing blocks, if the fill succeeded.
+ */
+grid: string | null, 
+/**
+ * The entries in the fill, in slot order, if the fill succeeded.
+ */
+entries: Array<FilledEntry>, 
+/**
+ * A description of the problem, if the fill failed.
+ */
+error: string | null, 
+/**
+ * `FillSuccess::word_list_hash` and `FillSuccess::config_fingerprint`, as 16-digit hex
+ * strings (since JSON numbers can't reliably hold 64-bit integers), if the fill succeeded.
+ */
+word_list_hash: string | null, config_fingerprint: string | null, 
+/**
+ * Present whenever a search was attempted.
+ */
+diagnostics: Diagnostics | null, };
+
+export type FilledEntry = { slot_id: number, 
+/**
+ * The slot's key; see `SlotSpec::to_key`.
+ */
+slot_spec: string, 
+/**
+ * The word as it appears in the grid (e.g., "halfdome").
+ */
+normalized: string, 
+/**
+ * The word as it appears in its word list, with capitalization, spaces, and punctuation intact
+ * (e.g., "Half Dome"). For words that aren't in any list, this is the same as `normalized`.
+ */
+display: string, };
@@ -12,10 +12,13 @@
 //! We keep applying these rules until no more eliminations are possible.
 
 use float_ord::FloatOrd;
+use smallvec::{smallvec, SmallVec};
 use std::cmp::Reverse;
 use std::collections::HashMap;
 use std::fmt::Debug;
+use std::ops::{Deref, DerefMut};
 
+use crate::crossing_weights::CrossingWeights;
 use crate::grid_config::{Crossing, CrossingId, GridConfig, SlotConfig, SlotId};
 use crate::types::WordId;
 use crate::util::{build_glyph_counts_by_cell, GlyphCountsByCell};
@@ -32,17 +35,13 @@ pub struct EliminationSet {
 }
 
 impl EliminationSet {
-    /// Build all of the needed sets for the given slot configs and word list. We also leave extra
-    /// space in each set to account for the possibility of literal words from the fill needing to
-    /// be added.
+    /// Build all of the needed sets for the given slot configs and word list, in a pool that can be
+    /// reused for later fills (see `EliminationSetPool`).
     #[must_use]
-    pub fn build_all(slot_configs: &[SlotConfig], word_list: &WordList) -> Vec<EliminationSet> {
-        slot_configs
-            .iter()
-            .map(|slot_config| {
-                EliminationSet::new(word_list.words[slot_config.length].len() + slot_configs.len())
-            })
-            .collect()
+    pub fn build_all(slot_configs: &[SlotConfig], word_list: &WordList) -> EliminationSetPool {
+        let mut pool = EliminationSetPool::new();
+        pool.acquire(slot_configs, word_list);
+        pool
     }
 
     /// Build a set for a slot with the given number of options. This is based on the total number
@@ -58,8 +57,8 @@ impl EliminationSet {
 
     /// Record that the given word has been eliminated for this slot.
     pub fn add_elimination(&mut self, id: WordId) {
-        if !self.eliminations_by_id[id] {
-            self.eliminations_by_id[id] = true;
+        if !self.eliminations_by_id[id.index()] {
+            self.eliminations_by_id[id.index()] = true;
             self.eliminated_ids.push(id);
         }
     }
@@ -73,7 +72,7 @@ impl EliminationSet {
         // array needs to be reset, so we use a heuristic to decide.
         if self.eliminated_ids.len() < (size / 4) {
             for &id in &self.eliminated_ids {
-                self.eliminations_by_id[id] = false;
+                self.eliminations_by_id[id.index()] = false;
             }
             self.eliminated_ids.clear();
         } else {
@@ -86,7 +85,83 @@ impl EliminationSet {
     /// Has the given word been eliminated?
     #[must_use]
     pub fn contains(&self, id: WordId) -> bool {
-        self.eliminations_by_id[id]
+        self.eliminations_by_id[id.index()]
+    }
+
+    /// Empty the set and change the number of words it can hold, keeping its existing allocations
+    /// where possible.
+    fn resize(&mut self, size: usize) {
+        self.eliminations_by_id.clear();
+        self.eliminations_by_id.resize(size, false);
+        self.eliminated_ids.clear();
+        self.eliminated_ids.reserve(size);
+    }
+}
+
+/// A reusable collection of `EliminationSet`s, one for each slot in a grid. Each set is sized to the
+/// number of words of its slot's length, so allocating them from scratch is a significant part of
+/// the cost of a fill when using a large word list. A long-lived caller (like a server handling
+/// many fill requests) can instead keep a single pool around and `acquire` it for each grid, which
+/// reuses the existing allocations.
+///
+/// The pool dereferences to the slice of sets for the most recently acquired grid, so it can be
+/// passed anywhere `&mut [EliminationSet]` is expected.
+#[derive(Debug, Default)]
+pub struct EliminationSetPool {
+    sets: Vec<EliminationSet>,
+
+    /// How many of the sets in `sets` belong to the current grid. Any others are kept idle for
+    /// future grids with more slots.
+    active_count: usize,
+}
+
+impl EliminationSetPool {
+    #[must_use]
+    pub fn new() -> EliminationSetPool {
+        EliminationSetPool::default()
+    }
+
+    /// Prepare empty sets for the given slot configs and word list, reusing allocations from
+    /// previous grids where possible. We also leave extra space in each set to account for the
+    /// possibility of literal words from the fill needing to be added.
+    pub fn acquire(
+        &mut self,
+        slot_configs: &[SlotConfig],
+        word_list: &WordList,
+    ) -> &mut [EliminationSet] {
+        for (slot_idx, slot_config) in slot_configs.iter().enumerate() {
+            let size = word_list.words[slot_config.length].len() + slot_configs.len();
+
+            if let Some(set) = self.sets.get_mut(slot_idx) {
+                set.resize(size);
+            } else {
+                self.sets.push(EliminationSet::new(size));
+            }
+        }
+        self.active_count = slot_configs.len();
+
+        &mut self.sets[..self.active_count]
+    }
+
+    /// Clear the eliminations in all of the current grid's sets without resizing them.
+    pub fn reset(&mut self) {
+        for set in self.iter_mut() {
+            set.reset_eliminations();
+        }
+    }
+}
+
+impl Deref for EliminationSetPool {
+    type Target = [EliminationSet];
+
+    fn deref(&self) -> &[EliminationSet] {
+        &self.sets[..self.active_count]
+    }
+}
+
+impl DerefMut for EliminationSetPool {
+    fn deref_mut(&mut self) -> &mut [EliminationSet] {
+        &mut self.sets[..self.active_count]
     }
 }
 
@@ -115,6 +190,64 @@ pub struct ArcConsistencyFailure {
 /// Result from a call to `establish_arc_consistency`.
 pub type ArcConsistencyResult = Result<(), ArcConsistencyFailure>;
 
+/// A set of cell indices within a single slot, stored as a bitmask. Any slot up to 64 cells long
+/// (comfortably more than `MAX_SLOT_LENGTH`) fits in a single inline word.
+#[derive(Debug, Clone)]
+struct CellMask {
+    bits: SmallVec<[u64; 1]>,
+}
+
+impl CellMask {
+    fn new(length: usize) -> CellMask {
+        CellMask {
+            bits: smallvec![0; length.div_ceil(64).max(1)],
+        }
+    }
+
+    /// Add the given cell to the set, returning true if it wasn't already present.
+    fn insert(&mut self, cell_idx: usize) -> bool {
+        let word = &mut self.bits[cell_idx / 64];
+        let bit = 1 << (cell_idx % 64);
+        let is_new = *word & bit == 0;
+        *word |= bit;
+        is_new
+    }
+
+    /// Iterate over the cells in the set, in ascending order. (This is regardless of the order
+    /// they were inserted in, which is what the `Vec` this replaced used to yield.)
+    fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.bits.iter().enumerate().flat_map(|(word_idx, &word)| {
+            let mut remaining = word;
+            std::iter::from_fn(move || {
+                (remaining != 0).then(|| {
+                    let bit_idx = remaining.trailing_zeros() as usize;
+                    remaining &= remaining - 1;
+                    word_idx * 64 + bit_idx
+                })
+            })
+        })
+    }
+
+    /// The cells in the set, in descending order of the weight of their crossings in the given
+    /// slot. Ties go to the lowest cell index, so the order is fully determined by the set's
+    /// contents and the weights.
+    fn by_crossing_weight(
+        &self,
+        slot_config: &SlotConfig,
+        crossing_weights: &CrossingWeights,
+    ) -> Vec<usize> {
+        let mut cell_idxs: Vec<usize> = self.iter().collect();
+        cell_idxs.sort_by_cached_key(|&cell_idx| {
+            let crossing_id = slot_config.crossings[cell_idx]
+                .as_ref()
+                .expect("queued cell_idx must have a crossing")
+                .crossing_id;
+            Reverse(FloatOrd(crossing_weights[crossing_id]))
+        });
+        cell_idxs
+    }
+}
+
 /// Struct tracking the state of a given slot during the process of establishing arc consistency.
 struct ArcConsistencySlotState<'a> {
     /// The id of the underlying slot; this is an index into various slices passed into
@@ -138,7 +271,7 @@ struct ArcConsistencySlotState<'a> {
 
     /// A set of cell indices that we need to propagate *outward* from, removing any incompatible
     /// options from the crossing entry.
-    queued_cell_idxs: Option<Vec<usize>>,
+    queued_cell_idxs: Option<CellMask>,
 
     /// Do we need to do singleton propagation (e.g., uniqueness checks) from this slot? This can
     /// only be true if the slot has exactly one entry and we've never done this propagation from
@@ -162,6 +295,101 @@ impl ArcConsistencySlotState<'_> {
     }
 }
 
+/// An indexed binary min-heap of the slots that have queued cells, keyed by their live `dom/wdeg`
+/// ratios. This lets us find the next slot to propagate from without scanning every slot, and
+/// update a queued slot's priority in place when its domain shrinks. Ties go to the lowest slot id.
+struct SlotQueue {
+    heap: Vec<SlotId>,
+
+    /// Each slot's index in `heap`, if it's queued, indexed by `SlotId`.
+    heap_idxs: Vec<Option<usize>>,
+
+    /// Each queued slot's priority, indexed by `SlotId`.
+    keys: Vec<f32>,
+}
+
+impl SlotQueue {
+    fn new(slot_count: usize) -> SlotQueue {
+        SlotQueue {
+            heap: Vec::with_capacity(slot_count),
+            heap_idxs: vec![None; slot_count],
+            keys: vec![0.0; slot_count],
+        }
+    }
+
+    fn contains(&self, slot_id: SlotId) -> bool {
+        self.heap_idxs[slot_id.index()].is_some()
+    }
+
+    /// Queue the given slot with the given priority, or update its priority if it's already queued.
+    fn set_priority(&mut self, slot_id: SlotId, key: f32) {
+        self.keys[slot_id.index()] = key;
+        if let Some(heap_idx) = self.heap_idxs[slot_id.index()] {
+            self.sift_up(heap_idx);
+            self.sift_down(heap_idx);
+        } else {
+            self.heap.push(slot_id);
+            self.heap_idxs[slot_id.index()] = Some(self.heap.len() - 1);
+            self.sift_up(self.heap.len() - 1);
+        }
+    }
+
+    /// Remove and return the queued slot with the lowest priority.
+    fn pop(&mut self) -> Option<SlotId> {
+        if self.heap.is_empty() {
+            return None;
+        }
+        let last_idx = self.heap.len() - 1;
+        self.swap(0, last_idx);
+        let slot_id = self.heap.pop().unwrap();
+        self.heap_idxs[slot_id.index()] = None;
+        if !self.heap.is_empty() {
+            self.sift_down(0);
+        }
+        Some(slot_id)
+    }
+
+    fn sort_key(&self, heap_idx: usize) -> (FloatOrd<f32>, SlotId) {
+        let slot_id = self.heap[heap_idx];
+        (FloatOrd(self.keys[slot_id.index()]), slot_id)
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        self.heap.swap(a, b);
+        self.heap_idxs[self.heap[a].index()] = Some(a);
+        self.heap_idxs[self.heap[b].index()] = Some(b);
+    }
+
+    fn sift_up(&mut self, mut heap_idx: usize) {
+        while heap_idx > 0 {
+            let parent_idx = (heap_idx - 1) / 2;
+            if self.sort_key(heap_idx) >= self.sort_key(parent_idx) {
+                break;
+            }
+            self.swap(heap_idx, parent_idx);
+            heap_idx = parent_idx;
+        }
+    }
+
+    fn sift_down(&mut self, mut heap_idx: usize) {
+        loop {
+            let mut smallest_idx = heap_idx;
+            for child_idx in [2 * heap_idx + 1, 2 * heap_idx + 2] {
+                if child_idx < self.heap.len()
+                    && self.sort_key(child_idx) < self.sort_key(smallest_idx)
+                {
+                    smallest_idx = child_idx;
+                }
+            }
+            if smallest_idx == heap_idx {
+                break;
+            }
+            self.swap(heap_idx, smallest_idx);
+            heap_idx = smallest_idx;
+        }
+    }
+}
+
 /// Determine which eliminations are needed to bring the grid into an arc-consistent state.
 /// If it's impossible to make the grid consistent, return weight values reflecting which
 /// constraints are responsible for the failure (sort of).
@@ -176,7 +404,7 @@ pub fn establish_arc_consistency<Adapter: ArcConsistencyAdapter>(
 
     // For each crossing, what "weight" value has been assigned to it so far? A higher weight means
     // the crossing has been more difficult to satisfy.
-    crossing_weights: &[f32],
+    crossing_weights: &CrossingWeights,
 
     // For each slot, what "weight" value has been assigned to it so far? A higher weight means
     // the slot's currently-unfilled crossings have been more difficult to satisfy.
@@ -207,7 +435,7 @@ pub fn establish_arc_consistency<Adapter: ArcConsistencyAdapter>(
                 slot_id: slot_config.id,
                 eliminations: elimination_set,
                 blame_counts: vec![0; slot_config.length],
-                option_count: initial_option_counts[slot_config.id],
+                option_count: initial_option_counts[slot_config.id.index()],
                 glyph_counts_by_cell: None,
                 queued_cell_idxs: None,
                 needs_singleton_propagation: false,
@@ -215,73 +443,81 @@ pub fn establish_arc_consistency<Adapter: ArcConsistencyAdapter>(
         })
         .collect();
 
+    // The live `dom/wdeg` of each slot, which determines the order in which we visit queued slots.
+    let priority = |slot_states: &[ArcConsistencySlotState], slot_id: SlotId| {
+        (slot_states[slot_id.index()].option_count as f32) / slot_weights[slot_id.index()]
+    };
+    let mut queue = SlotQueue::new(config.slot_configs.len());
+
     // If we were given an `evaluating_slot`, we can assume that the rest of the grid is fully
     // arc-consistent and start by just queueing the cells of this slot. Otherwise, we want to
     // examine the whole grid, except slots that are fixed already.
     let initial_slot_ids: Vec<SlotId> = evaluating_slot.map_or_else(
-        || (0..config.slot_configs.len()).collect(),
+        || SlotId::range(config.slot_configs.len()).collect(),
         |evaluating_slot| vec![evaluating_slot],
     );
     for slot_id in initial_slot_ids {
         // If any slot has zero options, we can fail immediately.
-        if slot_states[slot_id].option_count == 0 {
+        if slot_states[slot_id.index()].option_count == 0 {
             return Err(ArcConsistencyFailure {
                 weight_updates: HashMap::new(),
             });
         }
 
         // Queue all cells that have a crossing with a non-fixed slot.
-        slot_states[slot_id].queued_cell_idxs = Some(
-            config.slot_configs[slot_id]
-                .crossings
-                .iter()
-                .enumerate()
-                .filter(|(_, crossing_opt)| {
-                    if let Some(crossing) = crossing_opt {
-                        !fixed_slots[crossing.other_slot_id]
-                    } else {
-                        false
-                    }
-                })
-                .map(|(cell_idx, _)| cell_idx)
-                .collect(),
-        );
+        let slot_config = &config.slot_configs[slot_id.index()];
+        let mut queued_cell_idxs = CellMask::new(slot_config.length);
+        for (cell_idx, crossing_opt) in slot_config.crossings.iter().enumerate() {
+            if let Some(crossing) = crossing_opt {
+                if !fixed_slots[crossing.other_slot_id.index()] {
+                    queued_cell_idxs.insert(cell_idx);
+                }
+            }
+        }
+        slot_states[slot_id.index()].queued_cell_idxs = Some(queued_cell_idxs);
+        queue.set_priority(slot_id, priority(&slot_states, slot_id));
 
         // If this slot has a single option, we also want to remove dupes from other slots.
-        if slot_states[slot_id].option_count == 1 {
-            slot_states[slot_id].needs_singleton_propagation = true;
+        if slot_states[slot_id.index()].option_count == 1 {
+            slot_states[slot_id.index()].needs_singleton_propagation = true;
         }
     }
 
     #[cfg(feature = "check_invariants")]
-    for (slot_id, &fixed) in fixed_slots.iter().enumerate() {
+    for (slot_id, &fixed) in SlotId::range(fixed_slots.len()).zip(fixed_slots.iter()) {
         if !fixed {
             continue;
         }
         adapter
-            .get_single_option(slot_id, slot_states[slot_id].eliminations)
+            .get_single_option(slot_id, slot_states[slot_id.index()].eliminations)
             .expect("fixed slot must have exactly one option");
     }
 
     // Whenever we eliminate an option from a slot, we need to do some bookkeeping and potentially
     // enqueue cells from that slot for further propagation.
     let eliminate_word = |slot_states: &mut [ArcConsistencySlotState],
+                          queue: &mut SlotQueue,
                           slot_id: SlotId,
                           word_id: WordId,
                           blamed_cell_idx: Option<usize>|
      -> Result<(), ArcConsistencyFailure> {
-        let slot_config = &config.slot_configs[slot_id];
-
-        slot_states[slot_id].eliminations.add_elimination(word_id);
-        slot_states[slot_id].option_count -= 1;
+        let slot_config = &config.slot_configs[slot_id.index()];
+
+        slot_states[slot_id.index()]
+            .eliminations
+            .add_elimination(word_id);
+        slot_states[slot_id.index()].option_count -= 1;
+        if queue.contains(slot_id) {
+            queue.set_priority(slot_id, priority(slot_states, slot_id));
+        }
         if let Some(blamed_cell_idx) = blamed_cell_idx {
-            slot_states[slot_id].blame_counts[blamed_cell_idx] += 1;
+            slot_states[slot_id.index()].blame_counts[blamed_cell_idx] += 1;
         }
 
         // If this was the last option for the slot, we've failed to establish arc
         // consistency and need to bail out and return the relevant slot weights.
-        if slot_states[slot_id].option_count == 0 {
-            let initial_count = initial_option_counts[slot_id] as f32;
+        if slot_states[slot_id.index()].option_count == 0 {
+            let initial_count = initial_option_counts[slot_id.index()] as f32;
 
             return Err(ArcConsistencyFailure {
                 weight_updates: slot_config
@@ -301,7 +537,7 @@ pub fn establish_arc_consistency<Adapter: ArcConsistencyAdapter>(
                             // the grid pairwise every time, so it doesn't really seem worth it.
                             (
                                 crossing.crossing_id,
-                                (slot_states[slot_id].blame_counts[cell_idx] as f32)
+                                (slot_states[slot_id.index()].blame_counts[cell_idx] as f32)
                                     / initial_count,
                             )
                         })
@@ -312,18 +548,19 @@ pub fn establish_arc_consistency<Adapter: ArcConsistencyAdapter>(
 
         // If this was the *second*-to-last option for the slot, we'll want to propagate dupe rules,
         // etc., using that slot's now-locked-in value.
-        if slot_states[slot_id].option_count == 1 {
-            slot_states[slot_id].needs_singleton_propagation = true;
+        if slot_states[slot_id.index()].option_count == 1 {
+            slot_states[slot_id.index()].needs_singleton_propagation = true;
         }
 
         // Now we need to go through the letters of this word and decrement the glyph count for each
         // one. If any of them reach 0, and the crossing slot has a corresponding non-zero count, we
         // need to enqueue this cell to remove the no-longer-valid options from the crossing slot.
         for cell_idx in 0..slot_config.length {
-            let glyph_id = config.word_list.words[slot_config.length][word_id].glyphs[cell_idx];
+            let glyph_id =
+                config.word_list.words[slot_config.length][word_id.index()].glyphs[cell_idx];
 
             let glyph_counts_for_cell =
-                &mut slot_states[slot_id].get_glyph_counts(adapter)[cell_idx];
+                &mut slot_states[slot_id.index()].get_glyph_counts(adapter)[cell_idx];
 
             glyph_counts_for_cell[glyph_id] -= 1;
 
@@ -342,23 +579,24 @@ pub fn establish_arc_consistency<Adapter: ArcConsistencyAdapter>(
                     continue;
                 };
 
-                if fixed_slots[crossing.other_slot_id] {
+                if fixed_slots[crossing.other_slot_id.index()] {
                     continue;
                 }
 
-                let crossing_glyph_count = slot_states[crossing.other_slot_id]
+                let crossing_glyph_count = slot_states[crossing.other_slot_id.index()]
                     .get_glyph_counts(adapter)[crossing.other_slot_cell][glyph_id];
 
                 if crossing_glyph_count > 0 {
-                    if slot_states[slot_id].queued_cell_idxs.is_none() {
-                        slot_states[slot_id].queued_cell_idxs =
-                            Some(Vec::with_capacity(slot_config.length));
-                    }
-                    let queued_cell_idxs = slot_states[slot_id].queued_cell_idxs.as_mut().unwrap();
-
-                    if !queued_cell_idxs.contains(&cell_idx) {
-                        queued_cell_idxs.push(cell_idx);
+                    if slot_states[slot_id.index()].queued_cell_idxs.is_none() {
+                        slot_states[slot_id.index()].queued_cell_idxs =
+                            Some(CellMask::new(slot_config.length));
+                        queue.set_priority(slot_id, priority(slot_states, slot_id));
                     }
+                    slot_states[slot_id.index()]
+                        .queued_cell_idxs
+                        .as_mut()
+                        .unwrap()
+                        .insert(cell_idx);
                 }
             }
         }
@@ -384,28 +622,17 @@ pub fn establish_arc_consistency<Adapter: ArcConsistencyAdapter>(
     //
     loop {
         // First, run the AC-3 algorithm, propagating eliminations until the queue is empty.
-        loop {
-            // Identify the queued slot with the lowest `dom/wdeg`, based on our live domain sizes.
-            let slot_id = (0..config.slot_configs.len())
-                .filter(|&slot_id| slot_states[slot_id].queued_cell_idxs.is_some())
-                .min_by_key(|&slot_id| {
-                    FloatOrd((slot_states[slot_id].option_count as f32) / slot_weights[slot_id])
-                });
-
-            // If there are no queued slots left, we're done with this AC pass.
-            let Some(slot_id) = slot_id else {
-                break;
-            };
+        // Take the queued slot with the lowest `dom/wdeg`, based on our live domain sizes. If there
+        // are no queued slots left, we're done with this AC pass.
+        while let Some(slot_id) = queue.pop() {
+            let slot_id = slot_id.index();
 
             // We want to examine the slot's cells in descending order of crossing weight.
-            let mut cell_idxs = slot_states[slot_id].queued_cell_idxs.take().unwrap();
-            cell_idxs.sort_by_cached_key(|&cell_idx| {
-                let crossing_id = config.slot_configs[slot_id].crossings[cell_idx]
-                    .as_ref()
-                    .expect("queued cell_idx must have a crossing")
-                    .crossing_id;
-                Reverse(FloatOrd(crossing_weights[crossing_id]))
-            });
+            let cell_idxs = slot_states[slot_id]
+                .queued_cell_idxs
+                .take()
+                .unwrap()
+                .by_crossing_weight(&config.slot_configs[slot_id], crossing_weights);
 
             // For each queued cell, go through the crossing slot's options and eliminate any that
             // are incompatible with this slot's possible values.
@@ -418,21 +645,21 @@ pub fn establish_arc_consistency<Adapter: ArcConsistencyAdapter>(
                     .as_ref()
                     .unwrap();
 
-                let other_slot_config = &config.slot_configs[other_slot_id];
-                let other_slot_options = &config.slot_options[other_slot_id];
+                let other_slot_config = &config.slot_configs[other_slot_id.index()];
+                let other_slot_options = &config.slot_options[other_slot_id.index()];
 
                 for &slot_option_word_id in other_slot_options {
                     // If this word has already been eliminated, we don't need to check it again.
                     if adapter.is_word_eliminated(other_slot_id, slot_option_word_id)
-                        || slot_states[other_slot_id]
+                        || slot_states[other_slot_id.index()]
                             .eliminations
                             .contains(slot_option_word_id)
                     {
                         continue;
                     }
 
-                    let slot_option_word =
-                        &config.word_list.words[other_slot_config.length][slot_option_word_id];
+                    let slot_option_word = &config.word_list.words[other_slot_config.length]
+                        [slot_option_word_id.index()];
                     let slot_option_glyph = slot_option_word.glyphs[other_slot_cell];
 
                     let number_of_matching_options =
@@ -443,6 +670,7 @@ pub fn establish_arc_consistency<Adapter: ArcConsistencyAdapter>(
                     if number_of_matching_options == 0 {
                         eliminate_word(
                             &mut slot_states,
+                            &mut queue,
                             other_slot_id,
                             slot_option_word_id,
                             Some(other_slot_cell),
@@ -463,9 +691,9 @@ pub fn establish_arc_consistency<Adapter: ArcConsistencyAdapter>(
             .collect();
 
         for slot_id in singleton_propagation_slot_ids {
-            let slot_config = &config.slot_configs[slot_id];
+            let slot_config = &config.slot_configs[slot_id.index()];
             let word_id = adapter
-                .get_single_option(slot_id, slot_states[slot_id].eliminations)
+                .get_single_option(slot_id, slot_states[slot_id.index()].eliminations)
                 .expect("slot with `needs_singleton_propagation` must have exactly one option");
 
             let dupes_by_length = config
@@ -473,21 +701,80 @@ pub fn establish_arc_consistency<Adapter: ArcConsistencyAdapter>(
                 .dupe_index
                 .get_dupes_by_length((slot_config.length, word_id));
 
-            for other_slot_id in 0..config.slot_configs.len() {
-                if other_slot_id == slot_id || fixed_slots[other_slot_id] {
+            for other_slot_id in SlotId::range(config.slot_configs.len()) {
+                if other_slot_id == slot_id || fixed_slots[other_slot_id.index()] {
+                    continue;
+                }
+
+                let later_slot_config = &config.slot_configs[other_slot_id.index()];
+                let later_slot_options = &config.slot_options[other_slot_id.index()];
+
+                // A dupe policy, if present, overrides the dupe index for the pairs it covers.
+                let dupe_ids = dupes_by_length.get(&later_slot_config.length);
+                if dupe_ids.is_none() && config.dupe_policy.is_none() {
                     continue;
                 }
+                let is_dupe = |later_word_id: WordId| {
+                    config
+                        .dupe_policy
+                        .as_deref()
+                        .and_then(|dupe_policy| {
+                            dupe_policy.is_dupe(
+                                &config.word_list,
+                                (slot_config.length, word_id),
+                                (later_slot_config.length, later_word_id),
+                            )
+                        })
+                        .unwrap_or_else(|| {
+                            dupe_ids.map_or(false, |dupe_ids| dupe_ids.contains(&later_word_id))
+                        })
+                };
+
+                for &later_word_id in later_slot_options {
+                    if !adapter.is_word_eliminated(other_slot_id, later_word_id)
+                        && !slot_states[other_slot_id.index()]
+                            .eliminations
+                            .contains(later_word_id)
+                        && is_dupe(later_word_id)
+                    {
+                        eliminate_word(
+                            &mut slot_states,
+                            &mut queue,
+                            other_slot_id,
+                            later_word_id,
+                            None,
+                        )?;
+                    }
+                }
+            }
+
+            // If weak entries need fair crossings, remove any crossing options that can't coexist
+            // with this slot's word.
+            if let Some(crossing_quality) = config.crossing_quality {
+                let score = config.word_list.words[slot_config.length][word_id.index()].score;
 
-                let later_slot_config = &config.slot_configs[other_slot_id];
-                let later_slot_options = &config.slot_options[other_slot_id];
+                for crossing in slot_config.crossings.iter().flatten() {
+                    let other_slot_id = crossing.other_slot_id;
+                    if fixed_slots[other_slot_id.index()] {
+                        continue;
+                    }
 
-                if let Some(dupe_ids) = dupes_by_length.get(&later_slot_config.length) {
-                    for &word_id in later_slot_options {
-                        if !adapter.is_word_eliminated(other_slot_id, word_id)
-                            && dupe_ids.contains(&word_id)
-                            && !slot_states[other_slot_id].eliminations.contains(word_id)
+                    let other_length = config.slot_configs[other_slot_id.index()].length;
+                    let other_words = &config.word_list.words[other_length];
+                    for &other_word_id in &config.slot_options[other_slot_id.index()] {
+                        if !crossing_quality.allows(score, other_words[other_word_id.index()].score)
+                            && !adapter.is_word_eliminated(other_slot_id, other_word_id)
+                            && !slot_states[other_slot_id.index()]
+                                .eliminations
+                                .contains(other_word_id)
                         {
-                            eliminate_word(&mut slot_states, other_slot_id, word_id, None)?;
+                            eliminate_word(
+                                &mut slot_states,
+                                &mut queue,
+                                other_slot_id,
+                                other_word_id,
+                                None,
+                            )?;
                         }
                     }
                 }
@@ -529,9 +816,9 @@ pub fn establish_arc_consistency_for_static_grid(
 
         fn get_glyph_counts(&self, slot_id: SlotId) -> GlyphCountsByCell {
             build_glyph_counts_by_cell(
-                self.config.word_list,
-                self.config.slot_configs[slot_id].length,
-                &self.config.slot_options[slot_id],
+                &self.config.word_list,
+                self.config.slot_configs[slot_id.index()].length,
+                &self.config.slot_options[slot_id.index()],
             )
         }
 
@@ -540,7 +827,7 @@ pub fn establish_arc_consistency_for_static_grid(
             slot_id: SlotId,
             eliminations: &EliminationSet,
         ) -> Option<WordId> {
-            self.config.slot_options[slot_id]
+            self.config.slot_options[slot_id.index()]
                 .iter()
                 .find(|word_id| !eliminations.contains(**word_id))
                 .copied()
@@ -554,23 +841,23 @@ pub fn establish_arc_consistency_for_static_grid(
     let fixed_slots: Vec<bool> = (0..config.slot_configs.len())
         .map(|slot_id| {
             config.slot_configs[slot_id]
-                .complete_fill(config.fill, config.width)
+                .complete_fill(&config.fill, config.width)
                 .is_some()
         })
         .collect();
 
     // Since we don't know anything about which constraints are the most problematic, slot weight
     // is defined as "number of non-fixed crossing entries".
-    let constraint_weights: Vec<f32> = (0..config.crossing_count).map(|_| 1.0).collect();
+    let constraint_weights = CrossingWeights::new(config.crossing_count);
     let slot_weights: Vec<f32> = (0..config.slot_configs.len())
         .map(|slot_id| {
             config.slot_configs[slot_id]
                 .crossings
                 .iter()
                 .filter(|crossing| {
-                    crossing
-                        .as_ref()
-                        .map_or(false, |crossing| !fixed_slots[crossing.other_slot_id])
+                    crossing.as_ref().map_or(false, |crossing| {
+                        !fixed_slots[crossing.other_slot_id.index()]
+                    })
                 })
                 .count() as f32
         })
@@ -592,8 +879,11 @@ pub fn establish_arc_consistency_for_static_grid(
 
 #[cfg(test)]
 mod tests {
-    use crate::arc_consistency::{establish_arc_consistency_for_static_grid, EliminationSet};
-    use crate::grid_config::{generate_grid_config_from_template_string, OwnedGridConfig};
+    use crate::arc_consistency::{
+        establish_arc_consistency_for_static_grid, CellMask, EliminationSet, SlotQueue,
+    };
+    use crate::crossing_weights::CrossingWeights;
+    use crate::grid_config::{generate_grid_config_from_template_string, OwnedGridConfig, SlotId};
     use crate::word_list::tests::word_list_source_config;
     use crate::word_list::WordList;
     use std::time::Instant;
@@ -643,20 +933,22 @@ mod tests {
 
         let start = Instant::now();
 
-        let config_ref = grid_config.to_config_ref();
         let mut eliminations_by_slot =
-            EliminationSet::build_all(config_ref.slot_configs, config_ref.word_list);
-        establish_arc_consistency_for_static_grid(&config_ref, &mut eliminations_by_slot)
+            EliminationSet::build_all(&grid_config.slot_configs, &grid_config.word_list);
+        establish_arc_consistency_for_static_grid(&grid_config, &mut eliminations_by_slot)
             .expect("Failed to establish consistency");
 
         let checkpoint = start.elapsed();
         println!("Slot options eliminated in {:?}", start.elapsed());
 
-        for (slot_id, slot_options) in grid_config.slot_options.iter_mut().enumerate() {
+        for (slot_id, slot_options) in grid_config.slot_options.to_mut().iter_mut().enumerate() {
             slot_options.retain(|word_id| !eliminations_by_slot[slot_id].contains(*word_id));
         }
 
-        println!("Options pruned in {:?}", start.elapsed() - checkpoint);
+        println!(
+            "Options pruned in {:?}",
+            start.elapsed().saturating_sub(checkpoint)
+        );
 
         let opts = &grid_config.slot_options;
         assert_eq!(opts[0].len(), 1, "filled-in entry has one option");
@@ -671,4 +963,58 @@ mod tests {
             "entry crossing seeds has very few options"
         );
     }
+
+    #[test]
+    fn test_slot_queue() {
+        let mut queue = SlotQueue::new(6);
+        for (slot_id, key) in [(0, 3.0), (1, 1.0), (2, 5.0), (3, 2.0), (4, 2.0)] {
+            queue.set_priority(SlotId(slot_id), key);
+        }
+        assert!(queue.contains(SlotId(2)));
+        assert!(!queue.contains(SlotId(5)));
+
+        // Updating a queued slot's priority moves it in either direction.
+        queue.set_priority(SlotId(2), 0.5);
+        queue.set_priority(SlotId(1), 4.0);
+
+        let order: Vec<_> = std::iter::from_fn(|| queue.pop()).collect();
+        assert_eq!(
+            order,
+            [2, 3, 4, 0, 1].map(SlotId).to_vec(),
+            "slots should come out in priority order, with ties going to the lowest id",
+        );
+        assert!(!queue.contains(SlotId(2)));
+    }
+
+    #[test]
+    fn test_cell_mask() {
+        let mut mask = CellMask::new(70);
+        assert!(mask.insert(5));
+        assert!(mask.insert(0));
+        assert!(!mask.insert(5));
+        assert!(mask.insert(66));
+        assert_eq!(mask.iter().collect::<Vec<_>>(), vec![0, 5, 66]);
+
+        // Cells are visited by descending crossing weight, and then by ascending index, no matter
+        // what order they were queued in.
+        let grid_config = generate_grid_config_from_template_string(
+            WordList::new(word_list_source_config(), None, Some(4), None),
+            "....\n....\n....\n....",
+            40,
+        );
+        let slot_config = &grid_config.slot_configs[0];
+        let mut weights = vec![1.0; grid_config.crossing_count];
+        let heavy_crossing_id = slot_config.crossings[2].as_ref().unwrap().crossing_id;
+        weights[heavy_crossing_id.index()] = 5.0;
+        let crossing_weights = CrossingWeights::from_vec(weights);
+
+        let mut mask = CellMask::new(4);
+        for cell_idx in [3, 1, 2, 0] {
+            mask.insert(cell_idx);
+        }
+        assert_eq!(
+            mask.by_crossing_weight(slot_config, &crossing_weights),
+            vec![2, 0, 1, 3]
+        );
+    }
 }
@@ -0,0 +1,85 @@
+//! This module (enabled with the `tokio` feature) provides `find_fill_async`, which runs a search
+//! on Tokio's blocking thread pool so that async servers can await a fill without stalling their
+//! executor. Cancellation is cooperative, using the grid's `CancelToken` (see `cancel.rs`): the
+//! search stops at its next check once the token is cancelled, and dropping the returned future
+//! cancels it too, so a request handler that goes away takes its search with it.
+
+use instant::Duration;
+
+use crate::backtracking_search::{
+    find_fill_with_search_config, FillFailure, FillSuccess, SearchConfig,
+};
+use crate::cancel::CancelToken;
+use crate::grid_config::OwnedGridConfig;
+
+/// Search for a fill like `find_fill_with_search_config`, but on Tokio's blocking thread pool,
+/// returning the config along with the result so that the caller can render the fill. If the
+/// config doesn't already have a `cancel` token, one is added; either way, dropping the future
+/// before it completes cancels that token. Panics (on the awaiting task) if the search panics.
+pub async fn find_fill_async(
+    mut config: OwnedGridConfig,
+    timeout: Option<Duration>,
+    search_config: SearchConfig,
+) -> (OwnedGridConfig, Result<FillSuccess, FillFailure>) {
+    let cancel = config.cancel.get_or_insert_with(CancelToken::new).clone();
+    let cancel_guard = cancel.cancel_on_drop("fill future dropped");
+
+    let join_result = tokio::task::spawn_blocking(move || {
+        let result = find_fill_with_search_config(&config, timeout, None, &search_config);
+        (config, result)
+    })
+    .await;
+
+    // The search is over, so there's nothing left to cancel.
+    cancel_guard.disarm();
+
+    join_result.unwrap_or_else(|error| std::panic::resume_unwind(error.into_panic()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::async_search::find_fill_async;
+    use crate::backtracking_search::{FillFailure, SearchConfig};
+    use crate::cancel::{CancelReason, CancelToken};
+    use crate::grid_config::{generate_grid_config_from_template_string, render_grid};
+    use crate::word_list::tests::word_list_source_config;
+    use crate::word_list::WordList;
+
+    #[tokio::test]
+    async fn test_find_fill_async() {
+        let word_list = WordList::new(word_list_source_config(), None, Some(5), None);
+        let config = generate_grid_config_from_template_string(word_list, "...\n...\n...", 40);
+
+        let (config, result) = find_fill_async(config, None, SearchConfig::default()).await;
+        let result = result.unwrap();
+        assert_eq!(render_grid(&config, &result.choices).len(), 11);
+        assert!(config
+            .cancel
+            .as_ref()
+            .is_some_and(|cancel| !cancel.was_cancelled()));
+
+        // A search whose token is already cancelled stops right away.
+        let mut config = config;
+        let cancel = CancelToken::new();
+        cancel.cancel("switched grids");
+        config.cancel = Some(cancel);
+        let (_, result) = find_fill_async(config, None, SearchConfig::default()).await;
+        assert!(matches!(result, Err(FillFailure::Abort(_))));
+
+        // Dropping the future once the search has started cancels it. An open 15x15 grid takes far
+        // longer than a yield to fill, so the search is still running when the task is aborted.
+        let word_list = WordList::new(word_list_source_config(), None, Some(15), None);
+        let template = vec![".".repeat(15); 15].join("\n");
+        let mut config = generate_grid_config_from_template_string(word_list, &template, 40);
+        let cancel = CancelToken::new();
+        config.cancel = Some(cancel.clone());
+        let task = tokio::spawn(find_fill_async(config, None, SearchConfig::default()));
+        tokio::task::yield_now().await;
+        task.abort();
+        assert!(task.await.is_err_and(|error| error.is_cancelled()));
+        assert_eq!(
+            cancel.reason(),
+            Some(CancelReason::Cancelled("fill future dropped".into()))
+        );
+    }
+}
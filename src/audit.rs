@@ -0,0 +1,185 @@
+//! This module checks the live state of a fill (see `backtracking_search::Slot`) for internal
+//! consistency. For speed, each slot tracks its remaining options redundantly: as a set of
+//! eliminations, as a bitset of live options, as a count, and as glyph counts for each cell. If these ever disagree, as they
+//! might after an embedder edits slot state directly, the search goes wrong in confusing ways
+//! (most often by panicking much later), so it's useful to be able to check them explicitly. With
+//! the `check_invariants` feature, `find_fill_for_seed` audits its state after every step.
+
+use crate::backtracking_search::Slot;
+use crate::error::AuditError;
+use crate::grid_config::GridConfig;
+use crate::types::WordId;
+use crate::util::build_glyph_counts_by_cell;
+
+/// Check that the given slot states are consistent with the grid and with each other. For each
+/// slot, this verifies that `live_options`, `remaining_option_count`, and `glyph_counts_by_cell`
+/// agree with the options that haven't been eliminated, that the fixed word (if any) is one of the slot's options
+/// and agrees with `fixed_glyph_counts_by_cell`, and that every elimination blamed on another
+/// slot's choice refers to a slot that currently has one. This is slow, since it rebuilds each
+/// slot's glyph counts from scratch.
+pub fn audit_consistency(config: &GridConfig, slots: &[Slot]) -> Result<(), AuditError> {
+    if slots.len() != config.slot_configs.len() {
+        return Err(AuditError::SlotCountMismatch {
+            expected: config.slot_configs.len(),
+            actual: slots.len(),
+        });
+    }
+
+    for (slot, slot_config) in slots.iter().zip(config.slot_configs.iter()) {
+        let options = &config.slot_options[slot_config.id.index()];
+
+        if slot.id != slot_config.id
+            || slot.length != slot_config.length
+            || slot.eliminations.len() != config.word_list.words[slot.length].len()
+        {
+            return Err(AuditError::SlotConfigMismatch(slot_config.id));
+        }
+
+        let remaining_options: Vec<WordId> = options
+            .iter()
+            .copied()
+            .filter(|word_id| slot.eliminations[word_id.index()].is_none())
+            .collect();
+
+        let live_options: Vec<Option<WordId>> = slot
+            .live_options
+            .positions_from(0)
+            .map(|position| options.get(position).copied())
+            .collect();
+        if !live_options
+            .iter()
+            .copied()
+            .eq(remaining_options.iter().copied().map(Some))
+        {
+            return Err(AuditError::LiveOptionsMismatch(slot.id));
+        }
+
+        if remaining_options.len() != slot.remaining_option_count {
+            return Err(AuditError::OptionCountMismatch {
+                slot_id: slot.id,
+                expected: remaining_options.len(),
+                actual: slot.remaining_option_count,
+            });
+        }
+
+        if build_glyph_counts_by_cell(&config.word_list, slot.length, &remaining_options)
+            != slot.glyph_counts_by_cell
+        {
+            return Err(AuditError::GlyphCountMismatch(slot.id));
+        }
+
+        let fixed_glyph_counts = slot
+            .fixed_word_id
+            .map(|word_id| build_glyph_counts_by_cell(&config.word_list, slot.length, &[word_id]));
+        if slot
+            .fixed_word_id
+            .is_some_and(|word_id| !options.contains(&word_id))
+            || fixed_glyph_counts != slot.fixed_glyph_counts_by_cell
+        {
+            return Err(AuditError::InvalidFixedWord(slot.id));
+        }
+
+        for word_id in options {
+            if let Some(Some(blamed_slot_id)) = slot.eliminations[word_id.index()] {
+                if slots
+                    .get(blamed_slot_id.index())
+                    .map_or(true, |blamed_slot| blamed_slot.fixed_word_id.is_none())
+                {
+                    return Err(AuditError::StaleElimination {
+                        slot_id: slot.id,
+                        word_id: *word_id,
+                        blamed_slot_id,
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::audit::audit_consistency;
+    use crate::backtracking_search::{build_slots, maintain_arc_consistency, ArcConsistencyMode};
+    use crate::crossing_weights::CrossingWeights;
+    use crate::error::AuditError;
+    use crate::grid_config::{generate_grid_config_from_template_string, Choice, SlotId};
+    use crate::word_list::tests::memory_word_list;
+    use instant::Duration;
+
+    #[test]
+    fn test_audit_consistency() {
+        let word_list = memory_word_list(&["abc", "def", "ghi", "adg", "beh", "cfi", "xyz"]);
+        let grid_config = generate_grid_config_from_template_string(word_list, "...\n...\n...", 40);
+        let config = grid_config.borrowed();
+        let mut slots = build_slots(&config);
+        assert_eq!(audit_consistency(&config, &slots), Ok(()));
+
+        // Making a choice and propagating it leaves the state consistent.
+        let mut crossing_weights = CrossingWeights::new(config.crossing_count);
+        let slot_weights = vec![1.0; config.slot_configs.len()];
+        let mut elimination_sets = crate::arc_consistency::EliminationSet::build_all(
+            &config.slot_configs,
+            &config.word_list,
+        );
+        let choice = Choice {
+            slot_id: SlotId(0),
+            word_id: config.slot_options[0][0],
+        };
+        assert!(maintain_arc_consistency(
+            &config,
+            &mut slots,
+            &mut crossing_weights,
+            &slot_weights,
+            &ArcConsistencyMode::Choice(choice),
+            &mut Duration::default(),
+            &mut elimination_sets,
+        ));
+        assert_eq!(audit_consistency(&config, &slots), Ok(()));
+
+        // Clearing the choice without clearing the eliminations it caused doesn't.
+        let mut stale_slots = slots.clone();
+        stale_slots[0].clear_choice();
+        assert!(matches!(
+            audit_consistency(&config, &stale_slots),
+            Err(AuditError::StaleElimination {
+                blamed_slot_id: SlotId(0),
+                ..
+            })
+        ));
+
+        let mut miscounted_slots = slots.clone();
+        miscounted_slots[1].remaining_option_count += 1;
+        assert!(matches!(
+            audit_consistency(&config, &miscounted_slots),
+            Err(AuditError::OptionCountMismatch { .. })
+        ));
+
+        let mut unsynced_slots = slots.clone();
+        let live_word_id = config.slot_options[1]
+            .iter()
+            .find(|word_id| slots[1].eliminations[word_id.index()].is_none())
+            .unwrap();
+        unsynced_slots[1].live_options.remove(*live_word_id);
+        assert_eq!(
+            audit_consistency(&config, &unsynced_slots),
+            Err(AuditError::LiveOptionsMismatch(SlotId(1)))
+        );
+
+        let mut mismatched_slots = slots.clone();
+        mismatched_slots[1].glyph_counts_by_cell[0][0] += 1;
+        assert_eq!(
+            audit_consistency(&config, &mismatched_slots),
+            Err(AuditError::GlyphCountMismatch(SlotId(1)))
+        );
+
+        assert_eq!(
+            audit_consistency(&config, &slots[1..]),
+            Err(AuditError::SlotCountMismatch {
+                expected: 6,
+                actual: 5
+            })
+        );
+    }
+}
@@ -5,19 +5,30 @@
 //! branching" concept and randomized restarts.
 
 use float_ord::FloatOrd;
+use instant::{Duration, Instant};
 use rand::distributions::WeightedIndex;
 use rand::prelude::*;
+use std::cmp::Reverse;
+use std::collections::HashMap;
 use std::fmt;
 use std::fmt::{Debug, Formatter};
-use std::sync::atomic::Ordering;
-use instant::{Duration, Instant};
+use std::hash::Hasher;
+use std::sync::Arc;
 
 use crate::arc_consistency::{
     establish_arc_consistency, ArcConsistencyAdapter, ArcConsistencyFailure, EliminationSet,
+    EliminationSetPool,
 };
+use crate::crossing_weights::{CrossingWeights, WeightDecay};
+use crate::discrepancy_search::find_fill_with_discrepancies;
+use crate::dupe_index::DupePolicy;
+use crate::failure_bundle::FailureBundle;
 use crate::grid_config::{Choice, Crossing, GridConfig, SlotId};
+use crate::required_words::RequiredWords;
+use crate::tag_caps::{tag_caps_satisfied, TagCap};
 use crate::types::WordId;
-use crate::util::{build_glyph_counts_by_cell, GlyphCountsByCell};
+use crate::util::{build_glyph_counts_by_cell, GlyphCountsByCell, StableHasher};
+use crate::word_list::{WordList, WordRecency};
 
 /// If the previously-attempted slot is within this distance of the "best" (lowest-priority-value)
 /// slot, we should stick with the previous one instead of switching (per Balafoutis).
@@ -26,19 +37,444 @@ pub const ADAPTIVE_BRANCHING_THRESHOLD: f32 = 0.15;
 /// How many times should we loop before checking whether we've passed our deadline?
 pub const INTERRUPT_FREQUENCY: usize = 10;
 
-/// How much do we decrease the weight of each crossing every time we wipe out a domain?
-/// The lower this is, the more we prioritize recent information over older information.
-pub const WEIGHT_AGE_FACTOR: f32 = 0.99;
-
 /// How do we weigh the highest-ranked N slots when choosing which one to fill next?
 pub const RANDOM_SLOT_WEIGHTS: [u8; 3] = [4, 2, 1];
 
 /// How do we weigh the highest-ranked N words when choosing a word for a given slot?
 pub const RANDOM_WORD_WEIGHTS: [u8; 3] = [4, 2, 1];
 
-/// How much do we increase the backtrack limit when retrying?
+/// How much do we increase the retry budget (see `RetryBudget`) when retrying?
 pub const RETRY_GROWTH_FACTOR: f32 = 1.1;
 
+/// When using the impact-based heuristic, how much weight does each new observation of an
+/// assignment's impact get relative to the running average?
+pub const IMPACT_LEARNING_RATE: f32 = 0.25;
+
+/// Which heuristic should we use to decide which slot to fill next?
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub enum SlotHeuristic {
+    /// Balafoutis's variant of `dom/wdeg`, which prioritizes slots with few remaining options and
+    /// crossings that have frequently been involved in domain wipeouts.
+    #[default]
+    DomWdeg,
+
+    /// Refalo's impact-based search, which prioritizes slots whose assignments have historically
+    /// shrunk the search space the most, and (within the few best-scoring candidates) prefers words
+    /// that have historically shrunk it the least. This tends to do better than `dom/wdeg` on very
+    /// open grids with many long slots, where crossing weights take a long time to become
+    /// informative.
+    Impact,
+}
+
+/// How should the candidates in a `CandidateWindow` be ordered before we choose among them? The
+/// first candidate gets the most weight.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub enum CandidateOrder {
+    /// Keep the order of `GridConfig::slot_options`, which balances fillability against score.
+    #[default]
+    SlotOptions,
+
+    /// Put the highest-scoring candidates first, keeping the slot options' order among ties.
+    Score,
+
+    /// Put the highest-scoring candidates first, in random order among ties.
+    ScoreThenRandom,
+}
+
+/// Which of a slot's remaining options do we choose among when filling it, and how likely is each
+/// one to be chosen? The options are taken in the order of `GridConfig::slot_options`, so the
+/// window covers the best few, with each one's weight a fixed fraction of the previous one's.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct CandidateWindow {
+    /// How many options to choose among. A wider window adds variety, which can help when many
+    /// options are about equally good, at the cost of sometimes passing over the best one.
+    pub size: usize,
+
+    /// The weight of each option relative to the one before it. 1.0 makes every option in the
+    /// window equally likely, and lower values favor the best-ranked options more strongly.
+    pub decay: f32,
+
+    /// How to rank the options once they're in the window.
+    pub order: CandidateOrder,
+}
+
+impl Default for CandidateWindow {
+    /// The window used by default, matching `RANDOM_WORD_WEIGHTS`.
+    fn default() -> Self {
+        CandidateWindow {
+            size: RANDOM_WORD_WEIGHTS.len(),
+            decay: 0.5,
+            order: CandidateOrder::default(),
+        }
+    }
+}
+
+impl CandidateWindow {
+    /// The weight of each position in the window, starting at 1.0 for the best-ranked option. An
+    /// empty window is treated as having a single option. The decay is clamped to (0, 1], so a
+    /// decay that isn't positive (or isn't a number) acts as an extremely fast one, and one above
+    /// 1.0 makes every option equally likely rather than letting the weights grow without bound.
+    #[must_use]
+    pub fn weights(&self) -> Vec<f32> {
+        self.capped_weights(self.size)
+    }
+
+    /// Like `weights`, but with no more positions than `max_options`, since a slot never has more
+    /// candidates than that.
+    fn capped_weights(&self, max_options: usize) -> Vec<f32> {
+        let decay = if self.decay > 0.0 {
+            self.decay.min(1.0)
+        } else {
+            f32::EPSILON
+        };
+        std::iter::successors(Some(1.0_f32), |weight| Some(weight * decay))
+            .take(self.size.min(max_options).max(1))
+            .collect()
+    }
+
+    /// The distribution to draw window positions from for a grid whose slots have at most
+    /// `max_options` options each. If the weights somehow can't form a distribution, every
+    /// position is equally likely instead.
+    fn distribution(&self, max_options: usize) -> (usize, WeightedIndex<f32>) {
+        let weights = self.capped_weights(max_options);
+        let dist = WeightedIndex::new(&weights).unwrap_or_else(|_| {
+            WeightedIndex::new(vec![1.0_f32; weights.len()])
+                .expect("Uniform weights always form a distribution")
+        });
+        (weights.len(), dist)
+    }
+}
+
+/// How much work each attempt of a restart search gets before we give up on it and start over with
+/// a new seed, growing by `RETRY_GROWTH_FACTOR` with each retry. Backtracks are the traditional
+/// measure, but what a backtrack costs varies enormously with grid size, so a budget that suits a
+/// mini is miscalibrated for a Sunday-sized grid. A time budget costs about the same for any grid,
+/// at the expense of making the sequence of attempts depend on the speed of the machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum RetryBudget {
+    /// Restart once the attempt has backtracked more than this many times.
+    Backtracks(usize),
+
+    /// Restart at the first backtrack after the attempt has run for this long.
+    Time(Duration),
+
+    /// Restart at the first backtrack after the attempt has visited this many search states, each
+    /// of which propagates a single choice or elimination.
+    States(usize),
+}
+
+impl Default for RetryBudget {
+    fn default() -> Self {
+        RetryBudget::Backtracks(500)
+    }
+}
+
+impl RetryBudget {
+    /// Has an attempt that started at the given time, with the given statistics so far, used up
+    /// this budget?
+    fn is_exhausted(self, statistics: &Statistics, start: Instant) -> bool {
+        match self {
+            RetryBudget::Backtracks(max_backtracks) => statistics.backtracks > max_backtracks,
+            RetryBudget::Time(max_time) => start.elapsed() > max_time,
+            RetryBudget::States(max_states) => statistics.states > max_states,
+        }
+    }
+
+    /// The budget for the attempt after one that used up this budget, which is always at least a
+    /// little larger.
+    #[must_use]
+    pub fn grown(self) -> RetryBudget {
+        let grow = |count: usize| (count + 1).max((count as f32 * RETRY_GROWTH_FACTOR) as usize);
+
+        match self {
+            RetryBudget::Backtracks(max_backtracks) => {
+                RetryBudget::Backtracks(grow(max_backtracks))
+            }
+            RetryBudget::Time(max_time) => RetryBudget::Time(
+                max_time
+                    .mul_f32(RETRY_GROWTH_FACTOR)
+                    .max(max_time + Duration::from_millis(1)),
+            ),
+            RetryBudget::States(max_states) => RetryBudget::States(grow(max_states)),
+        }
+    }
+}
+
+/// The state of a restart search after an attempt has used up its budget, as passed to a
+/// `RetryCallback`.
+#[derive(Debug)]
+pub struct RetryInfo<'a> {
+    /// The number of attempts that have used up their budgets so far, including this one.
+    pub retries: usize,
+
+    /// Everything learned by the search so far, with statistics accumulated across attempts.
+    pub partial: &'a PartialFill,
+
+    /// The budget the next attempt will get unless the callback changes it.
+    pub next_budget: RetryBudget,
+}
+
+/// What a restart search should do after an attempt has used up its budget, as decided by a
+/// `RetryCallback`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryAction {
+    /// Keep retrying as usual.
+    Continue,
+
+    /// Keep retrying, giving the next attempt the given budget instead of the usual one. Later
+    /// attempts grow from this budget.
+    SetBudget(RetryBudget),
+
+    /// Rebuild every slot's options with the given minimum score (see
+    /// `GridConfig::regenerate_slot_options`, which makes the config's `option_constraints` again)
+    /// and keep retrying with the relaxed grid. The learned crossing weights are kept.
+    LowerMinScore(u16),
+
+    /// Stop retrying, failing with `FillFailure::ExceededBacktrackLimit`.
+    Stop,
+}
+
+/// A function that observes a restart search between attempts and decides how it continues, e.g.
+/// to give up after a given number of retries or to lower the minimum score once the grid turns out
+/// to be hard to fill. It's called on the searching thread, so it should return quickly.
+#[derive(Clone)]
+pub struct RetryCallback(pub Arc<dyn Fn(&RetryInfo) -> RetryAction + Send + Sync>);
+
+impl RetryCallback {
+    pub fn new(callback: impl Fn(&RetryInfo) -> RetryAction + Send + Sync + 'static) -> Self {
+        RetryCallback(Arc::new(callback))
+    }
+}
+
+impl Debug for RetryCallback {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "RetryCallback(..)")
+    }
+}
+
+/// How should we explore the search tree?
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub enum SearchStrategy {
+    /// Chronological backtracking with randomized value ordering, restarting with a new seed and
+    /// a larger backtrack limit whenever we get stuck.
+    #[default]
+    Restarts,
+
+    /// Limited-discrepancy search; see `discrepancy_search.rs`.
+    LimitedDiscrepancy,
+}
+
+/// A function building a random number generator from a seed; see `RngSource::Custom`.
+pub type RngFactory = Arc<dyn Fn(u64) -> Box<dyn RngCore> + Send + Sync>;
+
+/// Where the search gets the random numbers it uses to pick among the best few slots and words.
+/// Each attempt builds a new generator from its own seed, so any of these is deterministic for a
+/// given sequence of attempts.
+#[derive(Clone, Default)]
+pub enum RngSource {
+    /// `SmallRng`, which is fast but whose output isn't guaranteed to stay the same across
+    /// platforms or `rand` versions.
+    #[default]
+    Small,
+
+    /// `StdRng`, a cryptographically secure generator that's slower but higher-quality.
+    Std,
+
+    /// A generator supplied by the caller, such as a counter-based one for reproducible tests.
+    Custom(RngFactory),
+}
+
+impl RngSource {
+    /// Build a generator for a single attempt.
+    #[must_use]
+    pub fn build(&self, seed: u64) -> Box<dyn RngCore> {
+        match self {
+            RngSource::Small => Box::new(SmallRng::seed_from_u64(seed)),
+            RngSource::Std => Box::new(StdRng::seed_from_u64(seed)),
+            RngSource::Custom(factory) => factory(seed),
+        }
+    }
+}
+
+impl Debug for RngSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            RngSource::Small => write!(f, "Small"),
+            RngSource::Std => write!(f, "Std"),
+            RngSource::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+/// Options controlling the behavior of the search itself, as opposed to the grid being filled.
+#[derive(Debug, Clone, Default)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct SearchConfig {
+    pub slot_heuristic: SlotHeuristic,
+
+    /// Should we use Lecoutre's "last-conflict" reasoning? If so, after an assignment fails, we
+    /// keep selecting the slot it was made in (whenever it's still undetermined) until we manage
+    /// to assign it successfully, before returning to the normal slot ordering.
+    pub last_conflict: bool,
+
+    /// Should we prefer fresher entries (per `WordList::recency_by_word_id`) when choosing among
+    /// the candidates for a slot? This only reorders the handful of candidates we sample from at
+    /// each step, so it nudges the fill toward debuts without overriding fillability.
+    pub favor_fresh_words: bool,
+
+    /// If present, words that must appear somewhere in the fill; see `required_words.rs`.
+    pub required_words: Option<RequiredWords>,
+
+    /// Limits on the entries carrying particular word-list tags; see `tag_caps.rs`.
+    pub tag_caps: Vec<TagCap>,
+
+    /// Note that `slot_heuristic`, `last_conflict`, `favor_fresh_words`, `rng`, and
+    /// `candidate_window` only affect the `Restarts` strategy.
+    pub strategy: SearchStrategy,
+
+    /// The random number generator to use for each attempt.
+    pub rng: RngSource,
+
+    /// If the grid turns out to be unfillable, should we attach a `FailureBundle` to the failure,
+    /// so that it can be reproduced elsewhere? See `failure_bundle.rs`.
+    pub capture_failure_bundle: bool,
+
+    /// Should we record each choice made and undone by the search, e.g. to animate it afterward?
+    /// See `FillSuccess::history`.
+    pub record_history: bool,
+
+    /// Which candidates to choose among when picking a word for a slot.
+    pub candidate_window: CandidateWindow,
+
+    /// How the crossing weights learned from earlier wipeouts fade over the course of the search.
+    pub weight_decay: WeightDecay,
+
+    /// How much work the first attempt of a restart search gets before we try again with a new
+    /// seed. This only affects the `Restarts` strategy and `find_fill_parallel`.
+    pub retry_budget: RetryBudget,
+
+    /// If present, called between the attempts of a `Restarts` search; see `RetryCallback`.
+    pub retry_callback: Option<RetryCallback>,
+}
+
+/// Running averages of the "impact" of each assignment we've tried, meaning the proportion of the
+/// search space (the product of all remaining domain sizes) it eliminated. A failed assignment has
+/// an impact of 1. Like crossing weights, these are shared between retries.
+#[derive(Debug, Clone, Default)]
+pub struct ImpactTracker {
+    slot_impacts: Vec<Option<f32>>,
+    word_impacts: HashMap<(SlotId, WordId), f32>,
+}
+
+impl ImpactTracker {
+    #[must_use]
+    pub fn new(slot_count: usize) -> ImpactTracker {
+        ImpactTracker {
+            slot_impacts: vec![None; slot_count],
+            word_impacts: HashMap::new(),
+        }
+    }
+
+    /// Record the observed impact of the given choice.
+    pub fn record(&mut self, choice: &Choice, impact: f32) {
+        let update = |average: f32| average + (impact - average) * IMPACT_LEARNING_RATE;
+
+        let slot_impact = &mut self.slot_impacts[choice.slot_id.index()];
+        *slot_impact = Some(slot_impact.map_or(impact, update));
+
+        self.word_impacts
+            .entry((choice.slot_id, choice.word_id))
+            .and_modify(|average| *average = update(*average))
+            .or_insert(impact);
+    }
+
+    /// The average impact of assignments to the given slot, or 0 if we haven't tried any.
+    #[must_use]
+    pub fn slot_impact(&self, slot_id: SlotId) -> f32 {
+        self.slot_impacts[slot_id.index()].unwrap_or(0.0)
+    }
+
+    /// The average impact of the given assignment, falling back to the slot's average if we
+    /// haven't tried it.
+    #[must_use]
+    pub fn word_impact(&self, slot_id: SlotId, word_id: WordId) -> f32 {
+        self.word_impacts
+            .get(&(slot_id, word_id))
+            .copied()
+            .unwrap_or_else(|| self.slot_impact(slot_id))
+    }
+
+    /// Calculate slot weights that, when plugged into the `dom/wdeg` priority formula, give each
+    /// slot a priority of `dom * (1 - impact)`.
+    #[must_use]
+    pub fn calculate_slot_weights(&self) -> Vec<f32> {
+        self.slot_impacts
+            .iter()
+            .map(|impact| 1.0 / (1.0 - impact.unwrap_or(0.0)).max(f32::EPSILON))
+            .collect()
+    }
+
+    /// Sort the given word candidates for a slot so that the ones with the lowest impact (i.e.,
+    /// the ones that have constrained the grid the least) come first.
+    pub fn sort_candidates(&self, slot_id: SlotId, candidates: &mut [(usize, &WordId)]) {
+        candidates.sort_by_key(|&(_, &word_id)| FloatOrd(self.word_impact(slot_id, word_id)));
+    }
+}
+
+/// Sort the given word candidates for a slot of the given length so that the freshest ones come
+/// first, as defined by `WordRecency::freshness_key`.
+pub fn sort_candidates_by_freshness(
+    word_list: &WordList,
+    length: usize,
+    candidates: &mut [(usize, &WordId)],
+) {
+    candidates.sort_by_key(|&(_, &word_id)| {
+        WordRecency::freshness_key(word_list.get_word_recency((length, word_id)))
+    });
+}
+
+/// Sort the given word candidates for a slot of the given length so that the highest-scoring ones
+/// come first, keeping the existing order among equal scores.
+pub fn sort_candidates_by_score(
+    word_list: &WordList,
+    length: usize,
+    candidates: &mut [(usize, &WordId)],
+) {
+    candidates
+        .sort_by_key(|&(_, &word_id)| Reverse(word_list.words[length][word_id.index()].score));
+}
+
+/// Calculate the log of the size of the remaining search space, i.e. the sum of the logs of each
+/// undetermined slot's remaining option count.
+fn log_search_space_size(slots: &[Slot]) -> f32 {
+    slots
+        .iter()
+        .filter(|slot| slot.fixed_word_id.is_none())
+        .map(|slot| (slot.remaining_option_count.max(1) as f32).ln())
+        .sum()
+}
+
 /// A struct tracking stats about the filling process.
 #[derive(Debug, Clone, Default)]
 #[allow(dead_code)]
@@ -52,6 +488,128 @@ pub struct Statistics {
     pub initial_arc_consistency_time: Duration,
     pub choice_arc_consistency_time: Duration,
     pub elimination_arc_consistency_time: Duration,
+
+    /// How many times we tried a word in each slot, and how many times one of each slot's options
+    /// was ruled out while backtracking, indexed by `SlotId`. Unlike the totals above, these cover
+    /// every attempt of a successful search, including the ones abandoned at their backtrack limits,
+    /// so that `crossing_report::hot_slots` can show where the search struggled along the way. They
+    /// are empty for backends that don't track them.
+    pub slot_choices: Vec<usize>,
+    pub slot_backtracks: Vec<usize>,
+
+    /// The fewest options each slot had at any step of the search, indexed by `SlotId` and
+    /// covering every attempt like the counts above; see `option_counts.rs`. This is empty for
+    /// backends that don't track it.
+    pub min_option_counts: Vec<usize>,
+}
+
+impl Statistics {
+    /// Add the counts and durations from a single fill attempt to these stats.
+    pub fn accumulate(&mut self, other: &Statistics) {
+        self.states += other.states;
+        self.backtracks += other.backtracks;
+        self.restricted_branchings += other.restricted_branchings;
+        self.try_time += other.try_time;
+        self.choice_arc_consistency_time += other.choice_arc_consistency_time;
+        self.elimination_arc_consistency_time += other.elimination_arc_consistency_time;
+        self.accumulate_slot_counts(other);
+    }
+
+    /// Add just the per-slot counts from a single fill attempt to these stats.
+    pub fn accumulate_slot_counts(&mut self, other: &Statistics) {
+        for (counts, other_counts) in [
+            (&mut self.slot_choices, &other.slot_choices),
+            (&mut self.slot_backtracks, &other.slot_backtracks),
+        ] {
+            if counts.len() < other_counts.len() {
+                counts.resize(other_counts.len(), 0);
+            }
+            for (count, other_count) in counts.iter_mut().zip(other_counts) {
+                *count += other_count;
+            }
+        }
+
+        if self.min_option_counts.is_empty() {
+            self.min_option_counts.clone_from(&other.min_option_counts);
+        } else {
+            for (count, &other_count) in self
+                .min_option_counts
+                .iter_mut()
+                .zip(&other.min_option_counts)
+            {
+                *count = (*count).min(other_count);
+            }
+        }
+    }
+}
+
+/// The options still available for a slot, as a bitset over their positions in the slot's
+/// `GridConfig::slot_options`. This lets us find the next few available options, in order, without
+/// scanning all of the eliminated ones in between, and options can be removed and restored in any
+/// order in constant time.
+#[derive(Debug, Clone)]
+pub struct LiveOptions {
+    /// The position of each word in the slot's options, indexed by `WordId`. This never changes, so
+    /// it's shared between copies.
+    positions: Arc<[Option<u32>]>,
+
+    bits: Vec<u64>,
+}
+
+impl LiveOptions {
+    /// Start with all of the given options available. `word_count` is the number of words of the
+    /// slot's length in the word list.
+    #[must_use]
+    pub fn new(options: &[WordId], word_count: usize) -> LiveOptions {
+        let mut positions = vec![None; word_count];
+        for (position, word_id) in options.iter().enumerate() {
+            positions[word_id.index()] = Some(position as u32);
+        }
+
+        let mut bits = vec![u64::MAX; options.len().div_ceil(64)];
+        if options.len() % 64 != 0 {
+            *bits.last_mut().unwrap() = (1 << (options.len() % 64)) - 1;
+        }
+
+        LiveOptions {
+            positions: positions.into(),
+            bits,
+        }
+    }
+
+    /// Mark the given word as eliminated. Words that aren't among the slot's options are ignored.
+    pub fn remove(&mut self, word_id: WordId) {
+        if let Some(position) = self.positions[word_id.index()] {
+            self.bits[position as usize / 64] &= !(1 << (position % 64));
+        }
+    }
+
+    /// Mark the given word as available again. Words that aren't among the slot's options are
+    /// ignored.
+    pub fn insert(&mut self, word_id: WordId) {
+        if let Some(position) = self.positions[word_id.index()] {
+            self.bits[position as usize / 64] |= 1 << (position % 64);
+        }
+    }
+
+    /// Iterate over the positions of the available options in the slot's options, in order,
+    /// starting at the given position.
+    pub fn positions_from(&self, start: usize) -> impl Iterator<Item = usize> + '_ {
+        let first_block_idx = start / 64;
+        (first_block_idx..self.bits.len()).flat_map(move |block_idx| {
+            let mut block = self.bits[block_idx];
+            if block_idx == first_block_idx {
+                block &= u64::MAX << (start % 64);
+            }
+            std::iter::from_fn(move || {
+                (block != 0).then(|| {
+                    let bit = block.trailing_zeros() as usize;
+                    block &= block - 1;
+                    block_idx * 64 + bit
+                })
+            })
+        })
+    }
 }
 
 /// A struct tracking the live state of a single slot during filling.
@@ -78,6 +636,10 @@ pub struct Slot {
     /// includes hidden words that aren't available for this fill attempt.
     pub remaining_option_count: usize,
 
+    /// The options that haven't been eliminated, in a form that's quick to iterate over in order.
+    /// This is kept in sync with `eliminations`.
+    pub live_options: LiveOptions,
+
     // The word id explicitly chosen for this slot during the fill process (or as part of the input
     // to the fill process), if there is one. This takes precedence over `eliminations`,
     // `glyph_counts_by_cell`, and `remaining_option_count`, which will be kept in the state they
@@ -118,10 +680,11 @@ impl Slot {
             "Editing eliminations for a fixed slot?"
         );
 
-        self.eliminations[word_id] = Some(blamed_slot_id);
+        self.eliminations[word_id.index()] = Some(blamed_slot_id);
+        self.live_options.remove(word_id);
         self.remaining_option_count -= 1;
 
-        let word = &config.word_list.words[self.length][word_id];
+        let word = &config.word_list.words[self.length][word_id.index()];
         for (cell_idx, &glyph) in word.glyphs.iter().enumerate() {
             self.glyph_counts_by_cell[cell_idx][glyph] -= 1;
         }
@@ -135,10 +698,11 @@ impl Slot {
             "Editing eliminations for a fixed slot?"
         );
 
-        self.eliminations[word_id] = None;
+        self.eliminations[word_id.index()] = None;
+        self.live_options.insert(word_id);
         self.remaining_option_count += 1;
 
-        let word = &config.word_list.words[self.length][word_id];
+        let word = &config.word_list.words[self.length][word_id.index()];
         for (cell_idx, &glyph) in word.glyphs.iter().enumerate() {
             self.glyph_counts_by_cell[cell_idx][glyph] += 1;
         }
@@ -146,8 +710,8 @@ impl Slot {
 
     /// Remove all eliminations that were created because of the last choice in the given slot.
     pub fn clear_eliminations(&mut self, config: &GridConfig, slot_id: SlotId) {
-        for word_id in 0..self.eliminations.len() {
-            if self.eliminations[word_id] == Some(Some(slot_id)) {
+        for word_id in WordId::range(self.eliminations.len()) {
+            if self.eliminations[word_id.index()] == Some(Some(slot_id)) {
                 self.remove_elimination(config, word_id);
             }
         }
@@ -157,7 +721,7 @@ impl Slot {
     pub fn choose_word(&mut self, config: &GridConfig, word_id: WordId) {
         self.fixed_word_id = Some(word_id);
         self.fixed_glyph_counts_by_cell = Some(build_glyph_counts_by_cell(
-            config.word_list,
+            &config.word_list,
             self.length,
             &[word_id],
         ));
@@ -170,7 +734,19 @@ impl Slot {
         self.fixed_glyph_counts_by_cell = None;
     }
 
+    /// How many options this slot effectively has: one if a word has been chosen for it, or
+    /// otherwise the number that haven't been eliminated.
+    #[must_use]
+    pub fn effective_option_count(&self) -> usize {
+        if self.fixed_word_id.is_some() {
+            1
+        } else {
+            self.remaining_option_count
+        }
+    }
+
     /// Build a Choice struct representing this slot's single remaining word.
+    #[must_use]
     pub fn get_choice(&self, config: &GridConfig) -> Option<Choice> {
         self.fixed_word_id
             .map(|word_id| Choice {
@@ -182,22 +758,20 @@ impl Slot {
                     #[cfg(feature = "check_invariants")]
                     {
                         assert_eq!(
-                            config.slot_options[self.id]
+                            config.slot_options[self.id.index()]
                                 .iter()
-                                .filter(|&&word_id| self.eliminations[word_id].is_none())
+                                .filter(|&&word_id| self.eliminations[word_id.index()].is_none())
                                 .count(),
                             1,
                             "slot with one remaining option must have eliminations for all others"
                         );
                     }
 
-                    let word_id = config.slot_options[self.id]
-                        .iter()
-                        .find(|&&word_id| self.eliminations[word_id].is_none());
+                    let position = self.live_options.positions_from(0).next();
 
-                    word_id.map(|&word_id| Choice {
+                    position.map(|position| Choice {
                         slot_id: self.id,
-                        word_id,
+                        word_id: config.slot_options[self.id.index()][position],
                     })
                 } else {
                     None
@@ -206,15 +780,35 @@ impl Slot {
     }
 }
 
+/// List the words that are still available for the given slot.
+pub(crate) fn available_options(config: &GridConfig, slot: &Slot) -> Vec<WordId> {
+    let options = &config.slot_options[slot.id.index()];
+    slot.live_options
+        .positions_from(0)
+        .map(|position| options[position])
+        .collect()
+}
+
+/// Undo a choice that was successfully committed, restoring any options it eliminated from other
+/// slots.
+pub(crate) fn undo_choice(config: &GridConfig, slots: &mut [Slot], slot_id: SlotId) {
+    slots[slot_id.index()].clear_choice();
+    for slot in slots.iter_mut() {
+        if slot.id != slot_id && slot.fixed_word_id.is_none() {
+            slot.clear_eliminations(config, slot_id);
+        }
+    }
+}
+
 /// Calculate the weight of a slot as defined in the `wdeg` heuristic, which is the sum of the
 /// weights of any crossings it has where the other slot is still undetermined.
 fn calculate_slot_weight(
     config: &GridConfig,
     slots: &[Slot],
-    crossing_weights: &[f32],
+    crossing_weights: &CrossingWeights,
     slot_id: SlotId,
 ) -> f32 {
-    config.slot_configs[slot_id]
+    config.slot_configs[slot_id.index()]
         .crossings
         .iter()
         .map(|crossing| match crossing {
@@ -222,25 +816,22 @@ fn calculate_slot_weight(
                 other_slot_id,
                 crossing_id,
                 ..
-            }) => {
-                if slots[*other_slot_id].remaining_option_count > 1 {
-                    crossing_weights[*crossing_id]
-                } else {
-                    0.0
-                }
+            }) if slots[other_slot_id.index()].remaining_option_count > 1 => {
+                crossing_weights[*crossing_id]
             }
-            None => 0.0,
+            _ => 0.0,
         })
         .sum()
 }
 
 /// Calculate the weights of all slots as defined in the `wdeg` heuristic.
+#[must_use]
 pub fn calculate_slot_weights(
     config: &GridConfig,
     slots: &[Slot],
-    crossing_weights: &[f32],
+    crossing_weights: &CrossingWeights,
 ) -> Vec<f32> {
-    (0..slots.len())
+    SlotId::range(slots.len())
         .map(|slot_id| calculate_slot_weight(config, slots, crossing_weights, slot_id))
         .collect()
 }
@@ -249,8 +840,12 @@ pub fn calculate_slot_weights(
 /// next (where lower is better). This is an implementation of a version of the `dom/wdeg`
 /// heuristic, although the specific meaning of the "weight" of each crossing depends on
 /// our implementation of arc consistency.
-fn calculate_slot_priority(slots: &[Slot], slot_weights: &[f32], slot_id: SlotId) -> f32 {
-    (slots[slot_id].remaining_option_count as f32) / slot_weights[slot_id]
+pub(crate) fn calculate_slot_priority(
+    slots: &[Slot],
+    slot_weights: &[f32],
+    slot_id: SlotId,
+) -> f32 {
+    (slots[slot_id.index()].remaining_option_count as f32) / slot_weights[slot_id.index()]
 }
 
 #[derive(Debug)]
@@ -264,10 +859,10 @@ pub enum ArcConsistencyMode {
 /// impact of a choice, or propagate the impact of an elimination. Also update crossing weights
 /// if it turns out to be impossible to achieve consistency (a "domain wipeout").
 #[allow(clippy::too_many_lines)]
-fn maintain_arc_consistency(
+pub(crate) fn maintain_arc_consistency(
     config: &GridConfig,
     slots: &mut [Slot],
-    crossing_weights: &mut [f32],
+    crossing_weights: &mut CrossingWeights,
     slot_weights: &[f32],
     mode: &ArcConsistencyMode,
     time: &mut Duration,
@@ -280,14 +875,14 @@ fn maintain_arc_consistency(
 
     impl ArcConsistencyAdapter for Adapter<'_> {
         fn is_word_eliminated(&self, slot_id: SlotId, word_id: WordId) -> bool {
-            self.slots[slot_id].eliminations[word_id].is_some()
+            self.slots[slot_id.index()].eliminations[word_id.index()].is_some()
         }
 
         fn get_glyph_counts(&self, slot_id: SlotId) -> GlyphCountsByCell {
-            self.slots[slot_id]
+            self.slots[slot_id.index()]
                 .fixed_glyph_counts_by_cell
                 .clone()
-                .unwrap_or_else(|| self.slots[slot_id].glyph_counts_by_cell.clone())
+                .unwrap_or_else(|| self.slots[slot_id.index()].glyph_counts_by_cell.clone())
         }
 
         fn get_single_option(
@@ -295,13 +890,13 @@ fn maintain_arc_consistency(
             slot_id: SlotId,
             eliminations: &EliminationSet,
         ) -> Option<WordId> {
-            self.slots[slot_id].fixed_word_id.or_else(|| {
+            self.slots[slot_id.index()].fixed_word_id.or_else(|| {
                 #[cfg(feature = "check_invariants")]
                 {
-                    let first_two = self.config.slot_options[slot_id]
+                    let first_two = self.config.slot_options[slot_id.index()]
                         .iter()
                         .filter(|&word_id| {
-                            self.slots[slot_id].eliminations[*word_id].is_none()
+                            self.slots[slot_id.index()].eliminations[word_id.index()].is_none()
                                 && !eliminations.contains(*word_id)
                         })
                         .copied()
@@ -318,10 +913,10 @@ fn maintain_arc_consistency(
                 }
 
                 #[cfg(not(feature = "check_invariants"))]
-                self.config.slot_options[slot_id]
+                self.config.slot_options[slot_id.index()]
                     .iter()
                     .find(|&word_id| {
-                        self.slots[slot_id].eliminations[*word_id].is_none()
+                        self.slots[slot_id.index()].eliminations[word_id.index()].is_none()
                             && !eliminations.contains(*word_id)
                     })
                     .copied()
@@ -334,15 +929,15 @@ fn maintain_arc_consistency(
     // First, if we're testing a choice or elimination, update the relevant state provisionally.
     match mode {
         ArcConsistencyMode::Choice(choice) => {
-            slots[choice.slot_id].choose_word(config, choice.word_id);
+            slots[choice.slot_id.index()].choose_word(config, choice.word_id);
         }
 
         ArcConsistencyMode::Elimination(choice, blamed_slot_id) => {
-            slots[choice.slot_id].add_elimination(config, choice.word_id, *blamed_slot_id);
+            slots[choice.slot_id.index()].add_elimination(config, choice.word_id, *blamed_slot_id);
         }
 
         ArcConsistencyMode::Initial => {}
-    };
+    }
 
     let remaining_option_counts = slots
         .iter()
@@ -372,7 +967,7 @@ fn maintain_arc_consistency(
             // the grid. Also if we're evaluating a choice we'll treat that choice's slot as fixed.
             slots
                 .iter()
-                .map(|slot| remaining_option_counts[slot.id] == 1)
+                .map(|slot| remaining_option_counts[slot.id.index()] == 1)
                 .collect()
         }
     };
@@ -416,22 +1011,18 @@ fn maintain_arc_consistency(
         Err(ArcConsistencyFailure { weight_updates }) => {
             match mode {
                 ArcConsistencyMode::Choice(choice) => {
-                    slots[choice.slot_id].clear_choice();
+                    slots[choice.slot_id.index()].clear_choice();
                 }
 
                 ArcConsistencyMode::Elimination(choice, ..) => {
-                    slots[choice.slot_id].remove_elimination(config, choice.word_id);
+                    slots[choice.slot_id.index()].remove_elimination(config, choice.word_id);
                 }
 
                 ArcConsistencyMode::Initial => {}
-            };
-
-            for (slot_id, weight) in crossing_weights.iter_mut().enumerate() {
-                *weight = 1.0
-                    + ((*weight - 1.0) * WEIGHT_AGE_FACTOR)
-                    + weight_updates.get(&slot_id).unwrap_or(&0.0);
             }
 
+            crossing_weights.record_wipeout(&weight_updates);
+
             false
         }
     };
@@ -443,23 +1034,26 @@ fn maintain_arc_consistency(
 
 /// Identify the next slot we should try to fill, based on a combination of the `dom/wdeg` priority
 /// algorithm with an "adaptive branching" strategy that stays on the same slot if the "best" one
-/// is close enough in priority.
+/// is close enough in priority. If `last_conflict_slot_id` is given and that slot is still
+/// undetermined, it takes precedence over both.
 pub fn choose_next_slot(
     slots: &[Slot],
     slot_weights: &[f32],
     last_slot_id: Option<SlotId>,
-    rng: &mut SmallRng,
+    last_conflict_slot_id: Option<SlotId>,
+    rng: &mut dyn RngCore,
     dist: &WeightedIndex<u8>,
     statistics: &mut Statistics,
 ) -> Option<SlotId> {
     let mut best_slot_priority: Option<f32> = None;
     let mut last_slot_priority: Option<f32> = None;
 
-    let mut sorted_slot_ids: Vec<_> = (0..slots.len())
+    let mut sorted_slot_ids: Vec<_> = SlotId::range(slots.len())
         .filter(|&slot_id| {
             // If the slot only has one option, whether it was chosen explicitly or implicitly, we can
             // just leave it alone.
-            slots[slot_id].fixed_word_id.is_none() && slots[slot_id].remaining_option_count > 1
+            slots[slot_id.index()].fixed_word_id.is_none()
+                && slots[slot_id.index()].remaining_option_count > 1
         })
         .collect();
 
@@ -468,6 +1062,13 @@ pub fn choose_next_slot(
         return None;
     }
 
+    // If the slot involved in the most recent conflict is still open, go back to it.
+    if let Some(last_conflict_slot_id) = last_conflict_slot_id {
+        if sorted_slot_ids.contains(&last_conflict_slot_id) {
+            return Some(last_conflict_slot_id);
+        }
+    }
+
     // Otherwise, sort the remaining slots by priority.
     sorted_slot_ids.sort_by_cached_key(|&slot_id| {
         let priority = calculate_slot_priority(slots, slot_weights, slot_id);
@@ -498,81 +1099,474 @@ pub fn choose_next_slot(
 }
 
 /// A struct representing the results of a fill operation.
-#[derive(Debug)]
+#[derive(Debug, Default)]
 #[allow(dead_code)]
 pub struct FillSuccess {
     pub statistics: Statistics,
     pub choices: Vec<Choice>,
+
+    /// `WordList::content_hash` for the word list the fill came from.
+    pub word_list_hash: u64,
+
+    /// `config_fingerprint` for the grid and settings that produced the fill. Together with
+    /// `word_list_hash`, this identifies every input to the search apart from its random seeds, so
+    /// that a published fill can record what produced it.
+    pub config_fingerprint: u64,
+
+    /// Details about each slot's word and how the search arrived at it, indexed by `SlotId`.
+    /// Backends that don't track these details (like the SAT solver) report zero alternatives and
+    /// backtracks for every slot.
+    pub slot_stats: Vec<SlotFillStats>,
+
+    /// If requested with `SearchConfig::record_history`, every choice made, rejected, or undone by
+    /// the attempt that produced the fill, in order. Applying the `Chosen` and `Undone` events in
+    /// turn reproduces the words the search had chosen explicitly at each point. This is empty for
+    /// backends that don't record it.
+    pub history: Vec<ChoiceEvent>,
+
+    /// The crossing weights at the end of the search, as in `PartialFill::crossing_weights`. This
+    /// is empty for backends that don't track them.
+    pub crossing_weights: CrossingWeights,
 }
 
-#[derive(Debug)]
-pub enum FillFailure {
-    HardFailure,
-    Timeout,
-    Abort,
-    ExceededBacktrackLimit(usize),
+/// What happened to a choice at one point in a search; see `ChoiceEvent`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChoiceEventKind {
+    /// The choice was made, and its implications were propagated successfully.
+    Chosen,
+
+    /// The choice was tried, but ruled out because its implications couldn't be propagated.
+    Rejected,
+
+    /// A choice made earlier was undone while backtracking, after all of the alternatives to the
+    /// choices made since turned out to be impossible.
+    Undone,
 }
 
-/// Search for a valid fill for the given grid, bailing out if we reach the deadline or the
-/// specified number of backtracks. We receive some state as arguments that can be shared between
-/// multiple retries of the same overall search attempt.
-#[allow(clippy::too_many_lines)]
-pub fn find_fill_for_seed(
-    config: &GridConfig,
-    slots: &Vec<Slot>,
-    deadline: Option<Instant>,
-    max_backtracks: usize,
-    rng_seed: u64,
-    crossing_weights: &mut [f32],
-    elimination_sets: &mut [EliminationSet],
-) -> Result<FillSuccess, FillFailure> {
-    let start = Instant::now();
-    let mut rng: SmallRng = SeedableRng::seed_from_u64(rng_seed);
-    let mut statistics = Statistics::default();
+/// A single step in the history of a search; see `FillSuccess::history`.
+#[derive(Debug, Clone)]
+pub struct ChoiceEvent {
+    pub kind: ChoiceEventKind,
+    pub choice: Choice,
 
-    let mut slots: Vec<Slot> = (*slots).clone();
+    /// The number of states the attempt had visited when the event happened (see
+    /// `Statistics::states`), and how long the attempt had been running.
+    pub state: usize,
+    pub elapsed: Duration,
+}
 
-    // Track slot choices made so far in the process.
-    let mut choices: Vec<Choice> = Vec::with_capacity(config.slot_configs.len());
+/// A summary of how a single slot was filled; see `FillSuccess::slot_stats`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlotFillStats {
+    pub slot_id: SlotId,
+    pub word_id: WordId,
+    pub score: u16,
 
-    let mut last_slot_id: Option<SlotId> = None;
-    let mut last_starting_word_idx: Option<usize> = None;
+    /// How many other options the slot still had when the search chose its word. This is 0 for
+    /// slots whose words were fixed by the input grid or implied by the choices in other slots.
+    pub alternatives: usize,
 
-    let slot_dist = WeightedIndex::new(RANDOM_SLOT_WEIGHTS).unwrap();
-    let word_dist = WeightedIndex::new(RANDOM_WORD_WEIGHTS).unwrap();
+    /// How many times one of the slot's options was ruled out while backtracking, in the attempt
+    /// that produced the fill.
+    pub backtracks: usize,
+}
 
-    // Enter the main loop:
-    // * Choose an option for a slot and try to propagate constraints for it. If we succeed, we keep
-    //   the choice and continue the loop.
-    // * If we failed to choose an option, record that the option is unavailable and try to
-    //   propagate constraints for that. If we succeed, we continue the loop, most likely trying to
-    //   pick another option for the same slot but also potentially changing slots.
-    // * If we also failed to propagate constraints with the chosen option being *un*available, it
-    //   means the previous choice we made is untenable. Try to undo it and propagate the
-    //   information that *that* choice is unavailable. Repeat until we reach a viable state, or
-    //   abandon the fill attempt if we can't.
-    loop {
-        statistics.states += 1;
+/// Build `FillSuccess::slot_stats` for the given choices (one per slot, in order of `SlotId`). The
+/// counts are indexed by `SlotId`, and any that are missing are treated as 0.
+#[must_use]
+pub fn slot_fill_stats(
+    config: &GridConfig,
+    choices: &[Choice],
+    alternatives_by_slot: &[usize],
+    backtracks_by_slot: &[usize],
+) -> Vec<SlotFillStats> {
+    choices
+        .iter()
+        .map(|choice| {
+            let length = config.slot_configs[choice.slot_id.index()].length;
+            SlotFillStats {
+                slot_id: choice.slot_id,
+                word_id: choice.word_id,
+                score: config.word_list.words[length][choice.word_id.index()].score,
+                alternatives: alternatives_by_slot
+                    .get(choice.slot_id.index())
+                    .copied()
+                    .unwrap_or(0),
+                backtracks: backtracks_by_slot
+                    .get(choice.slot_id.index())
+                    .copied()
+                    .unwrap_or(0),
+            }
+        })
+        .collect()
+}
 
-        if statistics.states % INTERRUPT_FREQUENCY == 0 {
-            if let Some(deadline) = deadline {
-                if Instant::now() > deadline {
-                    return Err(FillFailure::Timeout);
-                }
+impl FillSuccess {
+    /// Fill in `word_list_hash` and `config_fingerprint` for a fill of the given grid.
+    pub fn record_inputs(&mut self, config: &GridConfig, search_config: Option<&SearchConfig>) {
+        self.word_list_hash = config.word_list.content_hash();
+        self.config_fingerprint = config_fingerprint(config, search_config);
+    }
+
+    /// Flatten the statistics, crossing weights, and per-slot details of this fill of the given
+    /// grid into a single JSON document; see `fill_stats.rs`.
+    #[cfg(feature = "serde")]
+    #[must_use]
+    pub fn stats_json(&self, config: &GridConfig) -> String {
+        crate::fill_stats::FillStats::new(config, self).to_json()
+    }
+}
+
+/// A stable hash of the given grid (its fill and each slot's options) and search settings, apart
+/// from the source of randomness (`SearchConfig::rng`), which is treated like the seeds. The
+/// search config is `None` for backends that don't take one, like the SAT solver and the Wasm
+/// search. Options are identified by word id, so fingerprints are only comparable between fills
+/// using the same word list, as identified by `WordList::content_hash`.
+#[must_use]
+pub fn config_fingerprint(config: &GridConfig, search_config: Option<&SearchConfig>) -> u64 {
+    let mut hasher = StableHasher::new();
+
+    hasher.write_usize(config.width);
+    hasher.write_usize(config.height);
+    for cell in config.fill.iter() {
+        match cell {
+            Some(glyph_id) => hasher.write_u32(config.word_list.glyphs[*glyph_id] as u32),
+            None => hasher.write_u32(u32::MAX),
+        }
+    }
+    for (slot_config, options) in config.slot_configs.iter().zip(config.slot_options.iter()) {
+        hasher.write_usize(slot_config.start_cell.0);
+        hasher.write_usize(slot_config.start_cell.1);
+        hasher.write_u8(slot_config.direction as u8);
+        hasher.write_usize(slot_config.length);
+        hasher.write_usize(options.len());
+        for word_id in options {
+            hasher.write_usize(word_id.index());
+        }
+    }
+    hasher.write_usize(config.word_list.dupe_index.window_size());
+    if let Some(crossing_quality) = config.crossing_quality {
+        hasher.write_u8(1);
+        hasher.write_u16(crossing_quality.weak_score);
+        hasher.write_u16(crossing_quality.fair_score);
+    } else {
+        hasher.write_u8(0);
+    }
+    let dupe_rules = config
+        .dupe_policy
+        .as_deref()
+        .map_or(&[][..], DupePolicy::rules);
+    hasher.write_usize(dupe_rules.len());
+    for rule in dupe_rules {
+        hasher.write_str(&rule.tag);
+        hasher.write_usize(
+            rule.max_shared_within
+                .map_or(0, |max_shared| max_shared + 1),
+        );
+        hasher.write_usize(
+            rule.max_shared_with_others
+                .map_or(0, |max_shared| max_shared + 1),
+        );
+    }
+
+    if let Some(search_config) = search_config {
+        hasher.write_u8(1);
+        hasher.write_u8(search_config.slot_heuristic as u8);
+        hasher.write_u8(u8::from(search_config.last_conflict));
+        hasher.write_u8(u8::from(search_config.favor_fresh_words));
+        hasher.write_u8(search_config.strategy as u8);
+        hasher.write_usize(search_config.candidate_window.size);
+        hasher.write_u32(search_config.candidate_window.decay.to_bits());
+        hasher.write_u8(search_config.candidate_window.order as u8);
+        match search_config.weight_decay {
+            WeightDecay::None => hasher.write_u8(0),
+            WeightDecay::Exponential(factor) => {
+                hasher.write_u8(1);
+                hasher.write_u32(factor.to_bits());
+            }
+            WeightDecay::Windowed(window_size) => {
+                hasher.write_u8(2);
+                hasher.write_usize(window_size);
             }
         }
-        if let Some(abort) = config.abort {
-            if abort.load(Ordering::Relaxed) {
-                return Err(FillFailure::Abort);
+        match search_config.retry_budget {
+            RetryBudget::Backtracks(max_backtracks) => {
+                hasher.write_u8(0);
+                hasher.write_usize(max_backtracks);
+            }
+            RetryBudget::Time(max_time) => {
+                hasher.write_u8(1);
+                hasher.write_u128(max_time.as_nanos());
             }
+            RetryBudget::States(max_states) => {
+                hasher.write_u8(2);
+                hasher.write_usize(max_states);
+            }
+        }
+        if let Some(required_words) = &search_config.required_words {
+            required_words.hash_into(&mut hasher);
         }
+        for tag_cap in &search_config.tag_caps {
+            tag_cap.hash_into(&mut hasher);
+        }
+    } else {
+        hasher.write_u8(0);
+    }
 
-        // Choose which slot to try to fill.
-        let slot_weights = calculate_slot_weights(config, &slots, crossing_weights);
-        let Some(slot_id) = choose_next_slot(
-            &slots,
-            &slot_weights,
-            last_slot_id,
+    hasher.finish()
+}
+
+/// Information collected by a search that was interrupted before it could finish.
+#[derive(Debug, Clone, Default)]
+pub struct PartialFill {
+    /// Stats about the search process, accumulated across all retries so far.
+    pub statistics: Statistics,
+
+    /// The choices (explicit or implied) making up the most complete partial fill we reached.
+    pub deepest_choices: Vec<Choice>,
+
+    /// The crossing weights at the time we stopped, indexed by `CrossingId`. These can be passed to
+    /// `crossing_report::explain_crossing_weights` to see which parts of the grid caused the most
+    /// trouble. This is empty if the search was stopped before it started learning weights, or if
+    /// the backend doesn't use them.
+    pub crossing_weights: CrossingWeights,
+
+    /// After a hard failure, a self-contained reproduction of the failed search, if it was
+    /// requested with `SearchConfig::capture_failure_bundle`.
+    pub failure_bundle: Option<Box<FailureBundle>>,
+
+    /// The slot that the search had backtracked out of most often when an attempt last used up its
+    /// retry budget, with that slot's options at the time. This is always present with
+    /// `FillFailure::ExceededBacktrackLimit` (unless the search never backtracked at all), and
+    /// also with other failures that came after at least one restart. It's `None` for backends
+    /// that don't track it.
+    pub stuck_slot: Option<StuckSlot>,
+}
+
+/// The slot a restart search had the most trouble with, as reported in `PartialFill::stuck_slot`.
+/// This gives a frontend something concrete to point the constructor at ("the SW corner is the
+/// problem") when a fill gives up without proving anything.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StuckSlot {
+    pub slot_id: SlotId,
+
+    /// How many times one of the slot's options was ruled out while backtracking, across all
+    /// attempts so far; see `Statistics::slot_backtracks`.
+    pub backtracks: usize,
+
+    /// The slot's remaining options when the attempt stopped, in `GridConfig::slot_options` order,
+    /// or just its word if one had been chosen for it.
+    pub remaining_options: Vec<WordId>,
+}
+
+impl StuckSlot {
+    /// Find the slot with the most backtracks in the given counts (breaking ties by slot id) and
+    /// capture its options in the given search state.
+    fn find(config: &GridConfig, slots: &[Slot], slot_backtracks: &[usize]) -> Option<StuckSlot> {
+        let (slot_idx, &backtracks) = slot_backtracks
+            .iter()
+            .enumerate()
+            .filter(|&(_, &backtracks)| backtracks > 0)
+            .max_by_key(|&(slot_idx, &backtracks)| (backtracks, Reverse(slot_idx)))?;
+        let slot = &slots[slot_idx];
+
+        let remaining_options = match slot.fixed_word_id {
+            Some(word_id) => vec![word_id],
+            None => slot
+                .live_options
+                .positions_from(0)
+                .map(|position| config.slot_options[slot_idx][position])
+                .collect(),
+        };
+
+        Some(StuckSlot {
+            slot_id: slot.id,
+            backtracks,
+            remaining_options,
+        })
+    }
+}
+
+impl PartialFill {
+    /// If the given state determines more slots than the deepest one we've seen, record it.
+    pub(crate) fn record_depth(&mut self, config: &GridConfig, slots: &[Slot]) {
+        let depth = slots
+            .iter()
+            .filter(|slot| slot.fixed_word_id.is_some() || slot.remaining_option_count == 1)
+            .count();
+
+        if depth > self.deepest_choices.len() {
+            self.deepest_choices = slots
+                .iter()
+                .filter_map(|slot| slot.get_choice(config))
+                .collect();
+        }
+    }
+}
+
+/// The ways a fill attempt can fail. Each one carries the information collected before the search
+/// stopped, including the learned crossing weights.
+#[derive(Debug)]
+pub enum FillFailure {
+    /// The grid has been proven to have no valid fill.
+    HardFailure(Box<PartialFill>),
+    Timeout(Box<PartialFill>),
+    Abort(Box<PartialFill>),
+    ExceededBacktrackLimit(usize, Box<PartialFill>),
+}
+
+impl FillFailure {
+    /// The information collected before the search stopped.
+    #[must_use]
+    pub fn partial(&self) -> &PartialFill {
+        match self {
+            FillFailure::HardFailure(partial)
+            | FillFailure::Timeout(partial)
+            | FillFailure::Abort(partial)
+            | FillFailure::ExceededBacktrackLimit(_, partial) => partial,
+        }
+    }
+
+    pub(crate) fn partial_mut(&mut self) -> &mut PartialFill {
+        match self {
+            FillFailure::HardFailure(partial)
+            | FillFailure::Timeout(partial)
+            | FillFailure::Abort(partial)
+            | FillFailure::ExceededBacktrackLimit(_, partial) => partial,
+        }
+    }
+}
+
+/// Search for a valid fill for the given grid, bailing out if we reach the deadline or use up the
+/// given retry budget. We receive some state as arguments that can be shared between
+/// multiple retries of the same overall search attempt.
+#[allow(clippy::too_many_lines)]
+#[allow(clippy::too_many_arguments)]
+pub fn find_fill_for_seed(
+    config: &GridConfig,
+    slots: &Vec<Slot>,
+    deadline: Option<Instant>,
+    retry_budget: RetryBudget,
+    rng_seed: u64,
+    crossing_weights: &mut CrossingWeights,
+    elimination_sets: &mut [EliminationSet],
+    search_config: &SearchConfig,
+    impacts: &mut ImpactTracker,
+    partial: &mut PartialFill,
+) -> Result<FillSuccess, FillFailure> {
+    let start = Instant::now();
+    let mut rng = search_config.rng.build(rng_seed);
+    let mut statistics = Statistics {
+        slot_choices: vec![0; config.slot_configs.len()],
+        slot_backtracks: vec![0; config.slot_configs.len()],
+        min_option_counts: vec![usize::MAX; config.slot_configs.len()],
+        ..Statistics::default()
+    };
+
+    let mut slots: Vec<Slot> = (*slots).clone();
+
+    // Track slot choices made so far in the process, along with how many other options each slot
+    // had when we made its choice.
+    let mut choices: Vec<Choice> = Vec::with_capacity(config.slot_configs.len());
+    let mut choice_alternatives: Vec<usize> = Vec::with_capacity(config.slot_configs.len());
+
+    // If requested, also track every choice we make or undo, for `FillSuccess::history`.
+    let mut history: Vec<ChoiceEvent> = vec![];
+    let record_event =
+        |history: &mut Vec<ChoiceEvent>, kind: ChoiceEventKind, choice: &Choice, state: usize| {
+            if search_config.record_history {
+                history.push(ChoiceEvent {
+                    kind,
+                    choice: choice.clone(),
+                    state,
+                    elapsed: start.elapsed(),
+                });
+            }
+        };
+
+    let mut last_slot_id: Option<SlotId> = None;
+    let mut last_starting_word_idx: Option<usize> = None;
+    let mut last_conflict_slot_id: Option<SlotId> = None;
+
+    let slot_dist = WeightedIndex::new(RANDOM_SLOT_WEIGHTS).unwrap();
+    let max_options = config.slot_options.iter().map(Vec::len).max().unwrap_or(0);
+    let (window_size, word_dist) = search_config.candidate_window.distribution(max_options);
+
+    // Required words and tag caps are checked after every choice and elimination, so a starting
+    // state that already violates them means there's no fill at all.
+    let required_words = search_config
+        .required_words
+        .as_ref()
+        .filter(|required_words| !required_words.is_empty());
+    let global_constraints_satisfiable = |slots: &[Slot]| {
+        required_words.map_or(true, |required| required.is_satisfiable(slots))
+            && tag_caps_satisfied(config, slots, &search_config.tag_caps)
+    };
+    if !global_constraints_satisfiable(&slots) {
+        statistics.try_time = start.elapsed();
+        partial.statistics.accumulate(&statistics);
+        partial.crossing_weights = crossing_weights.clone();
+        return Err(FillFailure::HardFailure(Box::new(std::mem::take(partial))));
+    }
+
+    // Enter the main loop:
+    // * Choose an option for a slot and try to propagate constraints for it. If we succeed, we keep
+    //   the choice and continue the loop.
+    // * If we failed to choose an option, record that the option is unavailable and try to
+    //   propagate constraints for that. If we succeed, we continue the loop, most likely trying to
+    //   pick another option for the same slot but also potentially changing slots.
+    // * If we also failed to propagate constraints with the chosen option being *un*available, it
+    //   means the previous choice we made is untenable. Try to undo it and propagate the
+    //   information that *that* choice is unavailable. Repeat until we reach a viable state, or
+    //   abandon the fill attempt if we can't.
+    loop {
+        statistics.states += 1;
+        for (min_count, slot) in statistics.min_option_counts.iter_mut().zip(&slots) {
+            *min_count = (*min_count).min(slot.effective_option_count());
+        }
+        if let Some(option_counts) = &config.option_counts {
+            option_counts.record(&slots);
+        }
+
+        #[cfg(feature = "check_invariants")]
+        crate::audit::audit_consistency(config, &slots).expect("fill state is inconsistent");
+
+        if statistics.states % INTERRUPT_FREQUENCY == 0 {
+            if let Some(deadline) = deadline {
+                if Instant::now() > deadline {
+                    statistics.try_time = start.elapsed();
+                    partial.statistics.accumulate(&statistics);
+                    partial.crossing_weights = crossing_weights.clone();
+                    return Err(FillFailure::Timeout(Box::new(std::mem::take(partial))));
+                }
+            }
+        }
+        if let Some(cancel) = &config.cancel {
+            // Only read the clock for the token's deadline as often as we check our own.
+            let cancelled = if statistics.states % INTERRUPT_FREQUENCY == 0 {
+                cancel.is_cancelled()
+            } else {
+                cancel.was_cancelled()
+            };
+            if cancelled {
+                statistics.try_time = start.elapsed();
+                partial.statistics.accumulate(&statistics);
+                partial.crossing_weights = crossing_weights.clone();
+                return Err(FillFailure::Abort(Box::new(std::mem::take(partial))));
+            }
+        }
+
+        // Choose which slot to try to fill.
+        let slot_weights = calculate_slot_weights(config, &slots, crossing_weights);
+        let selection_weights = match search_config.slot_heuristic {
+            SlotHeuristic::DomWdeg => None,
+            SlotHeuristic::Impact => Some(impacts.calculate_slot_weights()),
+        };
+        let Some(slot_id) = choose_next_slot(
+            &slots,
+            selection_weights.as_ref().unwrap_or(&slot_weights),
+            last_slot_id,
+            last_conflict_slot_id,
             &mut rng,
             &slot_dist,
             &mut statistics,
@@ -582,7 +1576,11 @@ pub fn find_fill_for_seed(
 
             // We need to build a `choices` array that includes both choices we made explicitly
             // and ones that were made implicitly by maintaining arc consistency.
-            let choices = slots
+            let mut alternatives_by_slot = vec![0; config.slot_configs.len()];
+            for (choice, &alternatives) in choices.iter().zip(&choice_alternatives) {
+                alternatives_by_slot[choice.slot_id.index()] = alternatives;
+            }
+            let choices: Vec<Choice> = slots
                 .into_iter()
                 .map(|slot| {
                     slot.get_choice(config)
@@ -591,8 +1589,16 @@ pub fn find_fill_for_seed(
                 .collect();
 
             return Ok(FillSuccess {
+                slot_stats: slot_fill_stats(
+                    config,
+                    &choices,
+                    &alternatives_by_slot,
+                    &statistics.slot_backtracks,
+                ),
                 statistics,
                 choices,
+                history,
+                ..FillSuccess::default()
             });
         };
 
@@ -604,34 +1610,67 @@ pub fn find_fill_for_seed(
             0
         };
 
-        // Take as many available candidate words as we have weights in `RANDOM_WORD_WEIGHTS`.
-        let word_candidates: Vec<(usize, &WordId)> = config.slot_options[slot_id]
-            .iter()
-            .enumerate()
-            .skip(starting_word_idx)
-            .filter(|&(_, &word_id)| slots[slot_id].eliminations[word_id].is_none())
-            .take(RANDOM_WORD_WEIGHTS.len())
+        // Take as many available candidate words as we have weights for.
+        let options = &config.slot_options[slot_id.index()];
+        let mut word_candidates: Vec<(usize, &WordId)> = slots[slot_id.index()]
+            .live_options
+            .positions_from(starting_word_idx)
+            .take(window_size)
+            .map(|position| (position, &options[position]))
             .collect();
 
         assert!(
             !word_candidates.is_empty(),
             "Unable to find option for slot {:?}",
-            slots[slot_id]
+            slots[slot_id.index()]
         );
 
-        // Choose one of the candidates at (weighted) random.
-        let (_, &word_id) =
-            word_candidates[word_dist.sample(&mut rng).min(word_candidates.len() - 1)];
-
         // Record our position so we can pick up where we left off if needed, using the first
         // candidate index to make sure we don't skip any words.
         last_slot_id = Some(slot_id);
         last_starting_word_idx = Some(word_candidates[0].0);
 
-        let choice = Choice { slot_id, word_id };
+        // Rank the candidates as requested. The impact and freshness orderings below are stable, so
+        // this still breaks ties between them.
+        let length = config.slot_configs[slot_id.index()].length;
+        match search_config.candidate_window.order {
+            CandidateOrder::SlotOptions => {}
+            CandidateOrder::Score => {
+                sort_candidates_by_score(&config.word_list, length, &mut word_candidates);
+            }
+            CandidateOrder::ScoreThenRandom => {
+                word_candidates.shuffle(&mut rng);
+                sort_candidates_by_score(&config.word_list, length, &mut word_candidates);
+            }
+        }
+
+        // If we're using impacts, favor the candidates that have constrained the grid the least.
+        if search_config.slot_heuristic == SlotHeuristic::Impact {
+            impacts.sort_candidates(slot_id, &mut word_candidates);
+        }
+
+        // If we're favoring fresh words, move them to the front, keeping the existing order among
+        // equally fresh candidates.
+        if search_config.favor_fresh_words {
+            sort_candidates_by_freshness(&config.word_list, length, &mut word_candidates);
+        }
+
+        // Choose one of the candidates at (weighted) random.
+        let (_, &word_id) =
+            word_candidates[word_dist.sample(&mut rng).min(word_candidates.len() - 1)];
 
-        // Try to propagate the implications of making this choice to the rest of the grid.
-        if maintain_arc_consistency(
+        let choice = Choice { slot_id, word_id };
+        statistics.slot_choices[slot_id.index()] += 1;
+        let alternatives = slots[slot_id.index()]
+            .remaining_option_count
+            .saturating_sub(1);
+        let log_size_before = (search_config.slot_heuristic == SlotHeuristic::Impact)
+            .then(|| log_search_space_size(&slots));
+
+        // Try to propagate the implications of making this choice to the rest of the grid. If
+        // that leaves no room for the required words or exceeds a tag cap, we treat it as a failure
+        // too.
+        let mut choice_succeeded = maintain_arc_consistency(
             config,
             &mut slots,
             crossing_weights,
@@ -639,19 +1678,56 @@ pub fn find_fill_for_seed(
             &ArcConsistencyMode::Choice(choice.clone()),
             &mut statistics.choice_arc_consistency_time,
             elimination_sets,
-        ) {
+        );
+        if choice_succeeded && !global_constraints_satisfiable(&slots) {
+            undo_choice(config, &mut slots, slot_id);
+            choice_succeeded = false;
+        }
+
+        if let Some(log_size_before) = log_size_before {
+            let impact = if choice_succeeded {
+                1.0 - (log_search_space_size(&slots) - log_size_before).exp()
+            } else {
+                1.0
+            };
+            impacts.record(&choice, impact.clamp(0.0, 1.0));
+        }
+
+        if choice_succeeded {
             // If we successfully propagated constraints for this choice, we can record it and
             // move on to the next slot.
+            if last_conflict_slot_id == Some(slot_id) {
+                last_conflict_slot_id = None;
+            }
+            partial.record_depth(config, &slots);
+            record_event(
+                &mut history,
+                ChoiceEventKind::Chosen,
+                &choice,
+                statistics.states,
+            );
             choices.push(choice);
+            choice_alternatives.push(alternatives);
             continue;
         }
 
+        if search_config.last_conflict {
+            last_conflict_slot_id = Some(slot_id);
+        }
+        record_event(
+            &mut history,
+            ChoiceEventKind::Rejected,
+            &choice,
+            statistics.states,
+        );
+
         // Otherwise, we can rule this option out. If we can successfully propagate the implications
         // of that elimination, we can move on to the next slot; otherwise, we need to keep
         // backtracking until we find a choice we can successfully propagate the reversal of.
         let mut undoing_choice = choice;
         loop {
             statistics.backtracks += 1;
+            statistics.slot_backtracks[undoing_choice.slot_id.index()] += 1;
 
             if maintain_arc_consistency(
                 config,
@@ -664,7 +1740,8 @@ pub fn find_fill_for_seed(
                 ),
                 &mut statistics.elimination_arc_consistency_time,
                 elimination_sets,
-            ) {
+            ) && global_constraints_satisfiable(&slots)
+            {
                 // If we successfully propagated constraints for this elimination, we're done
                 // backtracking and can return to the top-level loop.
                 break;
@@ -675,14 +1752,24 @@ pub fn find_fill_for_seed(
             // nor `slot_id != word_id` are possible. We should undo the impact of that
             // choice and then continue the backtracking loop to see if it's possible to propagate
             // the opposite of the choice.
+            choice_alternatives.pop();
             let Some(last_choice) = choices.pop() else {
                 // If there are no previous choices, we've now proven that the whole grid is
                 // unsolvable.
-                return Err(FillFailure::HardFailure);
+                statistics.try_time = start.elapsed();
+                partial.statistics.accumulate(&statistics);
+                partial.crossing_weights = crossing_weights.clone();
+                return Err(FillFailure::HardFailure(Box::new(std::mem::take(partial))));
             };
+            record_event(
+                &mut history,
+                ChoiceEventKind::Undone,
+                &last_choice,
+                statistics.states,
+            );
             undoing_choice = last_choice;
 
-            slots[undoing_choice.slot_id].clear_choice();
+            slots[undoing_choice.slot_id.index()].clear_choice();
 
             for slot in &mut slots {
                 if slot.id != undoing_choice.slot_id && slot.fixed_word_id.is_none() {
@@ -690,9 +1777,20 @@ pub fn find_fill_for_seed(
                 }
             }
 
-            // If we've exceeded our backtrack limit, restart the fill process with a new seed.
-            if statistics.backtracks > max_backtracks {
-                return Err(FillFailure::ExceededBacktrackLimit(statistics.backtracks));
+            // If we've used up our retry budget, restart the fill process with a new seed.
+            if retry_budget.is_exhausted(&statistics, start) {
+                statistics.try_time = start.elapsed();
+                partial.statistics.accumulate(&statistics);
+                partial.crossing_weights = crossing_weights.clone();
+                partial.stuck_slot =
+                    StuckSlot::find(config, &slots, &partial.statistics.slot_backtracks);
+
+                // The caller will usually retry and keep accumulating into `partial`, so we leave
+                // it in place and report a snapshot.
+                return Err(FillFailure::ExceededBacktrackLimit(
+                    statistics.backtracks,
+                    Box::new(partial.clone()),
+                ));
             }
 
             // Our cached position in the last slot's option list is now invalid.
@@ -702,49 +1800,36 @@ pub fn find_fill_for_seed(
     }
 }
 
-/// Search for a valid fill for the given grid, if one can be found within the given amount of time.
-#[allow(dead_code)]
-pub fn find_fill(
-    config: &GridConfig,
-    timeout: Option<Duration>,
-    elimination_sets: Option<&mut [EliminationSet]>,
-) -> Result<FillSuccess, FillFailure> {
-    let start = Instant::now();
-    let deadline = timeout.map(|timeout| start + timeout);
-
-    let mut owned_elimination_sets: Option<Vec<EliminationSet>> = None;
-    let elimination_sets = elimination_sets.unwrap_or_else(|| {
-        owned_elimination_sets = Some(EliminationSet::build_all(
-            config.slot_configs,
-            config.word_list,
-        ));
-        owned_elimination_sets.as_mut().unwrap()
-    });
-
-    // Create basic Slot structs for the grid, which we can copy for each retry instead of having
-    // to regenerate from scratch.
-    let mut slots: Vec<Slot> = config
+/// Create the initial `Slot` structs for the given grid, treating any slots whose contents were
+/// provided verbatim as fixed.
+#[must_use]
+pub fn build_slots(config: &GridConfig) -> Vec<Slot> {
+    config
         .slot_configs
         .iter()
         .map(|slot_config| {
             let glyph_counts_by_cell = build_glyph_counts_by_cell(
-                config.word_list,
+                &config.word_list,
                 slot_config.length,
-                &config.slot_options[slot_config.id],
+                &config.slot_options[slot_config.id.index()],
             );
 
             let is_fixed = slot_config
-                .complete_fill(config.fill, config.width)
+                .complete_fill(&config.fill, config.width)
                 .is_some();
 
             Slot {
                 id: slot_config.id,
                 length: slot_config.length,
                 eliminations: vec![None; config.word_list.words[slot_config.length].len()],
-                remaining_option_count: config.slot_options[slot_config.id].len(),
+                remaining_option_count: config.slot_options[slot_config.id.index()].len(),
+                live_options: LiveOptions::new(
+                    &config.slot_options[slot_config.id.index()],
+                    config.word_list.words[slot_config.length].len(),
+                ),
                 fixed_word_id: if is_fixed {
-                    assert_eq!(config.slot_options[slot_config.id].len(), 1);
-                    Some(config.slot_options[slot_config.id][0])
+                    assert_eq!(config.slot_options[slot_config.id.index()].len(), 1);
+                    Some(config.slot_options[slot_config.id.index()][0])
                 } else {
                     None
                 },
@@ -756,55 +1841,256 @@ pub fn find_fill(
                 glyph_counts_by_cell,
             }
         })
-        .collect();
+        .collect()
+}
 
+/// Search for a valid fill for the given grid, if one can be found within the given amount of time.
+#[allow(dead_code)]
+pub fn find_fill(
+    config: &GridConfig,
+    timeout: Option<Duration>,
+    elimination_sets: Option<&mut [EliminationSet]>,
+) -> Result<FillSuccess, FillFailure> {
+    find_fill_with_search_config(config, timeout, elimination_sets, &SearchConfig::default())
+}
+
+/// Search for a valid fill for the given grid, like `find_fill`, but without using any of the given
+/// words (except where the input grid already contains them).
+pub fn find_fill_without_words(
+    config: &GridConfig,
+    forbidden_words: &[String],
+    timeout: Option<Duration>,
+) -> Result<FillSuccess, FillFailure> {
+    let mut config = config.borrowed();
+    config.forbid_words(forbidden_words);
+    find_fill(&config, timeout, None)
+}
+
+/// Search for a valid fill for the given grid, like `find_fill`, but with control over the search
+/// strategy.
+pub fn find_fill_with_search_config(
+    config: &GridConfig,
+    timeout: Option<Duration>,
+    elimination_sets: Option<&mut [EliminationSet]>,
+    search_config: &SearchConfig,
+) -> Result<FillSuccess, FillFailure> {
     // Start tracking weights representing how problematic each crossing is in the grid. These are
     // shared between retries so that we can learn from each one.
-    let mut crossing_weights: Vec<f32> = (0..config.crossing_count).map(|_| 1.0).collect();
+    let mut crossing_weights = CrossingWeights::new(config.crossing_count);
+
+    find_fill_with_crossing_weights(
+        config,
+        timeout,
+        elimination_sets,
+        search_config,
+        &mut crossing_weights,
+    )
+}
+
+/// Like `find_fill_with_search_config`, but starting from the given crossing weights (which must
+/// have an entry for each crossing) and leaving the updated weights there when we're done. Warm
+/// weights from a previous fill of the same grid can save a lot of the work of rediscovering the
+/// trouble spots. The weights are switched to `search_config`'s decay policy.
+pub fn find_fill_with_crossing_weights(
+    config: &GridConfig,
+    timeout: Option<Duration>,
+    elimination_sets: Option<&mut [EliminationSet]>,
+    search_config: &SearchConfig,
+    crossing_weights: &mut CrossingWeights,
+) -> Result<FillSuccess, FillFailure> {
+    let start = Instant::now();
+    let deadline = timeout.map(|timeout| start + timeout);
+    crossing_weights.set_decay(search_config.weight_decay);
+
+    let mut owned_elimination_sets: Option<EliminationSetPool> = None;
+    let elimination_sets = elimination_sets.unwrap_or_else(|| {
+        owned_elimination_sets = Some(EliminationSet::build_all(
+            &config.slot_configs,
+            &config.word_list,
+        ));
+        owned_elimination_sets.as_mut().unwrap()
+    });
+
+    // Create basic Slot structs for the grid, which we can copy for each retry instead of having
+    // to regenerate from scratch.
+    let mut slots = build_slots(config);
 
     // Establish initial arc consistency (including dupe-checking). If we can't even do that, we're
     // obviously not going to be able to find a fill.
-    let slot_weights = calculate_slot_weights(config, &slots, &crossing_weights);
+    let slot_weights = calculate_slot_weights(config, &slots, crossing_weights);
     let mut initial_arc_consistency_time = Duration::default();
     if !maintain_arc_consistency(
         config,
         &mut slots,
-        &mut crossing_weights,
+        crossing_weights,
         &slot_weights,
         &ArcConsistencyMode::Initial,
         &mut initial_arc_consistency_time,
         elimination_sets,
     ) {
-        return Err(FillFailure::HardFailure);
+        return Err(FillFailure::HardFailure(Box::new(PartialFill {
+            statistics: Statistics {
+                total_time: start.elapsed(),
+                initial_arc_consistency_time,
+                ..Statistics::default()
+            },
+            crossing_weights: crossing_weights.clone(),
+            failure_bundle: search_config
+                .capture_failure_bundle
+                .then(|| Box::new(FailureBundle::capture(config, search_config, None))),
+            ..PartialFill::default()
+        })));
     }
 
-    // We cap the number of backtracks for each retry so that we don't get hung up for too long on a
-    // bad starting point.
-    let mut max_backtracks: usize = 500;
+    let result = if search_config.strategy == SearchStrategy::LimitedDiscrepancy {
+        find_fill_with_discrepancies(
+            config,
+            &slots,
+            deadline,
+            crossing_weights,
+            elimination_sets,
+            search_config.required_words.as_ref(),
+            &search_config.tag_caps,
+        )
+        .map(|mut result| {
+            result.statistics.try_time = result.statistics.total_time;
+            result
+        })
+    } else {
+        find_fill_with_restarts(
+            config,
+            &slots,
+            deadline,
+            crossing_weights,
+            elimination_sets,
+            search_config,
+        )
+    };
+
+    match result {
+        Ok(mut result) => {
+            result.statistics.total_time = start.elapsed();
+            result.statistics.initial_arc_consistency_time = initial_arc_consistency_time;
+            result.crossing_weights = crossing_weights.clone();
+            result.record_inputs(config, Some(search_config));
+            Ok(result)
+        }
+        Err(mut failure) => {
+            let is_hard_failure = matches!(failure, FillFailure::HardFailure(_));
+            let partial = failure.partial_mut();
+            partial.statistics.total_time = start.elapsed();
+            partial.statistics.initial_arc_consistency_time = initial_arc_consistency_time;
+            if is_hard_failure && search_config.capture_failure_bundle {
+                // Each restart uses its retry number as its seed, so the number of retries before
+                // the failure is the seed of the attempt that failed.
+                let failing_seed = (search_config.strategy == SearchStrategy::Restarts)
+                    .then_some(partial.statistics.retries as u64);
+                partial.failure_bundle = Some(Box::new(FailureBundle::capture(
+                    config,
+                    search_config,
+                    failing_seed,
+                )));
+            }
+            Err(failure)
+        }
+    }
+}
+
+/// Starting from slots that have already been made arc-consistent, keep calling
+/// `find_fill_for_seed` with new seeds and growing retry budgets until we either succeed or hit a
+/// failure that retrying can't fix.
+pub fn find_fill_with_restarts(
+    config: &GridConfig,
+    slots: &Vec<Slot>,
+    deadline: Option<Instant>,
+    crossing_weights: &mut CrossingWeights,
+    elimination_sets: &mut [EliminationSet],
+    search_config: &SearchConfig,
+) -> Result<FillSuccess, FillFailure> {
+    let mut impacts = ImpactTracker::new(config.slot_configs.len());
+    let mut partial = PartialFill::default();
+
+    // We cap the work done by each retry so that we don't get hung up for too long on a bad
+    // starting point.
+    let mut retry_budget = search_config.retry_budget;
+
+    // If the retry callback relaxes the grid's minimum score, we switch to searching a copy of the
+    // grid with the new options.
+    let mut relaxed: Option<(GridConfig, Vec<Slot>)> = None;
 
     // Now keep trying to fill the grid until we either succeed or run out of time. Each attempt has
-    // a slightly larger `max_backtracks` value in addition to having a new RNG seed.
+    // a slightly larger budget in addition to having a new RNG seed.
     for retry_num in 0.. {
+        let (current_config, current_slots) = relaxed
+            .as_ref()
+            .map_or((config, slots), |(config, slots)| (config, slots));
+
         match find_fill_for_seed(
-            config,
-            &slots,
+            current_config,
+            current_slots,
             deadline,
-            max_backtracks,
+            retry_budget,
             retry_num,
-            &mut crossing_weights,
+            crossing_weights,
             elimination_sets,
+            search_config,
+            &mut impacts,
+            &mut partial,
         ) {
             Ok(mut result) => {
                 result.statistics.retries = retry_num as usize;
                 result.statistics.try_time = result.statistics.total_time;
-                result.statistics.total_time = start.elapsed();
-                result.statistics.initial_arc_consistency_time = initial_arc_consistency_time;
+                result
+                    .statistics
+                    .accumulate_slot_counts(&partial.statistics);
                 return Ok(result);
             }
-            Err(FillFailure::ExceededBacktrackLimit(_backtrack_count)) => {
-                // Ensure that we always increase `max_backtracks` by at least 1.
-                max_backtracks = (max_backtracks + 1)
-                    .max((max_backtracks as f32 * RETRY_GROWTH_FACTOR) as usize);
+            Err(FillFailure::ExceededBacktrackLimit(backtrack_count, _)) => {
+                partial.statistics.retries += 1;
+                retry_budget = retry_budget.grown();
+
+                let Some(retry_callback) = &search_config.retry_callback else {
+                    continue;
+                };
+                let action = retry_callback.0(&RetryInfo {
+                    retries: partial.statistics.retries,
+                    partial: &partial,
+                    next_budget: retry_budget,
+                });
+                match action {
+                    RetryAction::Continue => {}
+                    RetryAction::SetBudget(budget) => retry_budget = budget,
+                    RetryAction::LowerMinScore(min_score) => {
+                        let mut relaxed_config = config.borrowed();
+                        relaxed_config.regenerate_slot_options(min_score);
+                        let mut relaxed_slots = build_slots(&relaxed_config);
+                        let slot_weights = calculate_slot_weights(
+                            &relaxed_config,
+                            &relaxed_slots,
+                            crossing_weights,
+                        );
+                        let mut initial_arc_consistency_time = Duration::default();
+                        if !maintain_arc_consistency(
+                            &relaxed_config,
+                            &mut relaxed_slots,
+                            crossing_weights,
+                            &slot_weights,
+                            &ArcConsistencyMode::Initial,
+                            &mut initial_arc_consistency_time,
+                            elimination_sets,
+                        ) {
+                            partial.crossing_weights = crossing_weights.clone();
+                            return Err(FillFailure::HardFailure(Box::new(partial)));
+                        }
+                        relaxed = Some((relaxed_config, relaxed_slots));
+                    }
+                    RetryAction::Stop => {
+                        return Err(FillFailure::ExceededBacktrackLimit(
+                            backtrack_count,
+                            Box::new(partial),
+                        ));
+                    }
+                }
             }
             other_error => {
                 return other_error;
@@ -817,17 +2103,31 @@ pub fn find_fill(
 
 #[cfg(test)]
 mod tests {
-    use crate::backtracking_search::{find_fill, FillFailure};
+    use crate::arc_consistency::EliminationSetPool;
+    use crate::backtracking_search::{
+        build_slots, choose_next_slot, config_fingerprint, find_fill, find_fill_with_search_config,
+        sort_candidates_by_freshness, sort_candidates_by_score, CandidateOrder, CandidateWindow,
+        ChoiceEventKind, FillFailure, ImpactTracker, LiveOptions, RetryAction, RetryBudget,
+        RetryCallback, RngSource, SearchConfig, SearchStrategy, SlotHeuristic, Statistics,
+        IMPACT_LEARNING_RATE,
+    };
+    use crate::cancel::CancelToken;
+    use crate::error::FillError;
     use crate::grid_config::{
-        generate_grid_config_from_template_string, render_grid, OwnedGridConfig,
+        filled_entries, generate_grid_config_from_template_string, render_grid, Choice,
+        OwnedGridConfig, SlotId,
     };
-    use crate::types::GlobalWordId;
+    use crate::types::{GlobalWordId, WordId};
     use crate::word_list::tests::{dictionary_path, word_list_source_config};
-    use crate::word_list::{WordList, WordListSourceConfig};
+    use crate::word_list::{WordList, WordListSourceConfig, WordRecency};
     use indoc::indoc;
-    use std::sync::atomic::{AtomicBool, Ordering};
-    use std::sync::Arc;
     use instant::{Duration, Instant};
+    use rand::distributions::WeightedIndex;
+    use rand::rngs::mock::StepRng;
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
 
     fn load_word_list(max_length: usize) -> WordList {
         let word_list = WordList::new(word_list_source_config(), None, Some(max_length), Some(5));
@@ -848,7 +2148,7 @@ mod tests {
             template,
             min_score,
         );
-        config.abort = Some(Arc::new(AtomicBool::new(false)));
+        config.cancel = Some(CancelToken::new());
         config
     }
 
@@ -866,14 +2166,10 @@ mod tests {
             ",
         );
 
-        let result =
-            find_fill(&grid_config.to_config_ref(), None, None).expect("Failed to find a fill");
+        let result = find_fill(&grid_config, None, None).expect("Failed to find a fill");
 
         println!("{:?}", result.statistics);
-        println!(
-            "{}",
-            render_grid(&grid_config.to_config_ref(), &result.choices)
-        );
+        println!("{}", render_grid(&grid_config, &result.choices));
     }
 
     #[test]
@@ -888,14 +2184,640 @@ mod tests {
             ",
         );
 
-        let result =
-            find_fill(&grid_config.to_config_ref(), None, None).expect("Failed to find a fill");
+        let result = find_fill(&grid_config, None, None).expect("Failed to find a fill");
 
         println!("{:?}", result.statistics);
-        println!(
-            "{}",
-            render_grid(&grid_config.to_config_ref(), &result.choices)
+        println!("{}", render_grid(&grid_config, &result.choices));
+    }
+
+    #[test]
+    fn test_find_fill_with_impact_heuristic() {
+        let grid_config = generate_config(
+            "
+            .....
+            .....
+            .....
+            .....
+            .....
+            ",
+        );
+
+        let result = find_fill_with_search_config(
+            &grid_config,
+            None,
+            None,
+            &SearchConfig {
+                slot_heuristic: SlotHeuristic::Impact,
+                ..SearchConfig::default()
+            },
+        )
+        .expect("Failed to find a fill");
+
+        assert_eq!(result.choices.len(), 10);
+        println!("{:?}", result.statistics);
+        println!("{}", render_grid(&grid_config, &result.choices));
+    }
+
+    #[test]
+    fn test_impact_tracker() {
+        let mut impacts = ImpactTracker::new(2);
+        assert!(impacts.slot_impact(SlotId(0)).abs() < f32::EPSILON);
+        assert!(impacts.word_impact(SlotId(0), WordId(5)).abs() < f32::EPSILON);
+
+        impacts.record(
+            &Choice {
+                slot_id: SlotId(0),
+                word_id: WordId(5),
+            },
+            0.8,
+        );
+        impacts.record(
+            &Choice {
+                slot_id: SlotId(0),
+                word_id: WordId(6),
+            },
+            0.2,
+        );
+
+        let slot_impact = 0.8 + (0.2 - 0.8) * IMPACT_LEARNING_RATE;
+        assert!((impacts.slot_impact(SlotId(0)) - slot_impact).abs() < 1e-6);
+        assert!((impacts.word_impact(SlotId(0), WordId(5)) - 0.8).abs() < 1e-6);
+        assert!((impacts.word_impact(SlotId(0), WordId(6)) - 0.2).abs() < 1e-6);
+        assert!(
+            (impacts.word_impact(SlotId(0), WordId(7)) - slot_impact).abs() < 1e-6,
+            "untried words fall back to the slot's average"
+        );
+
+        // Plugging the weights into `dom/wdeg` should give a priority of `dom * (1 - impact)`.
+        let weights = impacts.calculate_slot_weights();
+        for (slot_id, impact) in [(SlotId(0), slot_impact), (SlotId(1), 0.0)] {
+            let dom = 12.0;
+            assert!((dom / weights[slot_id.index()] - dom * (1.0 - impact)).abs() < 1e-4);
+        }
+
+        let mut candidates = vec![(0, &WordId(5)), (1, &WordId(6)), (2, &WordId(7))];
+        impacts.sort_candidates(SlotId(0), &mut candidates);
+        assert_eq!(
+            candidates,
+            vec![(1, &WordId(6)), (2, &WordId(7)), (0, &WordId(5))]
+        );
+    }
+
+    #[test]
+    fn test_live_options() {
+        // Options are listed in reverse id order, spanning a few blocks, with one word left out.
+        let options: Vec<WordId> = WordId::range(150).rev().filter(|&id| id.0 != 3).collect();
+        let mut live_options = LiveOptions::new(&options, 150);
+        assert_eq!(
+            live_options.positions_from(0).collect::<Vec<_>>(),
+            (0..149).collect::<Vec<_>>()
+        );
+
+        for word_id in [WordId(149), WordId(80), WordId(3), WordId(0)] {
+            live_options.remove(word_id);
+        }
+        assert_eq!(
+            live_options.positions_from(60).take(10).collect::<Vec<_>>(),
+            vec![60, 61, 62, 63, 64, 65, 66, 67, 68, 70]
+        );
+        assert_eq!(
+            live_options.positions_from(145).collect::<Vec<_>>(),
+            vec![145, 146, 147]
+        );
+        assert_eq!(live_options.positions_from(0).next(), Some(1));
+        assert_eq!(live_options.positions_from(500).next(), None);
+
+        live_options.insert(WordId(80));
+        assert_eq!(live_options.positions_from(69).next(), Some(69));
+    }
+
+    #[test]
+    fn test_sort_candidates_by_score() {
+        let grid_config = generate_config("...\n...\n...");
+        let options = &grid_config.slot_options[0];
+        let score = |word_id: WordId| grid_config.word_list.words[3][word_id.index()].score;
+
+        let mut candidates: Vec<(usize, &WordId)> = options.iter().take(20).enumerate().collect();
+        sort_candidates_by_score(&grid_config.word_list, 3, &mut candidates);
+        // Candidates are sorted by descending score, and otherwise stay in their original order.
+        assert!(candidates.windows(2).all(|pair| {
+            let (first, second) = (score(*pair[0].1), score(*pair[1].1));
+            first > second || (first == second && pair[0].0 < pair[1].0)
+        }));
+    }
+
+    #[test]
+    fn test_sort_candidates_by_freshness() {
+        let mut grid_config = generate_config("...\n...\n...");
+        let options = grid_config.slot_options[0].clone();
+        grid_config
+            .word_list
+            .get_mut()
+            .unwrap()
+            .set_word_recency((3, options[1]), Some(WordRecency::LastSeen(19_000)));
+        grid_config
+            .word_list
+            .get_mut()
+            .unwrap()
+            .set_word_recency((3, options[2]), Some(WordRecency::LastSeen(18_000)));
+        grid_config
+            .word_list
+            .get_mut()
+            .unwrap()
+            .set_word_recency((3, options[3]), Some(WordRecency::Debut));
+
+        let mut candidates: Vec<(usize, &WordId)> = options.iter().take(5).enumerate().collect();
+        sort_candidates_by_freshness(&grid_config.word_list, 3, &mut candidates);
+        assert_eq!(
+            candidates.iter().map(|&(idx, _)| idx).collect::<Vec<_>>(),
+            vec![3, 0, 4, 2, 1]
+        );
+
+        // Clearing the recency puts the word back among the unknowns.
+        grid_config
+            .word_list
+            .get_mut()
+            .unwrap()
+            .set_word_recency((3, options[3]), None);
+        assert_eq!(
+            grid_config.word_list.get_word_recency((3, options[3])),
+            None
+        );
+
+        let result = find_fill_with_search_config(
+            &grid_config,
+            None,
+            None,
+            &SearchConfig {
+                favor_fresh_words: true,
+                ..SearchConfig::default()
+            },
+        )
+        .expect("Failed to find a fill");
+        assert_eq!(result.choices.len(), 6);
+    }
+
+    #[test]
+    fn test_fill_inputs_are_recorded() {
+        let grid_config = generate_config("...\n...\n...");
+        let config = grid_config.borrowed();
+
+        let first = find_fill(&config, None, None).expect("Failed to find a fill");
+        let second = find_fill(&config, None, None).expect("Failed to find a fill");
+        assert_eq!(first.word_list_hash, grid_config.word_list.content_hash());
+        assert_eq!(first.word_list_hash, second.word_list_hash);
+        assert_eq!(first.config_fingerprint, second.config_fingerprint);
+        assert_eq!(
+            first.config_fingerprint,
+            config_fingerprint(&config, Some(&SearchConfig::default()))
+        );
+
+        // Changing either the settings or the grid changes the fingerprint.
+        let lds_config = SearchConfig {
+            strategy: SearchStrategy::LimitedDiscrepancy,
+            ..SearchConfig::default()
+        };
+        assert_ne!(
+            config_fingerprint(&config, Some(&lds_config)),
+            first.config_fingerprint
         );
+        let other_grid_config = generate_config("a..\n...\n...");
+        assert_ne!(
+            config_fingerprint(&other_grid_config, Some(&SearchConfig::default())),
+            first.config_fingerprint
+        );
+
+        // A list with different scores hashes differently.
+        let mut word_list = WordList::new(
+            vec![WordListSourceConfig::Memory {
+                id: "0".into(),
+                enabled: true,
+                words: vec![("abc".into(), 50)],
+            }],
+            None,
+            None,
+            None,
+        );
+        let original_hash = word_list.content_hash();
+        word_list.words[3][0].score = 60;
+        assert_ne!(word_list.content_hash(), original_hash);
+    }
+
+    #[test]
+    fn test_slot_fill_stats() {
+        let grid_config = generate_config(
+            "
+            a....
+            .....
+            .....
+            .....
+            .....
+            ",
+        );
+        let config = grid_config.borrowed();
+
+        let result = find_fill(&config, None, None).expect("Failed to find a fill");
+        assert_eq!(result.slot_stats.len(), config.slot_configs.len());
+
+        for (slot_stats, choice) in result.slot_stats.iter().zip(&result.choices) {
+            let length = config.slot_configs[choice.slot_id.index()].length;
+            assert_eq!(slot_stats.slot_id, choice.slot_id);
+            assert_eq!(slot_stats.word_id, choice.word_id);
+            assert_eq!(
+                slot_stats.score,
+                config.word_list.words[length][choice.word_id.index()].score
+            );
+        }
+
+        // At least the first choice had alternatives, and every backtrack is attributed to a slot.
+        assert!(result.slot_stats.iter().any(|stats| stats.alternatives > 0));
+        assert_eq!(
+            result
+                .slot_stats
+                .iter()
+                .map(|stats| stats.backtracks)
+                .sum::<usize>(),
+            result.statistics.backtracks
+        );
+    }
+
+    #[test]
+    fn test_choice_history() {
+        let grid_config = generate_config(
+            "
+            a....
+            .....
+            .....
+            .....
+            .....
+            ",
+        );
+        let config = grid_config.borrowed();
+
+        let result = find_fill(&config, None, None).expect("Failed to find a fill");
+        assert!(result.history.is_empty());
+
+        let search_config = SearchConfig {
+            record_history: true,
+            ..SearchConfig::default()
+        };
+        let result = find_fill_with_search_config(&config, None, None, &search_config)
+            .expect("Failed to find a fill");
+        assert!(!result.history.is_empty());
+
+        // Replaying the history should leave us with a subset of the final fill, and choices
+        // should be undone in the reverse of the order they were made.
+        let mut chosen: Vec<Choice> = vec![];
+        let mut last_state = 0;
+        for event in &result.history {
+            assert!(event.state >= last_state);
+            last_state = event.state;
+            match event.kind {
+                ChoiceEventKind::Chosen => chosen.push(event.choice.clone()),
+                ChoiceEventKind::Undone => {
+                    let undone = chosen.pop().expect("undid a choice that wasn't made");
+                    assert_eq!(undone.slot_id, event.choice.slot_id);
+                    assert_eq!(undone.word_id, event.choice.word_id);
+                }
+                ChoiceEventKind::Rejected => {}
+            }
+        }
+        assert!(!chosen.is_empty());
+        for choice in &chosen {
+            assert_eq!(
+                result.choices[choice.slot_id.index()].word_id,
+                choice.word_id
+            );
+        }
+    }
+
+    #[test]
+    fn test_find_fill_with_custom_rng() {
+        let grid_config = generate_config("....\n....\n....\n....");
+        let config = grid_config.borrowed();
+
+        let built_rngs = Arc::new(AtomicUsize::new(0));
+        let search_config = SearchConfig {
+            rng: RngSource::Custom({
+                let built_rngs = built_rngs.clone();
+                Arc::new(move |seed| {
+                    built_rngs.fetch_add(1, Ordering::Relaxed);
+                    Box::new(StepRng::new(seed, 1))
+                })
+            }),
+            ..SearchConfig::default()
+        };
+
+        let first = find_fill_with_search_config(&config, None, None, &search_config)
+            .expect("Failed to find a fill");
+        let second = find_fill_with_search_config(&config, None, None, &search_config)
+            .expect("Failed to find a fill");
+        assert_eq!(
+            render_grid(&config, &first.choices),
+            render_grid(&config, &second.choices)
+        );
+        assert_eq!(
+            built_rngs.load(Ordering::Relaxed),
+            2 * (first.statistics.retries + 1)
+        );
+
+        let std_config = SearchConfig {
+            rng: RngSource::Std,
+            ..SearchConfig::default()
+        };
+        assert!(find_fill_with_search_config(&config, None, None, &std_config).is_ok());
+    }
+
+    #[test]
+    fn test_find_fill_with_candidate_window() {
+        assert_eq!(CandidateWindow::default().weights(), vec![1.0, 0.5, 0.25]);
+        assert_eq!(
+            CandidateWindow {
+                size: 0,
+                decay: -1.0,
+                ..CandidateWindow::default()
+            }
+            .weights(),
+            vec![1.0]
+        );
+        for decay in [2.0, f32::INFINITY, f32::NAN] {
+            let window = CandidateWindow {
+                size: 4,
+                decay,
+                ..CandidateWindow::default()
+            };
+            assert!(window
+                .weights()
+                .iter()
+                .all(|weight| weight.is_finite() && *weight > 0.0 && *weight <= 1.0));
+        }
+
+        let grid_config = generate_config("....\n....\n....\n....");
+        let config = grid_config.borrowed();
+        for candidate_window in [
+            CandidateWindow {
+                size: 1,
+                ..CandidateWindow::default()
+            },
+            CandidateWindow {
+                size: 10,
+                decay: 1.0,
+                ..CandidateWindow::default()
+            },
+            CandidateWindow {
+                size: usize::MAX,
+                decay: 1000.0,
+                ..CandidateWindow::default()
+            },
+            CandidateWindow {
+                order: CandidateOrder::ScoreThenRandom,
+                ..CandidateWindow::default()
+            },
+        ] {
+            let search_config = SearchConfig {
+                candidate_window,
+                ..SearchConfig::default()
+            };
+            let result = find_fill_with_search_config(&config, None, None, &search_config)
+                .expect("Failed to find a fill");
+            assert_ne!(
+                result.config_fingerprint,
+                config_fingerprint(&config, Some(&SearchConfig::default()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_find_fill_with_retry_budget() {
+        assert_eq!(
+            RetryBudget::Backtracks(0).grown(),
+            RetryBudget::Backtracks(1)
+        );
+        assert_eq!(RetryBudget::States(1000).grown(), RetryBudget::States(1100));
+        assert_eq!(
+            RetryBudget::Time(Duration::ZERO).grown(),
+            RetryBudget::Time(Duration::from_millis(1))
+        );
+
+        // Even a tiny starting budget gets a fill eventually, since it grows with each retry.
+        let grid_config = generate_config(".....\n.....\n.....\n.....\n.....");
+        let config = grid_config.borrowed();
+        for retry_budget in [
+            RetryBudget::Backtracks(0),
+            RetryBudget::Time(Duration::from_millis(1)),
+            RetryBudget::States(1),
+        ] {
+            let search_config = SearchConfig {
+                retry_budget,
+                ..SearchConfig::default()
+            };
+            let result = find_fill_with_search_config(&config, None, None, &search_config)
+                .expect("Failed to find a fill");
+            assert_ne!(
+                result.config_fingerprint,
+                config_fingerprint(&config, Some(&SearchConfig::default()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_find_fill_with_retry_callback() {
+        let grid_config = generate_config("......\n......\n......\n......\n......\n......");
+        let config = grid_config.borrowed();
+        let search_config = |callback: RetryCallback| SearchConfig {
+            retry_budget: RetryBudget::Backtracks(0),
+            retry_callback: Some(callback),
+            ..SearchConfig::default()
+        };
+
+        // The callback sees each retry in turn, and can give up on the search.
+        let calls = Arc::new(Mutex::new(vec![]));
+        let result = find_fill_with_search_config(
+            &config,
+            None,
+            None,
+            &search_config(RetryCallback::new({
+                let calls = calls.clone();
+                move |info| {
+                    calls.lock().unwrap().push((info.retries, info.next_budget));
+                    match info.retries {
+                        1 => RetryAction::SetBudget(RetryBudget::Backtracks(5)),
+                        2 => RetryAction::Continue,
+                        _ => RetryAction::Stop,
+                    }
+                }
+            })),
+        );
+        let Err(FillFailure::ExceededBacktrackLimit(_, partial)) = result else {
+            panic!("Expected the callback to stop the search, got {result:?}");
+        };
+        assert_eq!(partial.statistics.retries, 3);
+
+        // The failure points at the slot the search backtracked out of most often.
+        let stuck_slot = partial.stuck_slot.as_ref().expect("Expected a stuck slot");
+        assert_eq!(
+            Some(&stuck_slot.backtracks),
+            partial.statistics.slot_backtracks.iter().max()
+        );
+        assert_eq!(
+            partial.statistics.slot_backtracks[stuck_slot.slot_id.index()],
+            stuck_slot.backtracks
+        );
+        assert!(!stuck_slot.remaining_options.is_empty());
+        assert!(stuck_slot
+            .remaining_options
+            .iter()
+            .all(|word_id| config.slot_options[stuck_slot.slot_id.index()].contains(word_id)));
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![
+                (1, RetryBudget::Backtracks(1)),
+                (2, RetryBudget::Backtracks(6)),
+                (3, RetryBudget::Backtracks(7)),
+            ]
+        );
+
+        // Lowering the minimum score makes the rest of the search easier.
+        let result = find_fill_with_search_config(
+            &config,
+            None,
+            None,
+            &search_config(RetryCallback::new(|info| {
+                if info.retries == 2 {
+                    RetryAction::LowerMinScore(20)
+                } else {
+                    RetryAction::Continue
+                }
+            })),
+        )
+        .expect("Failed to find a fill");
+        assert!(result.statistics.retries >= 2);
+
+        // Words forbidden before the search stay forbidden once the options are rebuilt.
+        let forbidden = filled_entries(&config, &result.choices)[0]
+            .normalized
+            .clone();
+        let mut config = config.borrowed();
+        config.forbid_words(std::slice::from_ref(&forbidden));
+        let result = find_fill_with_search_config(
+            &config,
+            None,
+            None,
+            &search_config(RetryCallback::new(|info| {
+                if info.retries == 2 {
+                    RetryAction::LowerMinScore(20)
+                } else {
+                    RetryAction::Continue
+                }
+            })),
+        )
+        .expect("Failed to find a fill");
+        assert!(filled_entries(&config, &result.choices)
+            .iter()
+            .all(|entry| entry.normalized != forbidden));
+    }
+
+    #[test]
+    fn test_choose_next_slot_with_last_conflict() {
+        let grid_config = generate_config("...\n...\n...");
+        let mut slots = build_slots(&grid_config);
+        let slot_weights = vec![1.0; slots.len()];
+        let mut rng = SmallRng::seed_from_u64(0);
+        let dist = WeightedIndex::new([1]).unwrap();
+        let mut statistics = Statistics::default();
+
+        for slot in &mut slots {
+            slot.remaining_option_count = 100;
+        }
+        slots[0].remaining_option_count = 50;
+        slots[1].remaining_option_count = 2;
+
+        let mut choose = |slots: &[_], last_conflict_slot_id| {
+            choose_next_slot(
+                slots,
+                &slot_weights,
+                None,
+                last_conflict_slot_id,
+                &mut rng,
+                &dist,
+                &mut statistics,
+            )
+        };
+
+        assert_eq!(choose(&slots, None), Some(SlotId(1)), "best priority");
+        assert_eq!(
+            choose(&slots, Some(SlotId(0))),
+            Some(SlotId(0)),
+            "open conflict slot wins over better priority"
+        );
+
+        slots[0].remaining_option_count = 1;
+        assert_eq!(
+            choose(&slots, Some(SlotId(0))),
+            Some(SlotId(1)),
+            "determined conflict slot is ignored"
+        );
+    }
+
+    #[test]
+    fn test_find_fill_with_last_conflict() {
+        let grid_config = generate_config(
+            "
+            .....
+            .....
+            .....
+            .....
+            .....
+            ",
+        );
+
+        let result = find_fill_with_search_config(
+            &grid_config,
+            None,
+            None,
+            &SearchConfig {
+                last_conflict: true,
+                ..SearchConfig::default()
+            },
+        )
+        .expect("Failed to find a fill");
+
+        assert_eq!(result.choices.len(), 10);
+        println!("{:?}", result.statistics);
+    }
+
+    #[test]
+    fn test_find_fill_with_reused_elimination_set_pool() {
+        let mut pool = EliminationSetPool::new();
+
+        for template in [
+            "....\n....\n....\n....",
+            "...\n...\n...",
+            ".....\n.....\n.....",
+        ] {
+            let grid_config = generate_config(template);
+            let config = grid_config.borrowed();
+
+            let elimination_sets = pool.acquire(&config.slot_configs, &config.word_list);
+            assert_eq!(elimination_sets.len(), config.slot_configs.len());
+            assert!(elimination_sets
+                .iter()
+                .all(|set| set.eliminated_ids.is_empty()));
+            assert!(
+                !elimination_sets[0].contains(WordId(0)),
+                "stale eliminations are cleared"
+            );
+
+            find_fill(&config, None, Some(&mut pool)).expect("Failed to find a fill");
+
+            // Leave some stale eliminations behind for `reset` (and then the next `acquire`) to
+            // clear out.
+            pool[0].add_elimination(WordId(0));
+            pool.reset();
+            assert!(!pool[0].contains(WordId(0)));
+            assert!(pool[0].eliminated_ids.is_empty());
+            pool[0].add_elimination(WordId(0));
+        }
     }
 
     #[test]
@@ -911,14 +2833,10 @@ mod tests {
             ",
         );
 
-        let result =
-            find_fill(&grid_config.to_config_ref(), None, None).expect("Failed to find a fill");
+        let result = find_fill(&grid_config, None, None).expect("Failed to find a fill");
 
         println!("{:?}", result.statistics);
-        println!(
-            "{}",
-            render_grid(&grid_config.to_config_ref(), &result.choices)
-        );
+        println!("{}", render_grid(&grid_config, &result.choices));
     }
 
     #[test]
@@ -935,14 +2853,10 @@ mod tests {
             ",
         );
 
-        let result =
-            find_fill(&grid_config.to_config_ref(), None, None).expect("Failed to find a fill");
+        let result = find_fill(&grid_config, None, None).expect("Failed to find a fill");
 
         println!("{:?}", result.statistics);
-        println!(
-            "{}",
-            render_grid(&grid_config.to_config_ref(), &result.choices)
-        );
+        println!("{}", render_grid(&grid_config, &result.choices));
     }
 
     #[test]
@@ -959,14 +2873,10 @@ mod tests {
             ",
         );
 
-        let result =
-            find_fill(&grid_config.to_config_ref(), None, None).expect("Failed to find a fill");
+        let result = find_fill(&grid_config, None, None).expect("Failed to find a fill");
 
         println!("{:?}", result.statistics);
-        println!(
-            "{}",
-            render_grid(&grid_config.to_config_ref(), &result.choices)
-        );
+        println!("{}", render_grid(&grid_config, &result.choices));
     }
 
     #[test]
@@ -983,8 +2893,7 @@ mod tests {
             ",
         );
 
-        let result =
-            find_fill(&grid_config.to_config_ref(), None, None).expect("Failed to find a fill");
+        let result = find_fill(&grid_config, None, None).expect("Failed to find a fill");
 
         println!("{:?}", result.statistics);
     }
@@ -1003,8 +2912,19 @@ mod tests {
             ",
         );
 
-        find_fill(&grid_config.to_config_ref(), None, None)
-            .expect_err("Found an impossible fill??");
+        let failure = find_fill(&grid_config, None, None).expect_err("Found an impossible fill??");
+
+        // Even when the grid is proven unfillable, we report what we learned along the way.
+        assert!(matches!(failure, FillFailure::HardFailure(_)));
+        assert_eq!(
+            failure.partial().crossing_weights.len(),
+            grid_config.crossing_count
+        );
+        let error = FillError::from(failure);
+        assert!(
+            error.partial().is_some(),
+            "FillError keeps the partial fill"
+        );
     }
 
     #[test]
@@ -1029,14 +2949,10 @@ mod tests {
             ",
         );
 
-        let result =
-            find_fill(&grid_config.to_config_ref(), None, None).expect("Failed to find a fill");
+        let result = find_fill(&grid_config, None, None).expect("Failed to find a fill");
 
         println!("{:?}", result.statistics);
-        println!(
-            "{}",
-            render_grid(&grid_config.to_config_ref(), &result.choices)
-        );
+        println!("{}", render_grid(&grid_config, &result.choices));
     }
 
     #[test]
@@ -1061,14 +2977,10 @@ mod tests {
             ",
         );
 
-        let result =
-            find_fill(&grid_config.to_config_ref(), None, None).expect("Failed to find a fill");
+        let result = find_fill(&grid_config, None, None).expect("Failed to find a fill");
 
         println!("{:?}", result.statistics);
-        println!(
-            "{}",
-            render_grid(&grid_config.to_config_ref(), &result.choices)
-        );
+        println!("{}", render_grid(&grid_config, &result.choices));
     }
 
     #[test]
@@ -1093,14 +3005,10 @@ mod tests {
             ",
         );
 
-        let result =
-            find_fill(&grid_config.to_config_ref(), None, None).expect("Failed to find a fill");
+        let result = find_fill(&grid_config, None, None).expect("Failed to find a fill");
 
         println!("{:?}", result.statistics);
-        println!(
-            "{}",
-            render_grid(&grid_config.to_config_ref(), &result.choices)
-        );
+        println!("{}", render_grid(&grid_config, &result.choices));
     }
 
     #[test]
@@ -1125,14 +3033,10 @@ mod tests {
             ",
         );
 
-        let result =
-            find_fill(&grid_config.to_config_ref(), None, None).expect("Failed to find a fill");
+        let result = find_fill(&grid_config, None, None).expect("Failed to find a fill");
 
         println!("{:?}", result.statistics);
-        println!(
-            "{}",
-            render_grid(&grid_config.to_config_ref(), &result.choices)
-        );
+        println!("{}", render_grid(&grid_config, &result.choices));
     }
 
     #[test]
@@ -1158,19 +3062,24 @@ mod tests {
             50,
         );
 
-        let abort = grid_config.abort.clone().unwrap();
+        let cancel = grid_config.cancel.clone().unwrap();
+        let crossing_count = grid_config.crossing_count;
         let start = Instant::now();
 
-        let thread =
-            std::thread::spawn(move || find_fill(&grid_config.to_config_ref(), None, None));
+        let thread = std::thread::spawn(move || find_fill(&grid_config, None, None));
 
         std::thread::sleep(Duration::from_secs(1));
-        abort.store(true, Ordering::Relaxed);
+        cancel.cancel("test");
 
         let result = thread.join().unwrap().unwrap_err();
         let time = start.elapsed();
 
-        assert!(matches!(result, FillFailure::Abort));
+        let FillFailure::Abort(partial) = result else {
+            panic!("Expected an abort, got {result:?}");
+        };
+        assert!(partial.statistics.states > 0);
+        assert!(!partial.deepest_choices.is_empty());
+        assert_eq!(partial.crossing_weights.len(), crossing_count);
         println!("Aborted in {time:?}");
     }
 
@@ -1188,13 +3097,12 @@ mod tests {
             ",
         );
 
-        let result_1 =
-            find_fill(&grid_config.to_config_ref(), None, None).expect("Failed to find a fill");
+        let result_1 = find_fill(&grid_config, None, None).expect("Failed to find a fill");
 
         // Obviously we'll have to rewrite this test if the algorithm changes in
         // a way that affects the output, but w/e.
         assert_eq!(
-            render_grid(&grid_config.to_config_ref(), &result_1.choices),
+            render_grid(&grid_config, &result_1.choices),
             indoc! {"
             .fas...
             .abit..
@@ -1219,15 +3127,16 @@ mod tests {
 
         grid_config
             .word_list
+            .get_mut()
+            .unwrap()
             .dupe_index
             .as_mut()
             .add_dupe_pair(airmass_id, fas_id);
 
-        let result_2 =
-            find_fill(&grid_config.to_config_ref(), None, None).expect("Failed to find a fill");
+        let result_2 = find_fill(&grid_config, None, None).expect("Failed to find a fill");
 
         assert_eq!(
-            render_grid(&grid_config.to_config_ref(), &result_2.choices),
+            render_grid(&grid_config, &result_2.choices),
             indoc! {"
             .zas...
             .abit..
@@ -1274,13 +3183,36 @@ mod tests {
 
         let grid_config = generate_grid_config_from_template_string(word_list, template, 40);
 
-        let result =
-            find_fill(&grid_config.to_config_ref(), None, None).expect("Failed to find a fill");
+        let result = find_fill(&grid_config, None, None).expect("Failed to find a fill");
 
         println!("{:?}", result.statistics);
-        println!(
-            "{}",
-            render_grid(&grid_config.to_config_ref(), &result.choices)
+        println!("{}", render_grid(&grid_config, &result.choices));
+    }
+
+    #[test]
+    fn test_find_fill_with_large_alphabet() {
+        // Give the word list well over 64 glyphs, with the ones used by the fill coming last.
+        let filler_glyphs: Vec<char> = ('a'..='z').chain('0'..='9').chain('α'..='ω').collect();
+        let mut words: Vec<(String, u16)> = filler_glyphs
+            .chunks(3)
+            .map(|chunk| (chunk.iter().collect(), 50))
+            .collect();
+        words.extend(["жз", "ий", "жи", "зй"].map(|word| (word.into(), 50)));
+
+        let word_list = WordList::new(
+            vec![WordListSourceConfig::Memory {
+                id: "0".into(),
+                enabled: true,
+                words,
+            }],
+            None,
+            Some(3),
+            None,
         );
+        assert!(word_list.glyphs.len() > 64);
+
+        let grid_config = generate_grid_config_from_template_string(word_list, "..\n..", 40);
+        let result = find_fill(&grid_config, None, None).expect("Failed to find a fill");
+        assert_eq!(render_grid(&grid_config, &result.choices), "жз\nий",);
     }
 }
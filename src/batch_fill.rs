@@ -0,0 +1,105 @@
+//! This module supports filling a set of grids in one session (e.g., for a puzzle pack) without
+//! repeating any answer between them. A `UsedWordTracker` records the words in each fill, and
+//! those words are removed from the slot options of every later grid in the batch. Words are
+//! tracked by their normalized strings, so the grids don't need to share a `WordList`.
+
+use instant::Duration;
+use std::collections::HashSet;
+
+use crate::backtracking_search::{find_fill_without_words, FillFailure, FillSuccess};
+use crate::grid_config::{Choice, GridConfig};
+
+/// The words used so far in a batch of fills.
+#[derive(Debug, Clone, Default)]
+pub struct UsedWordTracker {
+    used_words: HashSet<String>,
+}
+
+impl UsedWordTracker {
+    #[must_use]
+    pub fn new() -> UsedWordTracker {
+        UsedWordTracker::default()
+    }
+
+    /// Has the given normalized word been used in any fill so far?
+    #[must_use]
+    pub fn contains(&self, normalized_word: &str) -> bool {
+        self.used_words.contains(normalized_word)
+    }
+
+    /// The number of distinct words used so far.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.used_words.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.used_words.is_empty()
+    }
+
+    /// Record the words making up the given fill, including any that were part of the input grid.
+    pub fn record_fill(&mut self, config: &GridConfig, choices: &[Choice]) {
+        for choice in choices {
+            let length = config.slot_configs[choice.slot_id.index()].length;
+            let word = config.word_list.get_word((length, choice.word_id));
+            self.used_words.insert(word.normalized_string.clone());
+        }
+    }
+
+    /// Search for a fill for the given grid that doesn't use any word from a previous fill, and
+    /// record its words if we find one. Entries that were already complete in the input grid are
+    /// kept even if they repeat an earlier answer.
+    pub fn find_fill(
+        &mut self,
+        config: &GridConfig,
+        timeout: Option<Duration>,
+    ) -> Result<FillSuccess, FillFailure> {
+        let used_words: Vec<String> = self.used_words.iter().cloned().collect();
+        let result = find_fill_without_words(config, &used_words, timeout)?;
+        self.record_fill(config, &result.choices);
+        Ok(result)
+    }
+}
+
+/// Fill each of the given grids in order, with no answer appearing in more than one of them. A grid
+/// that can't be filled doesn't stop the batch; its words just aren't recorded.
+#[must_use]
+pub fn find_fills_for_batch(
+    configs: &[GridConfig],
+    timeout_per_grid: Option<Duration>,
+) -> Vec<Result<FillSuccess, FillFailure>> {
+    let mut tracker = UsedWordTracker::new();
+    configs
+        .iter()
+        .map(|config| tracker.find_fill(config, timeout_per_grid))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::batch_fill::find_fills_for_batch;
+    use crate::grid_config::{generate_grid_config_from_template_string, render_grid};
+    use crate::word_list::tests::memory_word_list;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_find_fills_for_batch() {
+        let word_list = || memory_word_list(&["ab", "cd", "ac", "bd", "ef", "gh", "eg", "fh"]);
+        let configs: Vec<_> = (0..3)
+            .map(|_| generate_grid_config_from_template_string(word_list(), "..\n..", 40))
+            .collect();
+
+        let results = find_fills_for_batch(&configs, None);
+
+        // There are only two sets of words, so the third grid can't be filled.
+        let rendered: Vec<String> = results[..2]
+            .iter()
+            .zip(&configs)
+            .map(|(result, config)| render_grid(config, &result.as_ref().unwrap().choices))
+            .collect();
+        let letters: HashSet<char> = rendered.iter().flat_map(|grid| grid.chars()).collect();
+        assert_eq!(letters.len(), 9, "{rendered:?}");
+        assert!(results[2].is_err());
+    }
+}
@@ -0,0 +1,208 @@
+//! This module runs the solver over a corpus of reference grids and records how each fill went, so
+//! that the effects of a change to the word list or the search settings can be measured by running
+//! the same corpus before and after. Each grid is filled from scratch, without the crossing
+//! weights learned from any earlier fill, so results don't depend on the order of the corpus.
+
+use instant::{Duration, Instant};
+use std::sync::Arc;
+
+use crate::backtracking_search::SearchConfig;
+use crate::error::FillError;
+use crate::fill_service::{FillService, GridSpec};
+use crate::word_list::WordList;
+
+/// How the fill of a single benchmark grid ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum BenchOutcome {
+    Filled,
+    Unfillable,
+    Timeout,
+    ExceededBacktrackLimit,
+
+    /// The grid couldn't be searched at all (e.g., because its template was invalid).
+    Invalid,
+}
+
+impl BenchOutcome {
+    /// The outcome corresponding to a fill that failed with the given error.
+    #[must_use]
+    pub fn for_error(error: &FillError) -> BenchOutcome {
+        match error {
+            FillError::Unfillable(_) => BenchOutcome::Unfillable,
+            FillError::Timeout(_) => BenchOutcome::Timeout,
+            FillError::ExceededBacktrackLimit(..) => BenchOutcome::ExceededBacktrackLimit,
+            _ => BenchOutcome::Invalid,
+        }
+    }
+}
+
+/// Timing and search statistics for a single benchmark grid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct GridBenchmark {
+    /// The grid's template, as given in its `GridSpec`.
+    pub template: String,
+
+    pub outcome: BenchOutcome,
+
+    /// The wall-clock time taken by the fill, including building the grid's slot options.
+    pub time: Duration,
+
+    pub states: usize,
+    pub backtracks: usize,
+    pub retries: usize,
+
+    /// A description of the problem, if the grid couldn't be searched.
+    pub error: Option<String>,
+}
+
+/// The results of running a corpus of grids; see `bench_corpus`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct CorpusBenchmark {
+    /// `WordList::content_hash` for the word list used, so that results from different lists
+    /// aren't mixed up.
+    pub word_list_hash: u64,
+
+    /// Results for each grid, in the order they were given.
+    pub grids: Vec<GridBenchmark>,
+}
+
+impl CorpusBenchmark {
+    /// The number of grids that were filled successfully.
+    #[must_use]
+    pub fn filled_count(&self) -> usize {
+        self.grids
+            .iter()
+            .filter(|grid| grid.outcome == BenchOutcome::Filled)
+            .count()
+    }
+
+    /// The total time spent on all grids.
+    #[must_use]
+    pub fn total_time(&self) -> Duration {
+        self.grids.iter().map(|grid| grid.time).sum()
+    }
+
+    /// The total number of backtracks across all grids.
+    #[must_use]
+    pub fn total_backtracks(&self) -> usize {
+        self.grids.iter().map(|grid| grid.backtracks).sum()
+    }
+}
+
+/// Fill each of the given grids with the given word list and search settings, recording how long
+/// each one took and how much backtracking it needed. A grid that can't be filled doesn't stop the
+/// run; its outcome is just recorded along with the statistics collected before the search
+/// stopped. Each grid's `timeout` is respected, so a corpus can include grids that are expected to
+/// time out.
+#[must_use]
+pub fn bench_corpus(
+    grids: &[GridSpec],
+    word_list: Arc<WordList>,
+    config: &SearchConfig,
+) -> CorpusBenchmark {
+    let word_list_hash = word_list.content_hash();
+    let service = FillService::new(word_list, config.clone());
+
+    let grids = grids
+        .iter()
+        .map(|grid_spec| {
+            service.clear_crossing_weights();
+
+            let start = Instant::now();
+            let result = service.fill(grid_spec);
+            let time = start.elapsed();
+
+            let (outcome, statistics, error) = match &result {
+                Ok(success) => (BenchOutcome::Filled, Some(&success.statistics), None),
+                Err(error) => {
+                    let outcome = BenchOutcome::for_error(error);
+                    let statistics = error.partial().map(|partial| &partial.statistics);
+                    let message = (outcome == BenchOutcome::Invalid).then(|| error.to_string());
+                    (outcome, statistics, message)
+                }
+            };
+
+            GridBenchmark {
+                template: grid_spec.template.clone(),
+                outcome,
+                time,
+                states: statistics.map_or(0, |statistics| statistics.states),
+                backtracks: statistics.map_or(0, |statistics| statistics.backtracks),
+                retries: statistics.map_or(0, |statistics| statistics.retries),
+                error,
+            }
+        })
+        .collect();
+
+    CorpusBenchmark {
+        word_list_hash,
+        grids,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backtracking_search::SearchConfig;
+    use crate::benchmark::{bench_corpus, BenchOutcome};
+    use crate::fill_service::GridSpec;
+    use crate::word_list::tests::word_list_source_config;
+    use crate::word_list::WordList;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_bench_corpus() {
+        let word_list = Arc::new(WordList::new(
+            word_list_source_config(),
+            None,
+            Some(5),
+            None,
+        ));
+        let grid_spec = |template: &str| GridSpec {
+            template: template.into(),
+            min_score: 40,
+            ..GridSpec::default()
+        };
+        let grids = [
+            grid_spec(".....\n.....\n.....\n.....\n....."),
+            GridSpec {
+                required_words: vec!["qqqqq".into()],
+                ..grid_spec(".....\n.....\n.....\n.....\n.....")
+            },
+            grid_spec("@....\n.....\n.....\n.....\n....."),
+        ];
+
+        let results = bench_corpus(&grids, word_list.clone(), &SearchConfig::default());
+
+        assert_eq!(results.word_list_hash, word_list.content_hash());
+        assert_eq!(
+            results
+                .grids
+                .iter()
+                .map(|grid| grid.outcome)
+                .collect::<Vec<_>>(),
+            vec![
+                BenchOutcome::Filled,
+                BenchOutcome::Unfillable,
+                BenchOutcome::Invalid
+            ],
+        );
+        assert_eq!(results.filled_count(), 1);
+        assert!(results.grids[0].states > 0);
+        assert!(results.grids[2].error.is_some());
+        assert_eq!(results.grids[2].template, grids[2].template);
+        assert!(results.total_time() >= results.grids[0].time);
+    }
+}
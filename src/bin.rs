@@ -1,10 +1,17 @@
 use clap::Parser;
 
 use ingrid_core::backtracking_search::find_fill;
-use ingrid_core::grid_config::{generate_grid_config_from_template_string, render_grid};
-use ingrid_core::word_list::{WordList, WordListSourceConfig};
+use ingrid_core::crossing_report::summarize_crossing_weights;
+use ingrid_core::error::FillError;
+#[cfg(feature = "serde")]
+use ingrid_core::fill_schema::FillRequest;
+use ingrid_core::grid_config::{
+    generate_slots_from_template_string, render_grid, template_string_dimensions,
+    try_generate_grid_config_from_hinted_template_string,
+    try_generate_grid_config_from_template_string,
+};
+use ingrid_core::word_list::{WordList, WordListError, WordListSourceConfig};
 use std::borrow::Cow; // Added import for Cow
-use std::collections::HashSet;
 use std::fmt::{Debug, Formatter};
 use std::fs;
 use std::time::Instant;
@@ -19,7 +26,8 @@ struct Args {
     /// Path to the grid file, as ASCII with # representing blocks and . representing empty squares
     grid_path: String,
 
-    /// Path to a scored wordlist file [default: (embedded copy of Spread the Wordlist)]
+    /// Path to a scored wordlist file, or a URL if built with the `http` feature [default:
+    /// (embedded copy of Spread the Wordlist)]
     #[arg(long)]
     wordlist: Option<String>,
 
@@ -34,6 +42,16 @@ struct Args {
     /// Print timing information along with the grid
     #[arg(short, long, default_value_t = false)]
     time: bool,
+
+    /// Treat the grid file as a JSON fill request and print a JSON fill response instead of the
+    /// grid (requires the `serde` feature)
+    #[arg(long, default_value_t = false)]
+    json: bool,
+
+    /// Treat uppercase letters in the grid as suggestions that the fill can override, rather than
+    /// locking them in like lowercase ones
+    #[arg(long, default_value_t = false)]
+    soft_uppercase: bool,
 }
 
 struct Error(String);
@@ -44,37 +62,61 @@ impl Debug for Error {
     }
 }
 
+impl<E: std::error::Error> From<E> for Error {
+    fn from(error: E) -> Self {
+        Error(error.to_string())
+    }
+}
 
 /// Function for command-line usage
+#[allow(private_interfaces)]
 pub fn main() -> Result<(), Error> {
     let args = Args::parse();
 
-    let raw_grid_content = fs::read_to_string(&args.grid_path)
-        .map_err(|_| Error(format!("Couldn't read file '{}'", args.grid_path)))?
+    let file_contents = fs::read_to_string(&args.grid_path)
+        .map_err(|_| Error(format!("Couldn't read file '{}'", args.grid_path)))?;
+
+    #[cfg(feature = "serde")]
+    let request = args
+        .json
+        .then(|| FillRequest::from_json(&file_contents))
+        .transpose()?;
+    #[cfg(feature = "serde")]
+    let (grid_source, min_score, soft_uppercase) = request.as_ref().map_or(
+        (file_contents.as_str(), args.min_score, args.soft_uppercase),
+        |request| {
+            (
+                request.grid.as_str(),
+                request.min_score(),
+                args.soft_uppercase || request.options.soft_uppercase,
+            )
+        },
+    );
+
+    #[cfg(not(feature = "serde"))]
+    if args.json {
+        return Err(Error("JSON requests require the `serde` feature".into()));
+    }
+    #[cfg(not(feature = "serde"))]
+    let (grid_source, min_score, soft_uppercase) =
+        (file_contents.as_str(), args.min_score, args.soft_uppercase);
+
+    let raw_grid_content = grid_source
         .trim()
         .lines()
-        .map(|line| line.trim().to_lowercase().nfc().collect::<String>())
+        .map(|line| {
+            let line = line.trim();
+            if soft_uppercase {
+                line.nfc().collect::<String>()
+            } else {
+                line.to_lowercase().nfc().collect::<String>()
+            }
+        })
         .collect::<Vec<_>>()
         .join("\n")
         + "\n";
 
-    let height = raw_grid_content.lines().count();
-
-    if height == 0 {
-        return Err(Error("Grid must have at least one row".into()));
-    }
-
-    if raw_grid_content
-        .lines()
-        .map(|line| line.chars().count())
-        .collect::<HashSet<_>>()
-        .len()
-        != 1
-    {
-        return Err(Error("Rows in grid must all be the same length".into()));
-    }
-
-    let width = raw_grid_content.lines().next().unwrap().chars().count() - 1;
+    let (width, height) = template_string_dimensions(&raw_grid_content)?;
     let max_side = width.max(height);
 
     if !args
@@ -86,10 +128,23 @@ pub fn main() -> Result<(), Error> {
         ));
     }
 
+    let slot_lengths = generate_slots_from_template_string(&raw_grid_content)
+        .iter()
+        .map(|slot_spec| slot_spec.length)
+        .collect();
+
     let start = Instant::now();
 
-    let word_list = WordList::new(
+    let word_list = WordList::new_with_dupe_index_lengths(
         vec![match args.wordlist {
+            Some(url) if url.starts_with("http://") || url.starts_with("https://") => {
+                WordListSourceConfig::Url {
+                    id: "0".into(),
+                    enabled: true,
+                    url,
+                    cache_dir: Some(std::env::temp_dir().join("ingrid_core")),
+                }
+            }
             Some(wordlist_path) => WordListSourceConfig::File {
                 id: "0".into(),
                 enabled: true,
@@ -104,12 +159,22 @@ pub fn main() -> Result<(), Error> {
         None,
         Some(max_side),
         args.max_shared_substring,
+        Some(slot_lengths),
     );
 
     let word_list_time = start.elapsed();
 
     #[allow(clippy::comparison_chain)]
     if let Some(errors) = word_list.get_source_errors().get("0") {
+        // Failing to fetch a URL isn't fatal if we still have a cached copy of the list.
+        let (warnings, errors): (Vec<_>, Vec<_>) = errors.iter().partition(|error| {
+            matches!(error, WordListError::FetchFailed { .. })
+                && !word_list.word_id_by_string.is_empty()
+        });
+        for warning in warnings {
+            eprintln!("Warning: {warning}");
+        }
+
         if errors.len() == 1 {
             return Err(Error(format!("{}", errors[0])));
         } else if errors.len() > 1 {
@@ -122,20 +187,44 @@ pub fn main() -> Result<(), Error> {
     }
 
     if word_list.word_id_by_string.is_empty() {
-        return Err(Error("Word list is empty".into()));
+        return Err(FillError::EmptyWordList.into());
     }
 
-    let grid_config =
-        generate_grid_config_from_template_string(word_list, &raw_grid_content, args.min_score);
+    let grid_config = if soft_uppercase {
+        try_generate_grid_config_from_hinted_template_string(
+            word_list,
+            &raw_grid_content,
+            min_score,
+        )?
+    } else {
+        try_generate_grid_config_from_template_string(word_list, &raw_grid_content, min_score)?
+    };
+
+    #[cfg(feature = "serde")]
+    if let Some(request) = request {
+        println!("{}", request.fill(&grid_config).to_json());
+        return Ok(());
+    }
 
-    let result = find_fill(&grid_config.to_config_ref(), None, None)
-        .map_err(|_| Error("Unfillable grid".into()))?;
+    let result = match find_fill(&grid_config, None, None) {
+        Ok(result) => result,
+        Err(failure) => {
+            let error = FillError::from(failure);
+            let mut message = error.to_string();
+            if let Some(summary) = error.partial().and_then(|partial| {
+                summarize_crossing_weights(&grid_config, &partial.crossing_weights, 5)
+            }) {
+                message.push_str(&format!("\nMost troublesome crossings:\n{summary}"));
+            }
+            return Err(Error(message));
+        }
+    };
 
     let fill_time = start.elapsed() - word_list_time;
 
     println!(
         "{}",
-        render_grid(&grid_config.to_config_ref(), &result.choices).replace('.', "#")
+        render_grid(&grid_config, &result.choices).replace('.', "#")
     );
 
     if args.time {
@@ -144,4 +233,3 @@ pub fn main() -> Result<(), Error> {
 
     Ok(())
 }
-
@@ -0,0 +1,201 @@
+//! This module provides `CancelToken`, the handle used to stop a fill from outside the search. A
+//! token is shared with the search through `GridConfig::cancel` (or `GridSpec::cancel`, for a
+//! `FillService`), and the search checks it periodically, giving up with an `Abort` failure once
+//! it's been cancelled. Clones of a token share their state, so the caller keeps one clone and
+//! hands the other to the search.
+//!
+//! Besides being cancelled explicitly, a token can carry a deadline, after which it counts as
+//! cancelled. Unlike a fill's `timeout`, which only covers a single search, a deadline is an
+//! absolute point in time, so one token can bound a whole batch of fills. Either way, the caller
+//! can find out afterwards why the fill stopped from `CancelToken::reason`.
+
+use instant::Instant;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Why a `CancelToken` counts as cancelled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CancelReason {
+    /// The token was cancelled explicitly, with the given explanation.
+    Cancelled(String),
+
+    /// The token's deadline passed before it was cancelled explicitly.
+    DeadlinePassed,
+}
+
+impl Display for CancelReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CancelReason::Cancelled(reason) => write!(f, "cancelled: {reason}"),
+            CancelReason::DeadlinePassed => write!(f, "deadline passed"),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct CancelState {
+    cancelled: AtomicBool,
+    reason: Mutex<Option<String>>,
+    deadline: Option<Instant>,
+}
+
+/// A shared, thread-safe signal that a fill should stop; see the module docs.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    state: Arc<CancelState>,
+}
+
+impl CancelToken {
+    /// Build a token that's only cancelled when `cancel` is called.
+    #[must_use]
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    /// Build a token that's cancelled when `cancel` is called or when the given time has passed,
+    /// whichever comes first.
+    #[must_use]
+    pub fn with_deadline(deadline: Instant) -> CancelToken {
+        CancelToken {
+            state: Arc::new(CancelState {
+                deadline: Some(deadline),
+                ..CancelState::default()
+            }),
+        }
+    }
+
+    /// Cancel this token and all of its clones. If it's already been cancelled, the original
+    /// reason is kept.
+    pub fn cancel(&self, reason: impl Into<String>) {
+        let mut current_reason = self.state.reason.lock().unwrap();
+        if current_reason.is_none() {
+            *current_reason = Some(reason.into());
+        }
+        self.state.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Has this token been cancelled, or has its deadline passed? If the token has a deadline,
+    /// this reads the clock, so searches only call it every `INTERRUPT_FREQUENCY` states and use
+    /// `was_cancelled` in between.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        if self.was_cancelled() {
+            return true;
+        }
+        let deadline_passed = self
+            .state
+            .deadline
+            .map_or(false, |deadline| Instant::now() >= deadline);
+        if deadline_passed {
+            self.state.cancelled.store(true, Ordering::Relaxed);
+        }
+        deadline_passed
+    }
+
+    /// Has this token been cancelled explicitly, or been found past its deadline by an earlier
+    /// call to `is_cancelled`? This never reads the clock, so it's cheap enough to call at every
+    /// step of a search.
+    #[must_use]
+    pub fn was_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// The time after which this token counts as cancelled, if any.
+    #[must_use]
+    pub fn deadline(&self) -> Option<Instant> {
+        self.state.deadline
+    }
+
+    /// Why this token counts as cancelled, or `None` if it doesn't yet.
+    #[must_use]
+    pub fn reason(&self) -> Option<CancelReason> {
+        if let Some(reason) = self.state.reason.lock().unwrap().as_ref() {
+            return Some(CancelReason::Cancelled(reason.clone()));
+        }
+        self.is_cancelled().then_some(CancelReason::DeadlinePassed)
+    }
+
+    /// Return a guard that cancels this token with the given reason when it's dropped, e.g. to
+    /// stop a fill running on another thread if the task waiting for it goes away.
+    #[must_use]
+    pub fn cancel_on_drop(&self, reason: impl Into<String>) -> CancelOnDrop {
+        CancelOnDrop {
+            token: Some(self.clone()),
+            reason: reason.into(),
+        }
+    }
+}
+
+/// A guard returned by `CancelToken::cancel_on_drop`.
+#[derive(Debug)]
+pub struct CancelOnDrop {
+    token: Option<CancelToken>,
+    reason: String,
+}
+
+impl CancelOnDrop {
+    /// Drop the guard without cancelling the token, e.g. once the work it was guarding is done.
+    pub fn disarm(mut self) {
+        self.token = None;
+    }
+}
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        if let Some(token) = self.token.take() {
+            token.cancel(std::mem::take(&mut self.reason));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backtracking_search::{find_fill, FillFailure};
+    use crate::cancel::{CancelReason, CancelToken};
+    use crate::grid_config::generate_grid_config_from_template_string;
+    use crate::word_list::tests::word_list_source_config;
+    use crate::word_list::WordList;
+    use instant::{Duration, Instant};
+
+    #[test]
+    fn test_cancel_token() {
+        let token = CancelToken::new();
+        let clone = token.clone();
+        assert!(!clone.is_cancelled());
+        assert_eq!(clone.reason(), None);
+
+        token.cancel("user pressed stop");
+        token.cancel("too late to matter");
+        assert!(clone.is_cancelled());
+        assert_eq!(
+            clone.reason(),
+            Some(CancelReason::Cancelled("user pressed stop".into()))
+        );
+
+        let expired = CancelToken::with_deadline(Instant::now());
+        assert!(
+            !expired.was_cancelled(),
+            "nothing has looked at the clock yet"
+        );
+        assert!(expired.is_cancelled());
+        assert!(expired.was_cancelled());
+        assert_eq!(expired.reason(), Some(CancelReason::DeadlinePassed));
+        let pending = CancelToken::with_deadline(Instant::now() + Duration::from_secs(3600));
+        assert!(!pending.is_cancelled());
+        drop(pending.cancel_on_drop("caller went away"));
+        assert_eq!(
+            pending.reason(),
+            Some(CancelReason::Cancelled("caller went away".into()))
+        );
+
+        // A search given an already-cancelled token stops right away.
+        let word_list = WordList::new(word_list_source_config(), None, Some(5), None);
+        let mut grid_config =
+            generate_grid_config_from_template_string(word_list, ".....\n.....\n.....", 40);
+        grid_config.cancel = Some(token);
+        let result = find_fill(&grid_config, None, None).unwrap_err();
+        assert!(matches!(result, FillFailure::Abort(_)), "{result:?}");
+    }
+}
@@ -0,0 +1,440 @@
+//! This module helps constructors compare candidate "cheater squares": blocks added to an
+//! existing grid to make a difficult area easier to fill. For each symmetric pair of empty cells
+//! that could legally become blocks, we measure the resulting grid and compare it against the
+//! original. The measurements are based on each slot's initial options, without any search, so
+//! they're cheap enough to compute for every candidate in a 15x15 grid.
+
+use float_ord::FloatOrd;
+use std::collections::HashMap;
+
+use crate::error::GridError;
+use crate::grid_config::{
+    generate_slot_configs, generate_slots_from_template_string, matching_slot_options,
+    template_string_dimensions, Direction, GridCoord, SlotSpec,
+};
+use crate::types::GlyphId;
+use crate::util::build_glyph_counts_by_cell;
+use crate::word_list::WordList;
+
+/// The shortest entry that adding a block is allowed to create.
+pub const MIN_ENTRY_LENGTH: usize = 3;
+
+/// Summary statistics for a grid, based on the options available to each slot before any search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridMetrics {
+    pub word_count: usize,
+
+    /// The mean number of options per slot.
+    pub average_domain_size: f32,
+
+    /// The base-10 log of the expected number of fills, treating each slot's options as
+    /// independent and each crossing as the chance that random options for its two slots agree on
+    /// the shared letter. This is crude, but it's good enough to rank similar grids against each
+    /// other. It's `None` if some slot has no options or some crossing can't be satisfied.
+    pub estimated_fillability: Option<f32>,
+}
+
+/// The effect of turning one symmetric pair of cells into blocks.
+#[derive(Debug, Clone)]
+pub struct CheaterSquareImpact {
+    /// The cells that would become blocks: the chosen cell and its counterpart under 180-degree
+    /// rotation, or just one cell if it's in the center of the grid.
+    pub cells: Vec<GridCoord>,
+
+    /// The template string for the grid with the blocks added.
+    pub template: String,
+
+    /// The measurements of the grid with the blocks added.
+    pub metrics: GridMetrics,
+
+    pub word_count_change: isize,
+    pub average_domain_size_change: f32,
+
+    /// The change in `estimated_fillability`, or `None` if it's missing for either grid.
+    pub estimated_fillability_change: Option<f32>,
+}
+
+/// The options available to a single slot, as needed by `GridMetrics`.
+struct SlotSummary {
+    option_count: usize,
+
+    /// For each cell, the fraction of options with each glyph in that cell.
+    glyph_frequencies: Vec<Vec<f32>>,
+}
+
+/// Split a template string into rows of cells, ignoring blank lines and surrounding whitespace.
+fn template_rows(template: &str) -> Vec<Vec<char>> {
+    template
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| line.chars().collect())
+        .collect()
+}
+
+fn is_open(rows: &[Vec<char>], (x, y): GridCoord) -> bool {
+    rows[y][x] != '#'
+}
+
+/// Summarize the options for the given slot. Letters that don't appear in the word list are given
+/// an out-of-range glyph id, so that they don't match anything.
+fn summarize_slot(
+    word_list: &WordList,
+    rows: &[Vec<char>],
+    slot_spec: &SlotSpec,
+    min_score: u16,
+) -> SlotSummary {
+    let entry_fill: Vec<Option<GlyphId>> = slot_spec
+        .cell_coords()
+        .iter()
+        .map(|&(x, y)| match rows[y][x] {
+            '.' => None,
+            letter => {
+                let letter = letter.to_lowercase().next().unwrap_or(letter);
+                Some(
+                    word_list
+                        .glyph_id_by_char
+                        .get(&letter)
+                        .copied()
+                        .unwrap_or(word_list.glyphs.len()),
+                )
+            }
+        })
+        .collect();
+
+    // As in `generate_slot_options`, a fully specified slot always has exactly one option, even if
+    // it isn't in the word list.
+    if let Some(complete_fill) = entry_fill.iter().copied().collect::<Option<Vec<GlyphId>>>() {
+        return SlotSummary {
+            option_count: 1,
+            glyph_frequencies: complete_fill
+                .iter()
+                .map(|&glyph| {
+                    let mut frequencies = vec![0.0; word_list.glyphs.len()];
+                    if let Some(frequency) = frequencies.get_mut(glyph) {
+                        *frequency = 1.0;
+                    }
+                    frequencies
+                })
+                .collect(),
+        };
+    }
+
+    let options = matching_slot_options(word_list, &entry_fill, min_score, None, None);
+    let glyph_counts = build_glyph_counts_by_cell(word_list, slot_spec.length, &options);
+
+    SlotSummary {
+        option_count: options.len(),
+        glyph_frequencies: glyph_counts
+            .iter()
+            .map(|counts| {
+                counts
+                    .iter()
+                    .map(|&count| count as f32 / options.len().max(1) as f32)
+                    .collect()
+            })
+            .collect(),
+    }
+}
+
+/// Measure the grid described by the given rows, reusing (and updating) the given cache of slot
+/// summaries, which is only valid for a single word list and min score.
+fn measure_rows(
+    word_list: &WordList,
+    rows: &[Vec<char>],
+    min_score: u16,
+    cache: &mut HashMap<SlotSpec, SlotSummary>,
+) -> Result<GridMetrics, GridError> {
+    let template: String = rows
+        .iter()
+        .map(|row| row.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let slot_specs = generate_slots_from_template_string(&template);
+    let (slot_configs, _) = generate_slot_configs(&slot_specs)?;
+
+    for slot_spec in &slot_specs {
+        if !cache.contains_key(slot_spec) {
+            let summary = summarize_slot(word_list, rows, slot_spec, min_score);
+            cache.insert(slot_spec.clone(), summary);
+        }
+    }
+    let summaries: Vec<&SlotSummary> = slot_specs.iter().map(|spec| &cache[spec]).collect();
+
+    let total_options: usize = summaries.iter().map(|summary| summary.option_count).sum();
+    let mut estimated_fillability = Some(0.0);
+
+    for (slot_config, summary) in slot_configs.iter().zip(&summaries) {
+        if summary.option_count == 0 {
+            estimated_fillability = None;
+            break;
+        }
+        let mut slot_fillability = (summary.option_count as f32).log10();
+
+        for (cell_idx, crossing) in slot_config.crossings.iter().enumerate() {
+            let Some(crossing) = crossing else {
+                continue;
+            };
+
+            // Each crossing is seen from both sides, so only count it from the first slot.
+            if crossing.other_slot_id < slot_config.id {
+                continue;
+            }
+
+            let agreement: f32 = summary.glyph_frequencies[cell_idx]
+                .iter()
+                .zip(
+                    &summaries[crossing.other_slot_id.index()].glyph_frequencies
+                        [crossing.other_slot_cell],
+                )
+                .map(|(a, b)| a * b)
+                .sum();
+            slot_fillability += agreement.log10();
+        }
+
+        estimated_fillability = estimated_fillability
+            .map(|total: f32| total + slot_fillability)
+            .filter(|total| total.is_finite());
+    }
+
+    Ok(GridMetrics {
+        word_count: slot_specs.len(),
+        average_domain_size: total_options as f32 / slot_specs.len().max(1) as f32,
+        estimated_fillability,
+    })
+}
+
+/// Measure the grid described by the given template string (see
+/// `try_generate_grid_config_from_template_string`).
+pub fn grid_metrics(
+    word_list: &WordList,
+    template: &str,
+    min_score: u16,
+) -> Result<GridMetrics, GridError> {
+    template_string_dimensions(template)?;
+    measure_rows(
+        word_list,
+        &template_rows(template),
+        min_score,
+        &mut HashMap::new(),
+    )
+}
+
+/// How many white cells, including `cell`, are in the unbroken run through `cell` in the given
+/// direction?
+fn run_length(rows: &[Vec<char>], cell: GridCoord, direction: Direction) -> usize {
+    let (width, height) = (rows[0].len(), rows.len());
+    let step = |(x, y): GridCoord, forward: bool| -> Option<GridCoord> {
+        match (direction, forward) {
+            (Direction::Across, true) => (x + 1 < width).then_some((x + 1, y)),
+            (Direction::Across, false) => x.checked_sub(1).map(|x| (x, y)),
+            (Direction::Down, true) => (y + 1 < height).then_some((x, y + 1)),
+            (Direction::Down, false) => y.checked_sub(1).map(|y| (x, y)),
+        }
+    };
+
+    if !is_open(rows, cell) {
+        return 0;
+    }
+    let mut length = 1;
+    for forward in [false, true] {
+        let mut current = cell;
+        while let Some(next) = step(current, forward).filter(|&next| is_open(rows, next)) {
+            length += 1;
+            current = next;
+        }
+    }
+    length
+}
+
+/// Are all of the white cells in the grid connected to each other?
+fn is_connected(rows: &[Vec<char>]) -> bool {
+    let (width, height) = (rows[0].len(), rows.len());
+    let open_cells: Vec<GridCoord> = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .filter(|&cell| is_open(rows, cell))
+        .collect();
+    let Some(&first_cell) = open_cells.first() else {
+        return true;
+    };
+
+    let mut visited = vec![false; width * height];
+    visited[first_cell.1 * width + first_cell.0] = true;
+    let mut stack = vec![first_cell];
+    let mut visited_count = 1;
+
+    while let Some((x, y)) = stack.pop() {
+        let neighbors = [
+            x.checked_sub(1).map(|x| (x, y)),
+            (x + 1 < width).then_some((x + 1, y)),
+            y.checked_sub(1).map(|y| (x, y)),
+            (y + 1 < height).then_some((x, y + 1)),
+        ];
+        for (nx, ny) in neighbors.into_iter().flatten() {
+            if is_open(rows, (nx, ny)) && !visited[ny * width + nx] {
+                visited[ny * width + nx] = true;
+                visited_count += 1;
+                stack.push((nx, ny));
+            }
+        }
+    }
+
+    visited_count == open_cells.len()
+}
+
+/// Can the given cells be turned into blocks? They all have to be empty (not blocks or letters),
+/// the blocks can't create any entries shorter than `MIN_ENTRY_LENGTH` or any unchecked cells, and
+/// they can't split the grid into disconnected parts unless it already was.
+fn is_legal_addition(rows: &[Vec<char>], new_rows: &[Vec<char>], cells: &[GridCoord]) -> bool {
+    let (width, height) = (rows[0].len(), rows.len());
+
+    if cells.iter().any(|&(x, y)| rows[y][x] != '.') {
+        return false;
+    }
+
+    let creates_short_run = cells.iter().any(|&(x, y)| {
+        let neighbors = [
+            (x.checked_sub(1).map(|x| (x, y)), Direction::Across),
+            ((x + 1 < width).then_some((x + 1, y)), Direction::Across),
+            (y.checked_sub(1).map(|y| (x, y)), Direction::Down),
+            ((y + 1 < height).then_some((x, y + 1)), Direction::Down),
+        ];
+        neighbors.into_iter().any(|(neighbor, direction)| {
+            neighbor.is_some_and(|neighbor| {
+                let length = run_length(new_rows, neighbor, direction);
+                length > 0 && length < MIN_ENTRY_LENGTH
+            })
+        })
+    });
+
+    !creates_short_run && (is_connected(new_rows) || !is_connected(rows))
+}
+
+/// Report the impact of each legal cheater square (or symmetric pair of cheater squares) that could
+/// be added to the grid described by the given template string, sorted from the biggest
+/// improvement in estimated fillability to the smallest.
+#[allow(clippy::cast_possible_wrap)]
+pub fn analyze_cheater_squares(
+    word_list: &WordList,
+    template: &str,
+    min_score: u16,
+) -> Result<Vec<CheaterSquareImpact>, GridError> {
+    let (width, height) = template_string_dimensions(template)?;
+    let rows = template_rows(template);
+    let mut cache: HashMap<SlotSpec, SlotSummary> = HashMap::new();
+    let baseline = measure_rows(word_list, &rows, min_score, &mut cache)?;
+
+    let mut impacts: Vec<CheaterSquareImpact> = vec![];
+
+    for y in 0..height {
+        for x in 0..width {
+            let mirror = (width - 1 - x, height - 1 - y);
+
+            // Only consider each pair once, from the cell that comes first in reading order.
+            if (mirror.1, mirror.0) < (y, x) {
+                continue;
+            }
+            let mut cells = vec![(x, y)];
+            if mirror != (x, y) {
+                cells.push(mirror);
+            }
+
+            let mut new_rows = rows.clone();
+            for &(cell_x, cell_y) in &cells {
+                new_rows[cell_y][cell_x] = '#';
+            }
+            if !is_legal_addition(&rows, &new_rows, &cells) {
+                continue;
+            }
+
+            let metrics = measure_rows(word_list, &new_rows, min_score, &mut cache)?;
+            let estimated_fillability_change = metrics
+                .estimated_fillability
+                .zip(baseline.estimated_fillability)
+                .map(|(after, before)| after - before);
+
+            impacts.push(CheaterSquareImpact {
+                cells,
+                template: new_rows
+                    .iter()
+                    .map(|row| row.iter().collect::<String>())
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                word_count_change: metrics.word_count as isize - baseline.word_count as isize,
+                average_domain_size_change: metrics.average_domain_size
+                    - baseline.average_domain_size,
+                estimated_fillability_change,
+                metrics,
+            });
+        }
+    }
+
+    impacts.sort_by_key(|impact| {
+        (
+            impact.estimated_fillability_change.is_none(),
+            FloatOrd(-impact.estimated_fillability_change.unwrap_or(0.0)),
+        )
+    });
+
+    Ok(impacts)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cheater_squares::{analyze_cheater_squares, grid_metrics};
+    use crate::word_list::{WordList, WordListSourceConfig};
+
+    fn word_list(words: &[&str]) -> WordList {
+        WordList::new(
+            vec![WordListSourceConfig::Memory {
+                id: "0".into(),
+                enabled: true,
+                words: words.iter().map(|&word| (word.into(), 50)).collect(),
+            }],
+            None,
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_analyze_cheater_squares() {
+        // The rows and columns of a double word square, plus the three-letter entries left over
+        // after blocking its top-left and bottom-right corners.
+        let word_list = word_list(&[
+            "abcd", "efgh", "ijkl", "mnop", "aeim", "bfjn", "cgko", "dhlp", "bcd", "mno", "eim",
+            "dhl",
+        ]);
+
+        let baseline = grid_metrics(&word_list, "....\n....\n....\n....", 0).unwrap();
+        assert_eq!(baseline.word_count, 8);
+        assert!((baseline.average_domain_size - 8.0).abs() < f32::EPSILON);
+        assert!(baseline.estimated_fillability.is_some());
+
+        // Any other block would leave an entry shorter than three letters.
+        let impacts = analyze_cheater_squares(&word_list, "....\n....\n....\n....", 0).unwrap();
+        assert_eq!(
+            impacts
+                .iter()
+                .map(|impact| impact.cells.clone())
+                .collect::<Vec<_>>(),
+            vec![vec![(0, 0), (3, 3)], vec![(3, 0), (0, 3)]],
+        );
+        assert_eq!(impacts[0].template, "#...\n....\n....\n...#");
+        assert_eq!(impacts[0].word_count_change, 0);
+        assert!(
+            impacts[0].estimated_fillability_change.unwrap()
+                > impacts[1].estimated_fillability_change.unwrap()
+        );
+
+        // Cells containing letters can't become blocks.
+        let impacts = analyze_cheater_squares(&word_list, "a...\n....\n....\n....", 0).unwrap();
+        assert_eq!(
+            impacts
+                .iter()
+                .map(|impact| impact.cells.clone())
+                .collect::<Vec<_>>(),
+            vec![vec![(3, 0), (0, 3)]],
+        );
+    }
+}
@@ -0,0 +1,373 @@
+//! This module pairs the entries of a finished fill with clues stored for them, so that
+//! constructors can start cluing straight from the solver's output. The entries themselves, with
+//! their numbers and cells, come from `extract_answers`, which exporters can also use directly.
+//! Word lists don't carry clues themselves, so stored clues live in a separate `ClueBank`, keyed by
+//! normalized word and loaded from a simple `word;clue` file. `clue_slots` returns each entry with
+//! its candidate clues (which can be serialized as JSON with the `serde` feature), and `export_xd`
+//! and `export_exolve` render the grid and the best candidate for each entry in the `.xd` and
+//! Exolve formats.
+//!
+//! Candidates are kept in the order they were stored, except that clues mentioning any entry of
+//! the same grid are skipped, since using them would dupe the answer or one of its neighbors.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use crate::crossing_report::slot_numbers;
+use crate::grid_config::{filled_cells, Choice, Direction, GridConfig, GridCoord};
+use crate::word_list::normalize_word;
+
+/// A collection of stored clues for each word; see the module docs.
+#[derive(Debug, Clone, Default)]
+pub struct ClueBank {
+    clues_by_word: HashMap<String, Vec<String>>,
+}
+
+impl ClueBank {
+    #[must_use]
+    pub fn new() -> ClueBank {
+        ClueBank::default()
+    }
+
+    /// Parse a file with one clue per line, in the form `word;clue`. A word can have any number of
+    /// lines, with better clues first. Lines without a clue are ignored.
+    #[must_use]
+    pub fn parse(contents: &str) -> ClueBank {
+        let mut clue_bank = ClueBank::new();
+        for line in contents.lines() {
+            if let Some((word, clue)) = line.split_once(';') {
+                clue_bank.add(word, clue);
+            }
+        }
+        clue_bank
+    }
+
+    /// Store another clue for the given (canonical or normalized) word, after any it already has.
+    pub fn add(&mut self, word: &str, clue: &str) {
+        let (word, clue) = (normalize_word(word), clue.trim());
+        if word.is_empty() || clue.is_empty() {
+            return;
+        }
+        self.clues_by_word
+            .entry(word)
+            .or_default()
+            .push(clue.to_string());
+    }
+
+    /// The clues stored for the given (canonical or normalized) word, best first.
+    #[must_use]
+    pub fn clues(&self, word: &str) -> &[String] {
+        self.clues_by_word
+            .get(&normalize_word(word))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// The number of words with at least one clue.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.clues_by_word.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.clues_by_word.is_empty()
+    }
+}
+
+/// A complete entry in a fill, as listed by `extract_answers`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde_derive::Serialize))]
+pub struct Answer {
+    /// The entry's conventional clue number and direction, as in "7D".
+    pub number: usize,
+    pub direction: Direction,
+
+    /// The entry's letters, in uppercase.
+    pub answer: String,
+
+    /// The coordinates of the entry's cells, in order.
+    pub cells: Vec<GridCoord>,
+}
+
+/// List each complete entry in the given fill with its number, direction, answer, and cells, across
+/// first and then down, each in clue-number order. Slots with any empty cells are left out.
+#[must_use]
+pub fn extract_answers(config: &GridConfig, choices: &[Choice]) -> Vec<Answer> {
+    let cells = filled_cells(config, choices);
+
+    let mut answers: Vec<Answer> = config
+        .slot_configs
+        .iter()
+        .zip(slot_numbers(config))
+        .filter_map(|(slot_config, number)| {
+            let answer = slot_config
+                .cell_fill_indices(config.width)
+                .into_iter()
+                .map(|cell_idx| cells[cell_idx])
+                .collect::<Option<String>>()?;
+            Some(Answer {
+                number,
+                direction: slot_config.direction,
+                answer: answer.to_uppercase(),
+                cells: slot_config.cell_coords(),
+            })
+        })
+        .collect();
+    answers.sort_by_key(|answer| (answer.direction, answer.number));
+
+    answers
+}
+
+/// A filled entry, along with the stored clues that could be used for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde_derive::Serialize))]
+pub struct ClueSlot {
+    /// The entry's conventional clue number and direction, as in "7D".
+    pub number: usize,
+    pub direction: Direction,
+    pub start_cell: GridCoord,
+
+    /// The entry's letters, in uppercase.
+    pub answer: String,
+
+    /// Stored clues for the entry, best first; see the module docs.
+    pub candidates: Vec<String>,
+}
+
+/// Does the given clue mention any of the given normalized entries as a whole word?
+fn mentions_any(clue: &str, entries: &HashSet<String>) -> bool {
+    clue.split_whitespace().any(|clue_word| {
+        let clue_word: String = clue_word
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect();
+        entries.contains(&clue_word)
+    })
+}
+
+/// Pair each complete entry in the given fill with up to `max_candidates` of its stored clues,
+/// in the same order as `extract_answers`.
+#[must_use]
+pub fn clue_slots(
+    config: &GridConfig,
+    choices: &[Choice],
+    clue_bank: &ClueBank,
+    max_candidates: usize,
+) -> Vec<ClueSlot> {
+    let answers = extract_answers(config, choices);
+    let normalized_entries: HashSet<String> = answers
+        .iter()
+        .map(|answer| normalize_word(&answer.answer))
+        .collect();
+
+    answers
+        .into_iter()
+        .map(|answer| ClueSlot {
+            number: answer.number,
+            direction: answer.direction,
+            start_cell: answer.cells[0],
+            candidates: clue_bank
+                .clues(&answer.answer)
+                .iter()
+                .filter(|clue| !mentions_any(clue, &normalized_entries))
+                .take(max_candidates)
+                .cloned()
+                .collect(),
+            answer: answer.answer,
+        })
+        .collect()
+}
+
+/// Render the given fill in the `.xd` format: the grid (with # for cells outside of any slot and .
+/// for empty ones), followed by the across and down clues, each using the entry's best candidate
+/// clue if it has one. No metadata section is included, so callers can prepend their own.
+#[must_use]
+pub fn export_xd(config: &GridConfig, choices: &[Choice], clue_bank: &ClueBank) -> String {
+    let cells = filled_cells(config, choices);
+    let mut in_slot = vec![false; config.width * config.height];
+    for slot_config in config.slot_configs.iter() {
+        for cell_idx in slot_config.cell_fill_indices(config.width) {
+            in_slot[cell_idx] = true;
+        }
+    }
+
+    let mut xd = String::new();
+    for (row, in_slot_row) in cells.chunks(config.width).zip(in_slot.chunks(config.width)) {
+        for (cell, &in_slot) in row.iter().zip(in_slot_row) {
+            xd.push(match cell {
+                _ if !in_slot => '#',
+                Some(letter) => letter.to_uppercase().next().unwrap_or(*letter),
+                None => '.',
+            });
+        }
+        xd.push('\n');
+    }
+
+    let mut last_direction = None;
+    for clue_slot in clue_slots(config, choices, clue_bank, 1) {
+        // The clues are separated from the grid by two blank lines, and the down clues from the
+        // across clues by one.
+        match last_direction {
+            None => xd.push_str("\n\n"),
+            Some(direction) if direction != clue_slot.direction => xd.push('\n'),
+            Some(_) => {}
+        }
+        last_direction = Some(clue_slot.direction);
+
+        let direction = match clue_slot.direction {
+            Direction::Across => 'A',
+            Direction::Down => 'D',
+        };
+        let clue = clue_slot.candidates.first().map_or("", String::as_str);
+        writeln!(
+            xd,
+            "{direction}{}. {clue} ~ {}",
+            clue_slot.number, clue_slot.answer
+        )
+        .unwrap();
+    }
+
+    xd
+}
+
+/// Render the given fill in the Exolve text format, ready to be pasted into an Exolve web page:
+/// the grid (with . for cells outside of any slot and 0 for empty ones), followed by the across
+/// and down clues, each using the entry's best candidate clue if it has one and ending with the
+/// entry's length. The title and setter are included if given.
+#[must_use]
+pub fn export_exolve(
+    config: &GridConfig,
+    choices: &[Choice],
+    clue_bank: &ClueBank,
+    title: Option<&str>,
+    setter: Option<&str>,
+) -> String {
+    let cells = filled_cells(config, choices);
+    let mut in_slot = vec![false; config.width * config.height];
+    for slot_config in config.slot_configs.iter() {
+        for cell_idx in slot_config.cell_fill_indices(config.width) {
+            in_slot[cell_idx] = true;
+        }
+    }
+
+    let mut exolve = String::from("exolve-begin\n");
+    writeln!(exolve, "  exolve-width: {}", config.width).unwrap();
+    writeln!(exolve, "  exolve-height: {}", config.height).unwrap();
+    if let Some(title) = title {
+        writeln!(exolve, "  exolve-title: {title}").unwrap();
+    }
+    if let Some(setter) = setter {
+        writeln!(exolve, "  exolve-setter: {setter}").unwrap();
+    }
+
+    exolve.push_str("  exolve-grid:\n");
+    for (row, in_slot_row) in cells.chunks(config.width).zip(in_slot.chunks(config.width)) {
+        exolve.push_str("    ");
+        for (cell, &in_slot) in row.iter().zip(in_slot_row) {
+            exolve.push(match cell {
+                _ if !in_slot => '.',
+                Some(letter) => letter.to_uppercase().next().unwrap_or(*letter),
+                None => '0',
+            });
+        }
+        exolve.push('\n');
+    }
+
+    let clue_slots = clue_slots(config, choices, clue_bank, 1);
+    for (direction, section) in [(Direction::Across, "across"), (Direction::Down, "down")] {
+        writeln!(exolve, "  exolve-{section}:").unwrap();
+        for clue_slot in clue_slots.iter().filter(|slot| slot.direction == direction) {
+            let clue = clue_slot
+                .candidates
+                .first()
+                .map_or(String::new(), |clue| format!("{clue} "));
+            writeln!(
+                exolve,
+                "    {} {clue}({})",
+                clue_slot.number,
+                clue_slot.answer.chars().count()
+            )
+            .unwrap();
+        }
+    }
+
+    exolve.push_str("exolve-end\n");
+    exolve
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clue_export::{clue_slots, export_exolve, export_xd, extract_answers, ClueBank};
+    use crate::grid_config::{generate_grid_config_from_template_string, Direction};
+    use crate::word_list::tests::memory_word_list;
+
+    #[test]
+    fn test_clue_export() {
+        let word_list = memory_word_list(&["ab", "cd", "ac", "bd"]);
+        let grid_config = generate_grid_config_from_template_string(word_list, "ab\ncd", 40);
+        let clue_bank = ClueBank::parse(
+            "ab;Partner of CD\nAB;Gym muscle, briefly\nab;Sit-up target\nac;Cooling system\nbd;\n",
+        );
+        assert_eq!(clue_bank.len(), 2);
+
+        // The first clue for AB mentions another entry, so it's skipped.
+        let clue_slots = clue_slots(&grid_config, &[], &clue_bank, 5);
+        assert_eq!(clue_slots.len(), 4);
+        assert_eq!(
+            (clue_slots[0].number, clue_slots[0].direction),
+            (1, Direction::Across)
+        );
+        assert_eq!(clue_slots[0].answer, "AB");
+        assert_eq!(
+            clue_slots[0].candidates,
+            vec![
+                "Gym muscle, briefly".to_string(),
+                "Sit-up target".to_string()
+            ]
+        );
+        assert!(clue_slots[1].candidates.is_empty());
+
+        let answers = extract_answers(&grid_config, &[]);
+        assert_eq!(
+            answers
+                .iter()
+                .map(|answer| (answer.number, answer.direction, answer.answer.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, Direction::Across, "AB"),
+                (3, Direction::Across, "CD"),
+                (1, Direction::Down, "AC"),
+                (2, Direction::Down, "BD"),
+            ]
+        );
+        assert_eq!(answers[3].cells, vec![(1, 0), (1, 1)]);
+
+        assert_eq!(
+            export_xd(&grid_config, &[], &clue_bank),
+            "AB\nCD\n\n\nA1. Gym muscle, briefly ~ AB\nA3.  ~ CD\n\nD1. Cooling system ~ AC\nD2.  ~ BD\n",
+        );
+
+        assert_eq!(
+            export_exolve(&grid_config, &[], &clue_bank, Some("Tiny"), None),
+            [
+                "exolve-begin",
+                "  exolve-width: 2",
+                "  exolve-height: 2",
+                "  exolve-title: Tiny",
+                "  exolve-grid:",
+                "    AB",
+                "    CD",
+                "  exolve-across:",
+                "    1 Gym muscle, briefly (2)",
+                "    3 (2)",
+                "  exolve-down:",
+                "    1 Cooling system (2)",
+                "    2 (2)",
+                "exolve-end",
+                "",
+            ]
+            .join("\n"),
+        );
+    }
+}
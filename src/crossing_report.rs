@@ -0,0 +1,323 @@
+//! This module turns the crossing weights learned during a fill attempt into a human-readable
+//! report. Every time arc consistency wipes out a slot's domain, the crossings that contributed to
+//! the wipeout have their weights increased (see `maintain_arc_consistency`), so after a failed or
+//! interrupted fill the heaviest crossings are the ones that caused the most trouble.
+//!
+//! It also ranks individual slots by how often the search had to back out of them (see
+//! `Statistics::slot_backtracks`), which is available even when the fill succeeded.
+
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+use crate::backtracking_search::Statistics;
+use crate::crossing_weights::CrossingWeights;
+use crate::grid_config::{CrossingId, Direction, GridConfig, GridCoord, SlotId};
+
+/// A summary of the learned weight of a single crossing.
+#[derive(Debug, Clone)]
+pub struct CrossingWeightEntry {
+    pub crossing_id: CrossingId,
+
+    /// The two slots that meet at this crossing, with the across slot first if there is one.
+    pub slot_ids: (SlotId, SlotId),
+
+    /// Conventional labels for the two slots (like "7D"), based on standard crossword numbering.
+    pub slot_labels: (String, String),
+
+    /// The cell where the two slots meet.
+    pub cell: GridCoord,
+
+    /// The raw crossing weight, which starts at 1.
+    pub weight: f32,
+
+    /// This crossing's share of all weight accumulated beyond the starting value of 1, as a
+    /// fraction between 0 and 1. This roughly corresponds to the share of (recent) domain wipeouts
+    /// that the crossing was responsible for.
+    pub share: f32,
+}
+
+impl Display for CrossingWeightEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{} crossing at ({}, {}): {:.0}% of wipeouts (weight {:.2})",
+            self.slot_labels.0,
+            self.slot_labels.1,
+            self.cell.0,
+            self.cell.1,
+            self.share * 100.0,
+            self.weight,
+        )
+    }
+}
+
+/// Generate the conventional clue number of each cell, numbering the cells where any slot starts in
+/// reading order. Cells where no slot starts are `None`.
+#[must_use]
+pub fn cell_numbers(config: &GridConfig) -> Vec<Option<usize>> {
+    let mut is_start_cell = vec![false; config.width * config.height];
+    for slot_config in config.slot_configs.iter() {
+        let (x, y) = slot_config.start_cell;
+        is_start_cell[y * config.width + x] = true;
+    }
+
+    let mut next_number = 1;
+    is_start_cell
+        .into_iter()
+        .map(|is_start_cell| {
+            is_start_cell.then(|| {
+                next_number += 1;
+                next_number - 1
+            })
+        })
+        .collect()
+}
+
+/// Generate the conventional clue number of each slot; see `cell_numbers`.
+#[must_use]
+pub fn slot_numbers(config: &GridConfig) -> Vec<usize> {
+    let numbers = cell_numbers(config);
+
+    config
+        .slot_configs
+        .iter()
+        .map(|slot_config| {
+            let (x, y) = slot_config.start_cell;
+            numbers[y * config.width + x].expect("every slot's start cell is numbered")
+        })
+        .collect()
+}
+
+/// Generate a conventional label (like "7D") for each slot; see `slot_numbers`.
+#[must_use]
+pub fn slot_labels(config: &GridConfig) -> Vec<String> {
+    config
+        .slot_configs
+        .iter()
+        .zip(slot_numbers(config))
+        .map(|(slot_config, number)| match slot_config.direction {
+            Direction::Across => format!("{number}A"),
+            Direction::Down => format!("{number}D"),
+        })
+        .collect()
+}
+
+/// Map the given crossing weights back to the slots and cells they belong to, sorted from heaviest
+/// to lightest. Crossings missing from `crossing_weights` are treated as having the starting weight.
+#[must_use]
+pub fn explain_crossing_weights(
+    config: &GridConfig,
+    crossing_weights: &CrossingWeights,
+) -> Vec<CrossingWeightEntry> {
+    let labels = slot_labels(config);
+    let excess_total: f32 = crossing_weights
+        .iter()
+        .map(|(_, weight)| (weight - 1.0).max(0.0))
+        .sum();
+
+    let mut entries: Vec<CrossingWeightEntry> = vec![];
+
+    for slot_config in config.slot_configs.iter() {
+        let cell_coords = slot_config.cell_coords();
+
+        for (cell_idx, crossing) in slot_config.crossings.iter().enumerate() {
+            let Some(crossing) = crossing else {
+                continue;
+            };
+
+            // Each crossing is seen from both sides, so only report it from the first slot.
+            if crossing.other_slot_id < slot_config.id {
+                continue;
+            }
+
+            let weight = crossing_weights
+                .as_slice()
+                .get(crossing.crossing_id.index())
+                .copied()
+                .unwrap_or(1.0);
+            let slot_ids = if slot_config.direction == Direction::Down {
+                (crossing.other_slot_id, slot_config.id)
+            } else {
+                (slot_config.id, crossing.other_slot_id)
+            };
+
+            entries.push(CrossingWeightEntry {
+                crossing_id: crossing.crossing_id,
+                slot_ids,
+                slot_labels: (
+                    labels[slot_ids.0.index()].clone(),
+                    labels[slot_ids.1.index()].clone(),
+                ),
+                cell: cell_coords[cell_idx],
+                weight,
+                share: if excess_total > 0.0 {
+                    (weight - 1.0).max(0.0) / excess_total
+                } else {
+                    0.0
+                },
+            });
+        }
+    }
+
+    entries.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+
+    entries
+}
+
+/// Describe the (at most `limit`) heaviest crossings that were involved in any wipeouts, one per
+/// line, or return `None` if there weren't any.
+#[must_use]
+pub fn summarize_crossing_weights(
+    config: &GridConfig,
+    crossing_weights: &CrossingWeights,
+    limit: usize,
+) -> Option<String> {
+    let lines: Vec<String> = explain_crossing_weights(config, crossing_weights)
+        .into_iter()
+        .filter(|entry| entry.share > 0.0)
+        .take(limit)
+        .map(|entry| format!("- {entry}"))
+        .collect();
+
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// A summary of how much trouble the search had with a single slot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HotSlotEntry {
+    pub slot_id: SlotId,
+
+    /// The slot's conventional label (like "7D").
+    pub slot_label: String,
+
+    /// How many times the search tried a word in this slot.
+    pub choices: usize,
+
+    /// How many times one of the slot's options was ruled out while backtracking.
+    pub backtracks: usize,
+}
+
+impl Display for HotSlotEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} backtracks over {} choices",
+            self.slot_label, self.backtracks, self.choices,
+        )
+    }
+}
+
+/// Rank the slots that the search had to backtrack out of, from most backtracks to fewest (and
+/// then by number of choices). Slots that were never backtracked over are left out.
+#[must_use]
+pub fn hot_slots(config: &GridConfig, statistics: &Statistics) -> Vec<HotSlotEntry> {
+    let labels = slot_labels(config);
+
+    let mut entries: Vec<HotSlotEntry> = statistics
+        .slot_backtracks
+        .iter()
+        .enumerate()
+        .filter(|&(_, &backtracks)| backtracks > 0)
+        .map(|(slot_idx, &backtracks)| HotSlotEntry {
+            slot_id: SlotId(slot_idx),
+            slot_label: labels[slot_idx].clone(),
+            choices: statistics.slot_choices.get(slot_idx).copied().unwrap_or(0),
+            backtracks,
+        })
+        .collect();
+
+    entries.sort_by(|a, b| {
+        (b.backtracks, b.choices, a.slot_id).cmp(&(a.backtracks, a.choices, b.slot_id))
+    });
+
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backtracking_search::Statistics;
+    use crate::crossing_report::{
+        explain_crossing_weights, hot_slots, slot_labels, summarize_crossing_weights,
+    };
+    use crate::crossing_weights::CrossingWeights;
+    use crate::grid_config::{generate_grid_config_from_template_string, CrossingId};
+    use crate::word_list::{WordList, WordListSourceConfig};
+
+    #[test]
+    fn test_explain_crossing_weights() {
+        let word_list = WordList::new(
+            vec![WordListSourceConfig::Memory {
+                id: "0".into(),
+                enabled: true,
+                words: vec![("abc".into(), 50)],
+            }],
+            None,
+            Some(3),
+            None,
+        );
+        let grid_config = generate_grid_config_from_template_string(word_list, "...\n.#.\n...", 40);
+        let config = grid_config.borrowed();
+
+        let labels = slot_labels(&config);
+        let mut sorted_labels = labels.clone();
+        sorted_labels.sort();
+        assert_eq!(sorted_labels, vec!["1A", "1D", "2D", "3A"]);
+
+        let mut crossing_weights = vec![1.0; config.crossing_count];
+        crossing_weights[2] = 4.0;
+        crossing_weights[3] = 2.0;
+        let crossing_weights = CrossingWeights::from_vec(crossing_weights);
+
+        let entries = explain_crossing_weights(&config, &crossing_weights);
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0].crossing_id, CrossingId(2));
+        assert!((entries[0].share - 0.75).abs() < 0.001);
+        assert!((entries[1].share - 0.25).abs() < 0.001);
+        assert!(entries[3].share.abs() < 0.001);
+
+        println!("{}", entries[0]);
+        assert!(entries[0].to_string().contains("75% of wipeouts"));
+
+        let summary = summarize_crossing_weights(&config, &crossing_weights, 5).unwrap();
+        assert_eq!(summary.lines().count(), 2, "only crossings with wipeouts");
+        assert!(summary.starts_with("- ") && summary.contains("75% of wipeouts"));
+        assert!(summarize_crossing_weights(&config, &CrossingWeights::default(), 5).is_none());
+    }
+
+    #[test]
+    fn test_hot_slots() {
+        let word_list = WordList::new(
+            vec![WordListSourceConfig::Memory {
+                id: "0".into(),
+                enabled: true,
+                words: vec![("abc".into(), 50)],
+            }],
+            None,
+            Some(3),
+            None,
+        );
+        let grid_config = generate_grid_config_from_template_string(word_list, "...\n.#.\n...", 40);
+        let config = grid_config.borrowed();
+        let labels = slot_labels(&config);
+
+        let statistics = Statistics {
+            slot_choices: vec![3, 5, 2, 1],
+            slot_backtracks: vec![2, 4, 0, 2],
+            ..Statistics::default()
+        };
+        let entries = hot_slots(&config, &statistics);
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (entry.slot_id.index(), entry.backtracks, entry.choices))
+                .collect::<Vec<_>>(),
+            vec![(1, 4, 5), (0, 2, 3), (3, 2, 1)],
+        );
+        assert_eq!(
+            entries[0].to_string(),
+            format!("{}: 4 backtracks over 5 choices", labels[1])
+        );
+
+        assert!(hot_slots(&config, &Statistics::default()).is_empty());
+    }
+}
@@ -0,0 +1,252 @@
+//! This module tracks the learned weight of each crossing in a grid, which drives the `dom/wdeg`
+//! slot-selection heuristic. Every crossing starts out with a weight of 1. Whenever arc consistency
+//! wipes out a slot's domain, the crossings it blames for the wipeout gain weight (see
+//! `ArcConsistencyFailure::weight_updates`). By default, all existing weights also decay a little,
+//! so that recent trouble counts for more than old trouble; `WeightDecay` selects other policies.
+//! A slot's weight is then the sum of the weights of its crossings with slots that are still
+//! undetermined.
+//!
+//! Weights are always indexed by `CrossingId`; `CrossingWeights` only exposes typed accessors so
+//! that they can't accidentally be looked up or updated by some other kind of id.
+
+use std::collections::{HashMap, VecDeque};
+use std::ops::Index;
+
+use crate::grid_config::CrossingId;
+
+/// How much do we decrease the weight of each crossing every time we wipe out a domain?
+/// The lower this is, the more we prioritize recent information over older information.
+pub const WEIGHT_AGE_FACTOR: f32 = 0.99;
+
+/// How should the weights learned from earlier wipeouts fade as new ones come in? A quick fill of
+/// a small grid benefits from reacting to its latest trouble spots, while a long search of a big
+/// grid can afford to accumulate evidence over many wipeouts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum WeightDecay {
+    /// Weights never decay, so every wipeout counts equally no matter how long ago it happened.
+    None,
+
+    /// Every wipeout multiplies each crossing's excess weight (beyond `INITIAL_WEIGHT`) by the
+    /// given factor, which should be between 0 and 1.
+    Exponential(f32),
+
+    /// Only the given number of most recent wipeouts count; older ones are forgotten entirely.
+    Windowed(usize),
+}
+
+impl Default for WeightDecay {
+    /// The policy used by default: exponential decay by `WEIGHT_AGE_FACTOR`.
+    fn default() -> Self {
+        WeightDecay::Exponential(WEIGHT_AGE_FACTOR)
+    }
+}
+
+/// The learned weight of each crossing in a grid; see the module docs.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CrossingWeights {
+    weights: Vec<f32>,
+    decay: WeightDecay,
+
+    /// With `WeightDecay::Windowed`, the updates from each wipeout still in the window, oldest
+    /// first, so that they can be taken back out once they fall out of it.
+    recent_updates: VecDeque<HashMap<CrossingId, f32>>,
+}
+
+impl CrossingWeights {
+    /// The weight each crossing starts out with.
+    pub const INITIAL_WEIGHT: f32 = 1.0;
+
+    /// Build a set of weights for a grid with the given number of crossings, all starting at
+    /// `INITIAL_WEIGHT`.
+    #[must_use]
+    pub fn new(crossing_count: usize) -> CrossingWeights {
+        CrossingWeights::with_decay(crossing_count, WeightDecay::default())
+    }
+
+    /// Build a set of weights like `new`, using the given decay policy.
+    #[must_use]
+    pub fn with_decay(crossing_count: usize, decay: WeightDecay) -> CrossingWeights {
+        CrossingWeights {
+            weights: vec![CrossingWeights::INITIAL_WEIGHT; crossing_count],
+            decay,
+            recent_updates: VecDeque::new(),
+        }
+    }
+
+    /// Build a set of weights from raw values indexed by `CrossingId`, e.g. ones saved from an
+    /// earlier fill of the same grid.
+    #[must_use]
+    pub fn from_vec(weights: Vec<f32>) -> CrossingWeights {
+        CrossingWeights {
+            weights,
+            ..CrossingWeights::default()
+        }
+    }
+
+    #[must_use]
+    pub fn decay(&self) -> WeightDecay {
+        self.decay
+    }
+
+    /// Switch to a different decay policy. Weights learned so far are kept, but if the new policy
+    /// is `Windowed`, they won't fall out of its window.
+    pub fn set_decay(&mut self, decay: WeightDecay) {
+        if decay != self.decay {
+            self.decay = decay;
+            self.recent_updates.clear();
+        }
+    }
+
+    /// The number of crossings these weights cover.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.weights.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.weights.is_empty()
+    }
+
+    /// Iterate over each crossing's id and current weight.
+    pub fn iter(&self) -> impl Iterator<Item = (CrossingId, f32)> + '_ {
+        CrossingId::range(self.weights.len()).zip(self.weights.iter().copied())
+    }
+
+    /// The raw weights, indexed by `CrossingId`.
+    #[must_use]
+    pub fn as_slice(&self) -> &[f32] {
+        &self.weights
+    }
+
+    #[must_use]
+    pub fn into_vec(self) -> Vec<f32> {
+        self.weights
+    }
+
+    /// Update the weights after a domain wipeout: age the existing weights according to our
+    /// `WeightDecay` policy, then add the given increments to the crossings blamed for the wipeout.
+    pub fn record_wipeout(&mut self, weight_updates: &HashMap<CrossingId, f32>) {
+        debug_assert!(
+            weight_updates
+                .keys()
+                .all(|crossing_id| crossing_id.index() < self.weights.len()),
+            "weight update for a crossing outside the grid",
+        );
+
+        match self.decay {
+            WeightDecay::None => {}
+
+            WeightDecay::Exponential(factor) => {
+                for weight in &mut self.weights {
+                    *weight = CrossingWeights::INITIAL_WEIGHT
+                        + (*weight - CrossingWeights::INITIAL_WEIGHT) * factor;
+                }
+            }
+
+            WeightDecay::Windowed(0) => return,
+
+            WeightDecay::Windowed(window_size) => {
+                while self.recent_updates.len() >= window_size {
+                    let expired_updates = self.recent_updates.pop_front().unwrap();
+                    for (crossing_id, update) in expired_updates {
+                        let weight = &mut self.weights[crossing_id.index()];
+                        *weight = (*weight - update).max(CrossingWeights::INITIAL_WEIGHT);
+                    }
+                }
+                self.recent_updates.push_back(weight_updates.clone());
+            }
+        }
+
+        for (&crossing_id, &update) in weight_updates {
+            self.weights[crossing_id.index()] += update;
+        }
+    }
+
+    /// Add the weight each crossing in `other` has gained beyond `INITIAL_WEIGHT` to this one, e.g.
+    /// to combine the weights learned by separate searches of the same grid.
+    pub fn add_excess(&mut self, other: &CrossingWeights) {
+        debug_assert_eq!(self.weights.len(), other.weights.len());
+        for (weight, other_weight) in self.weights.iter_mut().zip(&other.weights) {
+            *weight += other_weight - CrossingWeights::INITIAL_WEIGHT;
+        }
+    }
+
+    /// Return every crossing to `INITIAL_WEIGHT`.
+    pub fn reset(&mut self) {
+        self.weights.fill(CrossingWeights::INITIAL_WEIGHT);
+        self.recent_updates.clear();
+    }
+}
+
+impl Index<CrossingId> for CrossingWeights {
+    type Output = f32;
+
+    fn index(&self, crossing_id: CrossingId) -> &f32 {
+        &self.weights[crossing_id.index()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::crossing_weights::{CrossingWeights, WeightDecay, WEIGHT_AGE_FACTOR};
+    use crate::grid_config::CrossingId;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_record_wipeout() {
+        let mut weights = CrossingWeights::new(3);
+        weights.record_wipeout(&HashMap::from([(CrossingId(1), 2.0)]));
+        assert_eq!(weights.as_slice(), &[1.0, 3.0, 1.0]);
+
+        // Earlier increments decay with each new wipeout.
+        weights.record_wipeout(&HashMap::from([(CrossingId(2), 1.0)]));
+        assert_eq!(
+            weights.as_slice(),
+            &[1.0, 1.0 + 2.0 * WEIGHT_AGE_FACTOR, 2.0]
+        );
+
+        weights.reset();
+        assert_eq!(weights, CrossingWeights::new(3));
+    }
+
+    #[test]
+    fn test_weight_decay_policies() {
+        let wipeouts = [
+            HashMap::from([(CrossingId(0), 1.0)]),
+            HashMap::from([(CrossingId(1), 1.0)]),
+            HashMap::from([(CrossingId(0), 1.0), (CrossingId(2), 1.0)]),
+        ];
+        let weights_after_wipeouts = |decay| {
+            let mut weights = CrossingWeights::with_decay(3, decay);
+            for weight_updates in &wipeouts {
+                weights.record_wipeout(weight_updates);
+            }
+            weights.into_vec()
+        };
+
+        assert_eq!(
+            weights_after_wipeouts(WeightDecay::None),
+            vec![3.0, 2.0, 2.0]
+        );
+        assert_eq!(
+            weights_after_wipeouts(WeightDecay::Exponential(0.5)),
+            vec![2.25, 1.5, 2.0]
+        );
+
+        // Only the last two wipeouts count, so the first update to crossing 0 has been forgotten.
+        assert_eq!(
+            weights_after_wipeouts(WeightDecay::Windowed(2)),
+            vec![2.0, 2.0, 2.0]
+        );
+        assert_eq!(
+            weights_after_wipeouts(WeightDecay::Windowed(0)),
+            vec![1.0, 1.0, 1.0]
+        );
+    }
+}
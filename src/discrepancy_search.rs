@@ -0,0 +1,277 @@
+//! This module implements limited-discrepancy search (Harvey and Ginsberg), an alternative to the
+//! randomized restarts in `backtracking_search`. Words are always tried in score order, and taking
+//! the Nth-best available word for a slot instead of the best one costs N "discrepancies". Each
+//! iteration runs a complete depth-first search with a bounded discrepancy budget, increasing the
+//! budget until a fill is found. Since every iteration starts by trusting the value ordering almost
+//! completely, this tends to find fills quickly when the ordering is only misleading in a few
+//! places -- as in heavily-themed grids, where restarts keep stumbling on the same bad choices.
+
+use float_ord::FloatOrd;
+use instant::Instant;
+
+use crate::arc_consistency::EliminationSet;
+use crate::backtracking_search::{
+    available_options, calculate_slot_priority, calculate_slot_weights, maintain_arc_consistency,
+    slot_fill_stats, undo_choice, ArcConsistencyMode, FillFailure, FillSuccess, PartialFill, Slot,
+    INTERRUPT_FREQUENCY,
+};
+use crate::crossing_weights::CrossingWeights;
+use crate::grid_config::{Choice, GridConfig, SlotId};
+use crate::required_words::RequiredWords;
+use crate::tag_caps::{tag_caps_satisfied, TagCap};
+
+/// Shared state threaded through the recursive search.
+struct DiscrepancyState<'a> {
+    config: &'a GridConfig<'a>,
+    deadline: Option<Instant>,
+    crossing_weights: &'a mut CrossingWeights,
+    elimination_sets: &'a mut [EliminationSet],
+    start: Instant,
+
+    /// Our statistics and deepest partial fill so far, in case we get interrupted.
+    partial: PartialFill,
+
+    /// Did the current iteration skip any branches because they would have exceeded the budget?
+    pruned: bool,
+
+    /// Words that must appear somewhere in the fill, if any.
+    required_words: Option<&'a RequiredWords>,
+
+    /// Limits on the entries carrying particular tags.
+    tag_caps: &'a [TagCap],
+}
+
+impl DiscrepancyState<'_> {
+    /// Is there still room for all of the required words in the given state, without exceeding any
+    /// of the tag caps?
+    fn global_constraints_satisfiable(&self, slots: &[Slot]) -> bool {
+        self.required_words
+            .map_or(true, |required_words| required_words.is_satisfiable(slots))
+            && tag_caps_satisfied(self.config, slots, self.tag_caps)
+    }
+}
+
+/// Choose the undetermined slot with the best `dom/wdeg` priority. Unlike `choose_next_slot`, this
+/// is deterministic, so that each iteration explores a predictable part of the tree.
+fn choose_discrepancy_slot(slots: &[Slot], slot_weights: &[f32]) -> Option<SlotId> {
+    slots
+        .iter()
+        .filter(|slot| slot.fixed_word_id.is_none() && slot.remaining_option_count > 1)
+        .min_by_key(|slot| FloatOrd(calculate_slot_priority(slots, slot_weights, slot.id)))
+        .map(|slot| slot.id)
+}
+
+/// Search the subtree below the current state using at most `budget` discrepancies, returning
+/// whether we found a fill. If we did, `slots` is left in the solved state.
+fn search_with_budget(
+    state: &mut DiscrepancyState,
+    slots: &mut [Slot],
+    budget: usize,
+) -> Result<bool, FillFailure> {
+    state.partial.statistics.states += 1;
+
+    if state.partial.statistics.states % INTERRUPT_FREQUENCY == 0 {
+        if let Some(deadline) = state.deadline {
+            if Instant::now() > deadline {
+                state.partial.statistics.total_time = state.start.elapsed();
+                state.partial.crossing_weights = state.crossing_weights.clone();
+                return Err(FillFailure::Timeout(Box::new(std::mem::take(
+                    &mut state.partial,
+                ))));
+            }
+        }
+    }
+    if let Some(cancel) = &state.config.cancel {
+        // Only read the clock for the token's deadline as often as we check our own.
+        let cancelled = if state.partial.statistics.states % INTERRUPT_FREQUENCY == 0 {
+            cancel.is_cancelled()
+        } else {
+            cancel.was_cancelled()
+        };
+        if cancelled {
+            state.partial.statistics.total_time = state.start.elapsed();
+            state.partial.crossing_weights = state.crossing_weights.clone();
+            return Err(FillFailure::Abort(Box::new(std::mem::take(
+                &mut state.partial,
+            ))));
+        }
+    }
+
+    let slot_weights = calculate_slot_weights(state.config, slots, state.crossing_weights);
+    let Some(slot_id) = choose_discrepancy_slot(slots, &slot_weights) else {
+        return Ok(true);
+    };
+
+    for (rank, word_id) in available_options(state.config, &slots[slot_id.index()])
+        .into_iter()
+        .enumerate()
+    {
+        if rank > budget {
+            state.pruned = true;
+            break;
+        }
+
+        if maintain_arc_consistency(
+            state.config,
+            slots,
+            state.crossing_weights,
+            &slot_weights,
+            &ArcConsistencyMode::Choice(Choice { slot_id, word_id }),
+            &mut state.partial.statistics.choice_arc_consistency_time,
+            state.elimination_sets,
+        ) {
+            if !state.global_constraints_satisfiable(slots) {
+                undo_choice(state.config, slots, slot_id);
+                state.partial.statistics.backtracks += 1;
+                continue;
+            }
+
+            state.partial.record_depth(state.config, slots);
+            if search_with_budget(state, slots, budget - rank)? {
+                return Ok(true);
+            }
+            undo_choice(state.config, slots, slot_id);
+        }
+
+        state.partial.statistics.backtracks += 1;
+    }
+
+    Ok(false)
+}
+
+/// Search for a valid fill using limited-discrepancy search, starting from slots that have already
+/// been made arc-consistent. `statistics.retries` records the number of completed iterations. If
+/// `required_words` is given, only fills containing all of them are accepted, and likewise only fills
+/// respecting `tag_caps`.
+pub fn find_fill_with_discrepancies(
+    config: &GridConfig,
+    slots: &[Slot],
+    deadline: Option<Instant>,
+    crossing_weights: &mut CrossingWeights,
+    elimination_sets: &mut [EliminationSet],
+    required_words: Option<&RequiredWords>,
+    tag_caps: &[TagCap],
+) -> Result<FillSuccess, FillFailure> {
+    let mut state = DiscrepancyState {
+        config,
+        deadline,
+        crossing_weights,
+        elimination_sets,
+        start: Instant::now(),
+        partial: PartialFill::default(),
+        pruned: false,
+        required_words,
+        tag_caps,
+    };
+
+    if !state.global_constraints_satisfiable(slots) {
+        state.partial.statistics.total_time = state.start.elapsed();
+        state.partial.crossing_weights = state.crossing_weights.clone();
+        return Err(FillFailure::HardFailure(Box::new(state.partial)));
+    }
+
+    for budget in 0.. {
+        let mut iteration_slots = slots.to_vec();
+        state.pruned = false;
+
+        if search_with_budget(&mut state, &mut iteration_slots, budget)? {
+            let mut statistics = state.partial.statistics;
+            statistics.retries = budget;
+            statistics.total_time = state.start.elapsed();
+
+            let choices: Vec<Choice> = iteration_slots
+                .iter()
+                .map(|slot| {
+                    slot.get_choice(config)
+                        .expect("Failed to identify single choice for slot")
+                })
+                .collect();
+
+            return Ok(FillSuccess {
+                statistics,
+                slot_stats: slot_fill_stats(config, &choices, &[], &[]),
+                choices,
+                ..FillSuccess::default()
+            });
+        }
+
+        // If we didn't have to skip anything, this iteration covered the whole tree.
+        if !state.pruned {
+            state.partial.statistics.total_time = state.start.elapsed();
+            state.partial.crossing_weights = state.crossing_weights.clone();
+            return Err(FillFailure::HardFailure(Box::new(std::mem::take(
+                &mut state.partial,
+            ))));
+        }
+        state.partial.statistics.retries += 1;
+    }
+
+    unreachable!();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backtracking_search::{
+        find_fill_with_search_config, FillFailure, FillSuccess, SearchConfig, SearchStrategy,
+    };
+    use crate::grid_config::{
+        generate_grid_config_from_template_string, render_grid, OwnedGridConfig,
+    };
+    use crate::word_list::tests::word_list_source_config;
+    use crate::word_list::{WordList, WordListSourceConfig};
+
+    fn find_fill_lds(grid_config: &OwnedGridConfig) -> Result<FillSuccess, FillFailure> {
+        find_fill_with_search_config(
+            grid_config,
+            None,
+            None,
+            &SearchConfig {
+                strategy: SearchStrategy::LimitedDiscrepancy,
+                ..SearchConfig::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_find_fill_with_discrepancies() {
+        let word_list = WordList::new(word_list_source_config(), None, Some(5), Some(5));
+        let grid_config = generate_grid_config_from_template_string(
+            word_list,
+            "
+            .....
+            .....
+            .....
+            .....
+            .....
+            ",
+            40,
+        );
+
+        let result = find_fill_lds(&grid_config).expect("Failed to find a fill");
+
+        println!("{:?}", result.statistics);
+        println!("{}", render_grid(&grid_config, &result.choices));
+    }
+
+    #[test]
+    fn test_discrepancy_search_fails_gracefully() {
+        let word_list = WordList::new(
+            vec![WordListSourceConfig::Memory {
+                id: "0".into(),
+                enabled: true,
+                words: ["ab", "cd", "ac"]
+                    .iter()
+                    .map(|&word| (word.into(), 50))
+                    .collect(),
+            }],
+            None,
+            Some(2),
+            None,
+        );
+        let grid_config = generate_grid_config_from_template_string(word_list, "..\n..", 40);
+
+        let result = find_fill_lds(&grid_config);
+
+        assert!(matches!(result, Err(FillFailure::HardFailure(_))));
+    }
+}
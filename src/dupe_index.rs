@@ -1,5 +1,5 @@
 use crate::types::{GlobalWordId, GlyphId, WordId};
-use crate::word_list::Word;
+use crate::word_list::{Word, WordList};
 use std::collections::{HashMap, HashSet};
 use std::{mem, vec};
 
@@ -19,6 +19,18 @@ pub struct DupeIndex<const WINDOW_SIZE: usize> {
 
     /// For a given substring, the index in `groups` that represents words containing it.
     pub group_key_by_substring: HashMap<[GlyphId; WINDOW_SIZE], usize>,
+
+    /// For each multi-word entry, the words in the list that make it up (see `CompoundSplitting`).
+    pub components_by_compound: HashMap<GlobalWordId, Vec<GlobalWordId>>,
+
+    /// The inverse of `components_by_compound`: for each component word, the entries containing it.
+    pub compounds_by_component: HashMap<GlobalWordId, Vec<GlobalWordId>>,
+
+    /// Groups of words that sound alike (see `WordList::phonetic_encoding`).
+    pub phonetic_groups: Vec<Vec<GlobalWordId>>,
+
+    /// For a given word, the index in `phonetic_groups` of the group it belongs to, if any.
+    pub phonetic_group_key_by_word: HashMap<GlobalWordId, usize>,
 }
 
 impl<const WINDOW_SIZE: usize> Default for DupeIndex<WINDOW_SIZE> {
@@ -29,6 +41,10 @@ impl<const WINDOW_SIZE: usize> Default for DupeIndex<WINDOW_SIZE> {
             extra_dupes_by_word: HashMap::new(),
             group_keys_by_word: HashMap::new(),
             group_key_by_substring: HashMap::new(),
+            components_by_compound: HashMap::new(),
+            compounds_by_component: HashMap::new(),
+            phonetic_groups: vec![],
+            phonetic_group_key_by_word: HashMap::new(),
         }
     }
 }
@@ -42,8 +58,16 @@ pub trait AnyDupeIndex {
     fn get_dupes_by_length(&self, global_word_id: GlobalWordId) -> HashMap<usize, HashSet<WordId>>;
 
     // Allow moving extra dupe pairs in and out to facilitate replacing the word list.
+    fn extra_dupes(&self) -> &HashMap<GlobalWordId, Vec<GlobalWordId>>;
     fn take_extra_dupes(&mut self) -> HashMap<GlobalWordId, Vec<GlobalWordId>>;
     fn put_extra_dupes(&mut self, extra_dupes: HashMap<GlobalWordId, Vec<GlobalWordId>>);
+
+    // Compound components are derived from the whole list, so they're replaced all at once.
+    fn set_compound_components(
+        &mut self,
+        components_by_compound: HashMap<GlobalWordId, Vec<GlobalWordId>>,
+    );
+    fn set_phonetic_groups(&mut self, phonetic_groups: Vec<Vec<GlobalWordId>>);
 }
 
 impl<const WINDOW_SIZE: usize> AnyDupeIndex for DupeIndex<WINDOW_SIZE> {
@@ -137,9 +161,37 @@ impl<const WINDOW_SIZE: usize> AnyDupeIndex for DupeIndex<WINDOW_SIZE> {
             }
         }
 
+        // A compound dupes each of its components, along with any other compound sharing one.
+        if let Some(components) = self.components_by_compound.get(&global_word_id) {
+            for component in components {
+                let sharing_compounds = self.compounds_by_component.get(component);
+                for &(length, word) in
+                    std::iter::once(component).chain(sharing_compounds.into_iter().flatten())
+                {
+                    dupes_by_length.entry(length).or_default().insert(word);
+                }
+            }
+        }
+
+        if let Some(compounds) = self.compounds_by_component.get(&global_word_id) {
+            for &(length, word) in compounds {
+                dupes_by_length.entry(length).or_default().insert(word);
+            }
+        }
+
+        if let Some(&group_key) = self.phonetic_group_key_by_word.get(&global_word_id) {
+            for &(length, word) in &self.phonetic_groups[group_key] {
+                dupes_by_length.entry(length).or_default().insert(word);
+            }
+        }
+
         dupes_by_length
     }
 
+    fn extra_dupes(&self) -> &HashMap<GlobalWordId, Vec<GlobalWordId>> {
+        &self.extra_dupes_by_word
+    }
+
     fn take_extra_dupes(&mut self) -> HashMap<GlobalWordId, Vec<GlobalWordId>> {
         mem::take(&mut self.extra_dupes_by_word)
     }
@@ -147,6 +199,310 @@ impl<const WINDOW_SIZE: usize> AnyDupeIndex for DupeIndex<WINDOW_SIZE> {
     fn put_extra_dupes(&mut self, extra_dupes: HashMap<GlobalWordId, Vec<GlobalWordId>>) {
         self.extra_dupes_by_word = extra_dupes;
     }
+
+    /// Replace the index's record of which words make up each multi-word entry.
+    fn set_compound_components(
+        &mut self,
+        components_by_compound: HashMap<GlobalWordId, Vec<GlobalWordId>>,
+    ) {
+        let mut compounds_by_component: HashMap<GlobalWordId, Vec<GlobalWordId>> = HashMap::new();
+        for (&compound, components) in &components_by_compound {
+            for &component in components {
+                compounds_by_component
+                    .entry(component)
+                    .or_default()
+                    .push(compound);
+            }
+        }
+        self.components_by_compound = components_by_compound;
+        self.compounds_by_component = compounds_by_component;
+    }
+
+    /// Replace the index's groups of words that sound alike.
+    fn set_phonetic_groups(&mut self, phonetic_groups: Vec<Vec<GlobalWordId>>) {
+        self.phonetic_group_key_by_word = phonetic_groups
+            .iter()
+            .enumerate()
+            .flat_map(|(group_key, group)| group.iter().map(move |&word| (word, group_key)))
+            .collect();
+        self.phonetic_groups = phonetic_groups;
+    }
 }
 
 pub type BoxedDupeIndex = Box<dyn AnyDupeIndex + Send + Sync>;
+
+/// Settings for treating multi-word entries as dupes of the words they're made of, so that (for
+/// example) HOTDOGSTAND can't appear in the same fill as DOG, even though substring checks alone
+/// would only catch that with a very low `max_shared_substring`. An entry's components come from
+/// its display form in the word list (`hot dog stand` or `hot-dog stand`), or, if
+/// `segment_unspaced` is set, from splitting it into other words in the list. Components shorter
+/// than `min_component_length` are ignored, so that pieces like the A in ATEAM don't count.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct CompoundSplitting {
+    pub min_component_length: usize,
+
+    /// Should entries listed without spaces be split using the rest of the list? This finds far
+    /// more compounds, but also some spurious ones (like SCAR and LET in SCARLET), and is slow to
+    /// compute for large lists.
+    pub segment_unspaced: bool,
+}
+
+impl Default for CompoundSplitting {
+    fn default() -> Self {
+        CompoundSplitting {
+            min_component_length: 3,
+            segment_unspaced: false,
+        }
+    }
+}
+
+/// A per-fill override of the word list's dupe rules for entries carrying a given tag. Theme
+/// entries, for instance, can be allowed to echo each other deliberately while the rest of the fill
+/// is held to a stricter standard than usual against echoing them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct TagDupeRule {
+    pub tag: String,
+
+    /// The longest substring that two entries with the tag can share, or `None` if they can share
+    /// anything short of being identical.
+    pub max_shared_within: Option<usize>,
+
+    /// The longest substring that an entry with the tag can share with an entry without it, or
+    /// `None` if they can share anything short of being identical.
+    pub max_shared_with_others: Option<usize>,
+}
+
+/// A set of `TagDupeRule`s, along with which words each one covers. Any pair of words involving a
+/// tagged word is judged by the rules for its tags (the strictest one, if several apply) instead of
+/// by the word list's `DupeIndex`; all other pairs are left to the index. Like the index, this is
+/// enforced during arc consistency whenever a slot is narrowed down to a single option.
+#[derive(Debug, Clone)]
+pub struct DupePolicy {
+    rules: Vec<TagDupeRule>,
+
+    /// For each rule, whether each word carries its tag, indexed by length and then `WordId`.
+    tagged: Vec<Vec<Vec<bool>>>,
+}
+
+impl DupePolicy {
+    /// Build a policy applying the given rules to the words in the given list.
+    #[must_use]
+    pub fn new(word_list: &WordList, rules: Vec<TagDupeRule>) -> DupePolicy {
+        let tagged = rules
+            .iter()
+            .map(|rule| {
+                let mut tagged: Vec<Vec<bool>> = word_list
+                    .words
+                    .iter()
+                    .map(|bucket| vec![false; bucket.len()])
+                    .collect();
+                for (&(length, word_id), tags) in &word_list.tags_by_word_id {
+                    if tags.contains(&rule.tag) {
+                        tagged[length][word_id.index()] = true;
+                    }
+                }
+                tagged
+            })
+            .collect();
+
+        DupePolicy { rules, tagged }
+    }
+
+    #[must_use]
+    pub fn rules(&self) -> &[TagDupeRule] {
+        &self.rules
+    }
+
+    /// Are the two given words dupes under this policy? Returns `None` if no rule covers the pair,
+    /// meaning that the word list's `DupeIndex` decides.
+    #[must_use]
+    pub fn is_dupe(
+        &self,
+        word_list: &WordList,
+        global_word_id_1: GlobalWordId,
+        global_word_id_2: GlobalWordId,
+    ) -> Option<bool> {
+        let is_tagged = |tagged: &[Vec<bool>], (length, word_id): GlobalWordId| {
+            tagged
+                .get(length)
+                .and_then(|bucket| bucket.get(word_id.index()))
+                .copied()
+                .unwrap_or(false)
+        };
+
+        let mut covered = false;
+        let mut max_shared: Option<usize> = None;
+        for (rule, tagged) in self.rules.iter().zip(&self.tagged) {
+            let limit = match (
+                is_tagged(tagged, global_word_id_1),
+                is_tagged(tagged, global_word_id_2),
+            ) {
+                (true, true) => rule.max_shared_within,
+                (true, false) | (false, true) => rule.max_shared_with_others,
+                (false, false) => continue,
+            };
+            covered = true;
+            if let Some(limit) = limit {
+                max_shared = Some(max_shared.map_or(limit, |max_shared| max_shared.min(limit)));
+            }
+        }
+        if !covered {
+            return None;
+        }
+
+        if global_word_id_1 == global_word_id_2 {
+            return Some(true);
+        }
+        let Some(max_shared) = max_shared else {
+            return Some(false);
+        };
+
+        let glyphs_1 = &word_list.words[global_word_id_1.0][global_word_id_1.1.index()].glyphs;
+        let glyphs_2 = &word_list.words[global_word_id_2.0][global_word_id_2.1.index()].glyphs;
+        Some(glyphs_1.windows(max_shared + 1).any(|window| {
+            glyphs_2
+                .windows(max_shared + 1)
+                .any(|other| other == window)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dupe_index::{CompoundSplitting, DupePolicy, TagDupeRule};
+    use crate::grid_config::generate_grid_config_from_template_string;
+    use crate::solution_counting::count_solutions;
+    use crate::word_list::{WordIterSource, WordList, WordListOptions, WordListSourceConfig};
+    use std::borrow::Cow;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_dupe_policy() {
+        let word_list = || {
+            WordList::new(
+                vec![WordListSourceConfig::Iter {
+                    id: "0".into(),
+                    enabled: true,
+                    words: WordIterSource::new(|| {
+                        [
+                            ("stare", true),
+                            ("stark", true),
+                            ("tares", false),
+                            ("pluck", false),
+                        ]
+                        .into_iter()
+                        .map(|(word, is_theme)| {
+                            let tags = if is_theme {
+                                vec!["theme".into()]
+                            } else {
+                                vec![]
+                            };
+                            (word.to_string(), 50, tags)
+                        })
+                    }),
+                }],
+                None,
+                None,
+                Some(3),
+            )
+        };
+        let count_fills = |dupe_rules: Option<Vec<TagDupeRule>>| {
+            let mut grid_config =
+                generate_grid_config_from_template_string(word_list(), "stare\n#####\n.....", 40);
+            grid_config.dupe_policy =
+                dupe_rules.map(|rules| Arc::new(DupePolicy::new(&grid_config.word_list, rules)));
+            count_solutions(&grid_config, usize::MAX).count
+        };
+
+        // Normally both STARK and TARES share four letters with STARE.
+        assert_eq!(count_fills(None), 1);
+
+        // Theme entries can echo each other freely, but the fill can't share three letters with
+        // them.
+        assert_eq!(
+            count_fills(Some(vec![TagDupeRule {
+                tag: "theme".into(),
+                max_shared_within: None,
+                max_shared_with_others: Some(2),
+            }])),
+            2
+        );
+        assert_eq!(
+            count_fills(Some(vec![TagDupeRule {
+                tag: "theme".into(),
+                max_shared_within: Some(3),
+                max_shared_with_others: Some(3),
+            }])),
+            1
+        );
+    }
+
+    #[test]
+    fn test_compound_splitting() {
+        let mut word_list = WordList::new_with_options(
+            vec![WordListSourceConfig::FileContents {
+                id: "0".into(),
+                enabled: true,
+                contents: Cow::Borrowed(
+                    "hot dog stand;50\nhot;50\ndog;50\nsundog;50\nsun;50\nstar;50",
+                ),
+            }],
+            None,
+            WordListOptions {
+                compound_splitting: Some(CompoundSplitting::default()),
+                ..WordListOptions::default()
+            },
+        );
+        let id = |word: &str| (word.len(), word_list.word_id_by_string[word]);
+        let (hotdogstand, hot, dog, sundog, sun, star) = (
+            id("hotdogstand"),
+            id("hot"),
+            id("dog"),
+            id("sundog"),
+            id("sun"),
+            id("star"),
+        );
+        let is_dupe = |word_list: &WordList, (length_1, word_id_1), (length_2, word_id_2)| {
+            word_list
+                .dupe_index
+                .get_dupes_by_length((length_1, word_id_1))
+                .get(&length_2)
+                .map_or(false, |dupes| dupes.contains(&word_id_2))
+        };
+
+        // The display form splits HOTDOGSTAND even without substring checks.
+        assert!(is_dupe(&word_list, hotdogstand, dog));
+        assert!(is_dupe(&word_list, dog, hotdogstand));
+        assert!(is_dupe(&word_list, hot, hotdogstand));
+        assert!(!is_dupe(&word_list, hotdogstand, star));
+        assert!(!is_dupe(&word_list, sundog, dog));
+
+        // Segmentation also catches SUNDOG, which then dupes HOTDOGSTAND through their shared DOG.
+        word_list.set_compound_splitting(Some(CompoundSplitting {
+            segment_unspaced: true,
+            ..CompoundSplitting::default()
+        }));
+        assert!(is_dupe(&word_list, sundog, dog));
+        assert!(is_dupe(&word_list, sun, sundog));
+        assert!(is_dupe(&word_list, sundog, hotdogstand));
+
+        // Components shorter than the minimum don't count.
+        word_list.set_compound_splitting(Some(CompoundSplitting {
+            min_component_length: 4,
+            segment_unspaced: true,
+        }));
+        assert!(!is_dupe(&word_list, hotdogstand, dog));
+        assert!(!is_dupe(&word_list, sundog, dog));
+
+        word_list.set_compound_splitting(None);
+        assert!(!is_dupe(&word_list, hotdogstand, dog));
+    }
+}
@@ -0,0 +1,220 @@
+//! This module defines the error types returned by the crate's public entry points, so that
+//! embedders (including the CLI and the Wasm layer) can report problems with their input instead of
+//! having to catch panics or Wasm traps.
+
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use thiserror::Error;
+
+use crate::backtracking_search::{FillFailure, PartialFill};
+use crate::types::{SlotId, WordId};
+
+/// A problem with one of the sources making up a `WordList`.
+#[derive(Debug, Clone, Error)]
+pub enum WordListError {
+    #[error("Can’t read file: “{0}”")]
+    InvalidPath(String),
+
+    #[error(transparent)]
+    InvalidLine(#[from] SourceLineError),
+
+    #[error("Word list source “{0}” can’t be written to")]
+    ReadOnlySource(String),
+
+    #[error("Can’t fetch word list from “{url}”: {reason}")]
+    FetchFailed { url: String, reason: String },
+
+    #[error("The bundled word list “{0}” isn’t included in this build")]
+    BundledListUnavailable(String),
+}
+
+/// The reason a line in a word list file was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceLineErrorKind {
+    /// The score after the `;` isn't a valid integer from 0 to 65535.
+    InvalidScore,
+
+    /// The word contains a character that couldn't be decoded.
+    InvalidCharacter,
+
+    /// The word is longer than `MAX_WORD_LIST_ENTRY_LENGTH`.
+    TooLong,
+}
+
+/// A line in a word list file that was rejected, with enough context for an editor to highlight it.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub struct SourceLineError {
+    /// The 1-based number of the rejected line. For sources that provide words directly rather
+    /// than as text, this is the 1-based position of the rejected word.
+    pub line_number: usize,
+
+    /// The part of the line that caused the problem (the word or the score, depending on `kind`).
+    pub text: String,
+
+    pub kind: SourceLineErrorKind,
+}
+
+impl Display for SourceLineError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let description = match self.kind {
+            SourceLineErrorKind::InvalidScore => "invalid score",
+            SourceLineErrorKind::InvalidCharacter => "invalid word",
+            SourceLineErrorKind::TooLong => "overlong word",
+        };
+        write!(
+            f,
+            "Word list contains {description} on line {}: “{}”",
+            self.line_number, self.text
+        )
+    }
+}
+
+/// A problem with the shape or contents of a grid.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum GridError {
+    #[error("Grid must have at least one row")]
+    Empty,
+
+    #[error("Rows in grid must all be the same length")]
+    UnevenRows,
+
+    #[error("Grid fill has {actual} cells, but a {width}x{height} grid needs {expected}")]
+    FillSizeMismatch {
+        width: usize,
+        height: usize,
+        expected: usize,
+        actual: usize,
+    },
+
+    #[error("More than two entries cross at ({0}, {1})")]
+    TooManyCrossings(usize, usize),
+
+    #[error("Answer has {letters} letters, but {cells} cells were given to spell it")]
+    AnswerLengthMismatch { cells: usize, letters: usize },
+
+    #[error("Cell ({0}, {1}) isn't part of any entry")]
+    CellNotInEntry(usize, usize),
+
+    #[error("Cell ({0}, {1}) already contains a different letter")]
+    ConflictingCell(usize, usize),
+
+    #[error("Cell ({x}, {y}) contains {letter:?}, which isn't a letter or digit")]
+    InvalidLetter { x: usize, y: usize, letter: char },
+
+    #[error("Grid contains {0:?}, which doesn't appear in the word list")]
+    UnknownGlyph(char),
+
+    #[error("Grid contains {0:?}, which isn't in the word list")]
+    UnknownEntry(String),
+
+    #[error("Grid is {}x{}, but {}x{} was expected", actual.0, actual.1, expected.0, expected.1)]
+    SizeMismatch {
+        expected: (usize, usize),
+        actual: (usize, usize),
+    },
+
+    #[error("Invalid filter pattern: “{0}”")]
+    InvalidFilterPattern(String),
+}
+
+/// An inconsistency in the live state of a fill, as found by `audit::audit_consistency`.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum AuditError {
+    #[error("Grid has {expected} slots, but {actual} slot states were given")]
+    SlotCountMismatch { expected: usize, actual: usize },
+
+    #[error("State for slot {0} doesn't match the slot's config")]
+    SlotConfigMismatch(SlotId),
+
+    #[error(
+        "Slot {slot_id} has {actual} remaining options, but its eliminations leave {expected}"
+    )]
+    OptionCountMismatch {
+        slot_id: SlotId,
+        expected: usize,
+        actual: usize,
+    },
+
+    #[error("Slot {0} has live options that don't match its eliminations")]
+    LiveOptionsMismatch(SlotId),
+
+    #[error("Slot {0} has glyph counts that don't match its remaining options")]
+    GlyphCountMismatch(SlotId),
+
+    #[error("Slot {slot_id} has an elimination of word {word_id} blamed on slot {blamed_slot_id}, which has no choice")]
+    StaleElimination {
+        slot_id: SlotId,
+        word_id: WordId,
+        blamed_slot_id: SlotId,
+    },
+
+    #[error("Slot {0} has a fixed word that isn't one of its options or doesn't match its fixed glyph counts")]
+    InvalidFixedWord(SlotId),
+}
+
+/// A top-level error from trying to fill a grid, covering everything from invalid input to the
+/// search itself failing. Errors from the search carry the information it collected before it
+/// stopped, so that callers can still report on it (e.g. with `crossing_report`).
+#[derive(Debug, Error)]
+pub enum FillError {
+    #[error(transparent)]
+    WordList(#[from] WordListError),
+
+    #[error(transparent)]
+    Grid(#[from] GridError),
+
+    #[error("Word list is empty")]
+    EmptyWordList,
+
+    #[error("Unfillable grid")]
+    Unfillable(Box<PartialFill>),
+
+    #[error("Timed out before finding a fill")]
+    Timeout(Box<PartialFill>),
+
+    #[error("Fill was aborted")]
+    Abort(Box<PartialFill>),
+
+    #[error("Exceeded backtrack limit of {0}")]
+    ExceededBacktrackLimit(usize, Box<PartialFill>),
+
+    /// An alternative fill backend failed for reasons unrelated to the grid itself.
+    #[error("Fill backend failed: {0}")]
+    Solver(String),
+
+    /// A fill request was written for a version of the schema in `fill_schema.rs` that we don't
+    /// understand.
+    #[error("Unsupported fill request version {0}")]
+    UnsupportedSchemaVersion(u32),
+}
+
+impl FillError {
+    /// The information collected by the search before it failed, if the error came from a search.
+    #[must_use]
+    pub fn partial(&self) -> Option<&PartialFill> {
+        match self {
+            FillError::Unfillable(partial)
+            | FillError::Timeout(partial)
+            | FillError::Abort(partial)
+            | FillError::ExceededBacktrackLimit(_, partial) => Some(partial),
+            FillError::WordList(_)
+            | FillError::Grid(_)
+            | FillError::EmptyWordList
+            | FillError::Solver(_)
+            | FillError::UnsupportedSchemaVersion(_) => None,
+        }
+    }
+}
+
+impl From<FillFailure> for FillError {
+    fn from(failure: FillFailure) -> FillError {
+        match failure {
+            FillFailure::HardFailure(partial) => FillError::Unfillable(partial),
+            FillFailure::Timeout(partial) => FillError::Timeout(partial),
+            FillFailure::Abort(partial) => FillError::Abort(partial),
+            FillFailure::ExceededBacktrackLimit(limit, partial) => {
+                FillError::ExceededBacktrackLimit(limit, partial)
+            }
+        }
+    }
+}
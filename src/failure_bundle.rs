@@ -0,0 +1,337 @@
+//! This module supports reproducing a failed fill somewhere other than where it happened, e.g. when
+//! a user reports that a grid is unexpectedly unfillable. A `FailureBundle` captures everything
+//! the search depended on in a self-contained form: the grid itself, the subset of the word list
+//! that could actually appear in it, any per-slot restrictions on top of that, and the search
+//! settings. Since the bundle refers to words by their strings rather than their ids, it doesn't
+//! need the original `WordList`, and with the `serde` feature it can be saved as JSON and attached
+//! to a bug report. `reproduce` then runs the same search from the bundle alone.
+//!
+//! Bundles are captured on hard failures when `SearchConfig::capture_failure_bundle` is set (see
+//! `PartialFill::failure_bundle`), or can be built directly with `FailureBundle::capture`.
+
+use instant::Duration;
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+use crate::backtracking_search::{
+    config_fingerprint, find_fill_with_search_config, FillSuccess, SearchConfig, SearchStrategy,
+    SlotHeuristic,
+};
+use crate::error::FillError;
+use crate::grid_config::{
+    generate_grid_config, template_string_dimensions, template_string_fill, CrossingQuality,
+    GridConfig, SlotConfig, SlotSpec,
+};
+use crate::required_words::RequiredWords;
+use crate::types::GlobalWordId;
+use crate::word_list::{WordList, WordListSourceConfig};
+
+/// A self-contained description of a fill attempt; see the module docs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct FailureBundle {
+    /// The grid, as a template string with . representing empty cells, # representing cells that
+    /// aren't part of any slot, and letters representing themselves.
+    pub template: String,
+
+    /// The grid's slots, in `SlotId` order. These are usually the same as the slots implied by
+    /// `template`, but grids built from explicit `SlotSpec`s can leave some runs of cells unchecked.
+    pub slot_specs: Vec<SlotSpec>,
+
+    /// Every word that was an option for at least one slot, in the form given by its word list,
+    /// along with its score.
+    pub words: Vec<(String, u16)>,
+
+    /// For each slot, the normalized forms of its options if they were narrower than the words in
+    /// `words` that fit it (e.g., because of a forbidden word or a per-slot minimum score), or
+    /// `None` if the slot could use any of them.
+    pub slot_options: Vec<Option<Vec<String>>>,
+
+    /// The word list's dupe setting; see `WordList::max_shared_substring`.
+    pub max_shared_substring: Option<usize>,
+
+    /// The grid's `GridConfig::crossing_quality` rule, if any.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub crossing_quality: Option<CrossingQuality>,
+
+    pub slot_heuristic: SlotHeuristic,
+    pub last_conflict: bool,
+    pub favor_fresh_words: bool,
+    pub strategy: SearchStrategy,
+
+    /// Normalized forms of the words required by `SearchConfig::required_words`.
+    pub required_words: Vec<String>,
+
+    /// The seed of the attempt that proved the grid unfillable, if it failed during a restart
+    /// search. Attempts are seeded with their retry numbers, so `reproduce` replays every attempt
+    /// up to this one; the seed mostly serves to show how far the original search got.
+    pub failing_seed: Option<u64>,
+
+    /// `WordList::content_hash` and `config_fingerprint` for the original search, for matching a
+    /// bundle with the fill request it came from.
+    pub word_list_hash: u64,
+    pub config_fingerprint: u64,
+}
+
+impl FailureBundle {
+    /// Capture the given grid and search settings. Custom dupe rules, tag caps and dupe policies
+    /// (since the bundled words don't keep their tags), and the source of randomness aren't
+    /// captured; since a hard failure is a proof that there's no fill, it doesn't depend on
+    /// the random choices made along the way.
+    #[must_use]
+    pub fn capture(
+        config: &GridConfig,
+        search_config: &SearchConfig,
+        failing_seed: Option<u64>,
+    ) -> FailureBundle {
+        let word_list = &*config.word_list;
+
+        let mut in_slot = vec![false; config.width * config.height];
+        for slot_config in config.slot_configs.iter() {
+            for cell_idx in slot_config.cell_fill_indices(config.width) {
+                in_slot[cell_idx] = true;
+            }
+        }
+        let template = config
+            .fill
+            .chunks(config.width)
+            .zip(in_slot.chunks(config.width))
+            .map(|(fill_row, in_slot_row)| {
+                fill_row
+                    .iter()
+                    .zip(in_slot_row)
+                    .map(|(cell, &in_slot)| match cell {
+                        _ if !in_slot => '#',
+                        Some(glyph_id) => word_list.glyphs[*glyph_id],
+                        None => '.',
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        // We don't know which part of the grid is responsible for the failure, so we keep every
+        // word that any slot could use, in id order so that the bundle is deterministic.
+        let mut global_word_ids: Vec<GlobalWordId> = config
+            .slot_configs
+            .iter()
+            .zip(config.slot_options.iter())
+            .flat_map(|(slot_config, options)| {
+                options
+                    .iter()
+                    .map(move |&word_id| (slot_config.length, word_id))
+            })
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        global_word_ids.sort_unstable();
+
+        // A slot only needs an explicit list of options if some of the kept words would fit it
+        // without having been among its options.
+        let slot_options = config
+            .slot_configs
+            .iter()
+            .zip(config.slot_options.iter())
+            .map(|(slot_config, options)| {
+                let fill = slot_config.fill(&config.fill, config.width);
+                let fitting_count = global_word_ids
+                    .iter()
+                    .filter(|&&(length, word_id)| {
+                        length == slot_config.length
+                            && word_list.words[length][word_id.index()]
+                                .glyphs
+                                .iter()
+                                .zip(&fill)
+                                .all(|(&glyph, cell)| cell.map_or(true, |cell| cell == glyph))
+                    })
+                    .count();
+
+                (fitting_count != options.len()).then(|| {
+                    options
+                        .iter()
+                        .map(|&word_id| {
+                            word_list
+                                .get_word((slot_config.length, word_id))
+                                .normalized_string
+                                .clone()
+                        })
+                        .collect()
+                })
+            })
+            .collect();
+
+        FailureBundle {
+            template,
+            slot_specs: config
+                .slot_configs
+                .iter()
+                .map(SlotConfig::slot_spec)
+                .collect(),
+            words: global_word_ids
+                .into_iter()
+                .map(|global_word_id| {
+                    let word = word_list.get_word(global_word_id);
+                    (word.canonical_string.clone(), word.score)
+                })
+                .collect(),
+            slot_options,
+            max_shared_substring: word_list.max_shared_substring(),
+            crossing_quality: config.crossing_quality,
+            slot_heuristic: search_config.slot_heuristic,
+            last_conflict: search_config.last_conflict,
+            favor_fresh_words: search_config.favor_fresh_words,
+            strategy: search_config.strategy,
+            required_words: search_config
+                .required_words
+                .as_ref()
+                .map(|required_words| required_words.normalized_words(word_list))
+                .unwrap_or_default(),
+            failing_seed,
+            word_list_hash: word_list.content_hash(),
+            config_fingerprint: config_fingerprint(config, Some(search_config)),
+        }
+    }
+}
+
+/// Run the search described by the given bundle again, using only the information in the bundle.
+/// For a bundle captured from a hard failure, this should return `FillError::Unfillable`; anything
+/// else means the failure depended on something the bundle doesn't capture, or that the solver's
+/// behavior has changed since it was captured.
+pub fn reproduce(
+    bundle: &FailureBundle,
+    timeout: Option<Duration>,
+) -> Result<FillSuccess, FillError> {
+    let (width, height) = template_string_dimensions(&bundle.template)?;
+
+    let word_list = WordList::new(
+        vec![WordListSourceConfig::Memory {
+            id: "bundle".into(),
+            enabled: true,
+            words: bundle.words.clone(),
+        }],
+        None,
+        Some(width.max(height)),
+        bundle.max_shared_substring,
+    );
+    let mut grid_config = generate_grid_config(
+        word_list,
+        &bundle.slot_specs,
+        &template_string_fill(&bundle.template),
+        width,
+        height,
+        0,
+    )?;
+
+    let word_list = &*grid_config.word_list;
+    let slot_options: Vec<_> = grid_config
+        .slot_configs
+        .iter()
+        .zip(grid_config.slot_options.iter())
+        .zip(&bundle.slot_options)
+        .map(
+            |((slot_config, options), allowed_words)| match allowed_words {
+                Some(allowed_words) => {
+                    let allowed_words: HashSet<&str> =
+                        allowed_words.iter().map(String::as_str).collect();
+                    options
+                        .iter()
+                        .copied()
+                        .filter(|&word_id| {
+                            let word = word_list.get_word((slot_config.length, word_id));
+                            allowed_words.contains(word.normalized_string.as_str())
+                        })
+                        .collect()
+                }
+                None => options.clone(),
+            },
+        )
+        .collect();
+    grid_config.slot_options = Cow::Owned(slot_options);
+    grid_config.crossing_quality = bundle.crossing_quality;
+
+    let config = grid_config;
+    let search_config = SearchConfig {
+        slot_heuristic: bundle.slot_heuristic,
+        last_conflict: bundle.last_conflict,
+        favor_fresh_words: bundle.favor_fresh_words,
+        strategy: bundle.strategy,
+        required_words: (!bundle.required_words.is_empty())
+            .then(|| RequiredWords::new(&config, &bundle.required_words)),
+        ..SearchConfig::default()
+    };
+
+    find_fill_with_search_config(&config, timeout, None, &search_config).map_err(FillError::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backtracking_search::{find_fill_with_search_config, FillFailure, SearchConfig};
+    use crate::error::FillError;
+    use crate::failure_bundle::reproduce;
+    use crate::grid_config::generate_grid_config_from_template_string;
+    use crate::word_list::tests::memory_word_list;
+
+    #[test]
+    fn test_capture_and_reproduce_failure_bundle() {
+        let word_list = memory_word_list(&["ab", "cd", "ac", "bd", "Ef", "xyz"]);
+        let mut grid_config = generate_grid_config_from_template_string(word_list, "..\n..", 40);
+
+        // The grid is fillable with either "ab" or "ac" as 1-Across, so it becomes unfillable if
+        // both are ruled out there, even though the other slots can still use them.
+        let restricted_ids: Vec<_> = ["ab", "ac"]
+            .iter()
+            .map(|word| grid_config.word_list.word_id_by_string[*word])
+            .collect();
+        grid_config.slot_options.to_mut()[0].retain(|word_id| !restricted_ids.contains(word_id));
+        let config = grid_config;
+
+        let failure = find_fill_with_search_config(
+            &config,
+            None,
+            None,
+            &SearchConfig {
+                capture_failure_bundle: true,
+                ..SearchConfig::default()
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(failure, FillFailure::HardFailure(_)));
+
+        let bundle = failure
+            .partial()
+            .failure_bundle
+            .as_deref()
+            .expect("failure should include a bundle")
+            .clone();
+        assert_eq!(bundle.template, "..\n..");
+        assert_eq!(bundle.slot_specs.len(), 4);
+        assert_eq!(
+            bundle.words,
+            vec![
+                ("ab".into(), 50),
+                ("cd".into(), 50),
+                ("ac".into(), 50),
+                ("bd".into(), 50),
+                ("Ef".into(), 50),
+            ],
+        );
+        assert_eq!(
+            bundle.slot_options[0],
+            Some(vec!["cd".into(), "bd".into(), "ef".into()])
+        );
+        assert!(bundle.slot_options[1..].iter().all(Option::is_none));
+
+        assert!(matches!(
+            reproduce(&bundle, None),
+            Err(FillError::Unfillable(_))
+        ));
+
+        // Without the restriction on 1-Across, the bundled words are enough for a fill.
+        let mut unrestricted_bundle = bundle.clone();
+        unrestricted_bundle.slot_options[0] = None;
+        assert!(reproduce(&unrestricted_bundle, None).is_ok());
+    }
+}
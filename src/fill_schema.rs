@@ -0,0 +1,404 @@
+//! This module defines the versioned JSON schema for fill jobs, so that the CLI (with `--json`),
+//! the Wasm API (`fill_grid_json`), and the HTTP server (`/fill`) all accept the same requests
+//! and produce the same responses. Any incompatible change to these types should come with a bump
+//! to `FILL_SCHEMA_VERSION`; requests for any other version are rejected rather than guessed at.
+
+use instant::Duration;
+use serde_derive::{Deserialize, Serialize};
+use std::fmt::Display;
+
+use crate::backtracking_search::{
+    find_fill_with_search_config, FillSuccess, SearchConfig, Statistics, StuckSlot,
+};
+use crate::crossing_report::{explain_crossing_weights, slot_labels};
+use crate::error::FillError;
+use crate::fill_service::GridSpec;
+use crate::grid_config::{filled_entries, render_grid, FilledEntry, GridConfig};
+use crate::required_words::RequiredWords;
+
+/// The version of the schema implemented by this module.
+pub const FILL_SCHEMA_VERSION: u32 = 1;
+
+/// The default minimum score for requests that don't specify one.
+pub const DEFAULT_MIN_SCORE: u16 = 50;
+
+/// The maximum number of crossings listed in `Diagnostics::troublesome_crossings`.
+const MAX_TROUBLESOME_CROSSINGS: usize = 5;
+
+fn current_version() -> u32 {
+    FILL_SCHEMA_VERSION
+}
+
+/// A request to fill a single grid.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+pub struct FillRequest {
+    /// The schema version the request was written for; defaults to the current one.
+    #[serde(default = "current_version")]
+    #[cfg_attr(feature = "typescript", ts(as = "Option<u32>", optional))]
+    pub version: u32,
+
+    /// A template string with . representing empty cells, # representing blocks, and letters
+    /// representing themselves. Letters of either case are locked in unless
+    /// `options.soft_uppercase` is set.
+    pub grid: String,
+
+    #[serde(default)]
+    #[cfg_attr(feature = "typescript", ts(as = "Option<FillConstraints>", optional))]
+    pub constraints: FillConstraints,
+
+    #[serde(default)]
+    #[cfg_attr(feature = "typescript", ts(as = "Option<FillOptions>", optional))]
+    pub options: FillOptions,
+}
+
+/// Restrictions on which words can appear in the fill.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[serde(default)]
+pub struct FillConstraints {
+    /// The minimum score for words in the fill [default: `DEFAULT_MIN_SCORE`].
+    #[cfg_attr(feature = "typescript", ts(optional))]
+    pub min_score: Option<u16>,
+
+    /// Words that can't appear in the fill.
+    #[cfg_attr(feature = "typescript", ts(as = "Option<Vec<String>>", optional))]
+    pub forbidden_words: Vec<String>,
+
+    /// Words that must appear somewhere in the fill.
+    #[cfg_attr(feature = "typescript", ts(as = "Option<Vec<String>>", optional))]
+    pub required_words: Vec<String>,
+}
+
+/// Settings for the search itself.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[serde(default)]
+pub struct FillOptions {
+    /// How long to search before giving up, in milliseconds [default: no limit].
+    #[cfg_attr(feature = "typescript", ts(as = "Option<f64>", optional))]
+    pub timeout_ms: Option<u64>,
+
+    /// Treat uppercase letters in the grid as suggestions, which the fill can override, rather
+    /// than locking them in [default: false].
+    #[cfg_attr(feature = "typescript", ts(as = "Option<bool>", optional))]
+    pub soft_uppercase: bool,
+}
+
+impl FillRequest {
+    /// Parse a request from JSON.
+    pub fn from_json(json: &str) -> Result<FillRequest, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Make sure this request was written for a schema version we understand.
+    pub fn check_version(&self) -> Result<(), FillError> {
+        if self.version == FILL_SCHEMA_VERSION {
+            Ok(())
+        } else {
+            Err(FillError::UnsupportedSchemaVersion(self.version))
+        }
+    }
+
+    #[must_use]
+    pub fn min_score(&self) -> u16 {
+        self.constraints.min_score.unwrap_or(DEFAULT_MIN_SCORE)
+    }
+
+    #[must_use]
+    pub fn timeout(&self) -> Option<Duration> {
+        self.options.timeout_ms.map(Duration::from_millis)
+    }
+
+    /// The equivalent spec for filling this grid with a `FillService`.
+    #[must_use]
+    pub fn grid_spec(&self) -> GridSpec {
+        GridSpec {
+            template: self.grid.clone(),
+            min_score: self.min_score(),
+            timeout: self.timeout(),
+            forbidden_words: self.constraints.forbidden_words.clone(),
+            required_words: self.constraints.required_words.clone(),
+            soft_uppercase: self.options.soft_uppercase,
+            ..GridSpec::default()
+        }
+    }
+
+    /// Fill the given grid, which should have been generated from `self.grid`, `self.min_score()`,
+    /// and `self.options.soft_uppercase`, according to the rest of this request.
+    #[must_use]
+    pub fn fill(&self, config: &GridConfig) -> FillResponse {
+        if let Err(error) = self.check_version() {
+            return FillResponse::invalid(&error);
+        }
+
+        let mut config = config.borrowed();
+        config.forbid_words(&self.constraints.forbidden_words);
+        let search_config = SearchConfig {
+            required_words: (!self.constraints.required_words.is_empty())
+                .then(|| RequiredWords::new(&config, &self.constraints.required_words)),
+            ..SearchConfig::default()
+        };
+
+        let result = find_fill_with_search_config(&config, self.timeout(), None, &search_config);
+        FillResponse::from_result(&config, &result.map_err(FillError::from))
+    }
+}
+
+/// The outcome of a fill request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[serde(rename_all = "snake_case")]
+pub enum FillStatus {
+    Filled,
+    Unfillable,
+    Timeout,
+    Aborted,
+    ExceededBacktrackLimit,
+
+    /// The request itself was invalid (e.g., a malformed grid or an unsupported version).
+    InvalidInput,
+
+    /// The fill backend failed for reasons unrelated to the request.
+    SolverError,
+}
+
+impl FillStatus {
+    #[must_use]
+    pub fn from_error(error: &FillError) -> FillStatus {
+        match error {
+            FillError::WordList(_)
+            | FillError::Grid(_)
+            | FillError::EmptyWordList
+            | FillError::UnsupportedSchemaVersion(_) => FillStatus::InvalidInput,
+            FillError::Unfillable(_) => FillStatus::Unfillable,
+            FillError::Timeout(_) => FillStatus::Timeout,
+            FillError::Abort(_) => FillStatus::Aborted,
+            FillError::ExceededBacktrackLimit(..) => FillStatus::ExceededBacktrackLimit,
+            FillError::Solver(_) => FillStatus::SolverError,
+        }
+    }
+}
+
+/// Information about how the search went, whether or not it succeeded.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+pub struct Diagnostics {
+    pub states: usize,
+    pub backtracks: usize,
+    pub retries: usize,
+    #[cfg_attr(feature = "typescript", ts(type = "number"))]
+    pub time_ms: u64,
+
+    /// After a failed search, the crossings that caused the most trouble, most troublesome first;
+    /// see `crossing_report.rs`.
+    pub troublesome_crossings: Vec<String>,
+
+    /// After a failed search, the slot it backtracked out of most often and how many options that
+    /// slot had left, e.g. "17A: 212 backtracks, 3 options left"; see `StuckSlot`.
+    pub stuck_slot: Option<String>,
+}
+
+impl Diagnostics {
+    fn new(statistics: &Statistics) -> Diagnostics {
+        Diagnostics {
+            states: statistics.states,
+            backtracks: statistics.backtracks,
+            retries: statistics.retries,
+            time_ms: statistics.total_time.as_millis() as u64,
+            troublesome_crossings: vec![],
+            stuck_slot: None,
+        }
+    }
+}
+
+/// Describe the given stuck slot for `Diagnostics::stuck_slot`.
+fn describe_stuck_slot(config: &GridConfig, stuck_slot: &StuckSlot) -> String {
+    format!(
+        "{}: {} backtracks, {} options left",
+        slot_labels(config)[stuck_slot.slot_id.index()],
+        stuck_slot.backtracks,
+        stuck_slot.remaining_options.len(),
+    )
+}
+
+/// The response to a fill request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+pub struct FillResponse {
+    pub version: u32,
+
+    pub status: FillStatus,
+
+    /// The filled grid, with # representing blocks, if the fill succeeded.
+    pub grid: Option<String>,
+
+    /// The entries in the fill, in slot order, if the fill succeeded.
+    pub entries: Vec<FilledEntry>,
+
+    /// A description of the problem, if the fill failed.
+    pub error: Option<String>,
+
+    /// `FillSuccess::word_list_hash` and `FillSuccess::config_fingerprint`, as 16-digit hex
+    /// strings (since JSON numbers can't reliably hold 64-bit integers), if the fill succeeded.
+    pub word_list_hash: Option<String>,
+    pub config_fingerprint: Option<String>,
+
+    /// Present whenever a search was attempted.
+    pub diagnostics: Option<Diagnostics>,
+}
+
+impl FillResponse {
+    /// Build a response describing the result of filling the given grid.
+    #[must_use]
+    pub fn from_result(
+        config: &GridConfig,
+        result: &Result<FillSuccess, FillError>,
+    ) -> FillResponse {
+        match result {
+            Ok(success) => FillResponse {
+                version: FILL_SCHEMA_VERSION,
+                status: FillStatus::Filled,
+                grid: Some(render_grid(config, &success.choices).replace('.', "#")),
+                entries: filled_entries(config, &success.choices),
+                error: None,
+                word_list_hash: Some(format!("{:016x}", success.word_list_hash)),
+                config_fingerprint: Some(format!("{:016x}", success.config_fingerprint)),
+                diagnostics: Some(Diagnostics::new(&success.statistics)),
+            },
+            Err(error) => FillResponse {
+                diagnostics: error.partial().map(|partial| Diagnostics {
+                    troublesome_crossings: explain_crossing_weights(
+                        config,
+                        &partial.crossing_weights,
+                    )
+                    .into_iter()
+                    .filter(|entry| entry.share > 0.0)
+                    .take(MAX_TROUBLESOME_CROSSINGS)
+                    .map(|entry| entry.to_string())
+                    .collect(),
+                    stuck_slot: partial
+                        .stuck_slot
+                        .as_ref()
+                        .map(|stuck_slot| describe_stuck_slot(config, stuck_slot)),
+                    ..Diagnostics::new(&partial.statistics)
+                }),
+                ..FillResponse::failure(FillStatus::from_error(error), error)
+            },
+        }
+    }
+
+    /// Build a response for a request that couldn't be attempted at all.
+    #[must_use]
+    pub fn invalid(error: &impl Display) -> FillResponse {
+        FillResponse::failure(FillStatus::InvalidInput, error)
+    }
+
+    fn failure(status: FillStatus, error: &impl Display) -> FillResponse {
+        FillResponse {
+            version: FILL_SCHEMA_VERSION,
+            status,
+            grid: None,
+            entries: vec![],
+            error: Some(error.to_string()),
+            word_list_hash: None,
+            config_fingerprint: None,
+            diagnostics: None,
+        }
+    }
+
+    /// Serialize this response as JSON.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Fill responses are always serializable")
+    }
+}
+
+/// TypeScript declarations for the types above, as included in the Wasm package's `.d.ts` file.
+#[cfg(feature = "typescript")]
+#[must_use]
+pub fn typescript_declarations() -> String {
+    use ts_rs::TS;
+
+    let declarations = [
+        FillRequest::decl(),
+        FillConstraints::decl(),
+        FillOptions::decl(),
+        FillStatus::decl(),
+        Diagnostics::decl(),
+        FillResponse::decl(),
+        FilledEntry::decl(),
+    ];
+    let mut output = String::from(
+        "// Generated from the types in `src/fill_schema.rs`; see `typescript_declarations`.\n",
+    );
+    for declaration in declarations {
+        output.push_str("\nexport ");
+        output.push_str(&declaration);
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fill_schema::{FillRequest, FillResponse, FillStatus, FILL_SCHEMA_VERSION};
+    use crate::grid_config::generate_grid_config_from_template_string;
+    use crate::word_list::tests::memory_word_list;
+
+    #[test]
+    fn test_fill_request() {
+        let word_list = memory_word_list(&["ab", "cd", "ac", "bd", "ef", "gh", "eg", "fh"]);
+        let grid_config = generate_grid_config_from_template_string(word_list, "..\n..", 40);
+        let config = grid_config.borrowed();
+
+        let request = FillRequest::from_json(
+            r#"{"grid": "..\n..", "constraints": {"min_score": 40, "required_words": ["gh"]}}"#,
+        )
+        .unwrap();
+        assert_eq!(request.version, FILL_SCHEMA_VERSION);
+        let response = request.fill(&config);
+        assert_eq!(response.status, FillStatus::Filled);
+        assert_eq!(response.grid.as_deref(), Some("ef\ngh"));
+        assert_eq!(response.entries.len(), 4);
+        assert_eq!(
+            response.word_list_hash,
+            Some(format!("{:016x}", config.word_list.content_hash()))
+        );
+        assert_eq!(
+            serde_json::from_str::<FillResponse>(&response.to_json()).unwrap(),
+            response
+        );
+
+        let request = FillRequest::from_json(
+            r#"{"grid": "..\n..", "constraints": {"forbidden_words": ["ab", "ef"]}}"#,
+        )
+        .unwrap();
+        let response = request.fill(&config);
+        assert_eq!(response.status, FillStatus::Unfillable);
+        assert!(response.error.is_some());
+        assert!(response.diagnostics.is_some());
+
+        let request = FillRequest::from_json(r#"{"version": 99, "grid": "..\n.."}"#).unwrap();
+        let response = request.fill(&config);
+        assert_eq!(response.status, FillStatus::InvalidInput);
+        assert_eq!(response.diagnostics, None);
+    }
+
+    /// Check that the checked-in declarations match the Rust types. Run with
+    /// `UPDATE_TYPESCRIPT=1` to regenerate them after changing the schema.
+    #[cfg(feature = "typescript")]
+    #[test]
+    fn test_typescript_declarations() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/bindings/fill_schema.d.ts");
+        let declarations = crate::fill_schema::typescript_declarations();
+        if std::env::var_os("UPDATE_TYPESCRIPT").is_some() {
+            std::fs::write(path, &declarations).unwrap();
+        }
+        assert_eq!(
+            std::fs::read_to_string(path).unwrap(),
+            declarations,
+            "TypeScript declarations are out of date; rerun with UPDATE_TYPESCRIPT=1"
+        );
+    }
+}
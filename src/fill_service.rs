@@ -0,0 +1,482 @@
+//! This module provides `FillService`, a fill API for long-running servers. Instead of loading a
+//! word list and allocating elimination sets for every request, the service shares one `WordList`
+//! between all requests, keeps a pool of elimination sets to reuse, and remembers the crossing
+//! weights learned for recently filled grids so that refilling the same grid starts out knowing
+//! where the trouble spots are. All of its methods take `&self`, so it can be shared between threads.
+
+use instant::Duration;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+use crate::arc_consistency::EliminationSetPool;
+use crate::backtracking_search::{find_fill_with_crossing_weights, FillSuccess, SearchConfig};
+use crate::cancel::CancelToken;
+use crate::crossing_weights::CrossingWeights;
+use crate::dupe_index::{DupePolicy, TagDupeRule};
+use crate::error::{FillError, GridError};
+use crate::grid_config::{
+    generate_slot_configs, generate_slots_from_template_string, matching_slot_options,
+    sort_slot_options, template_string_dimensions, template_string_hints, CrossingQuality,
+    GridConfig, GridWordList, OwnedGridConfig, SlotConfig, SlotSpec,
+};
+use crate::required_words::RequiredWords;
+use crate::tag_caps::TagCap;
+use crate::types::{GlyphId, WordId};
+use crate::word_list::{normalize_word, WordList};
+
+/// A grid to fill with a `FillService`.
+#[derive(Debug, Clone, Default)]
+pub struct GridSpec {
+    /// A template string with . representing empty cells, # representing blocks, and letters
+    /// representing themselves.
+    pub template: String,
+
+    /// If true, uppercase letters in the template are only suggestions, as with
+    /// `try_generate_grid_config_from_hinted_template_string`; otherwise letters of either case
+    /// are locked in.
+    pub soft_uppercase: bool,
+
+    pub min_score: u16,
+
+    pub timeout: Option<Duration>,
+
+    /// Words to leave out of this fill; see `slot_options_without_words`.
+    pub forbidden_words: Vec<String>,
+
+    /// Words that must appear somewhere in this fill; see `required_words.rs`.
+    pub required_words: Vec<String>,
+
+    /// If present, a rule limiting which entries can cross each other; see `CrossingQuality`.
+    pub crossing_quality: Option<CrossingQuality>,
+
+    /// Limits on the entries carrying particular word-list tags, added to any in the service's
+    /// `SearchConfig`; see `tag_caps.rs`.
+    pub tag_caps: Vec<TagCap>,
+
+    /// Dupe rules for tagged entries, overriding the word list's for this fill; see `DupePolicy`.
+    pub dupe_rules: Vec<TagDupeRule>,
+
+    /// If present, a token that stops this fill when cancelled; see `cancel.rs`.
+    pub cancel: Option<CancelToken>,
+}
+
+/// How many grids' crossing weights a `FillService` remembers by default.
+pub const DEFAULT_CROSSING_WEIGHTS_CAPACITY: usize = 256;
+
+/// The crossing weights learned for up to `capacity` grids, forgetting the least recently used
+/// grid's to make room for a new one, so that a server that sees an endless stream of distinct
+/// grids doesn't hold on to all of them.
+struct CrossingWeightsCache {
+    capacity: usize,
+
+    /// Each grid's weights, along with the value of `clock` when they were last used.
+    entries: HashMap<u64, (CrossingWeights, u64)>,
+
+    /// Incremented on every access, to order the entries by recency.
+    clock: u64,
+}
+
+impl CrossingWeightsCache {
+    fn new(capacity: usize) -> CrossingWeightsCache {
+        CrossingWeightsCache {
+            capacity,
+            entries: HashMap::new(),
+            clock: 0,
+        }
+    }
+
+    fn get(&mut self, grid_hash: u64) -> Option<&CrossingWeights> {
+        self.clock += 1;
+        let (weights, last_used) = self.entries.get_mut(&grid_hash)?;
+        *last_used = self.clock;
+        Some(weights)
+    }
+
+    fn insert(&mut self, grid_hash: u64, weights: CrossingWeights) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&grid_hash) {
+            let least_recent = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(&key, _)| key);
+            if let Some(least_recent) = least_recent {
+                self.entries.remove(&least_recent);
+            }
+        }
+        self.clock += 1;
+        self.entries.insert(grid_hash, (weights, self.clock));
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// A thread-safe, reusable fill engine; see the module docs.
+pub struct FillService {
+    word_list: Arc<WordList>,
+    search_config: SearchConfig,
+    pools: Mutex<Vec<EliminationSetPool>>,
+    crossing_weights_by_grid: Mutex<CrossingWeightsCache>,
+}
+
+impl FillService {
+    #[must_use]
+    pub fn new(word_list: Arc<WordList>, search_config: SearchConfig) -> FillService {
+        FillService::with_crossing_weights_capacity(
+            word_list,
+            search_config,
+            DEFAULT_CROSSING_WEIGHTS_CAPACITY,
+        )
+    }
+
+    /// Create a service that remembers the crossing weights of at most `capacity` grids at a time
+    /// (or none, if it's 0), rather than `DEFAULT_CROSSING_WEIGHTS_CAPACITY`.
+    #[must_use]
+    pub fn with_crossing_weights_capacity(
+        word_list: Arc<WordList>,
+        search_config: SearchConfig,
+        capacity: usize,
+    ) -> FillService {
+        FillService {
+            word_list,
+            search_config,
+            pools: Mutex::new(vec![]),
+            crossing_weights_by_grid: Mutex::new(CrossingWeightsCache::new(capacity)),
+        }
+    }
+
+    /// The word list shared by all requests.
+    #[must_use]
+    pub fn word_list(&self) -> &Arc<WordList> {
+        &self.word_list
+    }
+
+    /// Forget the crossing weights learned from previous fills.
+    pub fn clear_crossing_weights(&self) {
+        self.crossing_weights_by_grid.lock().unwrap().clear();
+    }
+
+    /// Start the crossing weights for `to` from the ones learned by filling `from`, unless `to` has
+    /// been filled before. This is useful when the two specs describe the same grid with different
+    /// settings (like `min_score`), so that their trouble spots are likely to be the same.
+    pub fn share_crossing_weights(&self, from: &GridSpec, to: &GridSpec) {
+        let mut crossing_weights_by_grid = self.crossing_weights_by_grid.lock().unwrap();
+        let to_hash = grid_spec_hash(to);
+        if crossing_weights_by_grid.entries.contains_key(&to_hash) {
+            return;
+        }
+        let Some(weights) = crossing_weights_by_grid.get(grid_spec_hash(from)).cloned() else {
+            return;
+        };
+        crossing_weights_by_grid.insert(to_hash, weights);
+    }
+
+    /// Search for a fill for the given grid. Since the word list is shared, it can't be extended
+    /// with the grid's contents the way `generate_grid_config` does, so any letter or complete
+    /// entry in the template that isn't in the word list is an error.
+    pub fn fill(&self, grid_spec: &GridSpec) -> Result<FillSuccess, FillError> {
+        self.fill_and_then(grid_spec, |_, result| result)?
+    }
+
+    /// Search for a fill like `fill`, and then pass the result to the given function along with
+    /// the config of the grid that was filled, e.g. to render it.
+    pub fn fill_and_then<T>(
+        &self,
+        grid_spec: &GridSpec,
+        handle_result: impl FnOnce(&GridConfig, Result<FillSuccess, FillError>) -> T,
+    ) -> Result<T, GridError> {
+        let mut config = self.grid_config(grid_spec)?;
+        if !grid_spec.forbidden_words.is_empty() {
+            config.forbid_words(&grid_spec.forbidden_words);
+        }
+        let mut search_config = self.search_config.clone();
+        if !grid_spec.required_words.is_empty() {
+            search_config.required_words =
+                Some(RequiredWords::new(&config, &grid_spec.required_words));
+        }
+        search_config
+            .tag_caps
+            .extend(grid_spec.tag_caps.iter().cloned());
+
+        let grid_hash = grid_spec_hash(grid_spec);
+        let mut crossing_weights = self
+            .crossing_weights_by_grid
+            .lock()
+            .unwrap()
+            .get(grid_hash)
+            .filter(|weights| weights.len() == config.crossing_count)
+            .cloned()
+            .unwrap_or_else(|| CrossingWeights::new(config.crossing_count));
+
+        // Each request takes a pool for its own use and puts it back when it's done, so concurrent
+        // requests never wait on each other's searches.
+        let mut pool = self.pools.lock().unwrap().pop().unwrap_or_default();
+        let result = find_fill_with_crossing_weights(
+            &config,
+            grid_spec.timeout,
+            Some(pool.acquire(&config.slot_configs, &config.word_list)),
+            &search_config,
+            &mut crossing_weights,
+        );
+        self.pools.lock().unwrap().push(pool);
+
+        self.crossing_weights_by_grid
+            .lock()
+            .unwrap()
+            .insert(grid_hash, crossing_weights);
+
+        Ok(handle_result(&config, result.map_err(FillError::from)))
+    }
+
+    /// Check that the given grid could be passed to `fill`, returning the specs of any slots with no
+    /// options at all. An empty list doesn't mean the grid is fillable, only that no slot is
+    /// obviously impossible on its own.
+    pub fn validate(&self, grid_spec: &GridSpec) -> Result<Vec<SlotSpec>, GridError> {
+        let config = self.grid_config(grid_spec)?;
+        Ok(config
+            .slot_configs
+            .iter()
+            .zip(config.slot_options.iter())
+            .filter(|(_, options)| options.is_empty())
+            .map(|(slot_config, _)| slot_config.slot_spec())
+            .collect())
+    }
+
+    /// Build a `GridConfig` for the given grid, backed by the shared word list without modifying
+    /// it. The config doesn't borrow from the service, so it can be moved to another thread (e.g.,
+    /// to search it with a different `SearchConfig`) and outlive the service. As with `fill`, the
+    /// grid's `forbidden_words` and `required_words` aren't applied.
+    pub fn grid_config(&self, grid_spec: &GridSpec) -> Result<OwnedGridConfig, GridError> {
+        let template = &grid_spec.template;
+        let (width, height) = template_string_dimensions(template)?;
+        let (slot_configs, crossing_count) =
+            generate_slot_configs(&generate_slots_from_template_string(template))?;
+
+        let fill: Vec<Option<GlyphId>> = template
+            .lines()
+            .map(str::trim)
+            .flat_map(str::chars)
+            .map(|c| {
+                if c == '.' || c == '#' || (grid_spec.soft_uppercase && c.is_uppercase()) {
+                    return Ok(None);
+                }
+                let letter = normalize_word(&c.to_string()).chars().next().unwrap_or(c);
+                self.word_list
+                    .glyph_id_by_char
+                    .get(&letter)
+                    .map(|&glyph_id| Some(glyph_id))
+                    .ok_or(GridError::UnknownGlyph(letter))
+            })
+            .collect::<Result<_, _>>()?;
+
+        let mut slot_options = slot_configs
+            .iter()
+            .map(|slot_config| self.build_slot_options(slot_config, &fill, width, grid_spec))
+            .collect::<Result<Vec<_>, _>>()?;
+        sort_slot_options(&self.word_list, &slot_configs, &mut slot_options);
+
+        let mut config = GridConfig {
+            word_list: GridWordList::Shared(self.word_list.clone()),
+            fill: Cow::Owned(fill),
+            slot_configs: Cow::Owned(slot_configs),
+            slot_options: Cow::Owned(slot_options),
+            width,
+            height,
+            crossing_count,
+            crossing_quality: grid_spec.crossing_quality,
+            dupe_policy: (!grid_spec.dupe_rules.is_empty()).then(|| {
+                Arc::new(DupePolicy::new(
+                    &self.word_list,
+                    grid_spec.dupe_rules.clone(),
+                ))
+            }),
+            cancel: grid_spec.cancel.clone(),
+            option_counts: None,
+            option_constraints: Cow::Owned(vec![]),
+        };
+        if grid_spec.soft_uppercase {
+            config.apply_letter_hints(&template_string_hints(template));
+        }
+
+        Ok(config)
+    }
+
+    /// Generate the options for a single slot, looking up complete entries directly.
+    fn build_slot_options(
+        &self,
+        slot_config: &SlotConfig,
+        fill: &[Option<GlyphId>],
+        width: usize,
+        grid_spec: &GridSpec,
+    ) -> Result<Vec<WordId>, GridError> {
+        let entry_fill = slot_config.fill(fill, width);
+
+        if let Some(glyphs) = slot_config.complete_fill(fill, width) {
+            let word: String = glyphs
+                .iter()
+                .map(|&glyph_id| self.word_list.glyphs[glyph_id])
+                .collect();
+            return self
+                .word_list
+                .word_id_by_string
+                .get(&word)
+                .map(|&word_id| vec![word_id])
+                .ok_or(GridError::UnknownEntry(word));
+        }
+
+        Ok(matching_slot_options(
+            &self.word_list,
+            &entry_fill,
+            slot_config
+                .min_score_override
+                .unwrap_or(grid_spec.min_score),
+            slot_config.filter_pattern.as_ref(),
+            None,
+        ))
+    }
+}
+
+/// A key identifying the grid described by the given spec, for looking up its crossing weights.
+fn grid_spec_hash(grid_spec: &GridSpec) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for line in grid_spec.template.lines().map(str::trim) {
+        line.hash(&mut hasher);
+    }
+    grid_spec.min_score.hash(&mut hasher);
+    grid_spec.forbidden_words.hash(&mut hasher);
+    grid_spec.required_words.hash(&mut hasher);
+    grid_spec.crossing_quality.hash(&mut hasher);
+    grid_spec.tag_caps.hash(&mut hasher);
+    grid_spec.dupe_rules.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backtracking_search::{find_fill, SearchConfig};
+    use crate::crossing_weights::CrossingWeights;
+    use crate::error::{FillError, GridError};
+    use crate::fill_service::{CrossingWeightsCache, FillService, GridSpec};
+    use crate::word_list::tests::word_list_source_config;
+    use crate::word_list::WordList;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_fill_service() {
+        let word_list = WordList::new(word_list_source_config(), None, Some(5), Some(5));
+        let service = FillService::new(Arc::new(word_list), SearchConfig::default());
+        let grid_spec = |template: &str| GridSpec {
+            template: template.into(),
+            min_score: 40,
+            ..GridSpec::default()
+        };
+
+        thread::scope(|scope| {
+            for _ in 0..3 {
+                scope.spawn(|| {
+                    let result = service
+                        .fill(&grid_spec(".....\n.....\n.....\n.....\n....."))
+                        .expect("Failed to find a fill");
+                    assert_eq!(result.choices.len(), 10);
+                });
+            }
+        });
+        assert!(!service.pools.lock().unwrap().is_empty());
+        assert_eq!(
+            service
+                .crossing_weights_by_grid
+                .lock()
+                .unwrap()
+                .entries
+                .len(),
+            1
+        );
+
+        let result = service.fill(&grid_spec("a....\n.....\n.....\n.....\n....."));
+        assert!(result.is_ok());
+
+        assert!(matches!(
+            service.fill(&grid_spec("@....\n.....\n.....\n.....\n.....")),
+            Err(FillError::Grid(GridError::UnknownGlyph('@')))
+        ));
+        assert_eq!(
+            service.validate(&grid_spec("a....\n.....\n.....\n.....\n.....")),
+            Ok(vec![])
+        );
+        assert_eq!(
+            service
+                .validate(&grid_spec("xq...\n.....\n.....\n.....\n....."))
+                .map(|slot_specs| slot_specs.len()),
+            Ok(1)
+        );
+        assert!(matches!(
+            service.fill(&GridSpec {
+                required_words: vec!["qqqqq".into()],
+                ..grid_spec(".....\n.....\n.....\n.....\n.....")
+            }),
+            Err(FillError::Unfillable(_))
+        ));
+        assert!(matches!(
+            service.fill(&grid_spec("xqzxq\n.....\n.....\n.....\n.....")),
+            Err(FillError::Grid(GridError::UnknownEntry(_)))
+        ));
+    }
+
+    #[test]
+    fn test_crossing_weights_cache() {
+        let mut cache = CrossingWeightsCache::new(2);
+        cache.insert(1, CrossingWeights::new(1));
+        cache.insert(2, CrossingWeights::new(2));
+        assert!(cache.get(1).is_some());
+
+        // Grid 2 is the least recently used, so it makes way for grid 3.
+        cache.insert(3, CrossingWeights::new(3));
+        assert_eq!(cache.entries.len(), 2);
+        assert!(cache.get(2).is_none());
+        assert!(cache.get(1).is_some());
+        assert!(cache.get(3).is_some());
+
+        // Replacing a grid's weights doesn't evict anything.
+        cache.insert(1, CrossingWeights::new(4));
+        assert_eq!(cache.entries.len(), 2);
+        assert_eq!(cache.get(1).map(CrossingWeights::len), Some(4));
+
+        let mut cache = CrossingWeightsCache::new(0);
+        cache.insert(1, CrossingWeights::new(1));
+        assert!(cache.get(1).is_none());
+    }
+
+    #[test]
+    fn test_shared_grid_configs() {
+        let word_list = WordList::new(word_list_source_config(), None, Some(5), Some(5));
+        let service = FillService::new(Arc::new(word_list), SearchConfig::default());
+        let grid_spec = GridSpec {
+            template: ".....\n.....\n.....\n.....\n.....".into(),
+            min_score: 40,
+            ..GridSpec::default()
+        };
+
+        let configs: Vec<_> = (0..3)
+            .map(|_| service.grid_config(&grid_spec).unwrap())
+            .collect();
+        assert_eq!(Arc::strong_count(service.word_list()), 4);
+
+        let handles: Vec<_> = configs
+            .into_iter()
+            .map(|config| {
+                thread::spawn(move || find_fill(&config, None, None).map(|result| result.choices))
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap().unwrap().len(), 10);
+        }
+        assert_eq!(Arc::strong_count(service.word_list()), 1);
+    }
+}
@@ -0,0 +1,178 @@
+//! This module (enabled with the `serde` feature) flattens everything known about a successful
+//! fill into one JSON document, for logging pipelines and dashboards that want more than the
+//! summary in a `FillResponse`'s diagnostics: the search's `Statistics` (with durations in
+//! milliseconds), the learned crossing weights (see `crossing_report.rs`), and each slot's word and
+//! counts. Slots and crossings are identified by their conventional labels (like "7D") rather
+//! than internal IDs, so documents from different runs of the same grid can be compared.
+//!
+//! `FillSuccess::stats_json` is the usual entry point; `FillStats` is public so that native callers
+//! can adjust the document before serializing it.
+
+use instant::Duration;
+use serde_derive::Serialize;
+
+use crate::backtracking_search::FillSuccess;
+use crate::crossing_report::{explain_crossing_weights, slot_labels};
+use crate::grid_config::GridConfig;
+
+/// The statistics for a single slot in a `FillStats` document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SlotStats {
+    pub label: String,
+    pub word: String,
+    pub score: u16,
+
+    /// See `SlotFillStats::alternatives` and `SlotFillStats::backtracks`.
+    pub alternatives: usize,
+    pub backtracks: usize,
+
+    /// See `Statistics::slot_choices`, `Statistics::slot_backtracks`, and
+    /// `Statistics::min_option_counts`. These cover every attempt of the search, and are `None`
+    /// for backends that don't track them.
+    pub total_choices: Option<usize>,
+    pub total_backtracks: Option<usize>,
+    pub min_option_count: Option<usize>,
+}
+
+/// The learned weight of a single crossing in a `FillStats` document; see `CrossingWeightEntry`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CrossingStats {
+    /// The labels of the two slots, with the across slot first if there is one.
+    pub slots: (String, String),
+    pub cell: (usize, usize),
+    pub weight: f32,
+    pub share: f32,
+}
+
+/// Everything known about a successful fill; see the module docs.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FillStats {
+    pub states: usize,
+    pub backtracks: usize,
+    pub restricted_branchings: usize,
+    pub retries: usize,
+    pub total_time_ms: f64,
+    pub try_time_ms: f64,
+    pub initial_arc_consistency_time_ms: f64,
+    pub choice_arc_consistency_time_ms: f64,
+    pub elimination_arc_consistency_time_ms: f64,
+
+    /// `FillSuccess::word_list_hash` and `FillSuccess::config_fingerprint`, as 16-digit hex
+    /// strings (since JSON numbers can't reliably hold 64-bit integers).
+    pub word_list_hash: String,
+    pub config_fingerprint: String,
+
+    /// One entry per slot, in slot order.
+    pub slots: Vec<SlotStats>,
+
+    /// One entry per crossing, heaviest first, or none for backends that don't track crossing
+    /// weights.
+    pub crossings: Vec<CrossingStats>,
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+impl FillStats {
+    /// Gather the stats for the given fill of the given grid.
+    #[must_use]
+    pub fn new(config: &GridConfig, success: &FillSuccess) -> FillStats {
+        let statistics = &success.statistics;
+        let labels = slot_labels(config);
+
+        let slots = success
+            .slot_stats
+            .iter()
+            .map(|slot_stats| {
+                let slot_idx = slot_stats.slot_id.index();
+                let length = config.slot_configs[slot_idx].length;
+                SlotStats {
+                    label: labels[slot_idx].clone(),
+                    word: config.word_list.words[length][slot_stats.word_id.index()]
+                        .normalized_string
+                        .clone(),
+                    score: slot_stats.score,
+                    alternatives: slot_stats.alternatives,
+                    backtracks: slot_stats.backtracks,
+                    total_choices: statistics.slot_choices.get(slot_idx).copied(),
+                    total_backtracks: statistics.slot_backtracks.get(slot_idx).copied(),
+                    min_option_count: statistics.min_option_counts.get(slot_idx).copied(),
+                }
+            })
+            .collect();
+
+        let crossings = if success.crossing_weights.is_empty() {
+            vec![]
+        } else {
+            explain_crossing_weights(config, &success.crossing_weights)
+                .into_iter()
+                .map(|entry| CrossingStats {
+                    slots: entry.slot_labels,
+                    cell: entry.cell,
+                    weight: entry.weight,
+                    share: entry.share,
+                })
+                .collect()
+        };
+
+        FillStats {
+            states: statistics.states,
+            backtracks: statistics.backtracks,
+            restricted_branchings: statistics.restricted_branchings,
+            retries: statistics.retries,
+            total_time_ms: millis(statistics.total_time),
+            try_time_ms: millis(statistics.try_time),
+            initial_arc_consistency_time_ms: millis(statistics.initial_arc_consistency_time),
+            choice_arc_consistency_time_ms: millis(statistics.choice_arc_consistency_time),
+            elimination_arc_consistency_time_ms: millis(
+                statistics.elimination_arc_consistency_time,
+            ),
+            word_list_hash: format!("{:016x}", success.word_list_hash),
+            config_fingerprint: format!("{:016x}", success.config_fingerprint),
+            slots,
+            crossings,
+        }
+    }
+
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Fill stats are always serializable")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backtracking_search::find_fill;
+    use crate::fill_stats::FillStats;
+    use crate::grid_config::generate_grid_config_from_template_string;
+    use crate::word_list::tests::memory_word_list;
+
+    #[test]
+    fn test_fill_stats() {
+        let word_list = memory_word_list(&["ab", "cd", "ac", "bd"]);
+        let config = generate_grid_config_from_template_string(word_list, "..\n..", 40);
+        let success = find_fill(&config, None, None).unwrap();
+
+        let stats = FillStats::new(&config, &success);
+        assert_eq!(
+            stats
+                .slots
+                .iter()
+                .map(|slot| (slot.label.as_str(), slot.word.as_str()))
+                .collect::<Vec<_>>(),
+            vec![("1A", "ab"), ("3A", "cd"), ("1D", "ac"), ("2D", "bd")]
+        );
+        assert_eq!(stats.crossings.len(), 4);
+        assert!(stats
+            .crossings
+            .iter()
+            .all(|crossing| crossing.weight >= 1.0));
+
+        let json: serde_json::Value = serde_json::from_str(&success.stats_json(&config)).unwrap();
+        assert_eq!(json["slots"][3]["label"], "2D");
+        assert_eq!(json["crossings"][0]["slots"][0], stats.crossings[0].slots.0);
+        assert_eq!(json["word_list_hash"], stats.word_list_hash);
+        assert!(json["total_time_ms"].is_number());
+    }
+}
@@ -2,35 +2,37 @@
 //! specific fill algorithm.
 
 use fancy_regex::Regex;
+use float_ord::FloatOrd;
+use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
-use std::sync::atomic::AtomicBool;
+use std::hash::Hasher;
+use std::ops::Deref;
 use std::sync::Arc;
 
-#[cfg(feature = "serde")]
-use serde_derive::{Deserialize, Serialize};
-
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use crate::types::{GlyphId, WordId};
-use crate::util::build_glyph_counts_by_cell;
-use crate::word_list::WordList;
+use crate::cancel::CancelToken;
+use crate::dupe_index::DupePolicy;
+use crate::error::GridError;
+use crate::option_counts::OptionCounts;
+use crate::render::{render_grid_with_options, RenderOptions};
+use crate::types::{GlobalWordId, GlyphId, WordId};
+use crate::util::{build_glyph_counts_by_cell, StableHasher};
+use crate::word_list::{normalize_word, WordList};
 
-/// An identifier for the intersection between two slots; these correspond one-to-one with checked
-/// squares in the grid and are used to track weights (i.e., how often each square is involved in
-/// a domain wipeout).
-pub type CrossingId = usize;
-
-/// An identifier for a given slot, based on its index in the `GridConfig`'s `slot_configs` field.
-pub type SlotId = usize;
+pub use crate::types::{CrossingId, SlotId};
 
 /// Zero-indexed x and y coords for a cell in the grid, where y = 0 in the top row.
 pub type GridCoord = (usize, usize);
 
 /// The direction that a slot is facing.
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord)]
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
 #[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 #[allow(dead_code)]
 pub enum Direction {
@@ -116,24 +118,88 @@ impl SlotConfig {
     }
 }
 
-/// A struct holding references to all of the information needed as input to a crossword filling
-/// operation.
+/// The word list used by a `GridConfig`, which can either be owned by the config or borrowed from
+/// somewhere else. This plays the role of a `Cow`, which we can't use directly because `WordList`
+/// can't be cloned. A `Shared` list lets many configs use the same parsed word list (e.g., one per
+/// request in a multithreaded server) without borrowing it, so that each config can be `'static`.
+pub enum GridWordList<'a> {
+    Borrowed(&'a WordList),
+    Owned(Box<WordList>),
+    Shared(Arc<WordList>),
+}
+
+impl GridWordList<'_> {
+    /// Get mutable access to the word list, if it's owned, or if it's shared but no other config
+    /// is currently using it.
+    pub fn get_mut(&mut self) -> Option<&mut WordList> {
+        match self {
+            GridWordList::Borrowed(_) => None,
+            GridWordList::Owned(word_list) => Some(word_list),
+            GridWordList::Shared(word_list) => Arc::get_mut(word_list),
+        }
+    }
+
+    /// Take ownership of the word list, if it's owned, or if it's shared but no other config is
+    /// currently using it.
+    #[must_use]
+    pub fn into_inner(self) -> Option<WordList> {
+        match self {
+            GridWordList::Borrowed(_) => None,
+            GridWordList::Owned(word_list) => Some(*word_list),
+            GridWordList::Shared(word_list) => Arc::try_unwrap(word_list).ok(),
+        }
+    }
+}
+
+impl Deref for GridWordList<'_> {
+    type Target = WordList;
+
+    fn deref(&self) -> &WordList {
+        match self {
+            GridWordList::Borrowed(word_list) => word_list,
+            GridWordList::Owned(word_list) => word_list,
+            GridWordList::Shared(word_list) => word_list,
+        }
+    }
+}
+
+impl<'a> From<&'a WordList> for GridWordList<'a> {
+    fn from(word_list: &'a WordList) -> GridWordList<'a> {
+        GridWordList::Borrowed(word_list)
+    }
+}
+
+impl From<WordList> for GridWordList<'_> {
+    fn from(word_list: WordList) -> Self {
+        GridWordList::Owned(Box::new(word_list))
+    }
+}
+
+impl From<Arc<WordList>> for GridWordList<'_> {
+    fn from(word_list: Arc<WordList>) -> Self {
+        GridWordList::Shared(word_list)
+    }
+}
+
+/// All of the information needed as input to a crossword filling operation. Each piece can either
+/// be owned or borrowed, so the same type serves both for grids built from scratch (e.g., by
+/// `generate_grid_config`, which returns an `OwnedGridConfig`) and for cheap variations on an
+/// existing grid that only replace one piece, like its slot options (see `borrowed`).
 #[allow(dead_code)]
-#[derive(Clone)]
 pub struct GridConfig<'a> {
     /// The word list used to fill the grid; see `word_list.rs`.
-    pub word_list: &'a WordList,
+    pub word_list: GridWordList<'a>,
 
     /// A flat array of letters filled into the grid, in order of row and then column. `None` can
     /// represent a block or an unfilled cell.
-    pub fill: &'a [Option<GlyphId>],
+    pub fill: Cow<'a, [Option<GlyphId>]>,
 
     /// Config representing all of the slots in the grid and their crossings.
-    pub slot_configs: &'a [SlotConfig],
+    pub slot_configs: Cow<'a, [SlotConfig]>,
 
     /// An array of available words for each (respective) slot, based on both the word list config
     /// and the existing letters filled into the grid.
-    pub slot_options: &'a [Vec<WordId>],
+    pub slot_options: Cow<'a, [Vec<WordId>]>,
 
     /// The width and height of the grid.
     pub width: usize,
@@ -142,55 +208,416 @@ pub struct GridConfig<'a> {
     /// The number of distinct crossings represented in all of the `slot_configs`.
     pub crossing_count: usize,
 
-    /// An optional atomic flag that can be set to signal that the fill operation should be canceled.
-    pub abort: Option<&'a AtomicBool>,
+    /// If present, a rule limiting which entries can cross each other based on their scores.
+    pub crossing_quality: Option<CrossingQuality>,
+
+    /// If present, dupe rules for tagged entries that take precedence over the word list's own;
+    /// see `DupePolicy`.
+    pub dupe_policy: Option<Arc<DupePolicy>>,
+
+    /// If present, a token that can be cancelled to signal that the fill operation should stop;
+    /// see `cancel.rs`.
+    pub cancel: Option<CancelToken>,
+
+    /// If present, updated with each slot's remaining option count as the fill progresses; see
+    /// `option_counts.rs`.
+    pub option_counts: Option<Arc<OptionCounts>>,
+
+    /// The changes made to `slot_options` since they were generated (by `forbid_words`,
+    /// `constrain_cells_to_spell`, etc.), in order, so that `regenerate_slot_options` can make them
+    /// again.
+    pub option_constraints: Cow<'a, [OptionConstraint]>,
 }
 
-/// A struct that owns a copy of each piece of information needed by `GridConfig`.
-pub struct OwnedGridConfig {
-    pub word_list: WordList,
-    pub fill: Vec<Option<GlyphId>>,
-    pub slot_configs: Vec<SlotConfig>,
-    pub slot_options: Vec<Vec<WordId>>,
-    pub width: usize,
-    pub height: usize,
-    pub crossing_count: usize,
-    pub abort: Option<Arc<AtomicBool>>,
+/// A `GridConfig` that owns all of its data, as returned by `generate_grid_config`.
+pub type OwnedGridConfig = GridConfig<'static>;
+
+/// A house-style rule requiring the longest slots in the grid to be filled with marquee-quality
+/// entries; see `GridConfig::apply_marquee_rule`.
+#[derive(Debug, Clone, Default)]
+pub struct MarqueeRule {
+    /// The number of slots the rule applies to, starting with the longest. Slots of the same
+    /// length are taken in order of id.
+    pub slot_count: usize,
+
+    /// If present, words with at least this score qualify.
+    pub min_score: Option<u16>,
+
+    /// If present, words with this tag qualify regardless of their score.
+    pub tag: Option<String>,
 }
 
-impl OwnedGridConfig {
-    #[allow(dead_code)]
+/// A house-style rule that every weak entry must have fair crossings: each checked cell of an
+/// entry scoring below `weak_score` has to be crossed by an entry scoring at least `fair_score`.
+/// Since the rule is symmetric, it's enforced during arc consistency, whenever a slot is narrowed
+/// down to a single option, by removing any crossing options it can't coexist with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct CrossingQuality {
+    pub weak_score: u16,
+    pub fair_score: u16,
+}
+
+impl CrossingQuality {
+    /// Can entries with the given scores cross each other under this rule?
     #[must_use]
-    pub fn to_config_ref(&self) -> GridConfig {
+    pub fn allows(&self, score: u16, other_score: u16) -> bool {
+        !((score < self.weak_score && other_score < self.fair_score)
+            || (other_score < self.weak_score && score < self.fair_score))
+    }
+}
+
+/// A change to a grid's slot options, as recorded in `GridConfig::option_constraints`.
+#[derive(Debug, Clone)]
+pub enum OptionConstraint {
+    /// See `GridConfig::forbid_words`.
+    ForbiddenWords(Vec<String>),
+
+    /// See `GridConfig::constrain_cells_to_spell`.
+    SpelledCells {
+        cells: Vec<GridCoord>,
+        answer: String,
+    },
+
+    /// See `GridConfig::apply_marquee_rule`.
+    Marquee(MarqueeRule),
+
+    /// See `GridConfig::apply_letter_hints`.
+    LetterHints(Vec<LetterHint>),
+
+    /// See `GridConfig::break_ties_by_fillability`.
+    FillabilityTiebreak,
+}
+
+/// A soft preference for a letter in a given cell; see `GridConfig::apply_letter_hints`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LetterHint {
+    pub cell: GridCoord,
+    pub letter: char,
+
+    /// How strongly to avoid options that put a different letter in the cell, relative to the
+    /// other hints.
+    pub penalty: u32,
+}
+
+impl GridConfig<'_> {
+    /// A config borrowing everything from this one, which is cheap to make. Fields can be replaced
+    /// with struct update syntax to vary one piece of a grid without copying the rest, e.g.,
+    /// `GridConfig { slot_options: Cow::Borrowed(&slot_options), ..config.borrowed() }`.
+    #[must_use]
+    pub fn borrowed(&self) -> GridConfig<'_> {
         GridConfig {
-            word_list: &self.word_list,
-            fill: &self.fill,
-            slot_configs: &self.slot_configs,
-            slot_options: &self.slot_options,
+            word_list: GridWordList::Borrowed(&self.word_list),
+            fill: Cow::Borrowed(&self.fill),
+            slot_configs: Cow::Borrowed(&self.slot_configs),
+            slot_options: Cow::Borrowed(&self.slot_options),
             width: self.width,
             height: self.height,
             crossing_count: self.crossing_count,
-            abort: self.abort.as_deref(),
+            crossing_quality: self.crossing_quality,
+            dupe_policy: self.dupe_policy.clone(),
+            cancel: self.cancel.clone(),
+            option_counts: self.option_counts.clone(),
+            option_constraints: Cow::Borrowed(&self.option_constraints),
+        }
+    }
+
+    /// Make the given change to the slot options without recording it.
+    fn apply_option_constraint(
+        &mut self,
+        constraint: &OptionConstraint,
+    ) -> Result<Vec<SlotId>, GridError> {
+        match constraint {
+            OptionConstraint::ForbiddenWords(words) => {
+                self.slot_options = Cow::Owned(slot_options_without_words(self, words));
+            }
+            OptionConstraint::SpelledCells { cells, answer } => {
+                self.spell_cells(cells, answer)?;
+            }
+            OptionConstraint::Marquee(rule) => return Ok(self.restrict_marquee_slots(rule)),
+            OptionConstraint::LetterHints(hints) => self.sort_by_letter_hints(hints),
+            OptionConstraint::FillabilityTiebreak => sort_slot_options_with_tiebreak(
+                &self.word_list,
+                &self.slot_configs,
+                self.slot_options.to_mut(),
+                true,
+            ),
+        }
+        Ok(vec![])
+    }
+
+    /// Make the given change to the slot options and record it in `option_constraints`.
+    fn add_option_constraint(
+        &mut self,
+        constraint: OptionConstraint,
+    ) -> Result<Vec<SlotId>, GridError> {
+        let result = self.apply_option_constraint(&constraint)?;
+        self.option_constraints.to_mut().push(constraint);
+        Ok(result)
+    }
+
+    /// The id of the given letter, adding it to the word list if the list is owned. Letters that
+    /// aren't in a borrowed list have no id, but since no word can contain them either, callers
+    /// can treat them as matching nothing.
+    pub(crate) fn glyph_id_for_char(&mut self, ch: char) -> Option<GlyphId> {
+        match self.word_list.get_mut() {
+            Some(word_list) => Some(word_list.glyph_id_for_char(ch)),
+            None => self.word_list.glyph_id_by_char.get(&ch).copied(),
+        }
+    }
+
+    /// Require the given cells, in order, to spell out the given answer, as with a meta answer
+    /// hidden in circled squares. Each letter restricts the options of the slots passing through
+    /// its cell, and arc consistency propagates those restrictions to the rest of the grid.
+    pub fn constrain_cells_to_spell(
+        &mut self,
+        cells: &[GridCoord],
+        answer: &str,
+    ) -> Result<(), GridError> {
+        self.add_option_constraint(OptionConstraint::SpelledCells {
+            cells: cells.to_vec(),
+            answer: answer.to_string(),
+        })?;
+        Ok(())
+    }
+
+    fn spell_cells(&mut self, cells: &[GridCoord], answer: &str) -> Result<(), GridError> {
+        let letters: Vec<char> = normalize_word(answer).chars().collect();
+        if letters.len() != cells.len() {
+            return Err(GridError::AnswerLengthMismatch {
+                cells: cells.len(),
+                letters: letters.len(),
+            });
+        }
+
+        for (&(x, y), &letter) in cells.iter().zip(&letters) {
+            let glyph = self.glyph_id_for_char(letter);
+            let existing_glyph = (x < self.width && y < self.height)
+                .then(|| self.fill[x + y * self.width])
+                .flatten();
+            if existing_glyph.map_or(false, |existing_glyph| Some(existing_glyph) != glyph) {
+                return Err(GridError::ConflictingCell(x, y));
+            }
+
+            let mut in_entry = false;
+            for slot_config in self.slot_configs.iter() {
+                let Some(cell_idx) = slot_config
+                    .cell_coords()
+                    .iter()
+                    .position(|&coord| coord == (x, y))
+                else {
+                    continue;
+                };
+                in_entry = true;
+
+                let words = &self.word_list.words[slot_config.length];
+                self.slot_options.to_mut()[slot_config.id.index()]
+                    .retain(|word_id| Some(words[word_id.index()].glyphs[cell_idx]) == glyph);
+            }
+            if !in_entry {
+                return Err(GridError::CellNotInEntry(x, y));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Remove any options that don't satisfy the given rule from the longest slots, so that the
+    /// search never spends time on fills that would violate it. Slots that are already completely
+    /// filled in are left alone (and don't count toward `slot_count`). Returns the ids of the slots
+    /// the rule was applied to.
+    pub fn apply_marquee_rule(&mut self, rule: &MarqueeRule) -> Vec<SlotId> {
+        self.add_option_constraint(OptionConstraint::Marquee(rule.clone()))
+            .expect("Marquee rules can always be applied")
+    }
+
+    fn restrict_marquee_slots(&mut self, rule: &MarqueeRule) -> Vec<SlotId> {
+        let mut slot_ids: Vec<SlotId> = self
+            .slot_configs
+            .iter()
+            .filter(|slot_config| slot_config.complete_fill(&self.fill, self.width).is_none())
+            .map(|slot_config| slot_config.id)
+            .collect();
+        slot_ids
+            .sort_by_key(|slot_id| std::cmp::Reverse(self.slot_configs[slot_id.index()].length));
+        slot_ids.truncate(rule.slot_count);
+
+        if rule.min_score.is_none() && rule.tag.is_none() {
+            return slot_ids;
+        }
+
+        for &slot_id in &slot_ids {
+            let length = self.slot_configs[slot_id.index()].length;
+            let word_list = &self.word_list;
+            self.slot_options.to_mut()[slot_id.index()].retain(|&word_id| {
+                let word = &word_list.words[length][word_id.index()];
+                rule.min_score
+                    .map_or(false, |min_score| word.score >= min_score)
+                    || rule.tag.as_ref().map_or(false, |tag| {
+                        word_list.get_word_tags((length, word_id)).contains(tag)
+                    })
+            });
+        }
+
+        slot_ids
+    }
+
+    /// Nudge the fill toward the given letters without requiring them: in each slot passing
+    /// through a hinted cell, options are stably re-sorted by the total penalty of the hints they
+    /// violate, so that the search tries the options honoring the hints first but can still fall
+    /// back to the others. Hints for cells outside of any entry are ignored.
+    pub fn apply_letter_hints(&mut self, hints: &[LetterHint]) {
+        self.add_option_constraint(OptionConstraint::LetterHints(hints.to_vec()))
+            .expect("Letter hints can always be applied");
+    }
+
+    fn sort_by_letter_hints(&mut self, hints: &[LetterHint]) {
+        let glyph_hints: Vec<(GridCoord, GlyphId, u32)> = hints
+            .iter()
+            .filter_map(|hint| {
+                // A letter that isn't in a borrowed word list penalizes every option equally, so we
+                // can skip it.
+                let letter = normalize_word(&hint.letter.to_string()).chars().next()?;
+                let glyph = self.glyph_id_for_char(letter)?;
+                Some((hint.cell, glyph, hint.penalty))
+            })
+            .collect();
+
+        for slot_config in self.slot_configs.iter() {
+            let slot_hints: Vec<(usize, GlyphId, u32)> = slot_config
+                .cell_coords()
+                .iter()
+                .enumerate()
+                .flat_map(|(cell_idx, &coord)| {
+                    glyph_hints
+                        .iter()
+                        .filter(move |&&(cell, _, _)| cell == coord)
+                        .map(move |&(_, glyph, penalty)| (cell_idx, glyph, penalty))
+                })
+                .collect();
+            if slot_hints.is_empty() {
+                continue;
+            }
+
+            let words = &self.word_list.words[slot_config.length];
+            self.slot_options.to_mut()[slot_config.id.index()].sort_by_cached_key(|word_id| {
+                let glyphs = &words[word_id.index()].glyphs;
+                slot_hints
+                    .iter()
+                    .filter(|&&(cell_idx, glyph, _)| glyphs[cell_idx] != glyph)
+                    .map(|&(_, _, penalty)| u64::from(penalty))
+                    .sum::<u64>()
+            });
         }
     }
+
+    /// Remove the given words from every slot's options; see `slot_options_without_words`.
+    pub fn forbid_words(&mut self, words: &[String]) {
+        self.add_option_constraint(OptionConstraint::ForbiddenWords(words.to_vec()))
+            .expect("Words can always be forbidden");
+    }
+
+    /// Re-sort each slot's options, breaking ties between equally-rated options by the word
+    /// list's fillability scores. This can noticeably reduce backtracking in wide-open areas.
+    pub fn break_ties_by_fillability(&mut self) {
+        self.add_option_constraint(OptionConstraint::FillabilityTiebreak)
+            .expect("Ties can always be broken");
+    }
+
+    /// Rebuild every slot's options from the current state of the word list, e.g. after words have
+    /// been added, removed, or rescored, or to use a different minimum score. The grid's slots and
+    /// fill are reused as-is, so this is much cheaper than generating a new config from the
+    /// template. Any other changes made to the options since the config was generated (e.g., by
+    /// `forbid_words` or `constrain_cells_to_spell`) are made again, as recorded in
+    /// `option_constraints`.
+    ///
+    /// If the word list is borrowed (or shared with another config), complete entries that aren't
+    /// in the list can't be added to it, so slots whose fill is complete keep their current options.
+    pub fn regenerate_slot_options(&mut self, min_score: u16) {
+        // The recorded constraints all succeeded when they were first applied to the same slots and
+        // fill, so they can't fail now.
+        self.try_regenerate_slot_options(min_score)
+            .expect("Recorded option constraints must still apply");
+    }
+
+    /// Like `regenerate_slot_options`, but for a grid whose slots or fill may have changed since
+    /// its `option_constraints` were recorded, so that making them again can fail (e.g., if a cell
+    /// that had to spell part of an answer is now a block).
+    pub(crate) fn try_regenerate_slot_options(&mut self, min_score: u16) -> Result<(), GridError> {
+        let width = self.width;
+        let mut slot_options = match self.word_list.get_mut() {
+            Some(word_list) => generate_all_slot_options(
+                word_list,
+                &self.fill,
+                &self.slot_configs,
+                width,
+                min_score,
+            ),
+            None => self
+                .slot_configs
+                .iter()
+                .zip(self.slot_options.iter())
+                .map(|(slot_config, options)| {
+                    if slot_config.complete_fill(&self.fill, width).is_some() {
+                        return options.clone();
+                    }
+                    matching_slot_options(
+                        &self.word_list,
+                        &slot_config.fill(&self.fill, width),
+                        slot_config.min_score_override.unwrap_or(min_score),
+                        slot_config.filter_pattern.as_ref(),
+                        None,
+                    )
+                })
+                .collect(),
+        };
+
+        sort_slot_options(&self.word_list, &self.slot_configs, &mut slot_options);
+        self.slot_options = Cow::Owned(slot_options);
+
+        let option_constraints = self.option_constraints.clone();
+        for constraint in option_constraints.iter() {
+            self.apply_option_constraint(constraint)?;
+        }
+        Ok(())
+    }
 }
 
 /// Given a configured grid, reorder the options for each slot so that the "best" choices are at the
 /// front. This is a balance between fillability (the most important factor, since our odds of being
 /// able to find a fill in a reasonable amount of time depend on how many tries it takes us to find
 /// a usable word for each slot) and quality metrics like word score and letter score.
-#[allow(clippy::cast_lossless)]
 pub fn sort_slot_options(
     word_list: &WordList,
     slot_configs: &[SlotConfig],
     slot_options: &mut [Vec<WordId>],
+) {
+    sort_slot_options_with_tiebreak(word_list, slot_configs, slot_options, false);
+}
+
+/// Like `sort_slot_options`, but if `break_ties_by_fillability` is true, options with the same
+/// overall rating are ordered by the word list's static fillability scores, putting the words
+/// that are easiest to cross first.
+#[allow(clippy::cast_lossless)]
+pub fn sort_slot_options_with_tiebreak(
+    word_list: &WordList,
+    slot_configs: &[SlotConfig],
+    slot_options: &mut [Vec<WordId>],
+    break_ties_by_fillability: bool,
 ) {
     // To calculate the fillability score for each word, we need statistics about which letters are
     // most likely to appear in each position for each slot.
     let glyph_counts_by_cell_by_slot: Vec<_> = slot_configs
         .iter()
         .map(|slot_config| {
-            build_glyph_counts_by_cell(word_list, slot_config.length, &slot_options[slot_config.id])
+            build_glyph_counts_by_cell(
+                word_list,
+                slot_config.length,
+                &slot_options[slot_config.id.index()],
+            )
         })
         .collect();
 
@@ -200,7 +627,7 @@ pub fn sort_slot_options(
         let slot_options = &mut slot_options[slot_idx];
 
         slot_options.sort_by_cached_key(|&option| {
-            let word = &word_list.words[slot_config.length][option];
+            let word = &word_list.words[slot_config.length][option.index()];
 
             // To calculate the fill score for a word, average the logarithms of the number of
             // crossing options that are compatible with each letter (based on the grid geometry).
@@ -214,7 +641,7 @@ pub fn sort_slot_options(
                 .map(|(crossing, &glyph)| match crossing {
                     Some(crossing) => {
                         let crossing_counts_by_cell =
-                            &glyph_counts_by_cell_by_slot[crossing.other_slot_id];
+                            &glyph_counts_by_cell_by_slot[crossing.other_slot_id.index()];
 
                         (crossing_counts_by_cell[crossing.other_slot_cell][glyph] as f32).log10()
                     }
@@ -226,9 +653,17 @@ pub fn sort_slot_options(
             // This is arbitrary, based on visual inspection of the ranges for each value. Generally
             // increasing the weight of `fill_score` relative to the other two will reduce fill
             // time.
-            -((fill_score * 900.0) as i64
+            let rating = -((fill_score * 900.0) as i64
                 + ((word.letter_score as f32) * 5.0) as i64
-                + ((word.score as f32) * 5.0) as i64)
+                + ((word.score as f32) * 5.0) as i64);
+
+            let tiebreak = if break_ties_by_fillability {
+                -word_list.get_fillability_score((slot_config.length, option))
+            } else {
+                0.0
+            };
+
+            (rating, FloatOrd(tiebreak))
         });
     }
 }
@@ -327,11 +762,10 @@ impl<'de> Deserialize<'de> for SlotSpec {
 
 /// Given `GridEntry` structs specifying the positions of the slots in a grid, generate
 /// `SlotConfig`s containing derived information about crossings, etc.
-#[must_use]
-pub fn generate_slot_configs(entries: &[SlotSpec]) -> (Vec<SlotConfig>, usize) {
+pub fn generate_slot_configs(entries: &[SlotSpec]) -> Result<(Vec<SlotConfig>, usize), GridError> {
     #[derive(Debug)]
     struct GridCell {
-        entries: Vec<(usize, usize)>, // (entry index, cell index within entry)
+        entries: Vec<(SlotId, usize)>, // (entry id, cell index within entry)
         number: Option<u32>,
     }
 
@@ -341,7 +775,7 @@ pub fn generate_slot_configs(entries: &[SlotSpec]) -> (Vec<SlotConfig>, usize) {
     // crossings.
     let mut cell_by_loc: HashMap<GridCoord, GridCell> = HashMap::new();
 
-    for (entry_idx, entry) in entries.iter().enumerate() {
+    for (entry_idx, entry) in SlotId::range(entries.len()).zip(entries) {
         for (cell_idx, &loc) in entry.cell_coords().iter().enumerate() {
             let grid_cell = cell_by_loc.entry(loc).or_insert_with(|| GridCell {
                 entries: vec![],
@@ -373,11 +807,11 @@ pub fn generate_slot_configs(entries: &[SlotSpec]) -> (Vec<SlotConfig>, usize) {
     let mut constraint_id_cache: Vec<(SlotId, SlotId)> = vec![];
 
     // Now we can build the actual slot configs.
-    for (entry_idx, entry) in entries.iter().enumerate() {
+    for (entry_idx, entry) in SlotId::range(entries.len()).zip(entries) {
         let crossings: Vec<Option<Crossing>> = entry
             .cell_coords()
             .iter()
-            .map(|&loc| {
+            .map(|&loc| -> Result<Option<Crossing>, GridError> {
                 let crossing_idxs: Vec<_> = cell_by_loc[&loc]
                     .entries
                     .iter()
@@ -385,32 +819,30 @@ pub fn generate_slot_configs(entries: &[SlotSpec]) -> (Vec<SlotConfig>, usize) {
                     .collect();
 
                 if crossing_idxs.is_empty() {
-                    None
+                    Ok(None)
                 } else if crossing_idxs.len() > 1 {
-                    panic!("More than two entries crossing in cell?");
+                    Err(GridError::TooManyCrossings(loc.0, loc.1))
                 } else {
                     let &(other_slot_id, other_slot_cell) = crossing_idxs[0];
 
                     let crossing_id = if let Some(found_constraint_id) = constraint_id_cache
                         .iter()
-                        .enumerate()
-                        .find(|&(_, &id_pair)| id_pair == (entry_idx, other_slot_id))
-                        .map(|(crossing_id, _)| crossing_id)
+                        .position(|&id_pair| id_pair == (entry_idx, other_slot_id))
                     {
-                        found_constraint_id
+                        CrossingId(found_constraint_id)
                     } else {
                         constraint_id_cache.push((other_slot_id, entry_idx));
-                        constraint_id_cache.len() - 1
+                        CrossingId(constraint_id_cache.len() - 1)
                     };
 
-                    Some(Crossing {
+                    Ok(Some(Crossing {
                         other_slot_id,
                         other_slot_cell,
                         crossing_id,
-                    })
+                    }))
                 }
             })
-            .collect();
+            .collect::<Result<_, _>>()?;
 
         slot_configs.push(SlotConfig {
             id: entry_idx,
@@ -423,7 +855,7 @@ pub fn generate_slot_configs(entries: &[SlotSpec]) -> (Vec<SlotConfig>, usize) {
         });
     }
 
-    (slot_configs, constraint_id_cache.len())
+    Ok((slot_configs, constraint_id_cache.len()))
 }
 
 /// Given a single slot's fill, minimum score, and optional filter pattern, generate the possible
@@ -438,8 +870,6 @@ pub fn generate_slot_options(
     filter_pattern: Option<&Regex>,
     allowed_word_ids: Option<&HashSet<WordId>>,
 ) -> Vec<WordId> {
-    let length = entry_fill.len();
-
     // If the slot is fully specified, we need to either use an existing word or create a new
     // (hidden) one.
     let complete_fill: Option<Vec<GlyphId>> = entry_fill.iter().copied().collect();
@@ -454,38 +884,61 @@ pub fn generate_slot_options(
 
         vec![word_id]
     } else {
-        let options: Vec<WordId> = (0..word_list.words[length].len())
-            .filter(|&word_id| {
-                let word = &word_list.words[length][word_id];
-                let enforce_criteria = allowed_word_ids.map_or(true, |allowed_word_ids| {
-                    !allowed_word_ids.contains(&word_id)
-                });
-
-                if enforce_criteria {
-                    if word.hidden || word.score < min_score {
-                        return false;
-                    }
+        matching_slot_options(
+            word_list,
+            entry_fill,
+            min_score,
+            filter_pattern,
+            allowed_word_ids,
+        )
+    }
+}
 
-                    if let Some(filter_pattern) = filter_pattern.as_ref() {
-                        if !filter_pattern
-                            .is_match(&word.normalized_string)
-                            .unwrap_or(false)
-                        {
-                            return false;
-                        }
+/// Like `generate_slot_options`, but without special handling for complete fills (which only match
+/// themselves here if they meet the usual criteria), so it can be used with a `WordList` we can't
+/// modify.
+#[must_use]
+pub fn matching_slot_options(
+    word_list: &WordList,
+    entry_fill: &[Option<GlyphId>],
+    min_score: u16,
+    filter_pattern: Option<&Regex>,
+    allowed_word_ids: Option<&HashSet<WordId>>,
+) -> Vec<WordId> {
+    let length = entry_fill.len();
+    let Some(words) = word_list.words.get(length) else {
+        return vec![];
+    };
+
+    WordId::range(words.len())
+        .filter(|&word_id| {
+            let word = &words[word_id.index()];
+            let enforce_criteria = allowed_word_ids.map_or(true, |allowed_word_ids| {
+                !allowed_word_ids.contains(&word_id)
+            });
+
+            if enforce_criteria {
+                if word.hidden || word.score < min_score {
+                    return false;
+                }
+
+                if let Some(filter_pattern) = filter_pattern.as_ref() {
+                    if !filter_pattern
+                        .is_match(&word.normalized_string)
+                        .unwrap_or(false)
+                    {
+                        return false;
                     }
                 }
+            }
 
-                entry_fill.iter().enumerate().all(|(cell_idx, cell_fill)| {
-                    cell_fill
-                        .map(|g| g == word.glyphs[cell_idx])
-                        .unwrap_or(true)
-                })
+            entry_fill.iter().enumerate().all(|(cell_idx, cell_fill)| {
+                cell_fill
+                    .map(|g| g == word.glyphs[cell_idx])
+                    .unwrap_or(true)
             })
-            .collect();
-
-        options
-    }
+        })
+        .collect()
 }
 
 /// Given an input fill and an array of slot configs, generate the possible options for each slot
@@ -512,8 +965,41 @@ pub fn generate_all_slot_options(
         .collect()
 }
 
-/// Generate an `OwnedGridConfig` representing a grid with specified entries.
+/// Copy the given grid's slot options, leaving out the given (canonical or normalized) words, so
+/// that a single fill request can ban words without building a new `WordList`. Slots whose fill
+/// was completely specified in the input grid keep their option even if it's banned.
 #[must_use]
+pub fn slot_options_without_words(config: &GridConfig, words: &[String]) -> Vec<Vec<WordId>> {
+    let forbidden_ids: HashSet<GlobalWordId> = words
+        .iter()
+        .filter_map(|word| {
+            let normalized = normalize_word(word);
+            let &word_id = config.word_list.word_id_by_string.get(&normalized)?;
+            Some((normalized.chars().count(), word_id))
+        })
+        .collect();
+
+    config
+        .slot_configs
+        .iter()
+        .zip(config.slot_options.iter())
+        .map(|(slot_config, options)| {
+            if slot_config
+                .complete_fill(&config.fill, config.width)
+                .is_some()
+            {
+                return options.clone();
+            }
+            options
+                .iter()
+                .copied()
+                .filter(|&word_id| !forbidden_ids.contains(&(slot_config.length, word_id)))
+                .collect()
+        })
+        .collect()
+}
+
+/// Generate an `OwnedGridConfig` representing a grid with specified entries.
 pub fn generate_grid_config<'a>(
     mut word_list: WordList,
     entries: &'a [SlotSpec],
@@ -521,15 +1007,25 @@ pub fn generate_grid_config<'a>(
     width: usize,
     height: usize,
     min_score: u16,
-) -> OwnedGridConfig {
-    let (slot_configs, crossing_count) = generate_slot_configs(entries);
+) -> Result<OwnedGridConfig, GridError> {
+    if raw_fill.len() != width * height {
+        return Err(GridError::FillSizeMismatch {
+            width,
+            height,
+            expected: width * height,
+            actual: raw_fill.len(),
+        });
+    }
+
+    let (slot_configs, crossing_count) = generate_slot_configs(entries)?;
 
     let fill: Vec<Option<GlyphId>> = raw_fill
         .iter()
         .map(|cell_str| {
             cell_str
                 .as_ref()
-                .map(|cell_str| word_list.glyph_id_for_char(cell_str.chars().next().unwrap()))
+                .and_then(|cell_str| cell_str.chars().next())
+                .map(|char| word_list.glyph_id_for_char(char))
         })
         .collect();
 
@@ -538,16 +1034,20 @@ pub fn generate_grid_config<'a>(
 
     sort_slot_options(&word_list, &slot_configs, &mut slot_options);
 
-    OwnedGridConfig {
-        word_list,
-        fill,
-        slot_configs,
-        slot_options,
+    Ok(GridConfig {
+        word_list: word_list.into(),
+        fill: Cow::Owned(fill),
+        slot_configs: Cow::Owned(slot_configs),
+        slot_options: Cow::Owned(slot_options),
         width,
         height,
         crossing_count,
-        abort: None,
-    }
+        crossing_quality: None,
+        dupe_policy: None,
+        cancel: None,
+        option_counts: None,
+        option_constraints: Cow::Owned(vec![]),
+    })
 }
 
 /// Generate a list of `SlotSpec`s from a template string with . representing empty cells, # representing
@@ -618,8 +1118,29 @@ pub fn generate_slots_from_template_string(template: &str) -> Vec<SlotSpec> {
     slot_specs
 }
 
+/// Check that a template string describes a non-empty, rectangular grid, returning its width and
+/// height. Blank lines and leading or trailing whitespace are ignored, as elsewhere.
+pub fn template_string_dimensions(template: &str) -> Result<(usize, usize), GridError> {
+    let row_lengths: Vec<usize> = template
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| line.chars().count())
+        .collect();
+
+    let Some(&width) = row_lengths.first() else {
+        return Err(GridError::Empty);
+    };
+    if row_lengths.iter().any(|&row_length| row_length != width) {
+        return Err(GridError::UnevenRows);
+    }
+
+    Ok((width, row_lengths.len()))
+}
+
 /// Generate an `OwnedGridConfig` from a template string with . representing empty cells, # representing
-/// blocks, and letters representing themselves.
+/// blocks, and letters representing themselves. This panics if the template is invalid, so it's
+/// mostly useful for tests; see `try_generate_grid_config_from_template_string`.
 #[allow(dead_code)]
 #[must_use]
 pub fn generate_grid_config_from_template_string(
@@ -627,43 +1148,225 @@ pub fn generate_grid_config_from_template_string(
     template: &str,
     min_score: u16,
 ) -> OwnedGridConfig {
+    try_generate_grid_config_from_template_string(word_list, template, min_score)
+        .expect("Invalid grid template")
+}
+
+/// Generate an `OwnedGridConfig` from a template string with . representing empty cells, # representing
+/// blocks, and letters representing themselves, or return an error if the template is invalid.
+/// Letters are locked in regardless of case; see `try_generate_grid_config_from_hinted_template_string`
+/// for treating uppercase letters as suggestions instead.
+pub fn try_generate_grid_config_from_template_string(
+    word_list: WordList,
+    template: &str,
+    min_score: u16,
+) -> Result<OwnedGridConfig, GridError> {
+    let (width, height) = template_string_dimensions(template)?;
     let slot_specs = generate_slots_from_template_string(template);
 
-    let fill: Vec<Vec<Option<String>>> = template
-        .lines()
-        .filter_map(|line| {
-            let line = line.trim();
-            if line.is_empty() {
-                None
-            } else {
-                Some(
-                    line.chars()
-                        .map(|c| {
-                            if c == '.' || c == '#' {
-                                None
-                            } else {
-                                Some(c.to_lowercase().to_string())
-                            }
-                        })
-                        .collect(),
-                )
-            }
-        })
-        .collect();
+    generate_grid_config(
+        word_list,
+        &slot_specs,
+        &template_string_fill(template),
+        width,
+        height,
+        min_score,
+    )
+}
 
-    let width = fill[0].len();
-    let height = fill.len();
+/// Like `try_generate_grid_config_from_template_string`, but only lowercase letters are locked in,
+/// while uppercase ones are suggestions; see `template_string_hinted_fill` and
+/// `template_string_hints`.
+pub fn try_generate_grid_config_from_hinted_template_string(
+    word_list: WordList,
+    template: &str,
+    min_score: u16,
+) -> Result<OwnedGridConfig, GridError> {
+    let (width, height) = template_string_dimensions(template)?;
+    let slot_specs = generate_slots_from_template_string(template);
 
-    generate_grid_config(
+    let mut config = generate_grid_config(
         word_list,
         &slot_specs,
-        &fill.into_iter().flatten().collect::<Vec<_>>(),
+        &template_string_hinted_fill(template),
         width,
         height,
         min_score,
+    )?;
+    config.apply_letter_hints(&template_string_hints(template));
+
+    Ok(config)
+}
+
+/// Extract the letters from a template string, as a flat array suitable for passing to
+/// `generate_grid_config`. Blocks and empty cells are both `None`; letters are lowercased.
+#[must_use]
+pub fn template_string_fill(template: &str) -> Vec<Option<String>> {
+    template_string_fill_where(template, |c| c == '.' || c == '#')
+}
+
+/// Like `template_string_fill`, but uppercase letters are also `None`, since they mark cells where
+/// a letter is suggested but not required; see `template_string_hints`. Lowercase letters, digits,
+/// and other symbols are hard constraints.
+#[must_use]
+pub fn template_string_hinted_fill(template: &str) -> Vec<Option<String>> {
+    template_string_fill_where(template, |c| c == '.' || c == '#' || c.is_uppercase())
+}
+
+fn template_string_fill_where(
+    template: &str,
+    is_open: impl Fn(char) -> bool,
+) -> Vec<Option<String>> {
+    template
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .flat_map(|line| {
+            line.chars().map(|c| {
+                if is_open(c) {
+                    None
+                } else {
+                    Some(c.to_lowercase().to_string())
+                }
+            })
+        })
+        .collect()
+}
+
+/// Extract the uppercase letters from a template string as `LetterHint`s, all with the same
+/// penalty, for passing to `GridConfig::apply_letter_hints`. The search tries options agreeing with
+/// these letters first, but unlike lowercase letters, they can be overwritten if that's what it
+/// takes to fill the grid.
+#[must_use]
+pub fn template_string_hints(template: &str) -> Vec<LetterHint> {
+    template
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .enumerate()
+        .flat_map(|(y, line)| {
+            line.chars()
+                .enumerate()
+                .filter(|(_, c)| c.is_uppercase())
+                .map(move |(x, letter)| LetterHint {
+                    cell: (x, y),
+                    letter,
+                    penalty: 1,
+                })
+        })
+        .collect()
+}
+
+/// Convert a letter grid, as exported from a partially solved puzzle, into a template string. Each
+/// line is a row; # represents a block; ., -, _, and ? represent empty cells; and anything else is
+/// a letter or digit, which is normalized the same way as the word list (so that e.g. "É" becomes
+/// "é"). Digits are glyphs like any other, so entries like "B2B" work as long as the word list has
+/// them. Any cell can hold a letter, whether or not the rest of its entries are filled in, and each
+/// letter is treated as a hard constraint on the slots passing through it.
+pub fn template_from_letter_grid(letter_grid: &str) -> Result<String, GridError> {
+    let rows = letter_grid
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .enumerate()
+        .map(|(y, line)| {
+            line.chars()
+                .enumerate()
+                .map(|(x, cell)| match cell {
+                    '#' => Ok('#'),
+                    '.' | '-' | '_' | '?' => Ok('.'),
+                    letter => {
+                        let normalized: Vec<char> =
+                            normalize_word(&letter.to_string()).chars().collect();
+                        match normalized[..] {
+                            [normalized] if normalized.is_alphanumeric() => Ok(normalized),
+                            _ => Err(GridError::InvalidLetter { x, y, letter }),
+                        }
+                    }
+                })
+                .collect::<Result<String, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let template = rows.join("\n");
+    template_string_dimensions(&template)?;
+    Ok(template)
+}
+
+/// Generate an `OwnedGridConfig` from a letter grid; see `template_from_letter_grid`.
+pub fn try_generate_grid_config_from_letter_grid(
+    word_list: WordList,
+    letter_grid: &str,
+    min_score: u16,
+) -> Result<OwnedGridConfig, GridError> {
+    try_generate_grid_config_from_template_string(
+        word_list,
+        &template_from_letter_grid(letter_grid)?,
+        min_score,
     )
 }
 
+/// Reduce the given template string to its block pattern, in a canonical orientation: of the eight
+/// rotations and reflections of the grid, we use the one whose rendering (with # for blocks and .
+/// for everything else) sorts first. Two templates have the same canonical pattern iff one's
+/// blocks can be rotated or flipped to match the other's, regardless of any letters they contain.
+pub fn canonical_block_pattern(template: &str) -> Result<String, GridError> {
+    let (width, height) = template_string_dimensions(template)?;
+    let blocks: Vec<Vec<bool>> = template
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| line.chars().map(|cell| cell == '#').collect())
+        .collect();
+
+    // Each orientation optionally transposes the grid (swapping its width and height) and then
+    // optionally flips it horizontally and/or vertically.
+    Ok((0..8)
+        .map(|orientation| {
+            let transposed = orientation & 4 != 0;
+            let (out_width, out_height) = if transposed {
+                (height, width)
+            } else {
+                (width, height)
+            };
+            (0..out_height)
+                .map(|y| {
+                    (0..out_width)
+                        .map(|x| {
+                            let (x, y) = if transposed { (y, x) } else { (x, y) };
+                            let x = if orientation & 1 == 0 {
+                                x
+                            } else {
+                                width - 1 - x
+                            };
+                            let y = if orientation & 2 == 0 {
+                                y
+                            } else {
+                                height - 1 - y
+                            };
+                            if blocks[y][x] {
+                                '#'
+                            } else {
+                                '.'
+                            }
+                        })
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .min()
+        .unwrap())
+}
+
+/// A stable hash of the given template's canonical block pattern (see `canonical_block_pattern`),
+/// suitable for caching per-template results or spotting duplicate templates in a batch.
+pub fn block_pattern_hash(template: &str) -> Result<u64, GridError> {
+    let mut hasher = StableHasher::new();
+    hasher.write_str(&canonical_block_pattern(template)?);
+    Ok(hasher.finish())
+}
+
 /// A struct recording a slot assignment made during a fill process.
 #[derive(Debug, Clone)]
 pub struct Choice {
@@ -671,44 +1374,514 @@ pub struct Choice {
     pub word_id: WordId,
 }
 
-/// Turn the given grid config and fill choices into a rendered string.
-#[allow(dead_code)]
+/// Fill in the grid's cells from its existing letters and the given choices, leaving blocks and
+/// unfilled cells empty.
 #[must_use]
-pub fn render_grid(config: &GridConfig, choices: &[Choice]) -> String {
-    let mut grid: Vec<Option<char>> = config
+pub fn filled_cells(config: &GridConfig, choices: &[Choice]) -> Vec<Option<char>> {
+    let mut cells: Vec<Option<char>> = config
         .fill
         .iter()
-        .map(|&cell| cell.map(|glyph_id| config.word_list.glyphs[glyph_id as usize]))
+        .map(|&cell| cell.map(|glyph_id| config.word_list.glyphs[glyph_id]))
         .collect();
 
     for &Choice { slot_id, word_id } in choices {
-        let slot_config = &config.slot_configs[slot_id];
-        let word = &config.word_list.words[slot_config.length][word_id];
-
-        for (cell_idx, &glyph) in word.glyphs.iter().enumerate() {
-            let (x, y) = match slot_config.direction {
-                Direction::Across => (
-                    slot_config.start_cell.0 + cell_idx,
-                    slot_config.start_cell.1,
-                ),
-                Direction::Down => (
-                    slot_config.start_cell.0,
-                    slot_config.start_cell.1 + cell_idx,
-                ),
-            };
-
-            grid[y * config.width + x] = Some(config.word_list.glyphs[glyph]);
+        let slot_config = &config.slot_configs[slot_id.index()];
+        let word = &config.word_list.words[slot_config.length][word_id.index()];
+        for (cell_idx, &glyph) in slot_config
+            .cell_fill_indices(config.width)
+            .into_iter()
+            .zip(&word.glyphs)
+        {
+            cells[cell_idx] = Some(config.word_list.glyphs[glyph]);
         }
     }
 
-    grid.chunks(config.width)
-        .map(|line| {
-            line.iter()
-                .map(|cell| cell.unwrap_or('.').to_string())
-                .collect::<String>()
+    cells
+}
+
+/// Turn the given grid config and fill choices into a rendered string, with . for both empty cells
+/// and blocks; see `render::render_grid_with_options` for other formats.
+#[allow(dead_code)]
+#[must_use]
+pub fn render_grid(config: &GridConfig, choices: &[Choice]) -> String {
+    render_grid_with_options(config, choices, &RenderOptions::default())
+}
+
+/// A word placed in the grid by a fill, in both its normalized form and the form given by its
+/// word list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+pub struct FilledEntry {
+    #[cfg_attr(feature = "typescript", ts(type = "number"))]
+    pub slot_id: SlotId,
+
+    /// The slot's key; see `SlotSpec::to_key`.
+    #[cfg_attr(feature = "typescript", ts(type = "string"))]
+    pub slot_spec: SlotSpec,
+
+    /// The word as it appears in the grid (e.g., "halfdome").
+    pub normalized: String,
+
+    /// The word as it appears in its word list, with capitalization, spaces, and punctuation intact
+    /// (e.g., "Half Dome"). For words that aren't in any list, this is the same as `normalized`.
+    pub display: String,
+}
+
+/// List the words placed by the given fill choices, in slot order, so that exports can show each
+/// entry's display form rather than just the letters in the grid.
+#[must_use]
+pub fn filled_entries(config: &GridConfig, choices: &[Choice]) -> Vec<FilledEntry> {
+    let mut entries: Vec<FilledEntry> = choices
+        .iter()
+        .map(|&Choice { slot_id, word_id }| {
+            let slot_config = &config.slot_configs[slot_id.index()];
+            let word = &config.word_list.words[slot_config.length][word_id.index()];
+
+            FilledEntry {
+                slot_id,
+                slot_spec: slot_config.slot_spec(),
+                normalized: word.normalized_string.clone(),
+                display: word.canonical_string.clone(),
+            }
         })
-        .collect::<Vec<_>>()
-        .join("\n")
+        .collect();
+
+    entries.sort_by_key(|entry| entry.slot_id);
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backtracking_search::{find_fill, find_fill_without_words};
+    use crate::error::GridError;
+    use crate::grid_config::{
+        block_pattern_hash, canonical_block_pattern, filled_entries, render_grid,
+        template_from_letter_grid, template_string_dimensions, template_string_fill,
+        template_string_hinted_fill, try_generate_grid_config_from_hinted_template_string,
+        try_generate_grid_config_from_letter_grid, try_generate_grid_config_from_template_string,
+        CrossingQuality, Direction, LetterHint, MarqueeRule, OwnedGridConfig, SlotId,
+    };
+    use crate::word_list::tests::memory_word_list;
+    use crate::word_list::{WordIterSource, WordList, WordListSourceConfig};
+
+    #[test]
+    fn test_invalid_template_strings() {
+        assert_eq!(template_string_dimensions("  \n\n"), Err(GridError::Empty));
+        assert_eq!(
+            template_string_dimensions("...\n..\n..."),
+            Err(GridError::UnevenRows)
+        );
+        assert_eq!(template_string_dimensions("\n ...#\n.#..\n"), Ok((4, 2)));
+
+        let word_list = WordList::new(
+            vec![WordListSourceConfig::Memory {
+                id: "0".into(),
+                enabled: true,
+                words: vec![("abc".into(), 50)],
+            }],
+            None,
+            Some(3),
+            None,
+        );
+        let result = try_generate_grid_config_from_template_string(word_list, "...\n..", 40);
+        assert!(matches!(result, Err(GridError::UnevenRows)));
+    }
+
+    #[test]
+    fn test_letter_grid_import() {
+        assert_eq!(
+            template_from_letter_grid("A-?#\n_É.#\n"),
+            Ok("a..#\n.é.#".into())
+        );
+        assert_eq!(
+            template_from_letter_grid("a.\n.!"),
+            Err(GridError::InvalidLetter {
+                x: 1,
+                y: 1,
+                letter: '!'
+            })
+        );
+        assert_eq!(
+            template_from_letter_grid("ab\n-"),
+            Err(GridError::UnevenRows)
+        );
+
+        let word_list = memory_word_list(&["ab", "cd", "ac", "bd", "ef", "gh", "eg", "fh"]);
+
+        // A single letter that doesn't complete any entry still pins down the fill.
+        let grid_config =
+            try_generate_grid_config_from_letter_grid(word_list, "--\n-H", 40).unwrap();
+        let config = grid_config.borrowed();
+        let result = find_fill(&config, None, None).expect("Failed to find a fill");
+        assert_eq!(render_grid(&config, &result.choices), "ef\ngh");
+    }
+
+    #[test]
+    fn test_numeric_entries() {
+        let word_list = WordList::new(
+            vec![WordListSourceConfig::FileContents {
+                id: "0".into(),
+                enabled: true,
+                contents: "B2;50\n4U;50\nB4;50\n2U;50\nBB;50\n24KGOLD;50\n".into(),
+            }],
+            None,
+            Some(7),
+            None,
+        );
+        assert!(word_list.word_id_by_string.contains_key("24kgold"));
+
+        // A digit in the grid constrains the fill just like a letter.
+        let grid_config =
+            try_generate_grid_config_from_letter_grid(word_list, "-2\n--", 40).unwrap();
+        let config = grid_config.borrowed();
+        let result = find_fill(&config, None, None).expect("Failed to find a fill");
+        assert_eq!(render_grid(&config, &result.choices), "b2\n4u");
+    }
+
+    #[test]
+    fn test_canonical_block_pattern() {
+        let template = "
+            #...
+            ....
+            ..#.
+        ";
+        assert_eq!(
+            canonical_block_pattern(template),
+            Ok("#..\n...\n..#\n...".into())
+        );
+
+        // Every rotation and reflection has the same pattern and hash, and letters are ignored.
+        for variant in [
+            "...#\n....\n.#..",
+            ".#..\n....\n...#",
+            "#..\n...\n..#\n...",
+            "...\n..#\n...\n#ab",
+        ] {
+            assert_eq!(
+                canonical_block_pattern(variant),
+                canonical_block_pattern(template)
+            );
+            assert_eq!(block_pattern_hash(variant), block_pattern_hash(template));
+        }
+
+        assert_ne!(
+            block_pattern_hash("#...\n....\n.#.."),
+            block_pattern_hash(template)
+        );
+        assert_eq!(canonical_block_pattern(""), Err(GridError::Empty));
+    }
+
+    #[test]
+    fn test_filled_entries() {
+        let word_list = WordList::new(
+            vec![WordListSourceConfig::Memory {
+                id: "0".into(),
+                enabled: true,
+                words: vec![("Half Dome".into(), 50)],
+            }],
+            None,
+            None,
+            None,
+        );
+        let grid_config =
+            try_generate_grid_config_from_template_string(word_list, "........", 40).unwrap();
+        let config = grid_config.borrowed();
+        let result = find_fill(&config, None, None).unwrap();
+
+        let entries = filled_entries(&config, &result.choices);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].slot_spec.direction, Direction::Across);
+        assert_eq!(entries[0].normalized, "halfdome");
+        assert_eq!(entries[0].display, "Half Dome");
+    }
+
+    #[test]
+    fn test_constrain_cells_to_spell() {
+        let word_list = memory_word_list(&["ab", "cd", "ac", "bd", "ef", "gh", "eg", "fh"]);
+        let mut grid_config =
+            try_generate_grid_config_from_template_string(word_list, "..\n..", 40).unwrap();
+
+        assert!(matches!(
+            grid_config.constrain_cells_to_spell(&[(0, 0)], "eh"),
+            Err(GridError::AnswerLengthMismatch {
+                cells: 1,
+                letters: 2
+            })
+        ));
+        assert!(matches!(
+            grid_config.constrain_cells_to_spell(&[(2, 0)], "e"),
+            Err(GridError::CellNotInEntry(2, 0))
+        ));
+
+        grid_config
+            .constrain_cells_to_spell(&[(0, 0), (1, 1)], "EH")
+            .unwrap();
+        let config = grid_config.borrowed();
+        let result = find_fill(&config, None, None).unwrap();
+        assert_eq!(render_grid(&config, &result.choices), "ef\ngh");
+
+        let mut prefilled = try_generate_grid_config_from_template_string(
+            grid_config.word_list.into_inner().unwrap(),
+            "a.\n..",
+            40,
+        )
+        .unwrap();
+        assert!(matches!(
+            prefilled.constrain_cells_to_spell(&[(0, 0)], "e"),
+            Err(GridError::ConflictingCell(0, 0))
+        ));
+    }
+
+    #[test]
+    fn test_apply_marquee_rule() {
+        let word_list = WordList::new(
+            vec![WordListSourceConfig::Iter {
+                id: "0".into(),
+                enabled: true,
+                words: WordIterSource::new(|| {
+                    [
+                        ("abc", 90, false),
+                        ("abd", 50, true),
+                        ("abe", 50, false),
+                        ("ab", 50, false),
+                    ]
+                    .into_iter()
+                    .map(|(word, score, marquee)| {
+                        let tags = if marquee {
+                            vec!["marquee".into()]
+                        } else {
+                            vec![]
+                        };
+                        (word.into(), score, tags)
+                    })
+                }),
+            }],
+            None,
+            None,
+            None,
+        );
+        let mut grid_config =
+            try_generate_grid_config_from_template_string(word_list, "...\n##.\n##.", 40).unwrap();
+        let option_strings = |grid_config: &OwnedGridConfig, slot_id: SlotId| {
+            let length = grid_config.slot_configs[slot_id.index()].length;
+            let mut strings: Vec<String> = grid_config.slot_options[slot_id.index()]
+                .iter()
+                .map(|word_id| {
+                    grid_config.word_list.words[length][word_id.index()]
+                        .normalized_string
+                        .clone()
+                })
+                .collect();
+            strings.sort();
+            strings
+        };
+
+        let slot_ids = grid_config.apply_marquee_rule(&MarqueeRule {
+            slot_count: 1,
+            min_score: Some(80),
+            tag: Some("marquee".into()),
+        });
+        assert_eq!(slot_ids.len(), 1);
+        assert_eq!(option_strings(&grid_config, slot_ids[0]), ["abc", "abd"]);
+
+        // The other slot has the same length, but wasn't one of the first `slot_count`.
+        let other_slot_id = SlotId(1 - slot_ids[0].index());
+        assert_eq!(option_strings(&grid_config, other_slot_id).len(), 3);
+    }
+
+    #[test]
+    fn test_apply_letter_hints() {
+        let word_list = memory_word_list(&["ab", "cd", "ac", "bd", "ef", "gh", "eg", "fh"]);
+        let mut grid_config =
+            try_generate_grid_config_from_template_string(word_list, "..\n..", 40).unwrap();
+        grid_config.apply_letter_hints(&[
+            LetterHint {
+                cell: (0, 0),
+                letter: 'E',
+                penalty: 10,
+            },
+            LetterHint {
+                cell: (1, 1),
+                letter: 'd',
+                penalty: 1,
+            },
+        ]);
+
+        // The hints are contradictory, so the heavier one wins, but nothing is ruled out.
+        let config = grid_config.borrowed();
+        assert_eq!(grid_config.slot_options[0].len(), 8);
+        let result = find_fill(&config, None, None).unwrap();
+        assert_eq!(render_grid(&config, &result.choices), "ef\ngh");
+    }
+
+    #[test]
+    fn test_template_letter_case() {
+        assert_eq!(
+            template_string_fill("Ab\n.#"),
+            vec![Some("a".into()), Some("b".into()), None, None]
+        );
+        assert_eq!(
+            template_string_hinted_fill("Ab\n.#"),
+            vec![None, Some("b".into()), None, None]
+        );
+
+        let word_list = || memory_word_list(&["ab", "cd", "ac", "bd", "ef", "gh", "eg", "fh"]);
+
+        // By default, letters of either case rule out every option that doesn't match them.
+        for template in ["e.\n..", "E.\n.."] {
+            let grid_config =
+                try_generate_grid_config_from_template_string(word_list(), template, 40).unwrap();
+            assert_eq!(grid_config.slot_options[0].len(), 2, "only ef and eg");
+        }
+
+        // With hints, a lowercase letter still rules options out...
+        let grid_config =
+            try_generate_grid_config_from_hinted_template_string(word_list(), "e.\n..", 40)
+                .unwrap();
+        assert_eq!(grid_config.slot_options[0].len(), 2, "only ef and eg");
+
+        // ...but an uppercase letter only changes which options are tried first.
+        let grid_config =
+            try_generate_grid_config_from_hinted_template_string(word_list(), "E.\n..", 40)
+                .unwrap();
+        assert_eq!(grid_config.slot_options[0].len(), 8);
+        let result = find_fill(&grid_config, None, None).unwrap();
+        assert_eq!(render_grid(&grid_config, &result.choices), "ef\ngh");
+
+        // It can even be overruled when no fill agrees with it.
+        let grid_config =
+            try_generate_grid_config_from_hinted_template_string(word_list(), "Z.\n..", 40)
+                .unwrap();
+        assert!(find_fill(&grid_config, None, None).is_ok());
+    }
+
+    #[test]
+    fn test_crossing_quality() {
+        let word_list = WordList::new(
+            vec![WordListSourceConfig::Memory {
+                id: "0".into(),
+                enabled: true,
+                words: [("ab", 10), ("cd", 60), ("ac", 60), ("bd", 60)]
+                    .into_iter()
+                    .map(|(word, score)| (word.into(), score))
+                    .collect(),
+            }],
+            None,
+            None,
+            None,
+        );
+        let mut grid_config =
+            try_generate_grid_config_from_template_string(word_list, "..\n..", 0).unwrap();
+        let mut fill_with_rule = |weak_score, fair_score| {
+            grid_config.crossing_quality = Some(CrossingQuality {
+                weak_score,
+                fair_score,
+            });
+            find_fill(&grid_config, None, None).ok()
+        };
+
+        // "ab" is weak, so its crossings have to be fair: "ac" and "bd" qualify at 50 but not 70.
+        assert!(fill_with_rule(30, 50).is_some());
+        assert!(fill_with_rule(30, 70).is_none());
+
+        // If every entry is weak and none is fair, no entries can cross at all.
+        assert!(fill_with_rule(70, 70).is_none());
+    }
+
+    #[test]
+    fn test_forbid_words() {
+        let word_list = memory_word_list(&["ab", "cd", "ac", "bd", "ef", "gh", "eg", "fh"]);
+        let mut grid_config =
+            try_generate_grid_config_from_template_string(word_list, "..\n..", 40).unwrap();
+
+        let config = grid_config.borrowed();
+        let result = find_fill_without_words(&config, &["AB".into()], None).unwrap();
+        let rendered = render_grid(&config, &result.choices);
+        assert!(rendered == "ef\ngh" || rendered == "eg\nfh", "{rendered}");
+        assert_eq!(
+            grid_config.slot_options[0].len(),
+            8,
+            "the config is unchanged"
+        );
+
+        grid_config.forbid_words(&["ef".into()]);
+        let config = grid_config.borrowed();
+        let result = find_fill(&config, None, None).unwrap();
+        let rendered = render_grid(&config, &result.choices);
+        assert!(rendered == "ab\ncd" || rendered == "ac\nbd", "{rendered}");
+    }
+
+    #[test]
+    fn test_break_ties_by_fillability() {
+        // These all have the same score and letter score, but "tae" shares the most letters with
+        // the others in each position.
+        let word_list = memory_word_list(&["tea", "eat", "ate", "tae"]);
+        let tae_id = (3, word_list.word_id_by_string["tae"]);
+        let tea_id = (3, word_list.word_id_by_string["tea"]);
+        assert!(word_list.get_fillability_score(tae_id) > word_list.get_fillability_score(tea_id));
+
+        let mut grid_config =
+            try_generate_grid_config_from_template_string(word_list, "...", 40).unwrap();
+        assert_eq!(grid_config.slot_options[0][0], tea_id.1);
+
+        grid_config.break_ties_by_fillability();
+        assert_eq!(grid_config.slot_options[0][0], tae_id.1);
+        assert_eq!(grid_config.slot_options[0].len(), 4);
+    }
+
+    #[test]
+    fn test_regenerate_slot_options() {
+        let word_list = WordList::new(
+            vec![WordListSourceConfig::Memory {
+                id: "0".into(),
+                enabled: true,
+                words: vec![("ab".into(), 50), ("cd".into(), 30), ("ef".into(), 50)],
+            }],
+            None,
+            None,
+            None,
+        );
+        let mut grid_config =
+            try_generate_grid_config_from_template_string(word_list, "..\n##", 40).unwrap();
+        let option_strings = |grid_config: &OwnedGridConfig| {
+            let mut strings: Vec<String> = grid_config.slot_options[0]
+                .iter()
+                .map(|&word_id| {
+                    grid_config
+                        .word_list
+                        .get_word((2, word_id))
+                        .normalized_string
+                        .clone()
+                })
+                .collect();
+            strings.sort();
+            strings
+        };
+        assert_eq!(option_strings(&grid_config), vec!["ab", "ef"]);
+
+        grid_config.regenerate_slot_options(20);
+        assert_eq!(option_strings(&grid_config), vec!["ab", "cd", "ef"]);
+
+        let word_list = grid_config.word_list.get_mut().unwrap();
+        word_list.optimistically_update_word("gh", 60, "0");
+        word_list.optimistically_delete_word("ab", "0");
+        grid_config.regenerate_slot_options(40);
+        assert_eq!(option_strings(&grid_config), vec!["ef", "gh"]);
+
+        // A borrowed word list can still be used for everything but complete entries.
+        let mut config = grid_config.borrowed();
+        config.regenerate_slot_options(60);
+        assert_eq!(config.slot_options[0].len(), 1);
+
+        // Changes made to the options since they were generated are made again.
+        config.forbid_words(&["gh".into()]);
+        config.regenerate_slot_options(20);
+        assert_eq!(config.slot_options[0].len(), 2);
+    }
 }
 
 #[cfg(all(test, feature = "serde"))]
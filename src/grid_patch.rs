@@ -0,0 +1,484 @@
+//! This module describes the differences between two versions of a grid as a `GridPatch`, a list of
+//! small edits (block toggles, letter changes, and constraint changes) that can be applied to a
+//! config to bring it up to date. A collaborative editor or a client syncing an editing session with
+//! a server can send each patch instead of the whole grid, and the receiving side can update its
+//! config without regenerating it from a template.
+//!
+//! Edits identify cells by their coordinates and slots by their `SlotSpec`s, and letters are given
+//! as characters rather than glyph ids, so a patch means the same thing to any config with the same
+//! dimensions, even one built from a different word list.
+
+use fancy_regex::Regex;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::error::GridError;
+use crate::grid_config::{
+    filled_cells, generate_slot_configs, generate_slots_from_template_string, CrossingQuality,
+    GridConfig, GridCoord, SlotSpec,
+};
+use crate::types::WordId;
+
+/// A single change to a grid; see `GridPatch`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum GridEdit {
+    /// Turn the given cell into a block, clearing its letter, or back into an open cell.
+    SetBlock { cell: GridCoord, block: bool },
+
+    /// Fill in or clear the letter in the given open cell.
+    SetLetter {
+        cell: GridCoord,
+        letter: Option<char>,
+    },
+
+    /// Set or clear the given slot's `SlotConfig::min_score_override`.
+    SetSlotMinScore {
+        slot: SlotSpec,
+        min_score: Option<u16>,
+    },
+
+    /// Set or clear the given slot's `SlotConfig::filter_pattern`, given as the pattern's source.
+    SetSlotFilter {
+        slot: SlotSpec,
+        pattern: Option<String>,
+    },
+
+    /// Replace the grid's `GridConfig::crossing_quality` rule.
+    SetCrossingQuality(Option<CrossingQuality>),
+}
+
+/// The edits turning one version of a grid into another, as produced by `diff`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct GridPatch {
+    /// The dimensions of the grids the patch applies to.
+    pub width: usize,
+    pub height: usize,
+
+    /// The edits, with block toggles first so that letter edits always refer to the new layout.
+    pub edits: Vec<GridEdit>,
+}
+
+impl GridPatch {
+    /// Does this patch leave the grid unchanged?
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+}
+
+/// Which cells of the given grid are blocks, in the same order as `GridConfig::fill`. Cells that
+/// aren't part of any slot count as blocks.
+fn block_cells(config: &GridConfig) -> Vec<bool> {
+    let mut blocks = vec![true; config.width * config.height];
+    for slot_config in config.slot_configs.iter() {
+        for cell_idx in slot_config.cell_fill_indices(config.width) {
+            blocks[cell_idx] = false;
+        }
+    }
+    blocks
+}
+
+/// The per-slot constraints of the given grid, keyed by slot.
+fn slot_constraints(config: &GridConfig) -> HashMap<SlotSpec, (Option<u16>, Option<Regex>)> {
+    config
+        .slot_configs
+        .iter()
+        .map(|slot_config| {
+            (
+                slot_config.slot_spec(),
+                (
+                    slot_config.min_score_override,
+                    slot_config.filter_pattern.clone(),
+                ),
+            )
+        })
+        .collect()
+}
+
+/// Compute the edits turning `old` into `new`, which must have the same dimensions. Constraint
+/// edits are only listed for slots that exist in `new`.
+pub fn diff(old: &GridConfig, new: &GridConfig) -> Result<GridPatch, GridError> {
+    if (old.width, old.height) != (new.width, new.height) {
+        return Err(GridError::SizeMismatch {
+            expected: (old.width, old.height),
+            actual: (new.width, new.height),
+        });
+    }
+
+    let coord = |idx: usize| (idx % new.width, idx / new.width);
+    let mut edits = vec![];
+
+    let new_blocks = block_cells(new);
+    for (idx, (&old_block, &new_block)) in block_cells(old).iter().zip(&new_blocks).enumerate() {
+        if old_block != new_block {
+            edits.push(GridEdit::SetBlock {
+                cell: coord(idx),
+                block: new_block,
+            });
+        }
+    }
+
+    for (idx, (old_letter, new_letter)) in filled_cells(old, &[])
+        .into_iter()
+        .zip(filled_cells(new, &[]))
+        .enumerate()
+    {
+        if !new_blocks[idx] && old_letter != new_letter {
+            edits.push(GridEdit::SetLetter {
+                cell: coord(idx),
+                letter: new_letter,
+            });
+        }
+    }
+
+    let old_constraints = slot_constraints(old);
+    for slot_config in new.slot_configs.iter() {
+        let slot = slot_config.slot_spec();
+        let (old_min_score, old_pattern) = old_constraints
+            .get(&slot)
+            .map_or((None, None), |(min_score, pattern)| {
+                (*min_score, pattern.as_ref().map(Regex::as_str))
+            });
+
+        if slot_config.min_score_override != old_min_score {
+            edits.push(GridEdit::SetSlotMinScore {
+                slot: slot.clone(),
+                min_score: slot_config.min_score_override,
+            });
+        }
+        let new_pattern = slot_config.filter_pattern.as_ref().map(Regex::as_str);
+        if new_pattern != old_pattern {
+            edits.push(GridEdit::SetSlotFilter {
+                slot,
+                pattern: new_pattern.map(str::to_string),
+            });
+        }
+    }
+
+    if old.crossing_quality != new.crossing_quality {
+        edits.push(GridEdit::SetCrossingQuality(new.crossing_quality));
+    }
+
+    Ok(GridPatch {
+        width: new.width,
+        height: new.height,
+        edits,
+    })
+}
+
+/// The state of a grid partway through applying a patch, before its config has been rebuilt.
+struct PendingGrid {
+    width: usize,
+    height: usize,
+    blocks: Vec<bool>,
+    letters: Vec<Option<char>>,
+    constraints: HashMap<SlotSpec, (Option<u16>, Option<Regex>)>,
+    layout_changed: bool,
+    options_changed: bool,
+}
+
+impl PendingGrid {
+    fn new(config: &GridConfig) -> PendingGrid {
+        PendingGrid {
+            width: config.width,
+            height: config.height,
+            blocks: block_cells(config),
+            letters: filled_cells(config, &[]),
+            constraints: slot_constraints(config),
+            layout_changed: false,
+            options_changed: false,
+        }
+    }
+
+    /// The index of the given cell in `blocks` and `letters`.
+    fn cell_idx(&self, (x, y): GridCoord) -> Result<usize, GridError> {
+        (x < self.width && y < self.height)
+            .then_some(x + y * self.width)
+            .ok_or(GridError::CellNotInEntry(x, y))
+    }
+
+    /// Record the given edit, except for changes to the crossing quality rule, which don't affect
+    /// the slots or their options and can be made to the config directly.
+    fn record(&mut self, edit: &GridEdit) -> Result<(), GridError> {
+        match edit {
+            GridEdit::SetBlock { cell, block } => {
+                let idx = self.cell_idx(*cell)?;
+                self.layout_changed |= self.blocks[idx] != *block;
+                self.blocks[idx] = *block;
+                if *block {
+                    self.letters[idx] = None;
+                }
+            }
+            GridEdit::SetLetter { cell, letter } => {
+                let idx = self.cell_idx(*cell)?;
+                self.letters[idx] = *letter;
+                self.options_changed = true;
+            }
+            GridEdit::SetSlotMinScore { slot, min_score } => {
+                self.constraints.entry(slot.clone()).or_default().0 = *min_score;
+                self.options_changed = true;
+            }
+            GridEdit::SetSlotFilter { slot, pattern } => {
+                self.constraints.entry(slot.clone()).or_default().1 = pattern
+                    .as_ref()
+                    .map(|pattern| {
+                        Regex::new(pattern)
+                            .map_err(|_| GridError::InvalidFilterPattern(pattern.clone()))
+                    })
+                    .transpose()?;
+                self.options_changed = true;
+            }
+            GridEdit::SetCrossingQuality(_) => {}
+        }
+        Ok(())
+    }
+
+    /// Replace the given config's slots with ones matching our blocks.
+    fn rebuild_slots(&self, config: &mut GridConfig) -> Result<(), GridError> {
+        let template = self
+            .blocks
+            .chunks(self.width)
+            .map(|row| {
+                row.iter()
+                    .map(|&block| if block { '#' } else { '.' })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let (slot_configs, crossing_count) =
+            generate_slot_configs(&generate_slots_from_template_string(&template))?;
+        config.slot_configs = Cow::Owned(slot_configs);
+        config.crossing_count = crossing_count;
+        config.option_counts = None;
+        Ok(())
+    }
+
+    /// Update the given config's per-slot constraints and fill to match ours.
+    fn update_slots_and_fill(&self, config: &mut GridConfig) -> Result<(), GridError> {
+        for slot_config in config.slot_configs.to_mut() {
+            let (min_score, pattern) = self
+                .constraints
+                .get(&slot_config.slot_spec())
+                .cloned()
+                .unwrap_or_default();
+            slot_config.min_score_override = min_score;
+            slot_config.filter_pattern = pattern;
+        }
+
+        let mut fill = Vec::with_capacity(self.letters.len());
+        for (&block, &letter) in self.blocks.iter().zip(&self.letters) {
+            let glyph_id = match letter.filter(|_| !block) {
+                Some(letter) => Some(
+                    config
+                        .glyph_id_for_char(letter)
+                        .ok_or(GridError::UnknownGlyph(letter))?,
+                ),
+                None => None,
+            };
+            fill.push(glyph_id);
+        }
+        config.fill = Cow::Owned(fill);
+        Ok(())
+    }
+}
+
+/// Look up the word in each complete slot of the given grid, whose word list must be borrowed.
+/// Since `GridConfig::regenerate_slot_options` can't add words to a borrowed list, it leaves these
+/// slots' options alone, so they have to be set beforehand.
+fn complete_entry_options(config: &GridConfig) -> Result<Vec<Vec<WordId>>, GridError> {
+    config
+        .slot_configs
+        .iter()
+        .map(|slot_config| {
+            let Some(glyphs) = slot_config.complete_fill(&config.fill, config.width) else {
+                return Ok(vec![]);
+            };
+            let word: String = glyphs
+                .iter()
+                .map(|&glyph_id| config.word_list.glyphs[glyph_id])
+                .collect();
+            config
+                .word_list
+                .word_id_by_string
+                .get(&word)
+                .map(|&word_id| vec![word_id])
+                .ok_or(GridError::UnknownEntry(word))
+        })
+        .collect()
+}
+
+/// Apply the given patch to the given grid, regenerating its slots (if any blocks changed) and its
+/// slot options with the given minimum score. As with `GridConfig::regenerate_slot_options`, the
+/// changes recorded in the config's `option_constraints` are made again on the new options, which
+/// fails if the patch made any of them impossible. If the slots change, the config's
+/// `option_counts` tracker no longer fits the grid, so it's dropped.
+///
+/// If the config's word list is borrowed, letters and complete entries that aren't in it are
+/// errors, since they can't be added. If applying the patch fails, the config may be left partly
+/// updated.
+pub fn apply(config: &mut GridConfig, patch: &GridPatch, min_score: u16) -> Result<(), GridError> {
+    if (patch.width, patch.height) != (config.width, config.height) {
+        return Err(GridError::SizeMismatch {
+            expected: (config.width, config.height),
+            actual: (patch.width, patch.height),
+        });
+    }
+
+    let mut pending = PendingGrid::new(config);
+    for edit in &patch.edits {
+        pending.record(edit)?;
+        if let GridEdit::SetCrossingQuality(crossing_quality) = edit {
+            config.crossing_quality = *crossing_quality;
+        }
+    }
+
+    if pending.layout_changed {
+        pending.rebuild_slots(config)?;
+    } else if !pending.options_changed {
+        return Ok(());
+    }
+    pending.update_slots_and_fill(config)?;
+
+    if config.word_list.get_mut().is_none() {
+        config.slot_options = Cow::Owned(complete_entry_options(config)?);
+    }
+    config.try_regenerate_slot_options(min_score)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::GridError;
+    use crate::grid_config::{
+        generate_grid_config_from_template_string, render_grid, CrossingQuality, Direction,
+        SlotSpec,
+    };
+    use crate::grid_patch::{apply, diff, GridEdit, GridPatch};
+    use crate::word_list::tests::memory_word_list;
+    use crate::word_list::WordList;
+    use fancy_regex::Regex;
+
+    fn word_list() -> WordList {
+        memory_word_list(&[
+            "abc", "def", "ghi", "adg", "beh", "cfi", "ab", "de", "ad", "be",
+        ])
+    }
+
+    #[test]
+    fn test_diff_and_apply() {
+        let mut old = generate_grid_config_from_template_string(word_list(), "...\n...\n...", 40);
+        let mut new = generate_grid_config_from_template_string(word_list(), "a.#\n..#\n###", 40);
+        let down_slot = SlotSpec {
+            start_cell: (0, 0),
+            direction: Direction::Down,
+            length: 2,
+        };
+        new.slot_configs.to_mut()[2].min_score_override = Some(30);
+        new.slot_configs.to_mut()[2].filter_pattern = Some(Regex::new("^a").unwrap());
+        new.crossing_quality = Some(CrossingQuality {
+            weak_score: 30,
+            fair_score: 50,
+        });
+
+        let patch = diff(&old, &new).unwrap();
+        assert_eq!(
+            patch.edits,
+            vec![
+                GridEdit::SetBlock {
+                    cell: (2, 0),
+                    block: true
+                },
+                GridEdit::SetBlock {
+                    cell: (2, 1),
+                    block: true
+                },
+                GridEdit::SetBlock {
+                    cell: (0, 2),
+                    block: true
+                },
+                GridEdit::SetBlock {
+                    cell: (1, 2),
+                    block: true
+                },
+                GridEdit::SetBlock {
+                    cell: (2, 2),
+                    block: true
+                },
+                GridEdit::SetLetter {
+                    cell: (0, 0),
+                    letter: Some('a')
+                },
+                GridEdit::SetSlotMinScore {
+                    slot: down_slot.clone(),
+                    min_score: Some(30)
+                },
+                GridEdit::SetSlotFilter {
+                    slot: down_slot,
+                    pattern: Some("^a".into())
+                },
+                GridEdit::SetCrossingQuality(new.crossing_quality),
+            ]
+        );
+
+        apply(&mut old, &patch, 40).unwrap();
+        assert!(diff(&old, &new).unwrap().is_empty());
+        assert_eq!(render_grid(&old, &[]), render_grid(&new, &[]));
+        assert_eq!(old.crossing_count, new.crossing_count);
+        assert_eq!(old.slot_options, new.slot_options);
+
+        // Patches only apply to grids of the same size.
+        let small = generate_grid_config_from_template_string(word_list(), "..\n..", 40);
+        assert_eq!(
+            diff(&small, &new),
+            Err(GridError::SizeMismatch {
+                expected: (2, 2),
+                actual: (3, 3)
+            })
+        );
+        let mut small = small;
+        assert!(apply(&mut small, &patch, 40).is_err());
+    }
+
+    #[test]
+    fn test_apply_with_borrowed_word_list() {
+        let word_list = word_list();
+        let owned = generate_grid_config_from_template_string(word_list, "...\n...\n...", 40);
+        let mut config = owned.borrowed();
+        config.forbid_words(&["def".into()]);
+        let patch = GridPatch {
+            width: 3,
+            height: 3,
+            edits: vec![GridEdit::SetLetter {
+                cell: (1, 1),
+                letter: Some('e'),
+            }],
+        };
+
+        apply(&mut config, &patch, 40).unwrap();
+        assert_eq!(render_grid(&config, &[]), "...\n.e.\n...");
+
+        // The middle row could be DEF or BEH, but DEF is still forbidden.
+        assert_eq!(config.slot_options[1].len(), 1);
+
+        // A letter that isn't in the word list can't be added to a borrowed list.
+        let patch = GridPatch {
+            edits: vec![GridEdit::SetLetter {
+                cell: (0, 0),
+                letter: Some('z'),
+            }],
+            ..patch
+        };
+        assert_eq!(
+            apply(&mut config, &patch, 40),
+            Err(GridError::UnknownGlyph('z'))
+        );
+    }
+}
@@ -0,0 +1,327 @@
+//! This module provides a small JSON-over-HTTP server wrapping a `FillService`, for deployments
+//! that want to run the filler as a standalone process. It's only available with the `http-server`
+//! feature, and exposes three endpoints:
+//!
+//! - `POST /fill` takes a `FillRequest` and returns a `FillResponse` (see `fill_schema.rs`).
+//! - `POST /validate` checks a grid template against the word list without searching for a fill.
+//! - `POST /suggest` lists the words matching a pattern, best first.
+//!
+//! Fills run on Tokio's blocking thread pool, so a long search doesn't hold up other requests. If
+//! the client disconnects before a fill finishes, its search is cancelled rather than left running.
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::post;
+use axum::{Json, Router};
+use serde_derive::{Deserialize, Serialize};
+use std::io;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+use crate::cancel::CancelToken;
+use crate::fill_schema::{FillRequest, FillResponse, FillStatus, DEFAULT_MIN_SCORE};
+use crate::fill_service::{FillService, GridSpec};
+use crate::grid_config::SlotSpec;
+use crate::word_list::normalize_word;
+
+/// The default number of words returned by `/suggest`.
+const DEFAULT_SUGGESTION_LIMIT: usize = 100;
+
+fn default_min_score() -> u16 {
+    DEFAULT_MIN_SCORE
+}
+
+fn default_suggestion_limit() -> usize {
+    DEFAULT_SUGGESTION_LIMIT
+}
+
+/// The body of a `/validate` request, mirroring `GridSpec`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GridRequest {
+    /// A template string with . representing empty cells, # representing blocks, and letters
+    /// representing themselves. Letters of either case are locked in unless `soft_uppercase` is
+    /// set.
+    pub template: String,
+
+    #[serde(default = "default_min_score")]
+    pub min_score: u16,
+
+    /// Treat uppercase letters in the template as suggestions, which the fill can override.
+    #[serde(default)]
+    pub soft_uppercase: bool,
+}
+
+impl GridRequest {
+    fn grid_spec(&self) -> GridSpec {
+        GridSpec {
+            template: self.template.clone(),
+            min_score: self.min_score,
+            soft_uppercase: self.soft_uppercase,
+            ..GridSpec::default()
+        }
+    }
+}
+
+/// The body of a `/validate` response. Grids that can't be parsed or that use letters or entries
+/// missing from the word list are reported as errors rather than as invalid grids.
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidateResponse {
+    /// Does every slot have at least one option?
+    pub valid: bool,
+
+    /// The slots with no options.
+    pub empty_slots: Vec<SlotSpec>,
+}
+
+/// The body of a `/suggest` request.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SuggestRequest {
+    /// The pattern to match, with . representing any letter (e.g., "a..le").
+    pub pattern: String,
+
+    #[serde(default = "default_min_score")]
+    pub min_score: u16,
+
+    #[serde(default = "default_suggestion_limit")]
+    pub limit: usize,
+}
+
+/// A single word in a `/suggest` response.
+#[derive(Debug, Clone, Serialize)]
+pub struct SuggestionResponse {
+    pub normalized: String,
+    pub display: String,
+    pub score: u16,
+}
+
+/// The body of a `/suggest` response.
+#[derive(Debug, Clone, Serialize)]
+pub struct SuggestResponse {
+    pub words: Vec<SuggestionResponse>,
+}
+
+/// The body of any failed request.
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorResponse {
+    pub error: String,
+}
+
+type ErrorResult = (StatusCode, Json<ErrorResponse>);
+
+fn error_response(status: StatusCode, error: &impl ToString) -> ErrorResult {
+    (
+        status,
+        Json(ErrorResponse {
+            error: error.to_string(),
+        }),
+    )
+}
+
+/// Choose an HTTP status for a fill response. Bad input is the client's fault; a grid that can't
+/// be filled in time is reported as unprocessable, since the request itself was fine.
+fn fill_status_code(status: FillStatus) -> StatusCode {
+    match status {
+        FillStatus::Filled => StatusCode::OK,
+        FillStatus::InvalidInput => StatusCode::BAD_REQUEST,
+        FillStatus::SolverError => StatusCode::INTERNAL_SERVER_ERROR,
+        FillStatus::Unfillable
+        | FillStatus::Timeout
+        | FillStatus::Aborted
+        | FillStatus::ExceededBacktrackLimit => StatusCode::UNPROCESSABLE_ENTITY,
+    }
+}
+
+async fn fill(
+    State(service): State<Arc<FillService>>,
+    Json(request): Json<FillRequest>,
+) -> (StatusCode, Json<FillResponse>) {
+    // Axum drops this future when the client disconnects, which drops the guard.
+    let cancel = CancelToken::new();
+    let _cancel_guard = cancel.cancel_on_drop("request ended");
+
+    let response = tokio::task::spawn_blocking(move || {
+        if let Err(error) = request.check_version() {
+            return FillResponse::invalid(&error);
+        }
+        let grid_spec = GridSpec {
+            cancel: Some(cancel),
+            ..request.grid_spec()
+        };
+        service
+            .fill_and_then(&grid_spec, |config, result| {
+                FillResponse::from_result(config, &result)
+            })
+            .unwrap_or_else(|error| FillResponse::invalid(&error))
+    })
+    .await
+    .unwrap_or_else(|error| FillResponse {
+        status: FillStatus::SolverError,
+        ..FillResponse::invalid(&error)
+    });
+
+    (fill_status_code(response.status), Json(response))
+}
+
+async fn validate(
+    State(service): State<Arc<FillService>>,
+    Json(request): Json<GridRequest>,
+) -> Result<Json<ValidateResponse>, ErrorResult> {
+    let empty_slots = service
+        .validate(&request.grid_spec())
+        .map_err(|error| error_response(StatusCode::BAD_REQUEST, &error))?;
+
+    Ok(Json(ValidateResponse {
+        valid: empty_slots.is_empty(),
+        empty_slots,
+    }))
+}
+
+async fn suggest(
+    State(service): State<Arc<FillService>>,
+    Json(request): Json<SuggestRequest>,
+) -> Json<SuggestResponse> {
+    let word_list = service.word_list();
+    let pattern: Vec<Option<char>> = request
+        .pattern
+        .chars()
+        .map(|c| {
+            if c == '.' {
+                None
+            } else {
+                normalize_word(&c.to_string()).chars().next()
+            }
+        })
+        .collect();
+
+    let mut words: Vec<SuggestionResponse> = word_list
+        .words
+        .get(pattern.len())
+        .map(|words| {
+            words
+                .iter()
+                .filter(|word| {
+                    !word.hidden
+                        && word.score >= request.min_score
+                        && word
+                            .normalized_string
+                            .chars()
+                            .zip(&pattern)
+                            .all(|(letter, cell)| cell.map_or(true, |cell| cell == letter))
+                })
+                .map(|word| SuggestionResponse {
+                    normalized: word.normalized_string.clone(),
+                    display: word.canonical_string.clone(),
+                    score: word.score,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    words.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.normalized.cmp(&b.normalized))
+    });
+    words.truncate(request.limit);
+
+    Json(SuggestResponse { words })
+}
+
+/// Build a router serving the endpoints described in the module docs.
+pub fn router(service: Arc<FillService>) -> Router {
+    Router::new()
+        .route("/fill", post(fill))
+        .route("/validate", post(validate))
+        .route("/suggest", post(suggest))
+        .with_state(service)
+}
+
+/// Serve requests on the given listener until the process is stopped.
+pub async fn serve(listener: TcpListener, service: Arc<FillService>) -> io::Result<()> {
+    axum::serve(listener, router(service)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backtracking_search::SearchConfig;
+    use crate::fill_schema::{
+        FillConstraints, FillOptions, FillRequest, FillStatus, FILL_SCHEMA_VERSION,
+    };
+    use crate::fill_service::FillService;
+    use crate::http_server::{fill, suggest, validate, GridRequest, SuggestRequest};
+    use crate::word_list::tests::word_list_source_config;
+    use crate::word_list::WordList;
+    use axum::extract::State;
+    use axum::http::StatusCode;
+    use axum::Json;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_endpoints() {
+        let word_list = WordList::new(word_list_source_config(), None, Some(5), Some(5));
+        let service = Arc::new(FillService::new(
+            Arc::new(word_list),
+            SearchConfig::default(),
+        ));
+        let fill_request = |grid: &str| FillRequest {
+            version: FILL_SCHEMA_VERSION,
+            grid: grid.into(),
+            constraints: FillConstraints {
+                min_score: Some(40),
+                ..FillConstraints::default()
+            },
+            options: FillOptions::default(),
+        };
+
+        let (status, Json(response)) = fill(
+            State(service.clone()),
+            Json(fill_request("a....\n.....\n.....\n.....\n.....")),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(response.entries.len(), 10);
+        let grid = response.grid.unwrap();
+        assert!(grid.starts_with('a'));
+        assert!(!grid.contains('.'));
+
+        let (status, Json(response)) = fill(
+            State(service.clone()),
+            Json(fill_request("@....\n.....\n.....\n.....\n.....")),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(response.status, FillStatus::InvalidInput);
+
+        let Json(response) = validate(
+            State(service.clone()),
+            Json(GridRequest {
+                template: "xq...\n.....\n.....\n.....\n.....".into(),
+                min_score: 40,
+                soft_uppercase: false,
+            }),
+        )
+        .await
+        .unwrap();
+        assert!(!response.valid);
+        assert!(!response.empty_slots.is_empty());
+
+        let Json(response) = suggest(
+            State(service.clone()),
+            Json(SuggestRequest {
+                pattern: "a...".into(),
+                min_score: 40,
+                limit: 5,
+            }),
+        )
+        .await;
+        assert_eq!(response.words.len(), 5);
+        assert!(response
+            .words
+            .iter()
+            .all(|word| word.normalized.starts_with('a') && word.normalized.len() == 4));
+        assert!(response
+            .words
+            .windows(2)
+            .all(|pair| pair[0].score >= pair[1].score));
+    }
+}
@@ -1,19 +1,27 @@
+// Some of the allowed lints below have since been removed from Clippy, but are kept for anyone
+// building with an older toolchain.
+#![allow(renamed_and_removed_lints)]
 #![warn(clippy::pedantic)]
 #![allow(clippy::cast_possible_truncation)]
 #![allow(clippy::cast_precision_loss)]
 #![allow(clippy::cast_sign_loss)]
 #![allow(clippy::comparison_chain)]
 #![allow(clippy::implicit_hasher)]
+#![allow(clippy::map_unwrap_or)]
 #![allow(clippy::match_on_vec_items)]
 #![allow(clippy::missing_errors_doc)]
 #![allow(clippy::missing_panics_doc)]
 #![allow(clippy::module_name_repetitions)]
-#[cfg(target_arch = "wasm32")]
-use wee_alloc::WeeAlloc;
+#![allow(clippy::unnecessary_map_or)]
+// In Wasm builds, we use Rust's default allocator (dlmalloc) unless the `talc` feature selects
+// Talc instead, which is noticeably faster at the many small allocations made by `WordList::new`.
+// Talc's Wasm configuration assumes a single thread, so it can't be combined with `wasm-threads`.
+#[cfg(all(target_arch = "wasm32", feature = "talc", feature = "wasm-threads"))]
+compile_error!("The `talc` allocator is single-threaded and can't be used with `wasm-threads`");
 
-#[cfg(target_arch = "wasm32")]
+#[cfg(all(target_arch = "wasm32", feature = "talc"))]
 #[global_allocator]
-static ALLOC: WeeAlloc = WeeAlloc::INIT;
+static ALLOC: talc::TalckWasm = unsafe { talc::TalckWasm::new_global() };
 
 #[cfg(target_arch = "wasm32")]
 #[inline]
@@ -25,10 +33,58 @@ pub fn set_panic_hook() {
 }
 
 pub mod arc_consistency;
+#[cfg(all(feature = "tokio", not(target_arch = "wasm32")))]
+pub mod async_search;
+pub mod audit;
 pub mod backtracking_search;
+pub mod batch_fill;
+pub mod benchmark;
+pub mod cancel;
+pub mod cheater_squares;
+pub mod clue_export;
+pub mod crossing_report;
+pub mod crossing_weights;
+pub mod discrepancy_search;
 pub mod dupe_index;
+pub mod error;
+pub mod failure_bundle;
+#[cfg(feature = "serde")]
+pub mod fill_schema;
+pub mod fill_service;
+#[cfg(feature = "serde")]
+pub mod fill_stats;
 pub mod grid_config;
+pub mod grid_patch;
+#[cfg(all(feature = "http-server", not(target_arch = "wasm32")))]
+pub mod http_server;
+pub mod lint;
+pub mod minizinc;
+pub mod open_areas;
+pub mod option_counts;
+#[cfg(any(not(target_arch = "wasm32"), feature = "wasm-threads"))]
+pub mod parallel_search;
+pub mod phonetic;
+#[cfg(feature = "serde")]
+pub mod project;
+pub mod relaxation;
+pub mod render;
+pub mod required_words;
+pub mod roundtrip;
+#[cfg(feature = "sat")]
+pub mod sat_solver;
+pub mod score_overrides;
+pub mod score_sweep;
+pub mod solution_counting;
+pub mod suggestions;
+pub mod tag_caps;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod theme_density;
+#[cfg(feature = "image")]
+pub mod thumbnail;
 pub mod types;
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+pub mod url_source;
 pub mod util;
 pub mod word_list;
 
@@ -0,0 +1,154 @@
+//! This module checks a finished fill for outcomes that are structurally legal but editorially
+//! undesirable, so that they can be shown to the constructor as warnings rather than silently
+//! published. None of these are enforced by the search itself: a duplicate entry can slip through
+//! when dupe checking is disabled (see `WordList::max_shared_substring`), two-letter entries are
+//! only possible in grids that allow two-letter slots, and all-consonant entries (like TSKTSK) are
+//! sometimes fine but always worth a second look.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+use crate::crossing_report::slot_labels;
+use crate::grid_config::{filled_cells, Choice, GridConfig, GridCoord, SlotId};
+
+/// The letters that keep an entry from being flagged as `LintKind::AllConsonants`.
+const VOWELS: &[char] = &['a', 'e', 'i', 'o', 'u', 'y'];
+
+/// The kinds of problems reported by `lint_fill`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde_derive::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum LintKind {
+    /// The entry also appears in the given slot, which comes first in slot order.
+    DuplicateEntry(SlotId),
+
+    /// The entry is only two letters long.
+    TwoLetterEntry,
+
+    /// The entry is made up entirely of letters other than vowels.
+    AllConsonants,
+}
+
+/// A single problem found by `lint_fill`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde_derive::Serialize))]
+pub struct LintWarning {
+    pub kind: LintKind,
+    pub slot_id: SlotId,
+
+    /// A conventional label for the slot, like "7D".
+    pub label: String,
+
+    /// The coordinates of the entry's cells, in order.
+    pub cells: Vec<GridCoord>,
+
+    /// The entry's letters, in uppercase.
+    pub entry: String,
+}
+
+impl Display for LintWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let (x, y) = self.cells[0];
+        write!(f, "{} at ({x}, {y}), {}: ", self.label, self.entry)?;
+        match self.kind {
+            LintKind::DuplicateEntry(slot_id) => write!(f, "duplicates slot {slot_id}"),
+            LintKind::TwoLetterEntry => write!(f, "two-letter entry"),
+            LintKind::AllConsonants => write!(f, "no vowels"),
+        }
+    }
+}
+
+/// Check the complete entries in the given fill for the problems described in the module docs,
+/// returning warnings in order of slot id. Slots with any empty cells are skipped.
+#[must_use]
+pub fn lint_fill(config: &GridConfig, choices: &[Choice]) -> Vec<LintWarning> {
+    let cells = filled_cells(config, choices);
+    let labels = slot_labels(config);
+    let mut first_slot_by_entry: HashMap<String, SlotId> = HashMap::new();
+    let mut warnings = vec![];
+
+    for (slot_config, label) in config.slot_configs.iter().zip(labels) {
+        let Some(entry) = slot_config
+            .cell_fill_indices(config.width)
+            .into_iter()
+            .map(|cell_idx| cells[cell_idx])
+            .collect::<Option<String>>()
+        else {
+            continue;
+        };
+
+        let mut kinds = vec![];
+        if let Some(&first_slot_id) = first_slot_by_entry.get(&entry) {
+            kinds.push(LintKind::DuplicateEntry(first_slot_id));
+        } else {
+            first_slot_by_entry.insert(entry.clone(), slot_config.id);
+        }
+        if slot_config.length == 2 {
+            kinds.push(LintKind::TwoLetterEntry);
+        }
+        if entry.chars().all(char::is_alphabetic)
+            && !entry
+                .chars()
+                .flat_map(char::to_lowercase)
+                .any(|c| VOWELS.contains(&c))
+        {
+            kinds.push(LintKind::AllConsonants);
+        }
+
+        for kind in kinds {
+            warnings.push(LintWarning {
+                kind,
+                slot_id: slot_config.id,
+                label: label.clone(),
+                cells: slot_config.cell_coords(),
+                entry: entry.to_uppercase(),
+            });
+        }
+    }
+
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid_config::{generate_grid_config_from_template_string, SlotId};
+    use crate::lint::{lint_fill, LintKind};
+    use crate::word_list::{WordList, WordListSourceConfig};
+
+    #[test]
+    fn test_lint_fill() {
+        let word_list = WordList::new(
+            vec![WordListSourceConfig::Memory {
+                id: "0".into(),
+                enabled: true,
+                words: vec![],
+            }],
+            None,
+            None,
+            None,
+        );
+
+        // NAN appears both across and down, NTH has no vowels, and AT is only two letters long.
+        let grid_config =
+            generate_grid_config_from_template_string(word_list, "nth#\na#at\nnan#", 40);
+        let warnings = lint_fill(&grid_config, &[]);
+        let kinds: Vec<(&str, LintKind)> = warnings
+            .iter()
+            .map(|warning| (warning.label.as_str(), warning.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("1A", LintKind::AllConsonants),
+                ("3A", LintKind::TwoLetterEntry),
+                ("1D", LintKind::DuplicateEntry(SlotId(2))),
+            ]
+        );
+        assert_eq!(warnings[0].cells, vec![(0, 0), (1, 0), (2, 0)]);
+        assert_eq!(
+            warnings[2].to_string(),
+            "1D at (0, 0), NAN: duplicates slot 2"
+        );
+    }
+}
@@ -0,0 +1,247 @@
+//! This module exports a grid-filling problem as a `MiniZinc` model, so that the same instances can
+//! be run through external CP solvers for comparison with the built-in search. We only produce the
+//! `.mzn` source; solvers that need `FlatZinc` can get it with `minizinc --compile`.
+//!
+//! The model has one integer variable per cell, holding a `GlyphId`, and one integer variable per
+//! slot, holding the index of the chosen option in that slot's `slot_options` (1-based, as is
+//! conventional in `MiniZinc`). Each slot is linked to its cells with a `table` constraint, exact
+//! duplicates are ruled out with `alldifferent` over global word numbers, and any additional dupe
+//! relationships from the word list's dupe index are emitted as pairwise constraints. A slot with no
+//! options at all makes the model unsatisfiable via `constraint false`.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::grid_config::{GridConfig, SlotConfig};
+use crate::types::{GlobalWordId, WordId};
+
+/// Write the variable and `table` constraint for a single slot, returning a map from each of its
+/// options to its 1-based index.
+fn write_slot(
+    model: &mut String,
+    config: &GridConfig,
+    slot_config: &SlotConfig,
+    global_numbers: &mut HashMap<GlobalWordId, usize>,
+) -> HashMap<WordId, usize> {
+    let slot_id = slot_config.id;
+    let options = &config.slot_options[slot_id.index()];
+    let cell_idxs = slot_config.cell_fill_indices(config.width);
+
+    writeln!(model).unwrap();
+    writeln!(model, "% {}", slot_config.slot_key()).unwrap();
+
+    // An empty domain can't be expressed as a range or a table, so we declare placeholders that
+    // keep the rest of the model well-formed and then rule the whole thing out.
+    if options.is_empty() {
+        writeln!(model, "var 1..1: word_{slot_id};").unwrap();
+        writeln!(model, "array[1..1] of int: number_{slot_id} = [0];").unwrap();
+        writeln!(model, "constraint false;").unwrap();
+        return HashMap::new();
+    }
+
+    writeln!(model, "var 1..{}: word_{slot_id};", options.len()).unwrap();
+
+    let mut rows = String::new();
+    let mut numbers = vec![];
+    let mut indices = HashMap::new();
+    for (option_idx, &word_id) in options.iter().enumerate() {
+        let word = &config.word_list.words[slot_config.length][word_id.index()];
+        write!(rows, "\n  | {}", option_idx + 1).unwrap();
+        for glyph in &word.glyphs {
+            write!(rows, ", {glyph}").unwrap();
+        }
+
+        let next_number = global_numbers.len() + 1;
+        numbers.push(
+            *global_numbers
+                .entry((slot_config.length, word_id))
+                .or_insert(next_number),
+        );
+        indices.insert(word_id, option_idx + 1);
+    }
+
+    writeln!(
+        model,
+        "array[1..{}] of int: number_{slot_id} = [{}];",
+        numbers.len(),
+        numbers
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+    .unwrap();
+
+    writeln!(
+        model,
+        "constraint table([word_{slot_id}, {}], [|{} |]);",
+        cell_idxs
+            .iter()
+            .map(|cell_idx| format!("cell[{cell_idx}]"))
+            .collect::<Vec<_>>()
+            .join(", "),
+        rows
+    )
+    .unwrap();
+
+    indices
+}
+
+/// Generate a `MiniZinc` model representing the given grid config.
+#[must_use]
+pub fn export_minizinc(config: &GridConfig) -> String {
+    let mut model = String::new();
+    let cell_count = config.width * config.height;
+    let glyph_count = config.word_list.glyphs.len().max(1);
+
+    writeln!(model, "% Generated by ingrid_core.").unwrap();
+    writeln!(
+        model,
+        "% Glyphs: {}",
+        config.word_list.glyphs.iter().collect::<String>()
+    )
+    .unwrap();
+    writeln!(model, "include \"table.mzn\";").unwrap();
+    writeln!(model, "include \"alldifferent.mzn\";").unwrap();
+    writeln!(model).unwrap();
+    writeln!(
+        model,
+        "array[0..{}] of var 0..{}: cell;",
+        cell_count.max(1) - 1,
+        glyph_count - 1
+    )
+    .unwrap();
+
+    // Prefilled cells.
+    for (cell_idx, glyph) in config.fill.iter().enumerate() {
+        if let Some(glyph) = glyph {
+            writeln!(model, "constraint cell[{cell_idx}] = {glyph};").unwrap();
+        }
+    }
+
+    // Assign each word that appears in any slot a global number, so that we can rule out exact
+    // duplicates with a single `alldifferent` constraint.
+    let mut global_numbers: HashMap<GlobalWordId, usize> = HashMap::new();
+    let mut option_indices: Vec<HashMap<WordId, usize>> = vec![];
+
+    for slot_config in config.slot_configs.iter() {
+        option_indices.push(write_slot(
+            &mut model,
+            config,
+            slot_config,
+            &mut global_numbers,
+        ));
+    }
+
+    writeln!(model).unwrap();
+    writeln!(
+        model,
+        "constraint alldifferent([{}]);",
+        config
+            .slot_configs
+            .iter()
+            .map(|slot_config| format!("number_{0}[word_{0}]", slot_config.id))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+    .unwrap();
+
+    // Non-identical dupes (shared substrings or extra dupe pairs).
+    for slot_config in config.slot_configs.iter() {
+        for &word_id in &config.slot_options[slot_config.id.index()] {
+            let dupes_by_length = config
+                .word_list
+                .dupe_index
+                .get_dupes_by_length((slot_config.length, word_id));
+
+            for other_slot_config in &config.slot_configs[(slot_config.id.index() + 1)..] {
+                let Some(dupe_ids) = dupes_by_length.get(&other_slot_config.length) else {
+                    continue;
+                };
+                for dupe_id in dupe_ids {
+                    if other_slot_config.length == slot_config.length && *dupe_id == word_id {
+                        continue;
+                    }
+                    if let Some(other_idx) =
+                        option_indices[other_slot_config.id.index()].get(dupe_id)
+                    {
+                        writeln!(
+                            model,
+                            "constraint word_{} != {} \\/ word_{} != {};",
+                            slot_config.id,
+                            option_indices[slot_config.id.index()][&word_id],
+                            other_slot_config.id,
+                            other_idx
+                        )
+                        .unwrap();
+                    }
+                }
+            }
+        }
+    }
+
+    writeln!(model).unwrap();
+    writeln!(model, "solve satisfy;").unwrap();
+    writeln!(model, "output [show(cell)];").unwrap();
+
+    model
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid_config::generate_grid_config_from_template_string;
+    use crate::minizinc::export_minizinc;
+    use crate::word_list::{WordList, WordListSourceConfig};
+
+    #[test]
+    fn test_export_minizinc() {
+        let word_list = WordList::new(
+            vec![WordListSourceConfig::Memory {
+                id: "0".into(),
+                enabled: true,
+                words: vec![("ab".into(), 50), ("cd".into(), 50), ("ac".into(), 50)],
+            }],
+            None,
+            Some(2),
+            None,
+        );
+        let grid_config = generate_grid_config_from_template_string(word_list, "a.\n..", 40);
+
+        let model = export_minizinc(&grid_config);
+
+        assert!(model.contains("array[0..3] of var 0..3: cell;"));
+        assert!(model.contains("constraint cell[0] = 0;"));
+        assert!(model.contains("% 0,0,across,2\nvar 1..2: word_0;"));
+        assert!(model.contains("constraint table([word_0, cell[0], cell[1]], [|"));
+        assert!(
+            model.contains("\n  | 1, 0, 2\n  | 2, 0, 1 |]);"),
+            "'ac' then 'ab'"
+        );
+        assert!(model.contains("constraint alldifferent([number_0[word_0], number_1[word_1], number_2[word_2], number_3[word_3]]);"));
+        assert!(model.ends_with("solve satisfy;\noutput [show(cell)];\n"));
+    }
+
+    #[test]
+    fn test_export_minizinc_with_empty_slot() {
+        let word_list = WordList::new(
+            vec![WordListSourceConfig::Memory {
+                id: "0".into(),
+                enabled: true,
+                words: vec![("ab".into(), 50), ("cd".into(), 50)],
+            }],
+            None,
+            Some(2),
+            None,
+        );
+        let grid_config = generate_grid_config_from_template_string(word_list, "b.\n..", 40);
+
+        let model = export_minizinc(&grid_config);
+
+        // No word starts with "b", so the first row has no options.
+        assert!(model.contains(
+            "% 0,0,across,2\nvar 1..1: word_0;\narray[1..1] of int: number_0 = [0];\nconstraint false;\n"
+        ));
+        assert!(!model.contains("[| |]"));
+        assert!(!model.contains("array[1..0]"));
+    }
+}
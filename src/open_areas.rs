@@ -0,0 +1,159 @@
+//! This module finds the open areas of a grid: stretches of white cells without any blocks to
+//! break them up, which are usually the hardest parts of a grid to fill. We count a cell as open
+//! if it's part of some block-free square of a given size, and then group open cells into areas
+//! by adjacency, so that a wide-open corner and a wide-open center are reported separately unless
+//! they're joined by a comparably open channel.
+
+use crate::grid_config::{GridConfig, GridCoord};
+
+/// A connected group of open cells.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenArea {
+    /// The cells in the area, in reading order.
+    pub cells: Vec<GridCoord>,
+
+    /// The corners of the area's bounding box (inclusive).
+    pub top_left: GridCoord,
+    pub bottom_right: GridCoord,
+}
+
+impl OpenArea {
+    /// The number of cells in the area.
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.cells.len()
+    }
+
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.bottom_right.0 - self.top_left.0 + 1
+    }
+
+    #[must_use]
+    pub fn height(&self) -> usize {
+        self.bottom_right.1 - self.top_left.1 + 1
+    }
+}
+
+/// Find the open areas in the given grid, where a cell is open if it's part of a block-free square
+/// of `square_size` by `square_size` cells. The areas are sorted from largest to smallest, and
+/// then in reading order of their first cells.
+#[must_use]
+pub fn find_open_areas(config: &GridConfig, square_size: usize) -> Vec<OpenArea> {
+    let (width, height) = (config.width, config.height);
+    let square_size = square_size.max(1);
+
+    // Any cell that isn't part of a slot is either a block or isolated by blocks.
+    let mut is_white = vec![false; width * height];
+    for slot_config in config.slot_configs.iter() {
+        for (x, y) in slot_config.cell_coords() {
+            is_white[y * width + x] = true;
+        }
+    }
+
+    let mut is_open = vec![false; width * height];
+    for top in 0..(height + 1).saturating_sub(square_size) {
+        for left in 0..(width + 1).saturating_sub(square_size) {
+            let square = || {
+                (top..top + square_size)
+                    .flat_map(move |y| (left..left + square_size).map(move |x| y * width + x))
+            };
+            if square().all(|idx| is_white[idx]) {
+                for idx in square() {
+                    is_open[idx] = true;
+                }
+            }
+        }
+    }
+
+    let mut visited = vec![false; width * height];
+    let mut areas: Vec<OpenArea> = vec![];
+
+    for start_idx in 0..width * height {
+        if !is_open[start_idx] || visited[start_idx] {
+            continue;
+        }
+        visited[start_idx] = true;
+        let mut stack = vec![(start_idx % width, start_idx / width)];
+        let mut cells: Vec<GridCoord> = vec![];
+
+        while let Some((x, y)) = stack.pop() {
+            cells.push((x, y));
+
+            let neighbors = [
+                x.checked_sub(1).map(|x| (x, y)),
+                (x + 1 < width).then_some((x + 1, y)),
+                y.checked_sub(1).map(|y| (x, y)),
+                (y + 1 < height).then_some((x, y + 1)),
+            ];
+            for (nx, ny) in neighbors.into_iter().flatten() {
+                let idx = ny * width + nx;
+                if is_open[idx] && !visited[idx] {
+                    visited[idx] = true;
+                    stack.push((nx, ny));
+                }
+            }
+        }
+
+        cells.sort_by_key(|&(x, y)| (y, x));
+        let top_left = (
+            cells.iter().map(|cell| cell.0).min().unwrap(),
+            cells.iter().map(|cell| cell.1).min().unwrap(),
+        );
+        let bottom_right = (
+            cells.iter().map(|cell| cell.0).max().unwrap(),
+            cells.iter().map(|cell| cell.1).max().unwrap(),
+        );
+        areas.push(OpenArea {
+            cells,
+            top_left,
+            bottom_right,
+        });
+    }
+
+    // We found the areas in reading order, and the sort is stable.
+    areas.sort_by_key(|area| std::cmp::Reverse(area.size()));
+    areas
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid_config::generate_grid_config_from_template_string;
+    use crate::open_areas::find_open_areas;
+    use crate::word_list::WordList;
+
+    #[test]
+    fn test_find_open_areas() {
+        let grid_config = generate_grid_config_from_template_string(
+            WordList::new(vec![], None, None, None),
+            "
+            ....#....
+            ....#....
+            ....#....
+            ##.###.##
+            .........
+            .........
+            .........
+            ",
+            0,
+        );
+        let config = grid_config.borrowed();
+
+        let areas = find_open_areas(&config, 3);
+        assert_eq!(
+            areas
+                .iter()
+                .map(|area| (area.size(), area.top_left, area.bottom_right))
+                .collect::<Vec<_>>(),
+            vec![
+                (27, (0, 4), (8, 6)),
+                (12, (0, 0), (3, 2)),
+                (12, (5, 0), (8, 2))
+            ],
+        );
+        assert_eq!((areas[1].width(), areas[1].height()), (4, 3));
+
+        // None of the open stretches is four cells deep.
+        assert!(find_open_areas(&config, 4).is_empty());
+    }
+}
@@ -0,0 +1,117 @@
+//! This module lets a frontend watch how constrained each slot is while a fill is running, e.g. to
+//! draw the classic bar display of remaining options per slot. An `OptionCounts` is shared with
+//! the search through `GridConfig::option_counts`, like the `cancel` token, and the search updates
+//! it at every step; any other thread can read it at any time. Alongside each slot's current
+//! count, it keeps the lowest count the slot has reached, which shows where the search has come
+//! closest to a wipeout even after it backtracks out of trouble.
+//!
+//! The lowest counts for a finished search are also reported in `Statistics::min_option_counts`.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::backtracking_search::Slot;
+
+/// Live remaining-option counts for each slot; see the module docs.
+#[derive(Debug, Default)]
+pub struct OptionCounts {
+    current: Vec<AtomicUsize>,
+    minimum: Vec<AtomicUsize>,
+}
+
+impl OptionCounts {
+    /// Build a tracker for a grid with the given number of slots. Until the search starts, every
+    /// count is reported as 0 and every minimum as `usize::MAX`.
+    #[must_use]
+    pub fn new(slot_count: usize) -> OptionCounts {
+        OptionCounts {
+            current: (0..slot_count).map(|_| AtomicUsize::new(0)).collect(),
+            minimum: (0..slot_count)
+                .map(|_| AtomicUsize::new(usize::MAX))
+                .collect(),
+        }
+    }
+
+    /// Record the current state of the given slots, which must be the ones this tracker was built
+    /// for.
+    pub fn record(&self, slots: &[Slot]) {
+        for ((slot, current), minimum) in slots.iter().zip(&self.current).zip(&self.minimum) {
+            let count = slot.effective_option_count();
+            current.store(count, Ordering::Relaxed);
+            minimum.fetch_min(count, Ordering::Relaxed);
+        }
+    }
+
+    /// The number of options each slot had at the last step of the search, indexed by `SlotId`.
+    #[must_use]
+    pub fn current(&self) -> Vec<usize> {
+        self.current
+            .iter()
+            .map(|count| count.load(Ordering::Relaxed))
+            .collect()
+    }
+
+    /// The fewest options each slot has had at any step so far, indexed by `SlotId`.
+    #[must_use]
+    pub fn minimum(&self) -> Vec<usize> {
+        self.minimum
+            .iter()
+            .map(|count| count.load(Ordering::Relaxed))
+            .collect()
+    }
+
+    /// Forget the counts recorded so far, e.g. before filling the grid again.
+    pub fn reset(&self) {
+        for (current, minimum) in self.current.iter().zip(&self.minimum) {
+            current.store(0, Ordering::Relaxed);
+            minimum.store(usize::MAX, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backtracking_search::find_fill;
+    use crate::grid_config::generate_grid_config_from_template_string;
+    use crate::option_counts::OptionCounts;
+    use crate::word_list::tests::word_list_source_config;
+    use crate::word_list::WordList;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_option_counts() {
+        let word_list = WordList::new(word_list_source_config(), None, Some(5), None);
+        let mut grid_config = generate_grid_config_from_template_string(
+            word_list,
+            "
+            .....
+            .....
+            .....
+            .....
+            .....
+            ",
+            40,
+        );
+        let option_counts = Arc::new(OptionCounts::new(grid_config.slot_configs.len()));
+        grid_config.option_counts = Some(option_counts.clone());
+
+        let initial_counts: Vec<usize> = grid_config.slot_options.iter().map(Vec::len).collect();
+        let result = find_fill(&grid_config, None, None).expect("failed to fill grid");
+
+        // Every slot ends up with a single option, having started with all of them.
+        let minimum = option_counts.minimum();
+        assert_eq!(option_counts.current(), vec![1; initial_counts.len()]);
+        assert!(minimum.iter().all(|&count| count >= 1));
+        assert!(minimum
+            .iter()
+            .zip(&initial_counts)
+            .all(|(minimum, initial)| minimum <= initial));
+        assert_eq!(result.statistics.min_option_counts, minimum);
+
+        option_counts.reset();
+        assert_eq!(option_counts.current(), vec![0; initial_counts.len()]);
+        assert_eq!(
+            option_counts.minimum(),
+            vec![usize::MAX; initial_counts.len()]
+        );
+    }
+}
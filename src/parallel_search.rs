@@ -0,0 +1,418 @@
+//! This module implements a parallel fill mode that splits a single search across threads. After
+//! establishing initial arc consistency, we pick the first slot the way `dom/wdeg` would and deal
+//! its available words out to the worker threads round-robin, so that each thread searches a
+//! disjoint set of subtrees. Each thread cycles through its subtrees, giving each one a single
+//! restart-style attempt with a limited number of backtracks before moving on to the next, so that
+//! a hard (or unsatisfiable) subtree can't keep a thread from ever reaching the others. As soon as
+//! any thread finds a fill, it signals the others to stop and sends its result back over a channel.
+//!
+//! In wasm32 builds, this module is only available with the `wasm-threads` feature, since the
+//! workers need the Rayon thread pool that it sets up.
+
+use float_ord::FloatOrd;
+use instant::{Duration, Instant};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+#[cfg(not(all(target_arch = "wasm32", feature = "wasm-threads")))]
+use std::thread;
+
+use crate::arc_consistency::EliminationSetPool;
+use crate::backtracking_search::{
+    available_options, build_slots, calculate_slot_priority, calculate_slot_weights,
+    find_fill_for_seed, find_fill_with_restarts, maintain_arc_consistency, ArcConsistencyMode,
+    FillFailure, FillSuccess, ImpactTracker, PartialFill, RetryBudget, SearchConfig, Slot,
+};
+use crate::cancel::CancelToken;
+use crate::crossing_weights::CrossingWeights;
+use crate::grid_config::{Choice, GridConfig, SlotId};
+use crate::types::WordId;
+
+/// How long should we wait between checks of the caller's cancel token?
+#[cfg(not(all(target_arch = "wasm32", feature = "wasm-threads")))]
+const ABORT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A subtree that hasn't been exhausted yet, along with the restart state for its next attempt.
+struct Subtree {
+    word_id: WordId,
+    retry_budget: RetryBudget,
+    retry_num: u64,
+}
+
+/// Search the subtrees rooted at the given words for the given slot, taking turns between them
+/// until one of them yields a fill, all of them are proven unfillable, or we're told to stop.
+#[allow(clippy::too_many_arguments)]
+fn search_subtrees(
+    config: &GridConfig,
+    slots: &[Slot],
+    slot_id: SlotId,
+    word_ids: impl Iterator<Item = WordId>,
+    deadline: Option<Instant>,
+    search_config: &SearchConfig,
+    stop: &CancelToken,
+    pool: &mut EliminationSetPool,
+) -> Result<FillSuccess, FillFailure> {
+    let caller_cancel = config.cancel.clone();
+    let config = GridConfig {
+        cancel: Some(stop.clone()),
+        ..config.borrowed()
+    };
+    let elimination_sets = pool.acquire(&config.slot_configs, &config.word_list);
+    let mut crossing_weights =
+        CrossingWeights::with_decay(config.crossing_count, search_config.weight_decay);
+
+    let mut impacts = ImpactTracker::new(config.slot_configs.len());
+    let mut partial = PartialFill::default();
+    let mut subtrees: VecDeque<Subtree> = word_ids
+        .map(|word_id| Subtree {
+            word_id,
+            retry_budget: search_config.retry_budget,
+            retry_num: 0,
+        })
+        .collect();
+
+    while let Some(mut subtree) = subtrees.pop_front() {
+        if stop.is_cancelled()
+            || caller_cancel
+                .as_ref()
+                .map_or(false, CancelToken::is_cancelled)
+        {
+            partial.crossing_weights = crossing_weights;
+            return Err(FillFailure::Abort(Box::new(partial)));
+        }
+
+        // We rebuild each subtree's starting state on every visit instead of keeping it around,
+        // since holding a full set of slots for each of a thread's subtrees could take a lot of
+        // memory.
+        let mut subtree_slots = slots.to_vec();
+        let slot_weights = calculate_slot_weights(&config, &subtree_slots, &crossing_weights);
+        let mut choice_arc_consistency_time = Duration::default();
+        if !maintain_arc_consistency(
+            &config,
+            &mut subtree_slots,
+            &mut crossing_weights,
+            &slot_weights,
+            &ArcConsistencyMode::Choice(Choice {
+                slot_id,
+                word_id: subtree.word_id,
+            }),
+            &mut choice_arc_consistency_time,
+            elimination_sets,
+        ) {
+            continue;
+        }
+
+        match find_fill_for_seed(
+            &config,
+            &subtree_slots,
+            deadline,
+            subtree.retry_budget,
+            subtree.retry_num,
+            &mut crossing_weights,
+            elimination_sets,
+            search_config,
+            &mut impacts,
+            &mut partial,
+        ) {
+            Ok(mut result) => {
+                result.statistics.retries = partial.statistics.retries;
+                result.statistics.try_time = result.statistics.total_time;
+                return Ok(result);
+            }
+
+            // This subtree is empty, so we can drop it, keeping the progress info that the
+            // search moved out of `partial` when it gave up.
+            Err(FillFailure::HardFailure(subtree_partial)) => partial = *subtree_partial,
+
+            // Otherwise, we'll come back to this subtree after giving the others a turn, with a
+            // new seed and a slightly larger retry budget.
+            Err(FillFailure::ExceededBacktrackLimit(_backtrack_count, _)) => {
+                partial.statistics.retries += 1;
+                subtree.retry_num += 1;
+                subtree.retry_budget = subtree.retry_budget.grown();
+                subtrees.push_back(subtree);
+            }
+
+            other_error => return other_error,
+        }
+    }
+
+    partial.crossing_weights = crossing_weights;
+    Err(FillFailure::HardFailure(Box::new(partial)))
+}
+
+/// Fold one worker's proof that its subtrees are empty into the combined one, adding up the weight
+/// each crossing has gained beyond its starting value of 1.
+fn merge_hard_failures(combined: &mut PartialFill, other: &PartialFill) {
+    combined.statistics.accumulate(&other.statistics);
+    if combined.crossing_weights.is_empty() {
+        combined
+            .crossing_weights
+            .clone_from(&other.crossing_weights);
+    } else {
+        combined
+            .crossing_weights
+            .add_excess(&other.crossing_weights);
+    }
+    if other.deepest_choices.len() > combined.deepest_choices.len() {
+        combined.deepest_choices.clone_from(&other.deepest_choices);
+    }
+}
+
+/// Combine the results sent back by the worker threads: any success wins, and otherwise a timeout
+/// takes precedence over the aborts it causes in the other threads. If every thread exhausted its
+/// subtrees, the grid is unfillable.
+fn pick_result(
+    receiver: mpsc::Receiver<Result<FillSuccess, FillFailure>>,
+) -> Result<FillSuccess, FillFailure> {
+    let mut hard_failure = PartialFill::default();
+    let mut failure: Option<FillFailure> = None;
+    for result in receiver {
+        match result {
+            Ok(result) => return Ok(result),
+            Err(FillFailure::HardFailure(partial)) => {
+                merge_hard_failures(&mut hard_failure, &partial);
+            }
+            Err(FillFailure::Timeout(partial)) => failure = Some(FillFailure::Timeout(partial)),
+            Err(other_failure) => {
+                if !matches!(failure, Some(FillFailure::Timeout(_))) {
+                    failure = Some(other_failure);
+                }
+            }
+        }
+    }
+
+    Err(failure.unwrap_or_else(|| FillFailure::HardFailure(Box::new(hard_failure))))
+}
+
+/// Search for a valid fill for the given grid using up to `thread_count` threads, if one can be
+/// found within the given amount of time. Apart from `statistics.total_time` and
+/// `statistics.initial_arc_consistency_time`, the returned statistics only describe the work done
+/// by the thread that found the fill.
+pub fn find_fill_parallel(
+    config: &GridConfig,
+    timeout: Option<Duration>,
+    thread_count: usize,
+    search_config: &SearchConfig,
+) -> Result<FillSuccess, FillFailure> {
+    find_fill_parallel_with_pools(config, timeout, thread_count, search_config, &mut vec![])
+}
+
+/// Like `find_fill_parallel`, but reusing elimination set allocations from `pools` (one for the
+/// initial setup plus one per thread) and leaving them there for the next call. This is useful for
+/// long-lived callers that fill many grids.
+#[allow(clippy::too_many_lines)]
+pub fn find_fill_parallel_with_pools(
+    config: &GridConfig,
+    timeout: Option<Duration>,
+    thread_count: usize,
+    search_config: &SearchConfig,
+    pools: &mut Vec<EliminationSetPool>,
+) -> Result<FillSuccess, FillFailure> {
+    let start = Instant::now();
+    let deadline = timeout.map(|timeout| start + timeout);
+
+    if pools.is_empty() {
+        pools.push(EliminationSetPool::new());
+    }
+    let elimination_sets = pools[0].acquire(&config.slot_configs, &config.word_list);
+    let mut slots = build_slots(config);
+    let mut crossing_weights =
+        CrossingWeights::with_decay(config.crossing_count, search_config.weight_decay);
+
+    let slot_weights = calculate_slot_weights(config, &slots, &crossing_weights);
+    let mut initial_arc_consistency_time = Duration::default();
+    if !maintain_arc_consistency(
+        config,
+        &mut slots,
+        &mut crossing_weights,
+        &slot_weights,
+        &ArcConsistencyMode::Initial,
+        &mut initial_arc_consistency_time,
+        elimination_sets,
+    ) {
+        return Err(FillFailure::HardFailure(Box::new(PartialFill {
+            crossing_weights,
+            ..PartialFill::default()
+        })));
+    }
+
+    // Choose the slot to split on. If every slot is already determined, there's nothing to split,
+    // so we can let the ordinary search assemble the result.
+    let slot_weights = calculate_slot_weights(config, &slots, &crossing_weights);
+    let Some(slot_id) = slots
+        .iter()
+        .filter(|slot| slot.fixed_word_id.is_none() && slot.remaining_option_count > 1)
+        .min_by_key(|slot| FloatOrd(calculate_slot_priority(&slots, &slot_weights, slot.id)))
+        .map(|slot| slot.id)
+    else {
+        let mut result = find_fill_with_restarts(
+            config,
+            &slots,
+            deadline,
+            &mut crossing_weights,
+            elimination_sets,
+            search_config,
+        )?;
+        result.statistics.total_time = start.elapsed();
+        result.statistics.initial_arc_consistency_time = initial_arc_consistency_time;
+        result.record_inputs(config, Some(search_config));
+        return Ok(result);
+    };
+
+    let word_ids = available_options(config, &slots[slot_id.index()]);
+    let thread_count = thread_count.clamp(1, word_ids.len());
+    if pools.len() <= thread_count {
+        pools.resize_with(thread_count + 1, EliminationSetPool::new);
+    }
+
+    let stop = CancelToken::new();
+    let finished_count = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+
+    let run_worker = |thread_idx: usize,
+                      pool: &mut EliminationSetPool,
+                      sender: mpsc::Sender<Result<FillSuccess, FillFailure>>| {
+        let result = search_subtrees(
+            config,
+            &slots,
+            slot_id,
+            word_ids
+                .iter()
+                .copied()
+                .skip(thread_idx)
+                .step_by(thread_count),
+            deadline,
+            search_config,
+            &stop,
+            pool,
+        );
+
+        // A success or timeout ends the whole search; running out of subtrees just ends this
+        // thread's part of it.
+        if !matches!(
+            result,
+            Err(FillFailure::HardFailure(_) | FillFailure::Abort(_))
+        ) {
+            stop.cancel("another worker finished the search");
+        }
+
+        finished_count.fetch_add(1, Ordering::Relaxed);
+        sender.send(result).ok();
+    };
+
+    #[cfg(not(all(target_arch = "wasm32", feature = "wasm-threads")))]
+    thread::scope(|scope| {
+        for (thread_idx, pool) in pools[1..=thread_count].iter_mut().enumerate() {
+            let sender = sender.clone();
+            let run_worker = &run_worker;
+            scope.spawn(move || run_worker(thread_idx, pool, sender));
+        }
+
+        // Relay the caller's cancel token, if any, to the workers.
+        if let Some(cancel) = &config.cancel {
+            while finished_count.load(Ordering::Relaxed) < thread_count {
+                if let Some(reason) = cancel.reason() {
+                    stop.cancel(reason.to_string());
+                    break;
+                }
+                thread::sleep(ABORT_POLL_INTERVAL);
+            }
+        }
+    });
+
+    // In the browser, the workers come from the Rayon thread pool set up by `init_parallel_fill`
+    // in `wasm.rs`. The calling thread is blocked until they finish, so it can't relay the
+    // caller's cancel token, but each worker also checks it between subtree attempts.
+    #[cfg(all(target_arch = "wasm32", feature = "wasm-threads"))]
+    rayon::scope(|scope| {
+        for (thread_idx, pool) in pools[1..=thread_count].iter_mut().enumerate() {
+            let sender = sender.clone();
+            let run_worker = &run_worker;
+            scope.spawn(move |_| run_worker(thread_idx, pool, sender));
+        }
+    });
+
+    drop(sender);
+
+    let mut result = pick_result(receiver);
+    match &mut result {
+        Ok(result) => {
+            result.statistics.total_time = start.elapsed();
+            result.statistics.initial_arc_consistency_time = initial_arc_consistency_time;
+            result.record_inputs(config, Some(search_config));
+        }
+        Err(failure) => {
+            let partial = failure.partial_mut();
+            partial.statistics.total_time = start.elapsed();
+            partial.statistics.initial_arc_consistency_time = initial_arc_consistency_time;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backtracking_search::SearchConfig;
+    use crate::grid_config::{generate_grid_config_from_template_string, render_grid};
+    use crate::parallel_search::{find_fill_parallel, find_fill_parallel_with_pools};
+    use crate::word_list::tests::word_list_source_config;
+    use crate::word_list::WordList;
+
+    #[test]
+    fn test_find_fill_parallel() {
+        let word_list = WordList::new(word_list_source_config(), None, Some(6), Some(5));
+        let grid_config = generate_grid_config_from_template_string(
+            word_list,
+            "
+            ......
+            ......
+            ......
+            ......
+            ......
+            ......
+            ",
+            40,
+        );
+
+        let result = find_fill_parallel(&grid_config, None, 4, &SearchConfig::default())
+            .expect("Failed to find a fill");
+
+        assert_eq!(result.choices.len(), 12);
+        println!("{:?}", result.statistics);
+        println!("{}", render_grid(&grid_config, &result.choices));
+    }
+
+    #[test]
+    fn test_find_fill_parallel_with_one_thread() {
+        let word_list = WordList::new(word_list_source_config(), None, Some(5), Some(5));
+        let grid_config = generate_grid_config_from_template_string(
+            word_list,
+            "
+            .....
+            .....
+            .....
+            .....
+            .....
+            ",
+            40,
+        );
+
+        // With a single thread, every subtree of the split slot has to be searched by the same
+        // worker, taking turns between them. We also reuse the same pools for a second fill.
+        let mut pools = vec![];
+        for _ in 0..2 {
+            let result = find_fill_parallel_with_pools(
+                &grid_config,
+                None,
+                1,
+                &SearchConfig::default(),
+                &mut pools,
+            )
+            .expect("Failed to find a fill");
+
+            assert_eq!(result.choices.len(), 10);
+            assert_eq!(pools.len(), 2);
+        }
+    }
+}
@@ -0,0 +1,252 @@
+//! This module implements phonetic encodings used to catch homophone-style repeats, like WEIGH and
+//! WAY or KNIGHT and NIGHT, which share no substrings but would still read as dupes to most
+//! solvers. When a `WordList` is configured with a `PhoneticEncoding`, any two words with the same
+//! (non-empty) code are treated as dupes of each other. Both encodings are deliberately coarse and
+//! only look at the letters A-Z, so short words in particular will collide fairly often.
+
+/// A way of reducing a word to a code representing roughly how it sounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PhoneticEncoding {
+    /// American Soundex, which keeps the first letter and encodes the next three consonant sounds.
+    /// This is strict about initial letters, so it misses pairs like KNIGHT and NIGHT.
+    Soundex,
+
+    /// The original Metaphone algorithm, which accounts for silent letters and common digraphs.
+    Metaphone,
+}
+
+impl PhoneticEncoding {
+    /// Encode the given word, returning an empty string if it has no letters A-Z.
+    #[must_use]
+    pub fn encode(self, word: &str) -> String {
+        match self {
+            PhoneticEncoding::Soundex => soundex(word),
+            PhoneticEncoding::Metaphone => metaphone(word),
+        }
+    }
+}
+
+/// The uppercase ASCII letters in the given word, in order.
+fn ascii_letters(word: &str) -> Vec<u8> {
+    word.bytes()
+        .filter(u8::is_ascii_alphabetic)
+        .map(|byte| byte.to_ascii_uppercase())
+        .collect()
+}
+
+fn is_vowel(letter: u8) -> bool {
+    matches!(letter, b'A' | b'E' | b'I' | b'O' | b'U')
+}
+
+/// Encode the given word using American Soundex (e.g., ROBERT and RUPERT are both R163).
+#[must_use]
+pub fn soundex(word: &str) -> String {
+    let letters = ascii_letters(word);
+    let Some(&first) = letters.first() else {
+        return String::new();
+    };
+
+    let digit = |letter: u8| match letter {
+        b'B' | b'F' | b'P' | b'V' => Some(b'1'),
+        b'C' | b'G' | b'J' | b'K' | b'Q' | b'S' | b'X' | b'Z' => Some(b'2'),
+        b'D' | b'T' => Some(b'3'),
+        b'L' => Some(b'4'),
+        b'M' | b'N' => Some(b'5'),
+        b'R' => Some(b'6'),
+        _ => None,
+    };
+
+    let mut code = vec![first];
+    let mut last_digit = digit(first);
+    for &letter in &letters[1..] {
+        if code.len() == 4 {
+            break;
+        }
+        let letter_digit = digit(letter);
+        if letter_digit.is_some() && letter_digit != last_digit {
+            code.extend(letter_digit);
+        }
+        // H and W don't separate consonants with the same code, but vowels do.
+        if letter != b'H' && letter != b'W' {
+            last_digit = letter_digit;
+        }
+    }
+    code.resize(4, b'0');
+
+    String::from_utf8(code).unwrap()
+}
+
+/// Encode the given word using Metaphone (e.g., WEIGH and WAY are both W, and THUMB is 0M, with 0
+/// standing for TH).
+#[must_use]
+#[allow(clippy::too_many_lines)]
+pub fn metaphone(word: &str) -> String {
+    let mut letters = ascii_letters(word);
+
+    // Some initial letter pairs are pronounced as a single sound.
+    match letters.as_slice() {
+        [b'A', b'E', ..] | [b'G' | b'K' | b'P', b'N', ..] | [b'W', b'R', ..] => {
+            letters.remove(0);
+        }
+        [b'W', b'H', ..] => {
+            letters.remove(1);
+        }
+        [b'X', ..] => letters[0] = b'S',
+        _ => {}
+    }
+
+    let at = |idx: usize| letters.get(idx).copied();
+    let followed_by =
+        |idx: usize, options: &[u8]| at(idx + 1).map_or(false, |c| options.contains(&c));
+    let mut code = String::new();
+
+    for (idx, &letter) in letters.iter().enumerate() {
+        // Doubled letters are only pronounced once, except for C (as in ACCENT).
+        if idx > 0 && letters[idx - 1] == letter && letter != b'C' {
+            continue;
+        }
+        let previous = idx.checked_sub(1).and_then(at);
+        let is_last = idx + 1 == letters.len();
+
+        match letter {
+            b'A' | b'E' | b'I' | b'O' | b'U' => {
+                if idx == 0 {
+                    code.push(letter as char);
+                }
+            }
+            b'B' => {
+                if !(is_last && previous == Some(b'M')) {
+                    code.push('B');
+                }
+            }
+            b'C' => {
+                if followed_by(idx, b"H") || (followed_by(idx, b"I") && at(idx + 2) == Some(b'A')) {
+                    code.push_str(if previous == Some(b'S') { "K" } else { "X" });
+                } else if followed_by(idx, b"IEY") {
+                    if previous != Some(b'S') {
+                        code.push('S');
+                    }
+                } else {
+                    code.push('K');
+                }
+            }
+            b'D' => {
+                if followed_by(idx, b"G") && at(idx + 2).map_or(false, |c| b"EIY".contains(&c)) {
+                    code.push('J');
+                } else {
+                    code.push('T');
+                }
+            }
+            b'G' => {
+                let silent_before_h =
+                    followed_by(idx, b"H") && !at(idx + 2).map_or(false, is_vowel);
+                let silent_before_n = followed_by(idx, b"N")
+                    && (idx + 2 == letters.len() || &letters[idx + 2..] == b"ED");
+                if silent_before_h || silent_before_n {
+                    continue;
+                }
+                if followed_by(idx, b"EIY") && previous != Some(b'G') {
+                    code.push('J');
+                } else {
+                    code.push('K');
+                }
+            }
+            b'H' => {
+                let after_digraph = previous.map_or(false, |c| b"CGPST".contains(&c));
+                let after_vowel_only =
+                    previous.map_or(false, is_vowel) && !at(idx + 1).map_or(false, is_vowel);
+                if !after_digraph && !after_vowel_only {
+                    code.push('H');
+                }
+            }
+            b'K' => {
+                if previous != Some(b'C') {
+                    code.push('K');
+                }
+            }
+            b'P' => code.push(if followed_by(idx, b"H") { 'F' } else { 'P' }),
+            b'Q' => code.push('K'),
+            b'S' => {
+                if followed_by(idx, b"H")
+                    || (followed_by(idx, b"I") && at(idx + 2).map_or(false, |c| b"AO".contains(&c)))
+                {
+                    code.push('X');
+                } else {
+                    code.push('S');
+                }
+            }
+            b'T' => {
+                if followed_by(idx, b"I") && at(idx + 2).map_or(false, |c| b"AO".contains(&c)) {
+                    code.push('X');
+                } else if followed_by(idx, b"H") {
+                    code.push('0');
+                } else if !(followed_by(idx, b"C") && at(idx + 2) == Some(b'H')) {
+                    code.push('T');
+                }
+            }
+            b'V' => code.push('F'),
+            b'W' | b'Y' => {
+                if at(idx + 1).map_or(false, is_vowel) {
+                    code.push(letter as char);
+                }
+            }
+            b'X' => code.push_str("KS"),
+            b'Z' => code.push('S'),
+            _ => code.push(letter as char),
+        }
+    }
+
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::phonetic::{metaphone, soundex, PhoneticEncoding};
+    use crate::word_list::tests::memory_word_list;
+    use crate::word_list::WordList;
+
+    #[test]
+    fn test_phonetic_dupes() {
+        assert_eq!(soundex("Robert"), "R163");
+        assert_eq!(soundex("Rupert"), "R163");
+        assert_eq!(soundex("Ashcraft"), "A261");
+        assert_eq!(soundex("Tymczak"), "T522");
+        assert_eq!(metaphone("weigh"), "W");
+        assert_eq!(metaphone("way"), "W");
+        assert_eq!(metaphone("knight"), "NT");
+        assert_eq!(metaphone("night"), "NT");
+        assert_eq!(metaphone("thumb"), "0M");
+        assert_eq!(metaphone("phone"), "FN");
+        assert_eq!(metaphone(""), "");
+
+        let mut word_list = memory_word_list(&["weigh", "way", "knight", "night", "nugget"]);
+        let id = |word_list: &WordList, word: &str| (word.len(), word_list.word_id_by_string[word]);
+        let is_dupe = |word_list: &WordList, word_1: &str, word_2: &str| {
+            let (length, word_id) = id(word_list, word_2);
+            word_list
+                .dupe_index
+                .get_dupes_by_length(id(word_list, word_1))
+                .get(&length)
+                .map_or(false, |dupes| dupes.contains(&word_id))
+        };
+
+        assert!(!is_dupe(&word_list, "weigh", "way"));
+
+        word_list.set_phonetic_encoding(Some(PhoneticEncoding::Metaphone));
+        assert!(is_dupe(&word_list, "weigh", "way"));
+        assert!(is_dupe(&word_list, "night", "knight"));
+        assert!(!is_dupe(&word_list, "night", "nugget"));
+
+        word_list.set_phonetic_encoding(Some(PhoneticEncoding::Soundex));
+        assert!(!is_dupe(&word_list, "night", "knight"));
+        assert!(is_dupe(&word_list, "night", "nugget"));
+
+        word_list.set_phonetic_encoding(None);
+        assert!(!is_dupe(&word_list, "night", "nugget"));
+    }
+}
@@ -0,0 +1,337 @@
+//! This module provides `Project`, a complete construction project in a single value: the word
+//! lists to fill from, the grid, the constraints on its fill, the fills attempted so far, and some
+//! descriptive metadata. It's meant for scripts and command-line tools that want to manage a
+//! puzzle from start to finish through this crate alone, without keeping their own record of
+//! which lists and settings a grid goes with. A project is saved as a single JSON file with
+//! `save` and read back with `load`.
+//!
+//! The constraints and options are the same ones accepted by a `FillRequest` (see
+//! `fill_schema.rs`), so `Project::fill` behaves exactly like sending the project's grid to the
+//! CLI with `--json`. As with fill requests, project files carry a version number, and files
+//! written for any other version are rejected rather than guessed at.
+
+use serde_derive::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::error::{FillError, GridError};
+use crate::fill_schema::{
+    FillConstraints, FillOptions, FillRequest, FillResponse, FillStatus, FILL_SCHEMA_VERSION,
+};
+use crate::grid_config::{
+    try_generate_grid_config_from_hinted_template_string,
+    try_generate_grid_config_from_template_string, OwnedGridConfig,
+};
+use crate::word_list::{BundledList, WordList, WordListSourceConfig};
+
+/// The version of the project file format implemented by this module.
+pub const PROJECT_VERSION: u32 = 1;
+
+fn current_version() -> u32 {
+    PROJECT_VERSION
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+/// A problem loading or saving a `Project`.
+#[derive(Debug, Error)]
+pub enum ProjectError {
+    #[error("Can’t read or write project file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid project file: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Unsupported project file version {0}")]
+    UnsupportedVersion(u32),
+}
+
+/// A word list source as stored in a project file. This mirrors `WordListSourceConfig`, except
+/// that there's no equivalent of the `Iter` source (which can't be saved), and a list's contents
+/// are either stored inline (`Memory`) or referred to by location.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProjectWordList {
+    Memory {
+        id: String,
+        #[serde(default = "enabled_by_default")]
+        enabled: bool,
+        words: Vec<(String, u16)>,
+    },
+    File {
+        id: String,
+        #[serde(default = "enabled_by_default")]
+        enabled: bool,
+        path: PathBuf,
+    },
+    Url {
+        id: String,
+        #[serde(default = "enabled_by_default")]
+        enabled: bool,
+        url: String,
+        #[serde(default)]
+        cache_dir: Option<PathBuf>,
+    },
+    Bundled {
+        id: String,
+        #[serde(default = "enabled_by_default")]
+        enabled: bool,
+        list: BundledList,
+    },
+}
+
+impl ProjectWordList {
+    /// The equivalent source config for building a `WordList`.
+    #[must_use]
+    pub fn source_config(&self) -> WordListSourceConfig {
+        match self.clone() {
+            ProjectWordList::Memory { id, enabled, words } => {
+                WordListSourceConfig::Memory { id, enabled, words }
+            }
+            ProjectWordList::File { id, enabled, path } => WordListSourceConfig::File {
+                id,
+                enabled,
+                path: path.into_os_string(),
+            },
+            ProjectWordList::Url {
+                id,
+                enabled,
+                url,
+                cache_dir,
+            } => WordListSourceConfig::Url {
+                id,
+                enabled,
+                url,
+                cache_dir,
+            },
+            ProjectWordList::Bundled { id, enabled, list } => {
+                WordListSourceConfig::Bundled { id, enabled, list }
+            }
+        }
+    }
+}
+
+/// Descriptive information about a project, which doesn't affect its fills.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectMetadata {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub notes: Option<String>,
+}
+
+/// A fill attempted for a project, along with the grid it was attempted on (since the project's
+/// grid may have been edited since).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProjectFill {
+    pub grid: String,
+    pub response: FillResponse,
+}
+
+/// A complete construction project; see the module docs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Project {
+    /// The file format version the project was written with; defaults to the current one.
+    #[serde(default = "current_version")]
+    pub version: u32,
+
+    #[serde(default)]
+    pub metadata: ProjectMetadata,
+
+    /// The sources making up the project's word list, in priority order.
+    #[serde(default)]
+    pub word_lists: Vec<ProjectWordList>,
+
+    /// The word list's dupe setting; see `WordList::max_shared_substring`.
+    #[serde(default)]
+    pub max_shared_substring: Option<usize>,
+
+    /// The grid, as a template string with . representing empty cells, # representing blocks, and
+    /// letters representing themselves; see `FillRequest::grid`.
+    pub grid: String,
+
+    #[serde(default)]
+    pub constraints: FillConstraints,
+
+    #[serde(default)]
+    pub options: FillOptions,
+
+    /// Every fill attempted with `fill`, oldest first.
+    #[serde(default)]
+    pub history: Vec<ProjectFill>,
+}
+
+impl Project {
+    /// Start a project for the given grid, with no word lists or history yet.
+    #[must_use]
+    pub fn new(grid: impl Into<String>) -> Project {
+        Project {
+            version: PROJECT_VERSION,
+            metadata: ProjectMetadata::default(),
+            word_lists: vec![],
+            max_shared_substring: None,
+            grid: grid.into(),
+            constraints: FillConstraints::default(),
+            options: FillOptions::default(),
+            history: vec![],
+        }
+    }
+
+    /// Parse a project from JSON, making sure it was written for a version we understand.
+    pub fn from_json(json: &str) -> Result<Project, ProjectError> {
+        let project: Project = serde_json::from_str(json)?;
+        if project.version != PROJECT_VERSION {
+            return Err(ProjectError::UnsupportedVersion(project.version));
+        }
+        Ok(project)
+    }
+
+    /// Serialize this project as (pretty-printed) JSON.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Projects are always serializable")
+    }
+
+    /// Read a project from the given file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Project, ProjectError> {
+        Project::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// Write this project to the given file, replacing it if it exists.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ProjectError> {
+        std::fs::write(path, self.to_json() + "\n")?;
+        Ok(())
+    }
+
+    /// Load the project's word lists.
+    #[must_use]
+    pub fn word_list(&self) -> WordList {
+        WordList::new(
+            self.word_lists
+                .iter()
+                .map(ProjectWordList::source_config)
+                .collect(),
+            None,
+            None,
+            self.max_shared_substring,
+        )
+    }
+
+    /// The request `fill` sends for the project's current grid and settings.
+    #[must_use]
+    pub fn fill_request(&self) -> FillRequest {
+        FillRequest {
+            version: FILL_SCHEMA_VERSION,
+            grid: self.grid.clone(),
+            constraints: self.constraints.clone(),
+            options: self.options.clone(),
+        }
+    }
+
+    /// Build a config for the project's grid using the given word list (e.g., the one returned by
+    /// `word_list`).
+    pub fn grid_config(&self, word_list: WordList) -> Result<OwnedGridConfig, GridError> {
+        let min_score = self.fill_request().min_score();
+        if self.options.soft_uppercase {
+            try_generate_grid_config_from_hinted_template_string(word_list, &self.grid, min_score)
+        } else {
+            try_generate_grid_config_from_template_string(word_list, &self.grid, min_score)
+        }
+    }
+
+    /// Try to fill the project's grid, recording the attempt in `history` whether or not it
+    /// succeeds, and return the response. Problems with the word lists or the grid itself are
+    /// recorded as `InvalidInput` responses.
+    pub fn fill(&mut self) -> &FillResponse {
+        let response = self.attempt_fill();
+        self.history.push(ProjectFill {
+            grid: self.grid.clone(),
+            response,
+        });
+        &self.history.last().unwrap().response
+    }
+
+    fn attempt_fill(&self) -> FillResponse {
+        let word_list = self.word_list();
+        let mut source_errors: Vec<_> = word_list.get_source_errors().into_iter().collect();
+        source_errors.sort_by(|(a, _), (b, _)| a.cmp(b));
+        if let Some(error) = source_errors
+            .into_iter()
+            .find_map(|(_, errors)| errors.into_iter().next())
+        {
+            return FillResponse::invalid(&error);
+        }
+        if word_list.word_id_by_string.is_empty() {
+            return FillResponse::invalid(&FillError::EmptyWordList);
+        }
+
+        match self.grid_config(word_list) {
+            Ok(grid_config) => self.fill_request().fill(&grid_config),
+            Err(error) => FillResponse::invalid(&error),
+        }
+    }
+
+    /// The most recent successful fill, if any.
+    #[must_use]
+    pub fn latest_fill(&self) -> Option<&ProjectFill> {
+        self.history
+            .iter()
+            .rev()
+            .find(|fill| fill.response.status == FillStatus::Filled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fill_schema::FillStatus;
+    use crate::project::{Project, ProjectError, ProjectWordList};
+    use crate::word_list::tests::memory_words;
+
+    #[test]
+    fn test_project() {
+        let mut project = Project::new("..\n..");
+        project.metadata.title = Some("Tiny".into());
+        project.word_lists.push(ProjectWordList::Memory {
+            id: "0".into(),
+            enabled: true,
+            words: memory_words(&["ab", "cd", "ac", "bd", "ef", "gh", "eg", "fh"]),
+        });
+        project.constraints.min_score = Some(40);
+        project.constraints.required_words = vec!["gh".into()];
+
+        assert_eq!(project.fill().grid.as_deref(), Some("ef\ngh"));
+
+        project.constraints.forbidden_words = vec!["ab".into(), "ef".into()];
+        assert_eq!(project.fill().status, FillStatus::Unfillable);
+        assert_eq!(project.history.len(), 2);
+        assert_eq!(
+            project
+                .latest_fill()
+                .and_then(|fill| fill.response.grid.as_deref()),
+            Some("ef\ngh")
+        );
+
+        project.grid = "..\n.".into();
+        assert_eq!(project.fill().status, FillStatus::InvalidInput);
+
+        // The whole project, history included, survives a round trip through a file.
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tiny.json");
+        project.save(&path).unwrap();
+        assert_eq!(Project::load(&path).unwrap(), project);
+
+        assert!(matches!(
+            Project::from_json(r#"{"version": 99, "grid": ".."}"#),
+            Err(ProjectError::UnsupportedVersion(99))
+        ));
+        assert!(matches!(
+            Project::load(dir.path().join("missing.json")),
+            Err(ProjectError::Io(_))
+        ));
+
+        let project = Project::from_json(r#"{"grid": ".."}"#).unwrap();
+        assert_eq!(project.clone().fill().status, FillStatus::InvalidInput);
+    }
+}
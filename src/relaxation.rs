@@ -0,0 +1,222 @@
+//! This module implements an opt-in fallback for grids that can't be filled at the requested
+//! `min_score`. Rather than reporting a hard failure, `find_fill_with_relaxation` lowers the
+//! threshold step by step, regenerating the slot options each time, until the grid fills or the
+//! threshold reaches a floor. A fill found this way is reported along with the threshold that
+//! worked and the entries scoring below the one that was requested, so that the constructor knows
+//! exactly which parts of the grid to look at again.
+//!
+//! Only hard failures trigger relaxation; a search that times out or is cancelled at one threshold
+//! says nothing about whether the grid is fillable there. The crossing weights learned at each
+//! threshold carry over to the next, since the grid's trouble spots tend to stay the same.
+
+use instant::{Duration, Instant};
+
+use crate::arc_consistency::EliminationSet;
+use crate::backtracking_search::{
+    find_fill_with_crossing_weights, FillFailure, FillSuccess, SearchConfig,
+};
+use crate::crossing_weights::CrossingWeights;
+use crate::grid_config::{GridConfig, SlotId};
+
+/// How far `find_fill_with_relaxation` may lower the minimum score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct MinScoreRelaxation {
+    /// How much to lower the threshold after each hard failure. A step of 0 is treated as 1.
+    pub step: u16,
+
+    /// The lowest threshold to try before giving up.
+    pub floor: u16,
+}
+
+impl Default for MinScoreRelaxation {
+    fn default() -> Self {
+        MinScoreRelaxation { step: 5, floor: 0 }
+    }
+}
+
+/// An entry in a relaxed fill whose score is below the requested minimum.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde_derive::Serialize))]
+pub struct RelaxedEntry {
+    pub slot_id: SlotId,
+    pub normalized: String,
+
+    /// The word as it appears in its word list; see `FilledEntry::display`.
+    pub display: String,
+
+    pub score: u16,
+}
+
+/// A fill found by `find_fill_with_relaxation`.
+#[derive(Debug)]
+pub struct RelaxedFill {
+    /// The fill itself. Its statistics only cover the search at the final threshold.
+    pub fill: FillSuccess,
+
+    /// The threshold at which the grid was filled, which is the requested one if no relaxation
+    /// was needed.
+    pub min_score: u16,
+
+    /// The entries scoring below the requested threshold, in slot order. Slots with their own
+    /// `SlotConfig::min_score_override` and slots that were already complete in the input grid are
+    /// never relaxed, so they're never listed.
+    pub relaxed_entries: Vec<RelaxedEntry>,
+}
+
+/// Search for a fill for the given grid, whose slot options must have been generated with the
+/// given `min_score`, lowering the threshold as described in the module docs whenever the grid
+/// proves unfillable. The timeout covers the whole process rather than each threshold. Returns the
+/// failure from the last threshold tried if none of them yields a fill.
+pub fn find_fill_with_relaxation(
+    config: &GridConfig,
+    min_score: u16,
+    relaxation: MinScoreRelaxation,
+    timeout: Option<Duration>,
+    search_config: &SearchConfig,
+) -> Result<RelaxedFill, FillFailure> {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let mut elimination_sets = EliminationSet::build_all(&config.slot_configs, &config.word_list);
+    let mut crossing_weights = CrossingWeights::new(config.crossing_count);
+
+    let mut current_min_score = min_score;
+    let mut relaxed_config: Option<GridConfig> = None;
+
+    loop {
+        let current_config = relaxed_config.as_ref().unwrap_or(config);
+        let remaining = deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+
+        match find_fill_with_crossing_weights(
+            current_config,
+            remaining,
+            Some(&mut elimination_sets),
+            search_config,
+            &mut crossing_weights,
+        ) {
+            Ok(fill) => {
+                let relaxed_entries = relaxed_entries(config, &fill, min_score);
+                return Ok(RelaxedFill {
+                    fill,
+                    min_score: current_min_score,
+                    relaxed_entries,
+                });
+            }
+            Err(FillFailure::HardFailure(_)) if current_min_score > relaxation.floor => {
+                current_min_score = current_min_score
+                    .saturating_sub(relaxation.step.max(1))
+                    .max(relaxation.floor);
+                let mut next_config = config.borrowed();
+                next_config.regenerate_slot_options(current_min_score);
+                relaxed_config = Some(next_config);
+            }
+            Err(failure) => return Err(failure),
+        }
+    }
+}
+
+/// List the entries in the given fill that score below the given threshold, skipping slots that
+/// relaxation doesn't apply to.
+fn relaxed_entries(config: &GridConfig, fill: &FillSuccess, min_score: u16) -> Vec<RelaxedEntry> {
+    let mut entries: Vec<RelaxedEntry> = fill
+        .choices
+        .iter()
+        .filter_map(|choice| {
+            let slot_config = &config.slot_configs[choice.slot_id.index()];
+            if slot_config.min_score_override.is_some()
+                || slot_config
+                    .complete_fill(&config.fill, config.width)
+                    .is_some()
+            {
+                return None;
+            }
+            let word = config
+                .word_list
+                .get_word((slot_config.length, choice.word_id));
+            (word.score < min_score).then(|| RelaxedEntry {
+                slot_id: choice.slot_id,
+                normalized: word.normalized_string.clone(),
+                display: word.canonical_string.clone(),
+                score: word.score,
+            })
+        })
+        .collect();
+
+    entries.sort_by_key(|entry| entry.slot_id);
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backtracking_search::{FillFailure, SearchConfig};
+    use crate::grid_config::generate_grid_config_from_template_string;
+    use crate::relaxation::{find_fill_with_relaxation, MinScoreRelaxation};
+    use crate::word_list::{WordList, WordListSourceConfig};
+
+    #[test]
+    fn test_find_fill_with_relaxation() {
+        let word_list = WordList::new(
+            vec![WordListSourceConfig::Memory {
+                id: "0".into(),
+                enabled: true,
+                words: [("ab", 60), ("cd", 60), ("ac", 60), ("bd", 30)]
+                    .into_iter()
+                    .map(|(word, score)| (word.into(), score))
+                    .collect(),
+            }],
+            None,
+            None,
+            None,
+        );
+        let grid_config = generate_grid_config_from_template_string(word_list, "..\n..", 50);
+
+        let result = find_fill_with_relaxation(
+            &grid_config,
+            50,
+            MinScoreRelaxation {
+                step: 10,
+                floor: 20,
+            },
+            None,
+            &SearchConfig::default(),
+        )
+        .expect("failed to fill grid");
+
+        // BD can go either across or down, but either way it needs a relaxed score.
+        assert_eq!(result.min_score, 30);
+        assert_eq!(result.relaxed_entries.len(), 1);
+        assert_eq!(result.relaxed_entries[0].normalized, "bd");
+        assert_eq!(result.relaxed_entries[0].score, 30);
+
+        // The floor is respected, with the last failure coming back once it's reached.
+        let result = find_fill_with_relaxation(
+            &grid_config,
+            50,
+            MinScoreRelaxation {
+                step: 10,
+                floor: 40,
+            },
+            None,
+            &SearchConfig::default(),
+        );
+        assert!(matches!(result, Err(FillFailure::HardFailure(_))));
+
+        // Words forbidden before relaxing stay forbidden, even though they'd meet the lower
+        // threshold. BD is needed for every fill, so there's none to find.
+        let mut config = grid_config.borrowed();
+        config.forbid_words(&["bd".into()]);
+        let result = find_fill_with_relaxation(
+            &config,
+            50,
+            MinScoreRelaxation {
+                step: 10,
+                floor: 20,
+            },
+            None,
+            &SearchConfig::default(),
+        );
+        assert!(matches!(result, Err(FillFailure::HardFailure(_))));
+    }
+}
@@ -0,0 +1,298 @@
+//! This module renders grids as plain text. `render_grid` (in `grid_config.rs`) produces the
+//! compact form used throughout the tests and the CLI, with . for both empty cells and blocks;
+//! `render_grid_with_options` can instead mark blocks with their own character, separate rows and
+//! columns, uppercase the letters, and list how many candidates each unfilled slot has left, and
+//! `render_grid_diff` puts two fills of the same grid side by side to show how they differ.
+//!
+//! For print workflows, `render_numbered_grid` draws the blank grid with each cell's clue number,
+//! using the same numbering as the export formats (see `crossing_report::cell_numbers`).
+
+use std::fmt::Write;
+
+use crate::crossing_report::{cell_numbers, slot_labels};
+use crate::grid_config::{filled_cells, Choice, GridConfig};
+
+/// How to render a grid with `render_grid_with_options`. The default options produce the same
+/// output as `render_grid`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderOptions {
+    /// The character for cells that aren't part of any slot.
+    pub block: char,
+
+    /// The character for cells in a slot that don't have a letter yet.
+    pub empty: char,
+
+    /// If present, a character to put between the cells of each row.
+    pub column_separator: Option<char>,
+
+    /// If present, a character to fill a line between each pair of rows with.
+    pub row_separator: Option<char>,
+
+    /// Show letters in uppercase, as in a printed puzzle.
+    pub uppercase: bool,
+
+    /// After the grid, list each slot with any empty cells along with its number of options, e.g.
+    /// "7D: 212 candidates". Counts come from `GridConfig::slot_options`, so they account for the
+    /// letters in the grid's own fill but not for the given choices.
+    pub candidate_counts: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            block: '.',
+            empty: '.',
+            column_separator: None,
+            row_separator: None,
+            uppercase: false,
+            candidate_counts: false,
+        }
+    }
+}
+
+/// Render each row of the given fill as a line, without candidate counts.
+fn render_rows(config: &GridConfig, choices: &[Choice], options: &RenderOptions) -> Vec<String> {
+    let cells = filled_cells(config, choices);
+    let mut in_slot = vec![false; config.width * config.height];
+    for slot_config in config.slot_configs.iter() {
+        for cell_idx in slot_config.cell_fill_indices(config.width) {
+            in_slot[cell_idx] = true;
+        }
+    }
+
+    let mut rows: Vec<String> = vec![];
+    for (row, in_slot_row) in cells.chunks(config.width).zip(in_slot.chunks(config.width)) {
+        let rendered_cells: Vec<String> = row
+            .iter()
+            .zip(in_slot_row)
+            .map(|(cell, &in_slot)| {
+                let c = match cell {
+                    Some(letter) if options.uppercase => {
+                        letter.to_uppercase().next().unwrap_or(*letter)
+                    }
+                    Some(letter) => *letter,
+                    None if in_slot => options.empty,
+                    None => options.block,
+                };
+                c.to_string()
+            })
+            .collect();
+        let separator = options
+            .column_separator
+            .map_or(String::new(), |separator| separator.to_string());
+        let line = rendered_cells.join(&separator);
+
+        if let (Some(row_separator), Some(last_line)) = (options.row_separator, rows.last()) {
+            let width = last_line.chars().count();
+            rows.push(row_separator.to_string().repeat(width));
+        }
+        rows.push(line);
+    }
+    rows
+}
+
+/// Turn the given grid config and fill choices into a rendered string, as configured by `options`.
+#[must_use]
+pub fn render_grid_with_options(
+    config: &GridConfig,
+    choices: &[Choice],
+    options: &RenderOptions,
+) -> String {
+    let mut lines = render_rows(config, choices, options);
+
+    if options.candidate_counts {
+        let cells = filled_cells(config, choices);
+        let labels = slot_labels(config);
+        let mut counts: Vec<String> = config
+            .slot_configs
+            .iter()
+            .enumerate()
+            .filter(|(_, slot_config)| {
+                slot_config
+                    .cell_fill_indices(config.width)
+                    .into_iter()
+                    .any(|cell_idx| cells[cell_idx].is_none())
+            })
+            .map(|(slot_idx, _)| {
+                format!(
+                    "{}: {} candidates",
+                    labels[slot_idx],
+                    config.slot_options[slot_idx].len()
+                )
+            })
+            .collect();
+        if !counts.is_empty() {
+            lines.push(String::new());
+            lines.append(&mut counts);
+        }
+    }
+
+    lines.join("\n")
+}
+
+/// Render two fills of the same grid side by side, `before` on the left and `after` on the right,
+/// with an asterisk at the end of every row where they differ. `options.candidate_counts` is
+/// ignored, since the two fills can leave different slots open.
+#[must_use]
+pub fn render_grid_diff(
+    config: &GridConfig,
+    before: &[Choice],
+    after: &[Choice],
+    options: &RenderOptions,
+) -> String {
+    let before_rows = render_rows(config, before, options);
+    let after_rows = render_rows(config, after, options);
+
+    before_rows
+        .iter()
+        .zip(&after_rows)
+        .map(|(before_row, after_row)| {
+            let marker = if before_row == after_row { "" } else { " *" };
+            format!("{before_row}   {after_row}{marker}")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Cells in `render_numbered_grid` are this many characters wide, which fits the numbers of any
+/// grid up to `MAX_SLOT_LENGTH` on a side.
+const NUMBERED_CELL_WIDTH: usize = 3;
+
+/// Draw the given grid as a blank box diagram, with each cell two lines tall: the first holds the
+/// cell's clue number, if it has one, and the second is left blank for writing in an answer. Cells
+/// that aren't part of any slot are shaded with #. Any letters in the grid are left out, so the
+/// result only depends on the grid's shape.
+#[must_use]
+pub fn render_numbered_grid(config: &GridConfig) -> String {
+    let numbers = cell_numbers(config);
+    let mut in_slot = vec![false; config.width * config.height];
+    for slot_config in config.slot_configs.iter() {
+        for cell_idx in slot_config.cell_fill_indices(config.width) {
+            in_slot[cell_idx] = true;
+        }
+    }
+
+    let border = format!(
+        "+{}",
+        format!("{}+", "-".repeat(NUMBERED_CELL_WIDTH)).repeat(config.width)
+    );
+    let mut lines = vec![border.clone()];
+    for (number_row, in_slot_row) in numbers
+        .chunks(config.width)
+        .zip(in_slot.chunks(config.width))
+    {
+        let mut number_line = String::from("|");
+        let mut blank_line = String::from("|");
+        for (number, &in_slot) in number_row.iter().zip(in_slot_row) {
+            if in_slot {
+                let number = number.map_or(String::new(), |number| number.to_string());
+                write!(number_line, "{number:<NUMBERED_CELL_WIDTH$}|").unwrap();
+                write!(blank_line, "{:NUMBERED_CELL_WIDTH$}|", "").unwrap();
+            } else {
+                let shading = format!("{}|", "#".repeat(NUMBERED_CELL_WIDTH));
+                number_line.push_str(&shading);
+                blank_line.push_str(&shading);
+            }
+        }
+        lines.push(number_line);
+        lines.push(blank_line);
+        lines.push(border.clone());
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid_config::{generate_grid_config_from_template_string, render_grid, Choice};
+    use crate::render::{
+        render_grid_diff, render_grid_with_options, render_numbered_grid, RenderOptions,
+    };
+    use crate::types::SlotId;
+    use crate::word_list::tests::memory_word_list;
+    use crate::word_list::{WordList, WordListSourceConfig};
+
+    #[test]
+    fn test_render_grid_with_options() {
+        let word_list = memory_word_list(&["abc", "abd", "ade", "bef"]);
+        let config = generate_grid_config_from_template_string(word_list, "...\n.#.\n...", 40);
+        let choice = |slot_id: usize, word: &str| Choice {
+            slot_id: SlotId(slot_id),
+            word_id: config.word_list.word_id_by_string[word],
+        };
+        let before = vec![choice(0, "abc")];
+        let after = vec![choice(0, "abd")];
+
+        let options = RenderOptions::default();
+        assert_eq!(
+            render_grid_with_options(&config, &before, &options),
+            render_grid(&config, &before)
+        );
+
+        let options = RenderOptions {
+            block: '#',
+            column_separator: Some(' '),
+            row_separator: Some('-'),
+            uppercase: true,
+            ..RenderOptions::default()
+        };
+        assert_eq!(
+            render_grid_with_options(&config, &before, &options),
+            "A B C\n-----\n. # .\n-----\n. . ."
+        );
+
+        let options = RenderOptions {
+            block: '#',
+            candidate_counts: true,
+            ..RenderOptions::default()
+        };
+        assert_eq!(
+            render_grid_with_options(&config, &before, &options),
+            "abc\n.#.\n...\n\n3A: 4 candidates\n1D: 4 candidates\n2D: 4 candidates"
+        );
+
+        assert_eq!(
+            render_grid_diff(
+                &config,
+                &before,
+                &after,
+                &RenderOptions {
+                    block: '#',
+                    ..RenderOptions::default()
+                }
+            ),
+            "abc   abd *\n.#.   .#.\n...   ..."
+        );
+    }
+
+    #[test]
+    fn test_render_numbered_grid() {
+        let word_list = WordList::new(
+            vec![WordListSourceConfig::Memory {
+                id: "0".into(),
+                enabled: true,
+                words: vec![("abc".into(), 50)],
+            }],
+            None,
+            None,
+            None,
+        );
+        let config = generate_grid_config_from_template_string(word_list, "ab.\n.#.\n...", 40);
+        assert_eq!(
+            render_numbered_grid(&config),
+            [
+                "+---+---+---+",
+                "|1  |   |2  |",
+                "|   |   |   |",
+                "+---+---+---+",
+                "|   |###|   |",
+                "|   |###|   |",
+                "+---+---+---+",
+                "|3  |   |   |",
+                "|   |   |   |",
+                "+---+---+---+",
+            ]
+            .join("\n")
+        );
+    }
+}
@@ -0,0 +1,207 @@
+//! This module implements a global constraint requiring a set of words to appear somewhere in the
+//! fill, in whichever slots the search puts them. Arc consistency only reasons about pairs of
+//! crossing slots, so the search checks this constraint separately after each choice: every
+//! required word that hasn't been placed yet needs its own undetermined slot that can still hold
+//! it, and any branch where that's no longer possible is treated as a failed choice.
+
+use std::hash::Hasher;
+
+use crate::backtracking_search::Slot;
+use crate::grid_config::{GridConfig, SlotId};
+use crate::types::{GlobalWordId, WordId};
+use crate::util::StableHasher;
+use crate::word_list::{normalize_word, WordList};
+
+/// A set of words that must appear in the fill, along with the slots each one could go in.
+#[derive(Debug, Clone, Default)]
+pub struct RequiredWords {
+    /// Each required word, with the ids of the slots whose options include it.
+    words: Vec<(GlobalWordId, Vec<SlotId>)>,
+
+    /// Normalized forms of any required words that aren't in the word list at all.
+    missing_words: Vec<String>,
+
+    /// Did any of the words fail to match an option for any slot? If so, the constraint can never
+    /// be satisfied.
+    impossible: bool,
+}
+
+impl RequiredWords {
+    /// Look up the given (canonical or normalized) words in the given grid. Duplicates are ignored.
+    #[must_use]
+    pub fn new(config: &GridConfig, words: &[String]) -> RequiredWords {
+        let mut required_words = RequiredWords::default();
+
+        for word in words {
+            let normalized = normalize_word(word);
+            let Some(&word_id) = config.word_list.word_id_by_string.get(&normalized) else {
+                required_words.impossible = true;
+                if !required_words.missing_words.contains(&normalized) {
+                    required_words.missing_words.push(normalized);
+                }
+                continue;
+            };
+            let global_word_id = (normalized.chars().count(), word_id);
+            if required_words
+                .words
+                .iter()
+                .any(|&(existing_id, _)| existing_id == global_word_id)
+            {
+                continue;
+            }
+
+            let slot_ids: Vec<SlotId> = config
+                .slot_configs
+                .iter()
+                .filter(|slot_config| {
+                    slot_config.length == global_word_id.0
+                        && config.slot_options[slot_config.id.index()].contains(&word_id)
+                })
+                .map(|slot_config| slot_config.id)
+                .collect();
+            if slot_ids.is_empty() {
+                required_words.impossible = true;
+            }
+            required_words.words.push((global_word_id, slot_ids));
+        }
+
+        required_words
+    }
+
+    /// Are there no required words at all?
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty() && !self.impossible
+    }
+
+    /// The normalized forms of the required words, including any that aren't in the word list, so
+    /// that the constraint can be rebuilt for another grid or word list.
+    #[must_use]
+    pub fn normalized_words(&self, word_list: &WordList) -> Vec<String> {
+        self.words
+            .iter()
+            .map(|&(global_word_id, _)| {
+                word_list.get_word(global_word_id).normalized_string.clone()
+            })
+            .chain(self.missing_words.iter().cloned())
+            .collect()
+    }
+
+    /// Add this constraint to a fingerprint; see `config_fingerprint`.
+    pub(crate) fn hash_into(&self, hasher: &mut StableHasher) {
+        hasher.write_u8(u8::from(self.impossible));
+        for ((length, word_id), _) in &self.words {
+            hasher.write_usize(*length);
+            hasher.write_usize(word_id.index());
+        }
+    }
+
+    /// Could every required word that hasn't been placed yet still be placed, each in a different
+    /// undetermined slot?
+    #[must_use]
+    pub fn is_satisfiable(&self, slots: &[Slot]) -> bool {
+        if self.impossible {
+            return false;
+        }
+
+        let is_placed_in = |word_id: WordId, slot: &Slot| {
+            slot.fixed_word_id.map_or_else(
+                || slot.remaining_option_count == 1 && slot.eliminations[word_id.index()].is_none(),
+                |fixed_word_id| fixed_word_id == word_id,
+            )
+        };
+        let is_open_for = |word_id: WordId, slot: &Slot| {
+            slot.fixed_word_id.is_none()
+                && slot.remaining_option_count > 1
+                && slot.eliminations[word_id.index()].is_none()
+        };
+
+        // Find the slots still available to each unplaced word.
+        let unplaced: Vec<Vec<SlotId>> = self
+            .words
+            .iter()
+            .filter(|((_, word_id), slot_ids)| {
+                !slot_ids
+                    .iter()
+                    .any(|slot_id| is_placed_in(*word_id, &slots[slot_id.index()]))
+            })
+            .map(|((_, word_id), slot_ids)| {
+                slot_ids
+                    .iter()
+                    .copied()
+                    .filter(|slot_id| is_open_for(*word_id, &slots[slot_id.index()]))
+                    .collect()
+            })
+            .collect();
+
+        // Then check whether they can all be assigned distinct slots, by finding a maximum
+        // bipartite matching with augmenting paths.
+        let mut word_idx_by_slot: Vec<Option<usize>> = vec![None; slots.len()];
+        (0..unplaced.len()).all(|word_idx| {
+            let mut visited = vec![false; slots.len()];
+            augment(&unplaced, word_idx, &mut word_idx_by_slot, &mut visited)
+        })
+    }
+}
+
+/// Try to find a slot for the given word, reassigning previously-matched words if necessary.
+fn augment(
+    unplaced: &[Vec<SlotId>],
+    word_idx: usize,
+    word_idx_by_slot: &mut [Option<usize>],
+    visited: &mut [bool],
+) -> bool {
+    for &slot_id in &unplaced[word_idx] {
+        if visited[slot_id.index()] {
+            continue;
+        }
+        visited[slot_id.index()] = true;
+
+        let can_take_slot = match word_idx_by_slot[slot_id.index()] {
+            None => true,
+            Some(other_word_idx) => augment(unplaced, other_word_idx, word_idx_by_slot, visited),
+        };
+        if can_take_slot {
+            word_idx_by_slot[slot_id.index()] = Some(word_idx);
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backtracking_search::{find_fill_with_search_config, SearchConfig, SearchStrategy};
+    use crate::grid_config::{generate_grid_config_from_template_string, render_grid};
+    use crate::required_words::RequiredWords;
+    use crate::word_list::tests::memory_word_list;
+
+    #[test]
+    fn test_required_words() {
+        let word_list = memory_word_list(&["ab", "cd", "ac", "bd", "ef", "gh", "eg", "fh"]);
+        let grid_config = generate_grid_config_from_template_string(word_list, "..\n..", 40);
+        let config = grid_config.borrowed();
+
+        for strategy in [SearchStrategy::Restarts, SearchStrategy::LimitedDiscrepancy] {
+            let search_config = SearchConfig {
+                required_words: Some(RequiredWords::new(&config, &["FH".into()])),
+                strategy,
+                ..SearchConfig::default()
+            };
+            let result = find_fill_with_search_config(&config, None, None, &search_config)
+                .expect("Failed to find a fill");
+            let rendered = render_grid(&config, &result.choices);
+            assert!(rendered == "ef\ngh" || rendered == "eg\nfh", "{rendered}");
+
+            // Words from different fills can't both be placed, and neither can unknown words.
+            for required_words in [vec!["ab".into(), "fh".into()], vec!["xy".into()]] {
+                let search_config = SearchConfig {
+                    required_words: Some(RequiredWords::new(&config, &required_words)),
+                    strategy,
+                    ..SearchConfig::default()
+                };
+                assert!(find_fill_with_search_config(&config, None, None, &search_config).is_err());
+            }
+        }
+    }
+}
@@ -0,0 +1,420 @@
+//! This module checks how faithfully the crate's formats preserve existing files.
+//! `verify_roundtrip` parses a file, serializes it again with the crate's own writer, parses the
+//! result, and lists every semantic difference between the two (i.e., differences in content
+//! rather than in formatting), so that callers integrating these formats can see what would be
+//! lost before trusting them with their own files.
+//!
+//! Two formats are supported: `.xd` puzzles, which are written with `clue_export::export_xd`, and
+//! (with the `serde` feature) project files from `project.rs`. `.xd` files are parsed here with
+//! `XdPuzzle::parse`, which understands the metadata, grid, and clue sections; any notes section is
+//! kept as-is but can't be written back out.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use thiserror::Error;
+
+use crate::clue_export::{export_xd, ClueBank};
+use crate::error::GridError;
+use crate::grid_config::{try_generate_grid_config_from_letter_grid, Direction};
+use crate::word_list::WordList;
+
+/// A file format supported by `verify_roundtrip`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PuzzleFormat {
+    Xd,
+    #[cfg(feature = "serde")]
+    Project,
+}
+
+/// A problem parsing or re-serializing a file in `verify_roundtrip`.
+#[derive(Debug, Error)]
+pub enum RoundtripError {
+    #[error("File isn’t valid UTF-8")]
+    InvalidUtf8(#[from] std::str::Utf8Error),
+
+    #[error("Invalid .xd file: {0}")]
+    InvalidXd(String),
+
+    #[error("Can’t build a grid from the file: {0}")]
+    Grid(#[from] GridError),
+
+    #[cfg(feature = "serde")]
+    #[error(transparent)]
+    Project(#[from] crate::project::ProjectError),
+}
+
+/// A single clue from an `.xd` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XdClue {
+    pub direction: Direction,
+    pub number: usize,
+    pub clue: String,
+
+    /// The answer given after the clue's ~, if any.
+    pub answer: String,
+}
+
+/// The contents of an `.xd` file; see `XdPuzzle::parse`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct XdPuzzle {
+    /// The `Key: Value` lines of the metadata section, in order.
+    pub metadata: Vec<(String, String)>,
+
+    /// The rows of the grid, with # for blocks and . for empty cells.
+    pub grid: Vec<String>,
+
+    pub clues: Vec<XdClue>,
+
+    /// Everything after the clues, verbatim.
+    pub notes: String,
+}
+
+impl XdPuzzle {
+    /// Parse an `.xd` file. Its sections are separated by two or more blank lines: an optional
+    /// metadata section, the grid, the clues (with across and down usually separated by a single
+    /// blank line), and optional notes.
+    pub fn parse(contents: &str) -> Result<XdPuzzle, RoundtripError> {
+        let mut sections: Vec<Vec<&str>> = vec![];
+        let mut blank_lines = 0;
+        for line in contents.lines().map(str::trim_end) {
+            if line.is_empty() {
+                blank_lines += 1;
+                continue;
+            }
+            match sections.last_mut() {
+                Some(section) if blank_lines < 2 => {
+                    if blank_lines == 1 {
+                        section.push("");
+                    }
+                    section.push(line);
+                }
+                _ => sections.push(vec![line]),
+            }
+            blank_lines = 0;
+        }
+
+        let mut sections = sections.into_iter().peekable();
+        let mut puzzle = XdPuzzle::default();
+
+        // Grid rows never contain a colon, so a first section made of `Key: Value` lines must be
+        // metadata.
+        if let Some(section) = sections.next_if(|section| {
+            section
+                .iter()
+                .all(|line| line.is_empty() || line.contains(':'))
+        }) {
+            puzzle.metadata = section
+                .into_iter()
+                .filter_map(|line| line.split_once(':'))
+                .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+                .collect();
+        }
+
+        puzzle.grid = sections
+            .next()
+            .ok_or_else(|| RoundtripError::InvalidXd("missing grid".into()))?
+            .into_iter()
+            .filter(|line| !line.is_empty())
+            .map(|line| line.trim().to_string())
+            .collect();
+
+        for line in sections.next().unwrap_or_default() {
+            if line.is_empty() {
+                continue;
+            }
+            puzzle.clues.push(parse_xd_clue(line).ok_or_else(|| {
+                RoundtripError::InvalidXd(format!("can’t parse clue line “{line}”"))
+            })?);
+        }
+
+        puzzle.notes = sections
+            .map(|section| section.join("\n"))
+            .collect::<Vec<_>>()
+            .join("\n\n\n");
+
+        Ok(puzzle)
+    }
+
+    /// Write this puzzle back out with `export_xd`, prepending its metadata. Clues are looked up by
+    /// answer, so they're subject to the same filtering as any other exported clues, and the notes
+    /// section is dropped.
+    pub fn to_xd(&self) -> Result<String, RoundtripError> {
+        let config = try_generate_grid_config_from_letter_grid(
+            WordList::new(vec![], None, None, None),
+            &self.grid.join("\n"),
+            0,
+        )?;
+        let mut clue_bank = ClueBank::new();
+        for clue in &self.clues {
+            clue_bank.add(&clue.answer, &clue.clue);
+        }
+
+        let mut xd = String::new();
+        if !self.metadata.is_empty() {
+            for (key, value) in &self.metadata {
+                writeln!(xd, "{key}: {value}").unwrap();
+            }
+            xd.push_str("\n\n");
+        }
+        xd.push_str(&export_xd(&config, &[], &clue_bank));
+        Ok(xd)
+    }
+}
+
+/// Parse a clue line like `A1. Gym muscle, briefly ~ AB`.
+fn parse_xd_clue(line: &str) -> Option<XdClue> {
+    let direction = match line.chars().next()? {
+        'A' => Direction::Across,
+        'D' => Direction::Down,
+        _ => return None,
+    };
+    let (number, rest) = line[1..].split_once('.')?;
+    let (clue, answer) = rest.rsplit_once('~').unwrap_or((rest, ""));
+    Some(XdClue {
+        direction,
+        number: number.trim().parse().ok()?,
+        clue: clue.trim().to_string(),
+        answer: answer.trim().to_string(),
+    })
+}
+
+/// The result of `verify_roundtrip`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundtripReport {
+    /// The file as written back out by this crate.
+    pub reserialized: Vec<u8>,
+
+    /// A description of each semantic difference between the original file and the re-serialized
+    /// one, e.g. "3A: clue changed from “Partner of AB” to “”".
+    pub differences: Vec<String>,
+}
+
+impl RoundtripReport {
+    /// Did the file survive the round trip without any semantic changes?
+    #[must_use]
+    pub fn is_faithful(&self) -> bool {
+        self.differences.is_empty()
+    }
+}
+
+/// Parse the given file in the given format, serialize it again, and report any semantic
+/// differences between the two; see the module docs. An error means the original file couldn't be
+/// parsed (or, for `.xd` files, that its grid couldn't be loaded).
+pub fn verify_roundtrip(
+    format: PuzzleFormat,
+    bytes: &[u8],
+) -> Result<RoundtripReport, RoundtripError> {
+    let contents = std::str::from_utf8(bytes)?;
+    match format {
+        PuzzleFormat::Xd => {
+            let original = XdPuzzle::parse(contents)?;
+            let reserialized = original.to_xd()?;
+            let differences = xd_differences(&original, &XdPuzzle::parse(&reserialized)?);
+            Ok(RoundtripReport {
+                reserialized: reserialized.into_bytes(),
+                differences,
+            })
+        }
+
+        #[cfg(feature = "serde")]
+        PuzzleFormat::Project => {
+            let project = crate::project::Project::from_json(contents)?;
+            let reserialized = project.to_json();
+            let original: serde_json::Value =
+                serde_json::from_str(contents).map_err(crate::project::ProjectError::from)?;
+            let mut differences = vec![];
+            json_differences(
+                "",
+                &original,
+                &serde_json::from_str(&reserialized).expect("Projects are always valid JSON"),
+                &mut differences,
+            );
+            Ok(RoundtripReport {
+                reserialized: reserialized.into_bytes(),
+                differences,
+            })
+        }
+    }
+}
+
+/// Describe the differences between two parsed `.xd` files.
+fn xd_differences(before: &XdPuzzle, after: &XdPuzzle) -> Vec<String> {
+    let mut differences = vec![];
+
+    if before.metadata != after.metadata {
+        differences.push("metadata changed".into());
+    }
+
+    let dimensions = |grid: &[String]| {
+        (
+            grid.iter()
+                .map(|row| row.chars().count())
+                .max()
+                .unwrap_or(0),
+            grid.len(),
+        )
+    };
+    if dimensions(&before.grid) == dimensions(&after.grid) {
+        for (y, (before_row, after_row)) in before.grid.iter().zip(&after.grid).enumerate() {
+            for (x, (before_cell, after_cell)) in
+                before_row.chars().zip(after_row.chars()).enumerate()
+            {
+                if before_cell != after_cell {
+                    differences.push(format!(
+                        "cell ({x}, {y}) changed from {before_cell} to {after_cell}"
+                    ));
+                }
+            }
+        }
+    } else {
+        let (before_width, before_height) = dimensions(&before.grid);
+        let (after_width, after_height) = dimensions(&after.grid);
+        differences.push(format!(
+            "grid changed from {before_width}x{before_height} to {after_width}x{after_height}"
+        ));
+    }
+
+    let clues_by_label = |puzzle: &XdPuzzle| -> BTreeMap<(Direction, usize), XdClue> {
+        puzzle
+            .clues
+            .iter()
+            .map(|clue| ((clue.direction, clue.number), clue.clone()))
+            .collect()
+    };
+    let (before_clues, after_clues) = (clues_by_label(before), clues_by_label(after));
+    let label = |(direction, number): (Direction, usize)| match direction {
+        Direction::Across => format!("{number}A"),
+        Direction::Down => format!("{number}D"),
+    };
+    for (&key, before_clue) in &before_clues {
+        let Some(after_clue) = after_clues.get(&key) else {
+            differences.push(format!("{}: clue dropped", label(key)));
+            continue;
+        };
+        if before_clue.clue != after_clue.clue {
+            differences.push(format!(
+                "{}: clue changed from “{}” to “{}”",
+                label(key),
+                before_clue.clue,
+                after_clue.clue
+            ));
+        }
+        if before_clue.answer != after_clue.answer {
+            differences.push(format!(
+                "{}: answer changed from {} to {}",
+                label(key),
+                before_clue.answer,
+                after_clue.answer
+            ));
+        }
+    }
+    for &key in after_clues.keys() {
+        if !before_clues.contains_key(&key) {
+            differences.push(format!("{}: clue added", label(key)));
+        }
+    }
+
+    if before.notes != after.notes {
+        differences.push("notes dropped".into());
+    }
+
+    differences
+}
+
+/// Describe the differences between two JSON values, recursing into objects. Fields that only
+/// exist in `after` are ignored, since they're just defaults being filled in.
+#[cfg(feature = "serde")]
+fn json_differences(
+    path: &str,
+    before: &serde_json::Value,
+    after: &serde_json::Value,
+    differences: &mut Vec<String>,
+) {
+    match (before, after) {
+        (serde_json::Value::Object(before), serde_json::Value::Object(after)) => {
+            for (key, before_value) in before {
+                let field_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                match after.get(key) {
+                    Some(after_value) => {
+                        json_differences(&field_path, before_value, after_value, differences);
+                    }
+                    None => differences.push(format!("{field_path}: field dropped")),
+                }
+            }
+        }
+        _ if before != after => {
+            differences.push(format!("{path}: changed from {before} to {after}"));
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid_config::Direction;
+    use crate::roundtrip::{verify_roundtrip, PuzzleFormat, XdClue, XdPuzzle};
+
+    #[test]
+    fn test_xd_roundtrip() {
+        let xd = "Title: Tiny\nAuthor: Someone\n\n\nAB\nCD\n\n\nA1. Gym muscle, briefly ~ AB\nA3. Partner of AB ~ CD\n\nD1. Cooling system ~ AC\nD2. Tuesday? ~ BD\n\n\nSome notes\n";
+
+        let puzzle = XdPuzzle::parse(xd).unwrap();
+        assert_eq!(puzzle.metadata[0], ("Title".into(), "Tiny".into()));
+        assert_eq!(puzzle.grid, vec!["AB".to_string(), "CD".to_string()]);
+        assert_eq!(puzzle.clues.len(), 4);
+        assert_eq!(
+            puzzle.clues[3],
+            XdClue {
+                direction: Direction::Down,
+                number: 2,
+                clue: "Tuesday?".into(),
+                answer: "BD".into(),
+            }
+        );
+        assert_eq!(puzzle.notes, "Some notes");
+
+        // The exporter skips clues that mention another entry, and has nowhere to put notes.
+        let report = verify_roundtrip(PuzzleFormat::Xd, xd.as_bytes()).unwrap();
+        assert_eq!(
+            report.differences,
+            vec![
+                "3A: clue changed from “Partner of AB” to “”".to_string(),
+                "notes dropped".to_string(),
+            ]
+        );
+
+        let report = verify_roundtrip(PuzzleFormat::Xd, &report.reserialized).unwrap();
+        assert!(report.is_faithful());
+
+        assert!(verify_roundtrip(PuzzleFormat::Xd, b"").is_err());
+        assert!(verify_roundtrip(PuzzleFormat::Xd, b"AB\nCD\n\n\nX1. Nope ~ AB").is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_project_roundtrip() {
+        let report = verify_roundtrip(
+            PuzzleFormat::Project,
+            br#"{"grid": "..\n..", "metadata": {"title": "Tiny"}}"#,
+        )
+        .unwrap();
+        assert!(report.is_faithful());
+
+        let report = verify_roundtrip(
+            PuzzleFormat::Project,
+            br#"{"grid": "..", "colors": {"a": 1}, "metadata": {"title": "Tiny", "color": "red"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            report.differences,
+            vec![
+                "colors: field dropped".to_string(),
+                "metadata.color: field dropped".to_string(),
+            ]
+        );
+    }
+}
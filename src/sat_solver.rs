@@ -0,0 +1,305 @@
+//! This module implements an alternative fill algorithm that encodes the grid as a CNF formula and
+//! hands it to an embedded SAT solver (`varisat`). It's much slower than `backtracking_search` in
+//! typical cases, but since it learns clauses instead of relying on value-ordering heuristics, it
+//! can be useful for pathological grids where the heuristic search thrashes.
+//!
+//! The encoding uses two kinds of variables:
+//!
+//! - A "cell" variable for each (cell, glyph) pair, with a constraint that each cell contains at
+//!   most one glyph.
+//!
+//! - A "selector" variable for each (slot, option) pair, with a constraint that each slot selects
+//!   at least one option. Selecting an option implies the cell variables for each of its letters,
+//!   so two different options can't both be selected for the same slot.
+//!
+//! Dupe rules are encoded as binary clauses forbidding each pair of conflicting selectors.
+//!
+//! `varisat` has no direct way to interrupt a solve in progress, but it does stop as soon as writing
+//! its proof fails. So we have it write a (discarded) binary DRAT proof to a `SolverInterrupt`,
+//! which starts failing once we hit the deadline or the fill is aborted. The solver runs on the
+//! calling thread, so nothing is left running after `find_fill_sat` returns.
+
+use instant::{Duration, Instant};
+use std::collections::HashMap;
+use std::io;
+use varisat::{CnfFormula, ExtendFormula, Lit, ProofFormat, Solver};
+
+use crate::backtracking_search::{
+    slot_fill_stats, FillFailure, FillSuccess, PartialFill, Statistics,
+};
+use crate::cancel::CancelToken;
+use crate::error::FillError;
+use crate::grid_config::{Choice, GridConfig};
+use crate::types::{GlyphId, WordId};
+
+/// Why a `SolverInterrupt` stopped the solver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Interruption {
+    Timeout,
+    Abort,
+}
+
+/// A proof target that throws away everything written to it, but fails once the deadline has
+/// passed or the fill has been cancelled, which makes the solver give up. The solver buffers its
+/// proof, so this is checked every few hundred conflicts rather than continuously.
+struct SolverInterrupt {
+    deadline: Option<Instant>,
+    cancel: Option<CancelToken>,
+    interruption: Option<Interruption>,
+}
+
+impl SolverInterrupt {
+    fn check(&mut self) -> io::Result<()> {
+        if self.interruption.is_none() {
+            if self
+                .cancel
+                .as_ref()
+                .map_or(false, CancelToken::is_cancelled)
+            {
+                self.interruption = Some(Interruption::Abort);
+            } else if self
+                .deadline
+                .map_or(false, |deadline| Instant::now() > deadline)
+            {
+                self.interruption = Some(Interruption::Timeout);
+            }
+        }
+
+        match self.interruption {
+            Some(_) => Err(io::Error::other("fill interrupted")),
+            None => Ok(()),
+        }
+    }
+}
+
+impl io::Write for SolverInterrupt {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.check()?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.check()
+    }
+}
+
+/// Build a CNF encoding of the given grid and return it along with a lookup from each selector
+/// variable back to the slot choice it represents.
+fn encode_grid(config: &GridConfig, formula: &mut CnfFormula) -> HashMap<Lit, Choice> {
+    let mut cell_lits: HashMap<(usize, GlyphId), Lit> = HashMap::new();
+    let mut selector_lits: Vec<HashMap<WordId, Lit>> = vec![];
+    let mut choice_by_lit: HashMap<Lit, Choice> = HashMap::new();
+
+    for slot_config in config.slot_configs.iter() {
+        let cell_idxs = slot_config.cell_fill_indices(config.width);
+        let mut slot_selectors = HashMap::new();
+        let mut at_least_one: Vec<Lit> = vec![];
+
+        for &word_id in &config.slot_options[slot_config.id.index()] {
+            let selector = formula.new_lit();
+            let word = &config.word_list.words[slot_config.length][word_id.index()];
+
+            for (&cell_idx, &glyph) in cell_idxs.iter().zip(&word.glyphs) {
+                let cell_lit = *cell_lits
+                    .entry((cell_idx, glyph))
+                    .or_insert_with(|| formula.new_lit());
+                formula.add_clause(&[!selector, cell_lit]);
+            }
+
+            at_least_one.push(selector);
+            slot_selectors.insert(word_id, selector);
+            choice_by_lit.insert(
+                selector,
+                Choice {
+                    slot_id: slot_config.id,
+                    word_id,
+                },
+            );
+        }
+
+        formula.add_clause(&at_least_one);
+        selector_lits.push(slot_selectors);
+    }
+
+    // Each cell can only contain one glyph.
+    let mut lits_by_cell: HashMap<usize, Vec<Lit>> = HashMap::new();
+    for (&(cell_idx, _), &lit) in &cell_lits {
+        lits_by_cell.entry(cell_idx).or_default().push(lit);
+    }
+    for lits in lits_by_cell.values() {
+        for (idx, &lit_1) in lits.iter().enumerate() {
+            for &lit_2 in &lits[(idx + 1)..] {
+                formula.add_clause(&[!lit_1, !lit_2]);
+            }
+        }
+    }
+
+    // No two slots can contain words that are dupes of each other.
+    for slot_config in config.slot_configs.iter() {
+        for (&word_id, &selector) in &selector_lits[slot_config.id.index()] {
+            let dupes_by_length = config
+                .word_list
+                .dupe_index
+                .get_dupes_by_length((slot_config.length, word_id));
+
+            for other_slot_config in &config.slot_configs[(slot_config.id.index() + 1)..] {
+                let Some(dupe_ids) = dupes_by_length.get(&other_slot_config.length) else {
+                    continue;
+                };
+                for dupe_id in dupe_ids {
+                    if let Some(&other_selector) =
+                        selector_lits[other_slot_config.id.index()].get(dupe_id)
+                    {
+                        formula.add_clause(&[!selector, !other_selector]);
+                    }
+                }
+            }
+        }
+    }
+
+    choice_by_lit
+}
+
+/// Search for a valid fill for the given grid using the SAT backend, if one can be found within
+/// the given amount of time. Errors from the solver itself are returned as `FillError::Solver`
+/// rather than being treated as proof that the grid is unfillable.
+///
+/// The solver runs on the calling thread. The timeout and the config's `cancel` token are checked
+/// every few hundred solver conflicts (see the module docs), but not while the grid is being
+/// encoded, so the call can overrun them slightly; nothing is left running once it returns.
+pub fn find_fill_sat(
+    config: &GridConfig,
+    timeout: Option<Duration>,
+) -> Result<FillSuccess, FillError> {
+    let start = Instant::now();
+    let deadline = timeout.map(|timeout| start + timeout);
+    let mut formula = CnfFormula::new();
+    let choice_by_lit = encode_grid(config, &mut formula);
+
+    let partial_fill = || {
+        Box::new(PartialFill {
+            statistics: Statistics {
+                total_time: start.elapsed(),
+                ..Statistics::default()
+            },
+            ..PartialFill::default()
+        })
+    };
+
+    let mut interrupt = SolverInterrupt {
+        deadline,
+        cancel: config.cancel.clone(),
+        interruption: None,
+    };
+    let result = {
+        let mut solver = Solver::new();
+        solver.write_proof(&mut interrupt, ProofFormat::BinaryDrat);
+        solver.add_formula(&formula);
+        solver.solve().map(|satisfiable| {
+            satisfiable.then(|| {
+                solver
+                    .model()
+                    .expect("satisfiable formula must have a model")
+            })
+        })
+    };
+
+    let model = match (interrupt.interruption, result) {
+        (Some(Interruption::Abort), _) => return Err(FillFailure::Abort(partial_fill()).into()),
+        (Some(Interruption::Timeout), _) => {
+            return Err(FillFailure::Timeout(partial_fill()).into());
+        }
+        (None, Ok(Some(model))) => model,
+        (None, Ok(None)) => return Err(FillFailure::HardFailure(partial_fill()).into()),
+        (None, Err(err)) => return Err(FillError::Solver(err.to_string())),
+    };
+
+    let mut choices: Vec<Choice> = model
+        .iter()
+        .filter_map(|lit| choice_by_lit.get(lit).cloned())
+        .collect();
+    choices.sort_by_key(|choice| choice.slot_id);
+
+    #[cfg(feature = "check_invariants")]
+    assert!(
+        choices
+            .iter()
+            .map(|choice| choice.slot_id)
+            .eq(crate::types::SlotId::range(config.slot_configs.len())),
+        "SAT model must select exactly one option per slot"
+    );
+
+    let mut result = FillSuccess {
+        statistics: Statistics {
+            total_time: start.elapsed(),
+            ..Statistics::default()
+        },
+        slot_stats: slot_fill_stats(config, &choices, &[], &[]),
+        choices,
+        ..FillSuccess::default()
+    };
+    result.record_inputs(config, None);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cancel::CancelToken;
+    use crate::error::FillError;
+    use crate::grid_config::{generate_grid_config_from_template_string, render_grid};
+    use crate::sat_solver::find_fill_sat;
+    use crate::word_list::tests::word_list_source_config;
+    use crate::word_list::WordList;
+    use instant::Duration;
+
+    #[test]
+    fn test_find_fill_sat_for_4x4_square() {
+        let word_list = WordList::new(word_list_source_config(), None, Some(4), Some(5));
+        let grid_config = generate_grid_config_from_template_string(
+            word_list,
+            "
+            ....
+            ....
+            ....
+            ....
+            ",
+            50,
+        );
+
+        let result = find_fill_sat(&grid_config, None).expect("Failed to find a fill");
+        assert_eq!(result.choices.len(), 8);
+
+        let rendered = render_grid(&grid_config, &result.choices);
+        assert!(!rendered.contains('.'));
+        println!("{rendered}");
+    }
+
+    #[test]
+    fn test_find_fill_sat_timeout() {
+        let word_list = WordList::new(word_list_source_config(), None, Some(4), Some(5));
+        let grid_config = generate_grid_config_from_template_string(word_list, "....\n....", 50);
+
+        // With no time at all, we should give up before the solver has a chance to respond.
+        let result = find_fill_sat(&grid_config, Some(Duration::ZERO));
+        assert!(
+            matches!(result, Err(FillError::Timeout(_))),
+            "expected timeout, got {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_find_fill_sat_abort() {
+        let word_list = WordList::new(word_list_source_config(), None, Some(4), Some(5));
+        let mut grid_config =
+            generate_grid_config_from_template_string(word_list, "....\n....", 50);
+        let cancel = CancelToken::new();
+        cancel.cancel("switched grids");
+        grid_config.cancel = Some(cancel);
+
+        let result = find_fill_sat(&grid_config, None);
+        assert!(
+            matches!(result, Err(FillError::Abort(_))),
+            "expected abort, got {result:?}"
+        );
+    }
+}
@@ -0,0 +1,267 @@
+//! This module implements a constructor's personal adjustments to the scores in the lists they use,
+//! kept separately from the lists themselves. Shared lists get updated wholesale, so editing them
+//! in place means losing (or having to re-merge) any tweaks; instead, a `ScoreOverrides` overlay is
+//! applied on top of the merged list whenever it's loaded (see `WordList::set_score_overrides`),
+//! and can be saved to its own file with `to_file_contents` and read back with `parse`. Overrides
+//! can also be built up implicitly, by penalizing words as the user rejects them in fills (see
+//! `WordList::record_rejection`).
+//!
+//! The file format mirrors word list files, with one `word;adjustment` entry per line, where the
+//! adjustment is either an offset (like `+10` or `-25`) or a replacement score (like `=0`).
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::{Display, Formatter, Write};
+
+use crate::error::{SourceLineError, SourceLineErrorKind};
+use crate::word_list::normalize_word;
+
+/// A change to a single word's score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ScoreAdjustment {
+    /// Add the given amount to the score from the list, stopping at 0 and `u16::MAX`.
+    Offset(i32),
+
+    /// Ignore the score from the list and use this one instead.
+    Set(u16),
+}
+
+impl ScoreAdjustment {
+    #[must_use]
+    pub fn apply(self, score: u16) -> u16 {
+        match self {
+            ScoreAdjustment::Offset(offset) => {
+                let adjusted = i64::from(score) + i64::from(offset);
+                u16::try_from(adjusted.clamp(0, i64::from(u16::MAX))).unwrap()
+            }
+            ScoreAdjustment::Set(score) => score,
+        }
+    }
+}
+
+impl Display for ScoreAdjustment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ScoreAdjustment::Offset(offset) => write!(f, "{offset:+}"),
+            ScoreAdjustment::Set(score) => write!(f, "={score}"),
+        }
+    }
+}
+
+/// A set of per-word score adjustments; see the module docs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct ScoreOverrides {
+    adjustments_by_word: HashMap<String, ScoreAdjustment>,
+}
+
+impl ScoreOverrides {
+    #[must_use]
+    pub fn new() -> ScoreOverrides {
+        ScoreOverrides::default()
+    }
+
+    /// Parse a file in the format described in the module docs, returning the overrides from
+    /// every valid line along with an error for each line whose adjustment couldn't be read.
+    /// Blank lines are ignored.
+    #[must_use]
+    pub fn parse(contents: &str) -> (ScoreOverrides, Vec<SourceLineError>) {
+        let mut score_overrides = ScoreOverrides::new();
+        let mut errors = vec![];
+
+        for (line_idx, line) in contents.lines().enumerate() {
+            let (word, adjustment) = line.split_once(';').unwrap_or((line, ""));
+            if normalize_word(word).is_empty() {
+                continue;
+            }
+
+            let adjustment = adjustment.trim();
+            let parsed = if let Some(score) = adjustment.strip_prefix('=') {
+                score.trim().parse().ok().map(ScoreAdjustment::Set)
+            } else {
+                adjustment.parse().ok().map(ScoreAdjustment::Offset)
+            };
+            let Some(parsed) = parsed else {
+                errors.push(SourceLineError {
+                    line_number: line_idx + 1,
+                    text: adjustment.into(),
+                    kind: SourceLineErrorKind::InvalidScore,
+                });
+                continue;
+            };
+            score_overrides.set(word, parsed);
+        }
+
+        (score_overrides, errors)
+    }
+
+    /// Render these overrides in the format described in the module docs, sorted by word so that
+    /// saved files diff cleanly.
+    #[must_use]
+    pub fn to_file_contents(&self) -> String {
+        let mut entries: Vec<_> = self.adjustments_by_word.iter().collect();
+        entries.sort_unstable_by_key(|&(word, _)| word);
+
+        let mut contents = String::new();
+        for (word, adjustment) in entries {
+            writeln!(contents, "{word};{adjustment}").unwrap();
+        }
+        contents
+    }
+
+    /// Adjust the given (canonical or normalized) word, replacing any adjustment it already has.
+    pub fn set(&mut self, word: &str, adjustment: ScoreAdjustment) {
+        let word = normalize_word(word);
+        if !word.is_empty() {
+            self.adjustments_by_word.insert(word, adjustment);
+        }
+    }
+
+    /// Stop adjusting the given (canonical or normalized) word, returning its old adjustment.
+    pub fn remove(&mut self, word: &str) -> Option<ScoreAdjustment> {
+        self.adjustments_by_word.remove(&normalize_word(word))
+    }
+
+    /// The adjustment for the given normalized word, if any.
+    #[must_use]
+    pub fn get(&self, normalized: &str) -> Option<ScoreAdjustment> {
+        self.adjustments_by_word.get(normalized).copied()
+    }
+
+    /// The given normalized word's score from the merged list, after any adjustment.
+    #[must_use]
+    pub fn apply(&self, normalized: &str, score: u16) -> u16 {
+        self.get(normalized)
+            .map_or(score, |adjustment| adjustment.apply(score))
+    }
+
+    /// The number of words with an adjustment.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.adjustments_by_word.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.adjustments_by_word.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::SourceLineErrorKind;
+    use crate::score_overrides::{ScoreAdjustment, ScoreOverrides};
+    use crate::word_list::{WordList, WordListSourceConfig};
+
+    #[test]
+    fn test_score_overrides() {
+        let (score_overrides, errors) =
+            ScoreOverrides::parse("Wolves;+10\nsteev;= 5\n\ngolf;-100\nnope;lots\n");
+        assert_eq!(score_overrides.len(), 3);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line_number, 5);
+        assert_eq!(errors[0].kind, SourceLineErrorKind::InvalidScore);
+        assert_eq!(
+            score_overrides.to_file_contents(),
+            "golf;-100\nsteev;=5\nwolves;+10\n"
+        );
+        assert_eq!(
+            ScoreOverrides::parse(&score_overrides.to_file_contents()).0,
+            score_overrides
+        );
+
+        let mut word_list = WordList::new(
+            vec![WordListSourceConfig::Memory {
+                id: "0".into(),
+                enabled: true,
+                words: vec![
+                    ("wolves".into(), 60),
+                    ("steev".into(), 40),
+                    ("golf".into(), 50),
+                    ("yeti".into(), 50),
+                ],
+            }],
+            None,
+            None,
+            None,
+        );
+        let score = |word_list: &WordList, word: &str| {
+            let (length, word_id) = (word.len(), word_list.word_id_by_string[word]);
+            word_list.words[length][word_id.index()].score
+        };
+
+        let (_, less_visible_words) = word_list.set_score_overrides(score_overrides.clone());
+        assert_eq!(score(&word_list, "wolves"), 70);
+        assert_eq!(score(&word_list, "steev"), 5);
+        assert_eq!(score(&word_list, "golf"), 0);
+        assert_eq!(score(&word_list, "yeti"), 50);
+        assert_eq!(less_visible_words.len(), 2);
+
+        // The overlay survives refreshes and applies to edits, without changing the source.
+        word_list.refresh_from_disk();
+        assert_eq!(score(&word_list, "wolves"), 70);
+        word_list.optimistically_update_word("yeti", 30, "0");
+        word_list.optimistically_update_word("steev", 45, "0");
+        assert_eq!(score(&word_list, "yeti"), 30);
+        assert_eq!(score(&word_list, "steev"), 5);
+
+        let mut score_overrides = score_overrides;
+        score_overrides.remove("Steev");
+        score_overrides.set("yeti", ScoreAdjustment::Offset(-5));
+        word_list.set_score_overrides(score_overrides);
+        assert_eq!(score(&word_list, "steev"), 45);
+        assert_eq!(score(&word_list, "yeti"), 25);
+
+        word_list.set_score_overrides(ScoreOverrides::new());
+        assert_eq!(score(&word_list, "wolves"), 60);
+    }
+
+    #[test]
+    fn test_record_rejection() {
+        let mut word_list = WordList::new(
+            vec![WordListSourceConfig::Memory {
+                id: "0".into(),
+                enabled: true,
+                words: vec![("wolves".into(), 60), ("steev".into(), 40)],
+            }],
+            None,
+            None,
+            None,
+        );
+        let score = |word_list: &WordList, word: &str| {
+            let (length, word_id) = (word.len(), word_list.word_id_by_string[word]);
+            word_list.words[length][word_id.index()].score
+        };
+
+        assert_eq!(word_list.record_rejection("Wolves", None), 1);
+        assert_eq!(score(&word_list, "wolves"), 60);
+        assert!(word_list.score_overrides.is_empty());
+
+        // Each penalized rejection lowers the overlay score further, and survives refreshes.
+        assert_eq!(word_list.record_rejection("wolves", Some(15)), 2);
+        assert_eq!(word_list.record_rejection("wolves", Some(15)), 3);
+        assert_eq!(score(&word_list, "wolves"), 30);
+        assert_eq!(
+            word_list.score_overrides.get("wolves"),
+            Some(ScoreAdjustment::Offset(-30))
+        );
+        word_list.refresh_from_disk();
+        assert_eq!(score(&word_list, "wolves"), 30);
+
+        word_list
+            .score_overrides
+            .set("steev", ScoreAdjustment::Set(10));
+        word_list.refresh_from_disk();
+        assert_eq!(word_list.record_rejection("steev", Some(25)), 1);
+        assert_eq!(score(&word_list, "steev"), 0);
+        assert_eq!(word_list.rejection_counts["wolves"], 3);
+    }
+}
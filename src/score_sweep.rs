@@ -0,0 +1,192 @@
+//! This module helps constructors choose a `min_score` for a grid by filling it at several
+//! thresholds and comparing the results: a higher threshold means a cleaner fill, but past some
+//! point the grid gets slow or impossible to fill. The fills share one `FillService`, so the word
+//! list is only loaded once, and each threshold's search starts from the crossing weights learned
+//! at the one before it, so trouble spots found early don't have to be rediscovered.
+
+use instant::{Duration, Instant};
+
+use crate::benchmark::BenchOutcome;
+use crate::error::GridError;
+use crate::fill_service::{FillService, GridSpec};
+
+/// How the fill went at a single threshold.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct SweepLevel {
+    pub min_score: u16,
+    pub outcome: BenchOutcome,
+
+    /// The wall-clock time taken by the fill, including building the grid's slot options.
+    pub time: Duration,
+
+    pub states: usize,
+    pub backtracks: usize,
+
+    /// The mean score of the fill's entries, if the grid was filled.
+    pub average_score: Option<f32>,
+
+    /// The score of the fill's worst entry, if the grid was filled.
+    pub lowest_score: Option<u16>,
+}
+
+/// The results of a sweep; see `sweep_min_scores`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct MinScoreSweep {
+    /// Results for each threshold, in the order they were given.
+    pub levels: Vec<SweepLevel>,
+}
+
+impl MinScoreSweep {
+    /// The highest threshold at which the grid was filled, if any.
+    #[must_use]
+    pub fn best_min_score(&self) -> Option<u16> {
+        self.levels
+            .iter()
+            .filter(|level| level.outcome == BenchOutcome::Filled)
+            .map(|level| level.min_score)
+            .max()
+    }
+}
+
+/// Fill the given grid once for each of the given thresholds, replacing its `min_score` but
+/// otherwise using it as is (including its `timeout`, which applies to each fill separately).
+/// Returns an error if the grid can't be searched at all.
+pub fn sweep_min_scores(
+    service: &FillService,
+    grid_spec: &GridSpec,
+    min_scores: &[u16],
+) -> Result<MinScoreSweep, GridError> {
+    let mut levels = vec![];
+    let mut previous_spec: Option<GridSpec> = None;
+
+    for &min_score in min_scores {
+        let level_spec = GridSpec {
+            min_score,
+            ..grid_spec.clone()
+        };
+        if let Some(previous_spec) = &previous_spec {
+            service.share_crossing_weights(previous_spec, &level_spec);
+        }
+
+        let start = Instant::now();
+        let level = service.fill_and_then(&level_spec, |config, result| {
+            let time = start.elapsed();
+            match result {
+                Ok(success) => {
+                    let scores: Vec<u16> = success
+                        .choices
+                        .iter()
+                        .map(|choice| {
+                            let length = config.slot_configs[choice.slot_id.index()].length;
+                            config.word_list.get_word((length, choice.word_id)).score
+                        })
+                        .collect();
+                    #[allow(clippy::cast_precision_loss)]
+                    let average_score = (!scores.is_empty()).then(|| {
+                        scores.iter().map(|&score| f32::from(score)).sum::<f32>()
+                            / scores.len() as f32
+                    });
+                    SweepLevel {
+                        min_score,
+                        outcome: BenchOutcome::Filled,
+                        time,
+                        states: success.statistics.states,
+                        backtracks: success.statistics.backtracks,
+                        average_score,
+                        lowest_score: scores.iter().copied().min(),
+                    }
+                }
+                Err(error) => {
+                    let statistics = error.partial().map(|partial| &partial.statistics);
+                    SweepLevel {
+                        min_score,
+                        outcome: BenchOutcome::for_error(&error),
+                        time,
+                        states: statistics.map_or(0, |statistics| statistics.states),
+                        backtracks: statistics.map_or(0, |statistics| statistics.backtracks),
+                        average_score: None,
+                        lowest_score: None,
+                    }
+                }
+            }
+        })?;
+
+        levels.push(level);
+        previous_spec = Some(level_spec);
+    }
+
+    Ok(MinScoreSweep { levels })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backtracking_search::SearchConfig;
+    use crate::benchmark::BenchOutcome;
+    use crate::fill_service::{FillService, GridSpec};
+    use crate::score_sweep::sweep_min_scores;
+    use crate::word_list::{WordList, WordListSourceConfig};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_sweep_min_scores() {
+        let word_list = Arc::new(WordList::new(
+            vec![WordListSourceConfig::Memory {
+                id: "0".into(),
+                enabled: true,
+                words: [
+                    ("ab", 60),
+                    ("cd", 60),
+                    ("ac", 60),
+                    ("bd", 60),
+                    ("ef", 30),
+                    ("gh", 30),
+                    ("eg", 30),
+                    ("fh", 30),
+                ]
+                .into_iter()
+                .map(|(word, score)| (word.into(), score))
+                .collect(),
+            }],
+            None,
+            None,
+            None,
+        ));
+        let service = FillService::new(word_list, SearchConfig::default());
+        let grid_spec = GridSpec {
+            template: "..\n..".into(),
+            ..GridSpec::default()
+        };
+
+        let sweep = sweep_min_scores(&service, &grid_spec, &[20, 50, 70]).unwrap();
+        assert_eq!(
+            sweep
+                .levels
+                .iter()
+                .map(|level| (level.min_score, level.outcome, level.lowest_score))
+                .collect::<Vec<_>>(),
+            vec![
+                (20, BenchOutcome::Filled, Some(60)),
+                (50, BenchOutcome::Filled, Some(60)),
+                (70, BenchOutcome::Unfillable, None),
+            ]
+        );
+        assert!(sweep.levels[1]
+            .average_score
+            .is_some_and(|score| (score - 60.0).abs() < 0.01));
+        assert_eq!(sweep.best_min_score(), Some(50));
+
+        let invalid_spec = GridSpec {
+            template: "@.\n..".into(),
+            ..GridSpec::default()
+        };
+        assert!(sweep_min_scores(&service, &invalid_spec, &[20]).is_err());
+    }
+}
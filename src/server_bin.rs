@@ -0,0 +1,69 @@
+use clap::Parser;
+
+use ingrid_core::backtracking_search::SearchConfig;
+use ingrid_core::fill_service::FillService;
+use ingrid_core::http_server::serve;
+use ingrid_core::word_list::{WordList, WordListSourceConfig};
+use std::borrow::Cow;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+const STWL_RAW: &str = include_str!("../resources/XwiWordList.txt");
+
+/// ingrid_server: HTTP server for crossword generation
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// Path to a scored wordlist file [default: (embedded copy of Spread the Wordlist)]
+    #[arg(long)]
+    wordlist: Option<String>,
+
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    address: String,
+
+    /// Maximum length of words to load from the word list
+    #[arg(long, default_value_t = 21)]
+    max_length: usize,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), String> {
+    let args = Args::parse();
+
+    let word_list = WordList::new(
+        vec![match args.wordlist {
+            Some(wordlist_path) => WordListSourceConfig::File {
+                id: "0".into(),
+                enabled: true,
+                path: wordlist_path.into(),
+            },
+            None => WordListSourceConfig::FileContents {
+                id: "0".into(),
+                enabled: true,
+                contents: Cow::Borrowed(STWL_RAW),
+            },
+        }],
+        None,
+        Some(args.max_length),
+        None,
+    );
+    if let Some(errors) = word_list.get_source_errors().get("0") {
+        if let Some(error) = errors.first() {
+            return Err(error.to_string());
+        }
+    }
+
+    let service = Arc::new(FillService::new(
+        Arc::new(word_list),
+        SearchConfig::default(),
+    ));
+    let listener = TcpListener::bind(&args.address)
+        .await
+        .map_err(|error| format!("Couldn't listen on {}: {error}", args.address))?;
+    eprintln!("Listening on {}", args.address);
+
+    serve(listener, service)
+        .await
+        .map_err(|error| error.to_string())
+}
@@ -0,0 +1,490 @@
+//! This module implements exhaustive and sampled counting of the distinct fills a grid admits.
+//! Exact counting uses a plain depth-first search (no restarts or randomization) on top of the same
+//! arc-consistency machinery as `backtracking_search`, so that every branch of the search tree is
+//! visited exactly once. Estimation uses Knuth's tree-size estimator, which walks random paths
+//! from the root and multiplies the branching factors it encounters.
+//!
+//! It also checks whether a partially-filled grid has a unique completion, for puzzles that depend
+//! on a single intended solution: after finding one fill, we look for another fill that differs
+//! from it in each slot in turn, which tells us exactly which slots are ambiguous.
+
+use instant::Duration;
+use rand::prelude::*;
+use std::borrow::Cow;
+
+use crate::arc_consistency::{EliminationSet, EliminationSetPool};
+use crate::backtracking_search::{
+    available_options, build_slots, calculate_slot_weights, maintain_arc_consistency, undo_choice,
+    ArcConsistencyMode, Slot, Statistics, INTERRUPT_FREQUENCY,
+};
+use crate::cancel::CancelToken;
+use crate::crossing_weights::CrossingWeights;
+use crate::grid_config::{Choice, GridConfig, SlotId};
+
+/// The result of a call to `count_solutions`.
+#[derive(Debug, Clone)]
+pub struct SolutionCount {
+    /// The number of distinct fills found. If `exhaustive` is false, this is a lower bound.
+    pub count: usize,
+
+    /// Did we visit the whole search tree? This is false if we reached the requested cap with
+    /// branches still left to explore, or if the fill was aborted.
+    pub exhaustive: bool,
+
+    /// Stats about the search process. `retries` and `restricted_branchings` are always zero.
+    pub statistics: Statistics,
+}
+
+/// The result of a call to `estimate_solution_count`.
+#[derive(Debug, Clone)]
+pub struct SolutionEstimate {
+    /// The mean of the samples taken, or zero if none were.
+    pub estimate: f64,
+
+    /// The number of samples the estimate is based on.
+    pub samples: usize,
+
+    /// Did we take every requested sample? This is false if the fill was aborted, in which case
+    /// `estimate` only reflects the samples taken before then.
+    pub complete: bool,
+}
+
+/// Shared state threaded through the recursive counting search.
+struct CountingState<'a> {
+    config: &'a GridConfig<'a>,
+    crossing_weights: CrossingWeights,
+    elimination_sets: EliminationSetPool,
+    statistics: Statistics,
+    count: usize,
+    cap: usize,
+    aborted: bool,
+
+    /// Did we stop with part of the search tree still unexplored because we'd reached the cap?
+    capped: bool,
+
+    /// The first fill we found, if any.
+    first_solution: Option<Vec<Choice>>,
+}
+
+/// Choose the undetermined slot with the fewest remaining options, or `None` if every slot has
+/// been reduced to a single word (meaning that we've found a complete fill).
+fn choose_counting_slot(slots: &[Slot]) -> Option<SlotId> {
+    slots
+        .iter()
+        .filter(|slot| slot.fixed_word_id.is_none() && slot.remaining_option_count > 1)
+        .min_by_key(|slot| slot.remaining_option_count)
+        .map(|slot| slot.id)
+}
+
+/// Try to commit the given choice, returning whether arc consistency could be maintained.
+fn try_choice(
+    config: &GridConfig,
+    slots: &mut [Slot],
+    crossing_weights: &mut CrossingWeights,
+    elimination_sets: &mut [EliminationSet],
+    statistics: &mut Statistics,
+    choice: Choice,
+) -> bool {
+    let slot_weights = calculate_slot_weights(config, slots, crossing_weights);
+    maintain_arc_consistency(
+        config,
+        slots,
+        crossing_weights,
+        &slot_weights,
+        &ArcConsistencyMode::Choice(choice),
+        &mut statistics.choice_arc_consistency_time,
+        elimination_sets,
+    )
+}
+
+/// Recursively count the fills reachable from the current state.
+fn count_from_state(state: &mut CountingState, slots: &mut Vec<Slot>) {
+    state.statistics.states += 1;
+
+    if let Some(cancel) = &state.config.cancel {
+        // Only read the clock for the token's deadline every so often.
+        let cancelled = if state.statistics.states % INTERRUPT_FREQUENCY == 0 {
+            cancel.is_cancelled()
+        } else {
+            cancel.was_cancelled()
+        };
+        if cancelled {
+            state.aborted = true;
+            return;
+        }
+    }
+
+    let Some(slot_id) = choose_counting_slot(slots) else {
+        if state.first_solution.is_none() {
+            state.first_solution = slots
+                .iter()
+                .map(|slot| slot.get_choice(state.config))
+                .collect();
+        }
+        state.count += 1;
+        return;
+    };
+
+    for word_id in available_options(state.config, &slots[slot_id.index()]) {
+        if state.aborted {
+            return;
+        }
+
+        // We only know that the count is incomplete if there's still a branch left to explore.
+        if state.count >= state.cap {
+            state.capped = true;
+            return;
+        }
+
+        let count_before = state.count;
+        if try_choice(
+            state.config,
+            slots,
+            &mut state.crossing_weights,
+            &mut state.elimination_sets,
+            &mut state.statistics,
+            Choice { slot_id, word_id },
+        ) {
+            count_from_state(state, slots);
+            undo_choice(state.config, slots, slot_id);
+        }
+
+        // A choice that failed outright or led to a subtree with no fills is a dead end.
+        if state.count == count_before && !state.aborted {
+            state.statistics.backtracks += 1;
+        }
+    }
+}
+
+/// Count the distinct fills for the given grid, stopping once we've found `cap` of them. Passing
+/// `usize::MAX` as the cap gives an exact count, although this is only practical for small or
+/// heavily-constrained grids.
+#[must_use]
+pub fn count_solutions(config: &GridConfig, cap: usize) -> SolutionCount {
+    count_solutions_with_first(config, cap).0
+}
+
+/// Count the fills for the given grid like `count_solutions`, also returning the first fill found.
+fn count_solutions_with_first(
+    config: &GridConfig,
+    cap: usize,
+) -> (SolutionCount, Option<Vec<Choice>>) {
+    let start = instant::Instant::now();
+    let mut slots = build_slots(config);
+
+    let mut state = CountingState {
+        config,
+        crossing_weights: CrossingWeights::new(config.crossing_count),
+        elimination_sets: EliminationSet::build_all(&config.slot_configs, &config.word_list),
+        statistics: Statistics::default(),
+        count: 0,
+        cap,
+        aborted: false,
+        capped: false,
+        first_solution: None,
+    };
+
+    let slot_weights = calculate_slot_weights(config, &slots, &state.crossing_weights);
+    let mut initial_arc_consistency_time = Duration::default();
+    let consistent = maintain_arc_consistency(
+        config,
+        &mut slots,
+        &mut state.crossing_weights,
+        &slot_weights,
+        &ArcConsistencyMode::Initial,
+        &mut initial_arc_consistency_time,
+        &mut state.elimination_sets,
+    );
+
+    if consistent {
+        if cap > 0 {
+            count_from_state(&mut state, &mut slots);
+        } else {
+            state.capped = true;
+        }
+    }
+
+    let mut statistics = state.statistics;
+    statistics.initial_arc_consistency_time = initial_arc_consistency_time;
+    statistics.total_time = start.elapsed();
+
+    (
+        SolutionCount {
+            count: state.count,
+            exhaustive: !state.aborted && !state.capped,
+            statistics,
+        },
+        state.first_solution,
+    )
+}
+
+/// The result of a call to `verify_unique_solution`.
+#[derive(Debug, Clone)]
+pub struct UniquenessReport {
+    /// A complete fill of the grid, if it has any, with a choice for every slot.
+    pub solution: Option<Vec<Choice>>,
+
+    /// The slots that can be filled differently than in `solution` while still completing the grid,
+    /// in order of id. Slots whose contents were provided verbatim are never listed.
+    pub ambiguous_slot_ids: Vec<SlotId>,
+
+    /// Did every check run to completion? This is false if the fill was aborted, in which case
+    /// `ambiguous_slot_ids` may be missing some slots.
+    pub exhaustive: bool,
+
+    /// Stats about the search process, accumulated across all of the checks.
+    pub statistics: Statistics,
+}
+
+impl UniquenessReport {
+    /// Does the grid have exactly one fill?
+    #[must_use]
+    pub fn is_unique(&self) -> bool {
+        self.solution.is_some() && self.ambiguous_slot_ids.is_empty() && self.exhaustive
+    }
+}
+
+/// Determine whether the given (usually partially-filled) grid has exactly one completion, and if
+/// not, which of its slots could be filled in more than one way. This runs a separate search for
+/// each slot that isn't already known to be ambiguous, so it's only practical for grids whose
+/// prefilled entries leave little room; see the module docs.
+#[must_use]
+pub fn verify_unique_solution(config: &GridConfig) -> UniquenessReport {
+    // Each search stops as soon as it finds a fill, so it's only incomplete if it found nothing
+    // without exploring the whole tree.
+    let (result, solution) = count_solutions_with_first(config, 1);
+    let mut report = UniquenessReport {
+        exhaustive: result.exhaustive || solution.is_some(),
+        solution,
+        ambiguous_slot_ids: vec![],
+        statistics: result.statistics,
+    };
+    let Some(solution) = &report.solution else {
+        return report;
+    };
+
+    let open_slot_ids: Vec<SlotId> = build_slots(config)
+        .iter()
+        .filter(|slot| slot.fixed_word_id.is_none())
+        .map(|slot| slot.id)
+        .collect();
+    let mut is_ambiguous = vec![false; config.slot_configs.len()];
+
+    for slot_id in open_slot_ids {
+        if is_ambiguous[slot_id.index()] {
+            continue;
+        }
+
+        // Look for a fill that uses any other word in this slot. Any fill we find also tells us
+        // about every other slot it fills differently.
+        let chosen_word_id = solution[slot_id.index()].word_id;
+        let mut slot_options = config.slot_options.to_vec();
+        slot_options[slot_id.index()].retain(|&word_id| word_id != chosen_word_id);
+        let (result, alternative) = count_solutions_with_first(
+            &GridConfig {
+                slot_options: Cow::Owned(slot_options),
+                ..config.borrowed()
+            },
+            1,
+        );
+        report.statistics.accumulate(&result.statistics);
+        report.exhaustive &= result.exhaustive || alternative.is_some();
+
+        if let Some(alternative) = alternative {
+            for (choice, alternative_choice) in solution.iter().zip(&alternative) {
+                if choice.word_id != alternative_choice.word_id {
+                    is_ambiguous[choice.slot_id.index()] = true;
+                }
+            }
+        }
+    }
+
+    report.ambiguous_slot_ids = SlotId::range(is_ambiguous.len())
+        .filter(|slot_id| is_ambiguous[slot_id.index()])
+        .collect();
+    report
+}
+
+/// Estimate the number of distinct fills for the given grid using Knuth's estimator: each sample
+/// walks a random path from the root of the search tree, and the product of the domain sizes it
+/// sees along the way (or zero, if the path hits a dead end) is an unbiased estimate of the number
+/// of leaves. The result is the mean across `samples` paths, so its variance can be very high for
+/// open grids. The config's `cancel` token is checked between samples; if it's cancelled, we
+/// return the estimate from the samples taken so far.
+#[must_use]
+pub fn estimate_solution_count(
+    config: &GridConfig,
+    samples: usize,
+    rng_seed: u64,
+) -> SolutionEstimate {
+    let mut rng: SmallRng = SeedableRng::seed_from_u64(rng_seed);
+    let mut statistics = Statistics::default();
+    let mut crossing_weights = CrossingWeights::new(config.crossing_count);
+    let mut elimination_sets = EliminationSet::build_all(&config.slot_configs, &config.word_list);
+
+    let mut initial_slots = build_slots(config);
+    let slot_weights = calculate_slot_weights(config, &initial_slots, &crossing_weights);
+    if !maintain_arc_consistency(
+        config,
+        &mut initial_slots,
+        &mut crossing_weights,
+        &slot_weights,
+        &ArcConsistencyMode::Initial,
+        &mut statistics.initial_arc_consistency_time,
+        &mut elimination_sets,
+    ) || samples == 0
+    {
+        return SolutionEstimate {
+            estimate: 0.0,
+            samples: 0,
+            complete: true,
+        };
+    }
+
+    let mut total = 0.0;
+    let mut samples_taken = 0;
+
+    for _ in 0..samples {
+        if config
+            .cancel
+            .as_ref()
+            .map_or(false, CancelToken::is_cancelled)
+        {
+            break;
+        }
+
+        let mut slots = initial_slots.clone();
+        let mut estimate = 1.0;
+
+        while let Some(slot_id) = choose_counting_slot(&slots) {
+            let options = available_options(config, &slots[slot_id.index()]);
+            let &word_id = options.choose(&mut rng).expect("slot must have options");
+            estimate *= options.len() as f64;
+
+            if !try_choice(
+                config,
+                &mut slots,
+                &mut crossing_weights,
+                &mut elimination_sets,
+                &mut statistics,
+                Choice { slot_id, word_id },
+            ) {
+                estimate = 0.0;
+                break;
+            }
+        }
+
+        total += estimate;
+        samples_taken += 1;
+    }
+
+    SolutionEstimate {
+        estimate: if samples_taken == 0 {
+            0.0
+        } else {
+            total / samples_taken as f64
+        },
+        samples: samples_taken,
+        complete: samples_taken == samples,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cancel::CancelToken;
+    use crate::grid_config::generate_grid_config_from_template_string;
+    use crate::grid_config::SlotId;
+    use crate::solution_counting::{
+        count_solutions, estimate_solution_count, verify_unique_solution,
+    };
+    use crate::word_list::{WordList, WordListSourceConfig};
+
+    fn generate_config(template: &str) -> crate::grid_config::OwnedGridConfig {
+        let word_list = WordList::new(
+            vec![WordListSourceConfig::Memory {
+                id: "0".into(),
+                enabled: true,
+                words: ["ab", "cd", "ac", "bd"]
+                    .iter()
+                    .map(|&word| (word.into(), 50))
+                    .collect(),
+            }],
+            None,
+            Some(2),
+            None,
+        );
+
+        generate_grid_config_from_template_string(word_list, template, 40)
+    }
+
+    #[test]
+    fn test_count_solutions() {
+        let grid_config = generate_config("..\n..");
+
+        let result = count_solutions(&grid_config, usize::MAX);
+        assert_eq!(result.count, 2);
+        assert!(result.exhaustive);
+
+        let capped = count_solutions(&grid_config, 1);
+        assert_eq!(capped.count, 1);
+        assert!(!capped.exhaustive);
+
+        let prefilled = generate_config("a.\n..");
+        let result = count_solutions(&prefilled, usize::MAX);
+        assert_eq!(result.count, 2, "both fills start with 'a'");
+
+        let forced = generate_config("ab\n..");
+        let result = count_solutions(&forced, usize::MAX);
+        assert_eq!(result.count, 1);
+        assert!(result.exhaustive);
+
+        // Reaching the cap doesn't make the count inexact if there was nothing else to explore.
+        let result = count_solutions(&forced, 1);
+        assert_eq!(result.count, 1);
+        assert!(
+            result.exhaustive,
+            "forced completion is exhaustive at cap 1"
+        );
+        assert_eq!(result.statistics.backtracks, 0);
+    }
+
+    #[test]
+    fn test_estimate_solution_count() {
+        let grid_config = generate_config("..\n..");
+
+        let result = estimate_solution_count(&grid_config, 200, 0);
+        let estimate = result.estimate;
+        assert!(estimate > 0.5 && estimate < 4.0, "estimate was {estimate}");
+        assert_eq!(result.samples, 200);
+        assert!(result.complete);
+
+        // Once the fill is aborted, we stop taking samples.
+        let mut grid_config = grid_config;
+        let cancel = CancelToken::new();
+        cancel.cancel("switched grids");
+        grid_config.cancel = Some(cancel);
+        let result = estimate_solution_count(&grid_config, 200, 0);
+        assert_eq!(result.samples, 0);
+        assert!(!result.complete);
+    }
+
+    #[test]
+    fn test_verify_unique_solution() {
+        let report = verify_unique_solution(&generate_config("ab\n.."));
+        assert!(report.is_unique());
+        assert_eq!(report.solution.map(|solution| solution.len()), Some(4));
+
+        // The grid can be filled either way around, so every slot is ambiguous.
+        let report = verify_unique_solution(&generate_config("a.\n.."));
+        assert!(!report.is_unique());
+        assert!(report.exhaustive);
+        assert_eq!(
+            report.ambiguous_slot_ids,
+            SlotId::range(4).collect::<Vec<_>>()
+        );
+
+        let report = verify_unique_solution(&generate_config("ad\n.."));
+        assert!(report.solution.is_none());
+        assert!(!report.is_unique());
+    }
+}
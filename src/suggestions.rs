@@ -0,0 +1,116 @@
+//! This module suggests words for a single slot in a partially-filled grid, which is what a
+//! construction UI shows as the constructor moves around the grid. Rather than listing everything
+//! that matches the slot's current letters, we establish arc consistency across the whole grid
+//! first, so a word is only suggested if every slot it crosses would still have at least one option.
+
+use crate::arc_consistency::{establish_arc_consistency_for_static_grid, EliminationSet};
+use crate::grid_config::{Direction, GridConfig, GridCoord, SlotId};
+
+/// A candidate for a slot, as returned by `suggest_words`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct Suggestion {
+    /// The word as it would appear in the grid (e.g., "halfdome").
+    pub normalized: String,
+
+    /// The word as it appears in its word list (e.g., "Half Dome").
+    pub display: String,
+
+    pub score: u16,
+}
+
+/// Find the slot running in the given direction through the given cell, if there is one.
+#[must_use]
+pub fn find_slot_at(config: &GridConfig, cell: GridCoord, direction: Direction) -> Option<SlotId> {
+    config
+        .slot_configs
+        .iter()
+        .find(|slot_config| {
+            slot_config.direction == direction && slot_config.cell_coords().contains(&cell)
+        })
+        .map(|slot_config| slot_config.id)
+}
+
+/// List up to `limit` viable words for the given slot, in the order the slot's options are sorted
+/// in (i.e., best first). If the grid as a whole can't be made arc-consistent, nothing is viable
+/// and the list is empty. If `elimination_sets` isn't given, we allocate new ones.
+#[must_use]
+pub fn suggest_words(
+    config: &GridConfig,
+    slot_id: SlotId,
+    limit: usize,
+    elimination_sets: Option<&mut [EliminationSet]>,
+) -> Vec<Suggestion> {
+    let mut owned_elimination_sets;
+    let elimination_sets = if let Some(elimination_sets) = elimination_sets {
+        elimination_sets
+    } else {
+        owned_elimination_sets = EliminationSet::build_all(&config.slot_configs, &config.word_list);
+        &mut owned_elimination_sets[..]
+    };
+
+    if establish_arc_consistency_for_static_grid(config, elimination_sets).is_err() {
+        return vec![];
+    }
+
+    let eliminations = &elimination_sets[slot_id.index()];
+    let length = config.slot_configs[slot_id.index()].length;
+    config.slot_options[slot_id.index()]
+        .iter()
+        .filter(|&&word_id| !eliminations.contains(word_id))
+        .take(limit)
+        .map(|&word_id| {
+            let word = config.word_list.get_word((length, word_id));
+            Suggestion {
+                normalized: word.normalized_string.clone(),
+                display: word.canonical_string.clone(),
+                score: word.score,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid_config::{generate_grid_config_from_template_string, Direction};
+    use crate::suggestions::{find_slot_at, suggest_words};
+    use crate::word_list::{WordList, WordListSourceConfig};
+
+    #[test]
+    fn test_suggest_words() {
+        let word_list = WordList::new(
+            vec![WordListSourceConfig::Memory {
+                id: "0".into(),
+                enabled: true,
+                words: [("ab", 60), ("cd", 50), ("ac", 50), ("bd", 50), ("ax", 70)]
+                    .into_iter()
+                    .map(|(word, score)| (word.into(), score))
+                    .collect(),
+            }],
+            None,
+            None,
+            None,
+        );
+        let grid_config = generate_grid_config_from_template_string(word_list, "a.\n..", 40);
+        let config = grid_config.borrowed();
+
+        // "ax" matches the top-left across slot, but nothing starts with "x" going down.
+        let slot_id = find_slot_at(&config, (1, 0), Direction::Across).unwrap();
+        let suggestions = suggest_words(&config, slot_id, 10, None);
+        let words: Vec<&str> = suggestions
+            .iter()
+            .map(|suggestion| suggestion.normalized.as_str())
+            .collect();
+        assert_eq!(words, ["ab", "ac"]);
+        assert_eq!(suggestions[0].score, 60);
+
+        let slot_id = find_slot_at(&config, (0, 1), Direction::Down).unwrap();
+        let suggestions = suggest_words(&config, slot_id, 1, None);
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].normalized, "ab");
+        assert_eq!(find_slot_at(&config, (2, 0), Direction::Across), None);
+    }
+}
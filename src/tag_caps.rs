@@ -0,0 +1,164 @@
+//! This module implements editorial limits on the entries carrying a given word-list tag, such as
+//! "at most 3 abbreviations" or "no partials longer than 5 letters". Like required words, these are
+//! global constraints that arc consistency can't express, so the search checks them separately
+//! after each choice: every slot whose word has been determined counts toward the caps on its
+//! tags, and any branch that already exceeds a cap is treated as a failed choice.
+
+use std::hash::Hasher;
+
+use crate::backtracking_search::Slot;
+use crate::grid_config::GridConfig;
+use crate::util::StableHasher;
+
+/// A limit on the entries tagged with `tag` (see `WordListSourceConfig`) that can appear in a fill.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct TagCap {
+    pub tag: String,
+
+    /// The most tagged entries the fill can contain, if limited.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_count: Option<usize>,
+
+    /// The longest a tagged entry can be, if limited.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_length: Option<usize>,
+}
+
+impl TagCap {
+    /// Add this cap to a fingerprint; see `config_fingerprint`.
+    pub(crate) fn hash_into(&self, hasher: &mut StableHasher) {
+        hasher.write_str(&self.tag);
+        hasher.write_usize(self.max_count.map_or(0, |max_count| max_count + 1));
+        hasher.write_usize(self.max_length.map_or(0, |max_length| max_length + 1));
+    }
+}
+
+/// Do the words determined so far respect all of the given caps? Slots that are still undetermined
+/// don't count, so this only rules out branches that have already gone too far.
+#[must_use]
+pub fn tag_caps_satisfied(config: &GridConfig, slots: &[Slot], caps: &[TagCap]) -> bool {
+    if caps.is_empty() {
+        return true;
+    }
+
+    let mut counts = vec![0; caps.len()];
+    for slot in slots {
+        let Some(choice) = slot.get_choice(config) else {
+            continue;
+        };
+        let tags = config
+            .word_list
+            .get_word_tags((slot.length, choice.word_id));
+        for (cap, count) in caps.iter().zip(&mut counts) {
+            if !tags.contains(&cap.tag) {
+                continue;
+            }
+            if cap
+                .max_length
+                .map_or(false, |max_length| slot.length > max_length)
+            {
+                return false;
+            }
+            *count += 1;
+            if cap.max_count.map_or(false, |max_count| *count > max_count) {
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backtracking_search::{find_fill_with_search_config, SearchConfig, SearchStrategy};
+    use crate::grid_config::{generate_grid_config_from_template_string, Choice, GridConfig};
+    use crate::tag_caps::TagCap;
+    use crate::word_list::{WordIterSource, WordList, WordListSourceConfig};
+
+    #[test]
+    fn test_tag_caps() {
+        // Every fill of the 2x2 grid uses two of the tagged words, one across and one down.
+        let word_list = || {
+            WordList::new(
+                vec![WordListSourceConfig::Iter {
+                    id: "0".into(),
+                    enabled: true,
+                    words: WordIterSource::new(|| {
+                        [("ab", true), ("cd", false), ("ac", true), ("bd", false)]
+                            .into_iter()
+                            .map(|(word, is_abbr)| {
+                                let tags = if is_abbr { vec!["abbr".into()] } else { vec![] };
+                                (word.to_string(), 50, tags)
+                            })
+                    }),
+                }],
+                None,
+                None,
+                None,
+            )
+        };
+        let count_tagged = |config: &GridConfig, choices: &[Choice]| {
+            choices
+                .iter()
+                .filter(|choice| {
+                    let length = config.slot_configs[choice.slot_id.index()].length;
+                    !config
+                        .word_list
+                        .get_word_tags((length, choice.word_id))
+                        .is_empty()
+                })
+                .count()
+        };
+
+        for strategy in [SearchStrategy::Restarts, SearchStrategy::LimitedDiscrepancy] {
+            let grid_config = generate_grid_config_from_template_string(word_list(), "..\n..", 40);
+            let config = grid_config.borrowed();
+            let fill_with_caps = |tag_caps: Vec<TagCap>| {
+                find_fill_with_search_config(
+                    &config,
+                    None,
+                    None,
+                    &SearchConfig {
+                        tag_caps,
+                        strategy,
+                        ..SearchConfig::default()
+                    },
+                )
+            };
+
+            let result = fill_with_caps(vec![TagCap {
+                tag: "abbr".into(),
+                max_count: Some(2),
+                max_length: None,
+            }])
+            .expect("two tagged entries should be allowed");
+            assert_eq!(count_tagged(&config, &result.choices), 2);
+
+            assert!(fill_with_caps(vec![TagCap {
+                tag: "abbr".into(),
+                max_count: Some(1),
+                max_length: None,
+            }])
+            .is_err());
+            assert!(fill_with_caps(vec![TagCap {
+                tag: "abbr".into(),
+                max_count: None,
+                max_length: Some(1),
+            }])
+            .is_err());
+
+            // Caps on tags that no word carries have no effect.
+            assert!(fill_with_caps(vec![TagCap {
+                tag: "partial".into(),
+                max_count: Some(0),
+                max_length: None,
+            }])
+            .is_ok());
+        }
+    }
+}
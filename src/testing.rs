@@ -0,0 +1,249 @@
+//! This module provides helpers for property-testing code built on this crate (including the crate
+//! itself) with `proptest`: strategies that generate random valid grids and word lists, and
+//! assertions that check a fill is internally consistent and survives being rendered and parsed
+//! again. It's only available with the `testing` feature.
+
+use proptest::prelude::*;
+
+use crate::grid_config::{
+    generate_slots_from_template_string, render_grid, template_string_dimensions, Choice,
+    GridConfig, SlotConfig, SlotSpec,
+};
+use crate::types::GlyphId;
+use crate::word_list::{WordList, WordListSourceConfig};
+
+/// The chance that any given cell in a generated template starts out as a block.
+const BLOCK_PROBABILITY: f64 = 0.2;
+
+/// The shortest word generated by `arb_words`, matching the shortest slot in any template.
+const MIN_WORD_LENGTH: usize = 2;
+
+/// Turn a flat array of block flags into a template string, also filling in any white cell that
+/// isn't part of a slot, since it would never be filled.
+fn build_template(width: usize, height: usize, blocks: &[bool]) -> String {
+    let is_white = |x: usize, y: usize| !blocks[y * width + x];
+    let run_length = |x: usize, y: usize, dx: usize, dy: usize| {
+        let mut length = 0;
+        let (mut cx, mut cy) = (x, y);
+        while cx < width && cy < height && is_white(cx, cy) {
+            length += 1;
+            cx += dx;
+            cy += dy;
+        }
+        length
+    };
+    let is_in_slot = |x: usize, y: usize| {
+        let left = (0..x).rev().take_while(|&cx| is_white(cx, y)).count();
+        let up = (0..y).rev().take_while(|&cy| is_white(x, cy)).count();
+        left + run_length(x, y, 1, 0) >= MIN_WORD_LENGTH
+            || up + run_length(x, y, 0, 1) >= MIN_WORD_LENGTH
+    };
+
+    (0..height)
+        .map(|y| {
+            (0..width)
+                .map(|x| {
+                    if is_white(x, y) && is_in_slot(x, y) {
+                        '.'
+                    } else {
+                        '#'
+                    }
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A strategy generating empty grid templates (see `generate_grid_config_from_template_string`)
+/// between 2x2 and `max_width` by `max_height` cells, with randomly-placed blocks. Every generated
+/// template has at least one slot, and every white cell belongs to a slot.
+pub fn arb_template(max_width: usize, max_height: usize) -> impl Strategy<Value = String> {
+    (2..=max_width.max(2), 2..=max_height.max(2))
+        .prop_flat_map(|(width, height)| {
+            proptest::collection::vec(proptest::bool::weighted(BLOCK_PROBABILITY), width * height)
+                .prop_map(move |blocks| build_template(width, height, &blocks))
+        })
+        .prop_filter("template has no slots", |template| {
+            !generate_slots_from_template_string(template).is_empty()
+        })
+}
+
+/// A strategy generating up to `max_count` scored words, between 2 and `max_length` letters long,
+/// using only the letters in `alphabet`. A small alphabet makes it likely that random grids can be
+/// filled. Use `word_list_from_words` to turn the result into a `WordList`.
+pub fn arb_words(
+    alphabet: &str,
+    max_length: usize,
+    max_count: usize,
+) -> impl Strategy<Value = Vec<(String, u16)>> {
+    let letters: Vec<char> = alphabet.chars().collect();
+    assert!(!letters.is_empty(), "alphabet must not be empty");
+
+    let word = proptest::collection::vec(
+        proptest::sample::select(letters),
+        MIN_WORD_LENGTH..=max_length.max(MIN_WORD_LENGTH),
+    )
+    .prop_map(|letters| letters.into_iter().collect::<String>());
+
+    proptest::collection::vec((word, 0..=100_u16), 0..=max_count)
+}
+
+/// Build a `WordList` from a single in-memory source containing the given words. As with any
+/// `WordList`, `max_length` should be at least the length of the longest slot it'll be used to
+/// fill, even if the list doesn't contain any words that long.
+#[must_use]
+pub fn word_list_from_words(words: &[(String, u16)], max_length: usize) -> WordList {
+    WordList::new(
+        vec![WordListSourceConfig::Memory {
+            id: "0".into(),
+            enabled: true,
+            words: words.to_vec(),
+        }],
+        None,
+        Some(max_length),
+        None,
+    )
+}
+
+/// Assert that the given choices make up a complete, valid fill of the grid: every slot is filled
+/// exactly once, with one of its available options, and the words agree with each other and with
+/// any letters already in the grid wherever they cross.
+pub fn assert_valid_fill(config: &GridConfig, choices: &[Choice]) {
+    let mut is_filled = vec![false; config.slot_configs.len()];
+    let mut grid: Vec<Option<GlyphId>> = config.fill.to_vec();
+
+    for &Choice { slot_id, word_id } in choices {
+        assert!(
+            !is_filled[slot_id.index()],
+            "slot {slot_id} was filled more than once",
+        );
+        is_filled[slot_id.index()] = true;
+
+        assert!(
+            config.slot_options[slot_id.index()].contains(&word_id),
+            "slot {slot_id} was filled with word {word_id}, which isn't one of its options",
+        );
+
+        let slot_config = &config.slot_configs[slot_id.index()];
+        let word = &config.word_list.words[slot_config.length][word_id.index()];
+        for (cell_idx, &glyph) in slot_config
+            .cell_fill_indices(config.width)
+            .into_iter()
+            .zip(&word.glyphs)
+        {
+            assert!(
+                grid[cell_idx].map_or(true, |existing| existing == glyph),
+                "slot {slot_id} conflicts with the letter in cell {cell_idx}",
+            );
+            grid[cell_idx] = Some(glyph);
+        }
+    }
+
+    if let Some(slot_id) = is_filled.iter().position(|&is_filled| !is_filled) {
+        panic!("slot {slot_id} wasn't filled");
+    }
+}
+
+/// Assert that rendering the given fill and parsing it again as a template reproduces the same
+/// grid: the same dimensions, the same slots, and the same word in each slot.
+pub fn assert_fill_round_trips(config: &GridConfig, choices: &[Choice]) {
+    let mut is_white = vec![false; config.width * config.height];
+    for slot_config in config.slot_configs.iter() {
+        for cell_idx in slot_config.cell_fill_indices(config.width) {
+            is_white[cell_idx] = true;
+        }
+    }
+
+    // `render_grid` doesn't distinguish blocks from empty cells, so we restore them ourselves.
+    let rows: Vec<Vec<char>> = render_grid(config, choices)
+        .lines()
+        .enumerate()
+        .map(|(y, line)| {
+            line.chars()
+                .enumerate()
+                .map(|(x, cell)| {
+                    if is_white[y * config.width + x] {
+                        cell
+                    } else {
+                        '#'
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    let template = rows
+        .iter()
+        .map(|row| row.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    assert_eq!(
+        template_string_dimensions(&template),
+        Ok((config.width, config.height)),
+        "rendered grid has the wrong dimensions",
+    );
+
+    let mut expected_slots: Vec<SlotSpec> = config
+        .slot_configs
+        .iter()
+        .map(SlotConfig::slot_spec)
+        .collect();
+    let mut actual_slots = generate_slots_from_template_string(&template);
+    expected_slots.sort_by_key(SlotSpec::to_key);
+    actual_slots.sort_by_key(SlotSpec::to_key);
+    assert_eq!(
+        actual_slots, expected_slots,
+        "rendered grid has different slots"
+    );
+
+    for &Choice { slot_id, word_id } in choices {
+        let slot_config = &config.slot_configs[slot_id.index()];
+        let word = &config.word_list.words[slot_config.length][word_id.index()];
+        let expected: String = word
+            .glyphs
+            .iter()
+            .map(|&glyph| config.word_list.glyphs[glyph])
+            .collect();
+        let actual: String = slot_config
+            .cell_coords()
+            .into_iter()
+            .map(|(x, y)| rows[y][x])
+            .collect();
+        assert_eq!(actual, expected, "slot {slot_id} has the wrong word");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backtracking_search::find_fill;
+    use crate::grid_config::try_generate_grid_config_from_template_string;
+    use crate::testing::{
+        arb_template, arb_words, assert_fill_round_trips, assert_valid_fill, word_list_from_words,
+    };
+    use instant::Duration;
+    use proptest::prelude::*;
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn test_random_fills_round_trip(
+            template in arb_template(5, 5),
+            words in arb_words("abc", 5, 200),
+        ) {
+            let grid_config = try_generate_grid_config_from_template_string(
+                word_list_from_words(&words, 5),
+                &template,
+                0,
+            )
+            .expect("generated template should be valid");
+            let config = grid_config.borrowed();
+
+            if let Ok(result) = find_fill(&config, Some(Duration::from_secs(1)), None) {
+                assert_valid_fill(&config, &result.choices);
+                assert_fill_round_trips(&config, &result.choices);
+            }
+        }
+    }
+}
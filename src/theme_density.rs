@@ -0,0 +1,225 @@
+//! This module implements a fill mode that tries to maximize the number (or total length) of
+//! entries carrying a given tag, such as the words from a theme list, wherever they end up in the
+//! grid. "At least N tagged entries" can't be expressed as an arc-consistency constraint, so we
+//! hill-climb instead: we start from an ordinary fill that prefers tagged words, and then keep
+//! trying to convert one more slot to a tagged word while pinning every tagged entry we've already
+//! placed, accepting the first attempt that succeeds.
+
+use instant::{Duration, Instant};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::backtracking_search::{find_fill, FillFailure, FillSuccess};
+use crate::grid_config::{Choice, GridConfig, SlotConfig, SlotId};
+use crate::types::WordId;
+
+/// The quantity that `find_fill_maximizing_theme_density` tries to maximize.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ThemeDensityObjective {
+    /// The number of tagged entries in the grid.
+    #[default]
+    EntryCount,
+
+    /// The total number of letters in tagged entries in the grid.
+    TotalLength,
+}
+
+/// Does the given word, in a slot with the given config, carry the given tag?
+fn is_tagged(config: &GridConfig, slot_config: &SlotConfig, word_id: WordId, tag: &str) -> bool {
+    config
+        .word_list
+        .get_word_tags((slot_config.length, word_id))
+        .iter()
+        .any(|word_tag| word_tag == tag)
+}
+
+/// Measure the given fill according to the given objective.
+#[must_use]
+pub fn theme_density(
+    config: &GridConfig,
+    choices: &[Choice],
+    tag: &str,
+    objective: ThemeDensityObjective,
+) -> usize {
+    choices
+        .iter()
+        .map(|choice| (&config.slot_configs[choice.slot_id.index()], choice))
+        .filter(|(slot_config, choice)| is_tagged(config, slot_config, choice.word_id, tag))
+        .map(|(slot_config, _)| match objective {
+            ThemeDensityObjective::EntryCount => 1,
+            ThemeDensityObjective::TotalLength => slot_config.length,
+        })
+        .sum()
+}
+
+/// Search for a fill for the given grid that places as many entries with the given tag as we can
+/// manage within the given amount of time. This only fails if the grid can't be filled at all (or
+/// we run out of time before finding any fill); otherwise it returns the best fill found so far,
+/// with statistics covering every attempt.
+pub fn find_fill_maximizing_theme_density(
+    config: &GridConfig,
+    tag: &str,
+    objective: ThemeDensityObjective,
+    timeout: Option<Duration>,
+) -> Result<FillSuccess, FillFailure> {
+    let start = Instant::now();
+    let deadline = timeout.map(|timeout| start + timeout);
+    let remaining_time =
+        || deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+
+    // Move each slot's tagged options to the front, so that even the first fill favors them.
+    let (tagged_options, preferred_options): (Vec<Vec<WordId>>, Vec<Vec<WordId>>) = config
+        .slot_configs
+        .iter()
+        .zip(config.slot_options.iter())
+        .map(|(slot_config, options)| {
+            let (tagged, untagged): (Vec<WordId>, Vec<WordId>) = options
+                .iter()
+                .partition(|&&word_id| is_tagged(config, slot_config, word_id, tag));
+            let preferred = tagged.iter().chain(&untagged).copied().collect();
+            (tagged, preferred)
+        })
+        .unzip();
+
+    let mut best = find_fill(
+        &GridConfig {
+            slot_options: Cow::Borrowed(&preferred_options),
+            ..config.borrowed()
+        },
+        remaining_time(),
+        None,
+    )?;
+    let mut statistics = best.statistics.clone();
+
+    'improve: loop {
+        let chosen_words: HashMap<SlotId, WordId> = best
+            .choices
+            .iter()
+            .map(|choice| (choice.slot_id, choice.word_id))
+            .collect();
+        let is_slot_tagged = |slot_config: &SlotConfig| {
+            chosen_words.get(&slot_config.id).map_or(false, |&word_id| {
+                is_tagged(config, slot_config, word_id, tag)
+            })
+        };
+
+        // Try the slots that would add the most to the objective first, breaking ties in favor of
+        // the ones with the most tagged options to choose from.
+        let mut candidate_slot_ids: Vec<SlotId> = config
+            .slot_configs
+            .iter()
+            .filter(|slot_config| {
+                !is_slot_tagged(slot_config) && !tagged_options[slot_config.id.index()].is_empty()
+            })
+            .map(|slot_config| slot_config.id)
+            .collect();
+        candidate_slot_ids.sort_by_key(|&slot_id| {
+            let gain = match objective {
+                ThemeDensityObjective::EntryCount => 1,
+                ThemeDensityObjective::TotalLength => config.slot_configs[slot_id.index()].length,
+            };
+            (
+                std::cmp::Reverse(gain),
+                std::cmp::Reverse(tagged_options[slot_id.index()].len()),
+            )
+        });
+
+        for slot_id in candidate_slot_ids {
+            // Require a tagged word in this slot, and keep every tagged entry we already have.
+            let attempt_options: Vec<Vec<WordId>> = config
+                .slot_configs
+                .iter()
+                .map(|slot_config| {
+                    if slot_config.id == slot_id {
+                        tagged_options[slot_id.index()].clone()
+                    } else if is_slot_tagged(slot_config) {
+                        vec![chosen_words[&slot_config.id]]
+                    } else {
+                        preferred_options[slot_config.id.index()].clone()
+                    }
+                })
+                .collect();
+
+            match find_fill(
+                &GridConfig {
+                    slot_options: Cow::Borrowed(&attempt_options),
+                    ..config.borrowed()
+                },
+                remaining_time(),
+                None,
+            ) {
+                Ok(result) => {
+                    statistics.accumulate(&result.statistics);
+                    best = result;
+                    continue 'improve;
+                }
+                Err(FillFailure::HardFailure(partial)) => {
+                    statistics.accumulate(&partial.statistics);
+                }
+                Err(failure) => {
+                    statistics.accumulate(&failure.partial().statistics);
+                    break 'improve;
+                }
+            }
+        }
+
+        break;
+    }
+
+    statistics.total_time = start.elapsed();
+    best.statistics = statistics;
+    Ok(best)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid_config::generate_grid_config_from_template_string;
+    use crate::theme_density::{
+        find_fill_maximizing_theme_density, theme_density, ThemeDensityObjective,
+    };
+    use crate::word_list::{WordIterSource, WordList, WordListSourceConfig};
+
+    #[test]
+    fn test_find_fill_maximizing_theme_density() {
+        // The untagged words use high-value letters, so an ordinary fill would favor them.
+        let word_list = WordList::new(
+            vec![WordListSourceConfig::Iter {
+                id: "0".into(),
+                enabled: true,
+                words: WordIterSource::new(|| {
+                    ["ab", "cd", "ac", "bd", "jx", "qz", "jq", "xz", "ak", "kz"]
+                        .into_iter()
+                        .map(|word| {
+                            let tags = if word.contains(['j', 'q', 'x', 'z', 'k']) {
+                                vec![]
+                            } else {
+                                vec!["theme".to_string()]
+                            };
+                            (word.to_string(), 50, tags)
+                        })
+                }),
+            }],
+            None,
+            Some(2),
+            None,
+        );
+        let grid_config = generate_grid_config_from_template_string(word_list, "..\n..", 40);
+        let config = grid_config.borrowed();
+
+        for objective in [
+            ThemeDensityObjective::EntryCount,
+            ThemeDensityObjective::TotalLength,
+        ] {
+            let result = find_fill_maximizing_theme_density(&config, "theme", objective, None)
+                .expect("Failed to find a fill");
+            assert_eq!(result.choices.len(), 4);
+            assert_eq!(
+                theme_density(&config, &result.choices, "theme", objective),
+                match objective {
+                    ThemeDensityObjective::EntryCount => 4,
+                    ThemeDensityObjective::TotalLength => 8,
+                }
+            );
+        }
+    }
+}
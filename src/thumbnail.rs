@@ -0,0 +1,102 @@
+//! This module (enabled with the `image` feature) renders grids as small raster images, for
+//! gallery views and batch reports where a text rendering would be too large to scan. Thumbnails
+//! don't include letters, which wouldn't be legible at this size anyway; instead, cells outside of
+//! any slot are drawn in black, filled cells in gray, and empty cells in white, so a thumbnail
+//! shows both the grid's shape and how much of it has been filled.
+
+use image::{ImageFormat, Rgb, RgbImage};
+use std::io::Cursor;
+
+use crate::grid_config::{filled_cells, Choice, GridConfig};
+
+/// The size of each cell in pixels when none is specified, not counting the lines between cells.
+pub const DEFAULT_THUMBNAIL_CELL_SIZE: u32 = 8;
+
+const LINE_COLOR: Rgb<u8> = Rgb([128, 128, 128]);
+const BLOCK_COLOR: Rgb<u8> = Rgb([0, 0, 0]);
+const FILLED_COLOR: Rgb<u8> = Rgb([200, 200, 200]);
+const EMPTY_COLOR: Rgb<u8> = Rgb([255, 255, 255]);
+
+/// Draw the given fill as an image with `cell_size` pixels per cell, plus a one-pixel line around
+/// and between the cells; see the module docs.
+#[must_use]
+pub fn render_thumbnail(config: &GridConfig, choices: &[Choice], cell_size: u32) -> RgbImage {
+    let cells = filled_cells(config, choices);
+    let mut in_slot = vec![false; config.width * config.height];
+    for slot_config in config.slot_configs.iter() {
+        for cell_idx in slot_config.cell_fill_indices(config.width) {
+            in_slot[cell_idx] = true;
+        }
+    }
+
+    let pitch = cell_size + 1;
+    let mut image = RgbImage::from_pixel(
+        config.width as u32 * pitch + 1,
+        config.height as u32 * pitch + 1,
+        LINE_COLOR,
+    );
+    for (cell_idx, (cell, &in_slot)) in cells.iter().zip(&in_slot).enumerate() {
+        let color = match cell {
+            _ if !in_slot => BLOCK_COLOR,
+            Some(_) => FILLED_COLOR,
+            None => EMPTY_COLOR,
+        };
+        let x = (cell_idx % config.width) as u32 * pitch + 1;
+        let y = (cell_idx / config.width) as u32 * pitch + 1;
+        for dy in 0..cell_size {
+            for dx in 0..cell_size {
+                image.put_pixel(x + dx, y + dy, color);
+            }
+        }
+    }
+    image
+}
+
+/// Draw the given fill with `render_thumbnail` and encode it as a PNG file.
+pub fn thumbnail_png(
+    config: &GridConfig,
+    choices: &[Choice],
+    cell_size: u32,
+) -> Result<Vec<u8>, image::ImageError> {
+    let mut png = Cursor::new(vec![]);
+    render_thumbnail(config, choices, cell_size).write_to(&mut png, ImageFormat::Png)?;
+    Ok(png.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid_config::{generate_grid_config_from_template_string, Choice};
+    use crate::thumbnail::{render_thumbnail, thumbnail_png};
+    use crate::types::SlotId;
+    use crate::word_list::{WordList, WordListSourceConfig};
+
+    #[test]
+    fn test_thumbnail() {
+        let word_list = WordList::new(
+            vec![WordListSourceConfig::Memory {
+                id: "0".into(),
+                enabled: true,
+                words: vec![("abc".into(), 50)],
+            }],
+            None,
+            None,
+            None,
+        );
+        let config = generate_grid_config_from_template_string(word_list, "...\n.#.\n...", 40);
+        let choices = vec![Choice {
+            slot_id: SlotId(0),
+            word_id: config.word_list.word_id_by_string["abc"],
+        }];
+
+        let image = render_thumbnail(&config, &choices, 2);
+        assert_eq!(image.dimensions(), (10, 10));
+        assert_eq!(image.get_pixel(0, 0).0, [128, 128, 128]);
+        assert_eq!(image.get_pixel(1, 1).0, [200, 200, 200]);
+        assert_eq!(image.get_pixel(4, 4).0, [0, 0, 0]);
+        assert_eq!(image.get_pixel(7, 8).0, [255, 255, 255]);
+
+        let png = thumbnail_png(&config, &choices, 2).unwrap();
+        assert_eq!(png[..8], *b"\x89PNG\r\n\x1a\n");
+        assert_eq!(image::load_from_memory(&png).unwrap().to_rgb8(), image);
+    }
+}
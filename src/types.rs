@@ -1,10 +1,80 @@
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
 /// An identifier for a given letter or symbol, based on its index in the `WordList`'s `glyphs`
 /// field.
 pub type GlyphId = usize;
 
-/// An identifier for a given word, based on its index in the `WordList`'s `words` field (scoped to
-/// the relevant length bucket).
-pub type WordId = usize;
+/// Define a newtype wrapping a `usize` index, so that ids referring to different kinds of things
+/// can't be mixed up. Use `index()` to look an id up in a `Vec` or slice.
+macro_rules! define_id {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[cfg_attr(
+            feature = "serde",
+            derive(serde_derive::Serialize, serde_derive::Deserialize)
+        )]
+        #[cfg_attr(feature = "serde", serde(transparent))]
+        pub struct $name(pub usize);
+
+        impl $name {
+            /// The underlying index.
+            #[must_use]
+            pub const fn index(self) -> usize {
+                self.0
+            }
+
+            /// The id `delta` positions after this one.
+            #[must_use]
+            pub const fn offset(self, delta: usize) -> $name {
+                $name(self.0 + delta)
+            }
+
+            /// Iterate over all ids for a collection of the given size.
+            pub fn range(count: usize) -> impl DoubleEndedIterator<Item = $name> + Clone {
+                (0..count).map($name)
+            }
+        }
+
+        impl From<usize> for $name {
+            fn from(index: usize) -> $name {
+                $name(index)
+            }
+        }
+
+        impl From<$name> for usize {
+            fn from(id: $name) -> usize {
+                id.0
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                Display::fmt(&self.0, f)
+            }
+        }
+    };
+}
+
+define_id!(
+    /// An identifier for a given word, based on its index in the `WordList`'s `words` field (scoped
+    /// to the relevant length bucket).
+    WordId
+);
+
+define_id!(
+    /// An identifier for a given slot, based on its index in the `GridConfig`'s `slot_configs`
+    /// field.
+    SlotId
+);
+
+define_id!(
+    /// An identifier for the intersection between two slots; these correspond one-to-one with
+    /// checked squares in the grid and are used to track weights (i.e., how often each square is
+    /// involved in a domain wipeout).
+    CrossingId
+);
 
 /// An identifier that fully specifies a word by including both its length and `WordId`.
 pub type GlobalWordId = (usize, WordId);
@@ -0,0 +1,293 @@
+//! This module fetches word lists over HTTP for `WordListSourceConfig::Url`, mirroring the URL
+//! loading that the Wasm layer does through the browser's `fetch`. Responses can be cached to disk
+//! along with their `ETag`, so that refreshing an unchanged list only costs a conditional request,
+//! and so that a previously-fetched list is still usable when the server can't be reached.
+
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::error::WordListError;
+
+/// How long we wait to connect to the server before giving up (or falling back to the cache).
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long we wait for each read from the server, so that one that accepts the connection but
+/// stops responding can't block loading forever.
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The name under which we cache the given URL. This uses 64-bit FNV-1a rather than
+/// `DefaultHasher`, since the cache needs to survive across builds and `DefaultHasher`'s algorithm
+/// isn't guaranteed to stay the same.
+fn cache_key(url: &str) -> String {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+    let hash = url.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    });
+    format!("{hash:016x}")
+}
+
+/// The paths where we store the contents and `ETag` of the given URL within a cache directory.
+fn cache_paths(cache_dir: &Path, url: &str) -> (PathBuf, PathBuf) {
+    let key = cache_key(url);
+
+    (
+        cache_dir.join(format!("{key}.txt")),
+        cache_dir.join(format!("{key}.etag")),
+    )
+}
+
+/// Write the given file by writing a temporary file next to it and renaming it into place, so that
+/// a crash partway through leaves either the old contents or the new ones.
+fn write_atomically(path: &Path, contents: &str) -> io::Result<()> {
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    fs::write(&temp_path, contents)?;
+    fs::rename(&temp_path, path)
+}
+
+/// Save the given response body and `ETag` to the cache. The old `ETag` is removed before the new
+/// body is written, so that a crash can't leave a new body paired with a stale `ETag` (or vice
+/// versa), only a body without one. Failing to write the cache isn't fatal, since we already have
+/// the contents we need.
+fn write_cache(cache_dir: &Path, url: &str, contents: &str, etag: Option<&str>) {
+    let (contents_path, etag_path) = cache_paths(cache_dir, url);
+
+    if fs::create_dir_all(cache_dir).is_err() {
+        return;
+    }
+    if let Err(err) = fs::remove_file(&etag_path) {
+        if err.kind() != io::ErrorKind::NotFound {
+            return;
+        }
+    }
+    if write_atomically(&contents_path, contents).is_err() {
+        return;
+    }
+    if let Some(etag) = etag {
+        write_atomically(&etag_path, etag).ok();
+    }
+}
+
+/// Fetch the word list at the given URL, using and updating the cache in `cache_dir` if provided.
+/// If the request fails but we have a cached copy, we return that copy and still add an error to
+/// `errors`, since the list may be out of date. Connecting times out after `CONNECT_TIMEOUT` and
+/// each read after `READ_TIMEOUT`, so an unresponsive server is treated as a failed request.
+pub fn fetch_word_list(
+    url: &str,
+    cache_dir: Option<&Path>,
+    errors: &mut Vec<WordListError>,
+) -> Option<String> {
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(CONNECT_TIMEOUT)
+        .timeout_read(READ_TIMEOUT)
+        .build();
+
+    fetch_word_list_with_agent(&agent, url, cache_dir, errors)
+}
+
+/// The implementation of `fetch_word_list`, which lets tests use shorter timeouts.
+fn fetch_word_list_with_agent(
+    agent: &ureq::Agent,
+    url: &str,
+    cache_dir: Option<&Path>,
+    errors: &mut Vec<WordListError>,
+) -> Option<String> {
+    let fetch_error = |reason: String| WordListError::FetchFailed {
+        url: url.into(),
+        reason,
+    };
+
+    let cached_contents = cache_dir.and_then(|cache_dir| {
+        let (contents_path, etag_path) = cache_paths(cache_dir, url);
+        let contents = fs::read_to_string(contents_path).ok()?;
+        Some((contents, fs::read_to_string(etag_path).ok()))
+    });
+
+    let mut request = agent.get(url);
+    if let Some((_, Some(etag))) = &cached_contents {
+        request = request.set("If-None-Match", etag);
+    }
+
+    let response = match request.call() {
+        Ok(response) => response,
+
+        // If the server can't be reached or responds with an error status, a stale copy is better
+        // than nothing.
+        Err(err) => {
+            if let Some((contents, _)) = cached_contents {
+                errors.push(fetch_error(format!("{err} (using cached copy)")));
+                return Some(contents);
+            }
+            errors.push(fetch_error(err.to_string()));
+            return None;
+        }
+    };
+
+    if response.status() == 304 {
+        if let Some((contents, _)) = cached_contents {
+            return Some(contents);
+        }
+        errors.push(fetch_error("unexpected 304 response".into()));
+        return None;
+    }
+
+    let etag = response.header("ETag").map(String::from);
+    let mut contents = String::new();
+    if let Err(err) = response.into_reader().read_to_string(&mut contents) {
+        errors.push(fetch_error(err.to_string()));
+        return None;
+    }
+
+    if let Some(cache_dir) = cache_dir {
+        write_cache(cache_dir, url, &contents, etag.as_deref());
+    }
+
+    Some(contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::WordListError;
+    use crate::url_source::{
+        cache_key, cache_paths, fetch_word_list, fetch_word_list_with_agent, write_cache,
+    };
+    use std::fs;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    /// Serve the given canned responses, one per connection, recording each request's
+    /// `If-None-Match` header.
+    fn serve(responses: Vec<&'static str>) -> (String, thread::JoinHandle<Vec<Option<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/words.txt", listener.local_addr().unwrap());
+
+        let handle = thread::spawn(move || {
+            let mut etags = vec![];
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut etag = None;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("if-none-match") {
+                            etag = Some(value.trim().to_string());
+                        }
+                    }
+                }
+                etags.push(etag);
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+            etags
+        });
+
+        (url, handle)
+    }
+
+    #[test]
+    fn test_fetch_word_list_with_etag_cache() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let (url, handle) = serve(vec![
+            "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: 10\r\nConnection: close\r\n\r\nwolves;51\n",
+            "HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nConnection: close\r\n\r\n",
+        ]);
+
+        let mut errors = vec![];
+        let first = fetch_word_list(&url, Some(cache_dir.path()), &mut errors).unwrap();
+        let second = fetch_word_list(&url, Some(cache_dir.path()), &mut errors).unwrap();
+        assert_eq!(first, "wolves;51\n");
+        assert_eq!(second, first);
+        assert!(errors.is_empty());
+        assert_eq!(handle.join().unwrap(), vec![None, Some("\"v1\"".into())]);
+
+        // Once the server is gone, we fall back to the cached copy but still report the failure.
+        assert_eq!(
+            fetch_word_list(&url, Some(cache_dir.path()), &mut errors).unwrap(),
+            first
+        );
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn test_fetch_word_list_error_status() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let (url, handle) = serve(vec![
+            "HTTP/1.1 200 OK\r\nContent-Length: 10\r\nConnection: close\r\n\r\nwolves;51\n",
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        ]);
+
+        let mut errors = vec![];
+        let first = fetch_word_list(&url, Some(cache_dir.path()), &mut errors).unwrap();
+        let second = fetch_word_list(&url, Some(cache_dir.path()), &mut errors).unwrap();
+        handle.join().unwrap();
+
+        assert_eq!(second, first);
+        assert!(
+            matches!(&errors[..], [WordListError::FetchFailed { reason, .. }] if reason.contains("404")),
+            "unexpected errors: {errors:?}"
+        );
+
+        // Without a cache, the failure leaves us with nothing.
+        let mut errors = vec![];
+        assert_eq!(fetch_word_list(&url, None, &mut errors), None);
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn test_fetch_word_list_read_timeout() {
+        // A server that accepts connections but never responds.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/words.txt", listener.local_addr().unwrap());
+
+        let agent = ureq::AgentBuilder::new()
+            .timeout_read(Duration::from_millis(100))
+            .build();
+        let start = Instant::now();
+        let mut errors = vec![];
+        assert_eq!(
+            fetch_word_list_with_agent(&agent, &url, None, &mut errors),
+            None
+        );
+        assert!(start.elapsed() < Duration::from_secs(10));
+        assert!(
+            matches!(&errors[..], [WordListError::FetchFailed { .. }]),
+            "unexpected errors: {errors:?}"
+        );
+        drop(listener);
+    }
+
+    #[test]
+    fn test_write_cache() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let url = "http://example.com/words.txt";
+        let (contents_path, etag_path) = cache_paths(cache_dir.path(), url);
+
+        write_cache(cache_dir.path(), url, "wolves;51\n", Some("\"v1\""));
+        assert_eq!(fs::read_to_string(&contents_path).unwrap(), "wolves;51\n");
+        assert_eq!(fs::read_to_string(&etag_path).unwrap(), "\"v1\"");
+
+        // A response without an `ETag` replaces the body and drops the stale `ETag`.
+        write_cache(cache_dir.path(), url, "wolves;52\n", None);
+        assert_eq!(fs::read_to_string(&contents_path).unwrap(), "wolves;52\n");
+        assert!(!etag_path.exists());
+        assert_eq!(fs::read_dir(cache_dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_cache_key_is_stable() {
+        assert_eq!(cache_key(""), "cbf29ce484222325");
+        assert_eq!(cache_key("a"), "af63dc4c8601ec8c");
+    }
+}
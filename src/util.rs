@@ -1,13 +1,15 @@
+use std::hash::Hasher;
+
 use crate::types::WordId;
 use crate::word_list::WordList;
 
 /// Structure used to efficiently prune options based on their crossings. One of these reflects all
 /// of the currently-available options for a single slot.
 ///
-/// The outer `Vec` has an entry for each cell; each of these entries consists of a `SmallVec`
-/// indexed by `GlyphId`, containing the number of times that glyph occurs in that position in
-/// all of the available options.
-///
+/// The outer `Vec` has an entry for each cell; each of these entries consists of a `Vec` indexed by
+/// `GlyphId`, containing the number of times that glyph occurs in that position in all of the
+/// available options. The inner `Vec`s are as wide as the word list's set of glyphs, so there's no
+/// fixed limit on the size of the alphabet (letters, digits, and any other symbols all count).
 pub type GlyphCountsByCell = Vec<Vec<u32>>;
 
 /// Initialize the `glyph_counts_by_cell` structure for a slot.
@@ -22,7 +24,7 @@ pub fn build_glyph_counts_by_cell(
         .collect();
 
     for &word_id in options {
-        let word = &word_list.words[slot_length][word_id];
+        let word = &word_list.words[slot_length][word_id.index()];
         for (cell_idx, &glyph) in word.glyphs.iter().enumerate() {
             result[cell_idx][glyph] += 1;
         }
@@ -30,3 +32,64 @@ pub fn build_glyph_counts_by_cell(
 
     result
 }
+
+/// A 64-bit FNV-1a hasher. Unlike `DefaultHasher`, its output is specified, so hashes computed
+/// with it can be stored (e.g., alongside a published puzzle) and compared across builds.
+/// Integers are hashed in little-endian order regardless of platform.
+#[derive(Debug, Clone)]
+pub struct StableHasher {
+    state: u64,
+}
+
+impl StableHasher {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    #[must_use]
+    pub fn new() -> StableHasher {
+        StableHasher {
+            state: Self::OFFSET_BASIS,
+        }
+    }
+
+    /// Hash a string along with its length, so that consecutive strings can't run together.
+    pub fn write_str(&mut self, string: &str) {
+        self.write_usize(string.len());
+        self.write(string.as_bytes());
+    }
+}
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        StableHasher::new()
+    }
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.state
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.state ^= u64::from(byte);
+            self.state = self.state.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+}
@@ -1,20 +1,153 @@
 // No longer need to import find_fill as we use find_fill_wasm
-use crate::grid_config::{generate_grid_config_from_template_string, render_grid, GridConfig};
-use crate::word_list::{WordList, WordListSourceConfig};
-use crate::backtracking_search::{Slot, FillSuccess, FillFailure, WEIGHT_AGE_FACTOR, ArcConsistencyMode};
-use crate::arc_consistency::EliminationSet;
-use std::collections::HashSet;
+use crate::crossing_report::summarize_crossing_weights;
+use crate::crossing_weights::CrossingWeights;
+use crate::error::{FillError, GridError};
+#[cfg(feature = "serde")]
+use crate::fill_schema::{FillRequest, FillResponse};
+use crate::grid_config::{generate_slots_from_template_string, Direction, render_grid, template_string_dimensions, try_generate_grid_config_from_hinted_template_string, try_generate_grid_config_from_template_string, GridConfig, OwnedGridConfig};
+use crate::suggestions::find_slot_at;
+use crate::word_list::{StreamingWordListParser, WordList, WordListError, WordListSourceConfig};
+use crate::backtracking_search::{build_slots, slot_fill_stats, Slot, FillSuccess, ArcConsistencyMode, INTERRUPT_FREQUENCY};
+use crate::arc_consistency::{EliminationSet, EliminationSetPool};
+#[cfg(feature = "wasm-threads")]
+use crate::backtracking_search::SearchConfig;
+#[cfg(feature = "wasm-threads")]
+use crate::parallel_search::find_fill_parallel_with_pools;
+use std::cell::RefCell;
+#[cfg(feature = "wasm-threads")]
+use std::sync::atomic::{AtomicUsize, Ordering};
 use unicode_normalization::UnicodeNormalization;
 use wasm_bindgen::prelude::*;
 // use web_sys::console;
 #[wasm_bindgen(start)]
-pub fn initialize() -> Result<(), JsError> {
+pub fn start() -> Result<(), JsError> {
     // Set the panic hook for better error messages
     crate::set_panic_hook();
     Ok(())
 }
 const STWL_RAW: &str = include_str!("../resources/XwiWordList.txt");
 
+/// The minimum score used when neither the call nor `initialize` gives one.
+const DEFAULT_MIN_SCORE: u16 = 50;
+
+/// Defaults set by `initialize` for the calls that follow it.
+#[derive(Debug, Clone, Default)]
+struct Defaults {
+    min_score: Option<u16>,
+    word_list_source: Option<String>,
+}
+
+thread_local! {
+    static DEFAULTS: RefCell<Defaults> = RefCell::new(Defaults::default());
+
+    /// Elimination sets kept around between fills, since allocating them is a significant part of
+    /// the cost of filling a grid with a large word list.
+    static ELIMINATION_SET_POOL: RefCell<EliminationSetPool> = RefCell::new(EliminationSetPool::new());
+
+    /// Elimination sets for parallel fills, one for the calling thread plus one per worker.
+    #[cfg(feature = "wasm-threads")]
+    static PARALLEL_ELIMINATION_SET_POOLS: RefCell<Vec<EliminationSetPool>> = RefCell::new(vec![]);
+}
+
+// The JS glue for starting Rayon's workers, which `init_parallel_fill` relies on.
+#[cfg(feature = "wasm-threads")]
+pub use wasm_bindgen_rayon::init_thread_pool;
+
+/// The number of worker threads started by `init_parallel_fill`, or 0 if fills are single-threaded.
+#[cfg(feature = "wasm-threads")]
+static PARALLEL_THREAD_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Start the given number of Web Workers and use them for all later fills. This needs
+/// SharedArrayBuffer, so the page must be cross-origin isolated; if it isn't, this returns an error
+/// and fills keep running on a single thread. Since a parallel fill blocks its calling thread
+/// until the workers are done, fills must then be run from a worker rather than the main thread.
+#[cfg(feature = "wasm-threads")]
+#[wasm_bindgen]
+pub async fn init_parallel_fill(thread_count: usize) -> Result<(), JsError> {
+    wasm_bindgen_futures::JsFuture::from(init_thread_pool(thread_count))
+        .await
+        .map_err(|_| JsError::new("Failed to start worker threads; is the page cross-origin isolated?"))?;
+    PARALLEL_THREAD_COUNT.store(thread_count, Ordering::Relaxed);
+    Ok(())
+}
+
+/// The version of the API exported by this module, reported by `capabilities`. This is bumped
+/// whenever an export is removed or its signature changes incompatibly, independently of the
+/// crate's own version. Version 2 renamed the start function from `initialize` to `start`, and
+/// `initialize` now sets defaults for later calls.
+pub const WASM_API_VERSION: u32 = 2;
+
+/// What this build of the module supports, as reported by `capabilities`.
+#[wasm_bindgen(getter_with_clone)]
+pub struct Capabilities {
+    pub api_version: u32,
+    pub crate_version: String,
+
+    /// Whether the module was built with `wasm-threads`, so that `init_parallel_fill` exists.
+    pub threads: bool,
+
+    /// The number of workers started by `init_parallel_fill`, or 0 if fills are single-threaded.
+    pub worker_count: usize,
+
+    /// The grid formats accepted for filling: "template" (`fill_grid`) and, in builds with the
+    /// `serde` feature, "json" (`fill_grid_json`).
+    pub formats: Vec<String>,
+
+    /// Whether `suggest_words` is available.
+    pub suggest: bool,
+
+    /// Whether a word list is built into the module, to be used when no source is given.
+    pub embedded_word_list: bool,
+}
+
+/// Describe what this build of the module supports, so that frontends can check for a feature
+/// before using it rather than calling it and catching the error.
+#[wasm_bindgen]
+pub fn capabilities() -> Capabilities {
+    #[cfg(feature = "wasm-threads")]
+    let worker_count = PARALLEL_THREAD_COUNT.load(Ordering::Relaxed);
+    #[cfg(not(feature = "wasm-threads"))]
+    let worker_count = 0;
+
+    let mut formats = vec!["template".to_string()];
+    if cfg!(feature = "serde") {
+        formats.push("json".into());
+    }
+
+    Capabilities {
+        api_version: WASM_API_VERSION,
+        crate_version: env!("CARGO_PKG_VERSION").into(),
+        threads: cfg!(feature = "wasm-threads"),
+        worker_count,
+        formats,
+        suggest: true,
+        embedded_word_list: !STWL_RAW.is_empty(),
+    }
+}
+
+/// Set defaults for the calls that follow on this thread, so that they don't each need to be
+/// given the same arguments: the minimum score for `fill_grid`, `fill_grid_json`, and
+/// `suggest_words`, and the word list URL (or path) for every call that loads a word list. Calls
+/// that pass their own value still use it. Calling this again replaces all of the defaults, so
+/// calling it with no arguments goes back to the built-in ones.
+#[wasm_bindgen]
+pub fn initialize(min_score: Option<u16>, word_list_source: Option<String>) {
+    crate::set_panic_hook();
+    DEFAULTS.with(|defaults| {
+        *defaults.borrow_mut() = Defaults {
+            min_score,
+            word_list_source,
+        };
+    });
+}
+
+/// The minimum score to use for a call that doesn't give one.
+fn default_min_score() -> u16 {
+    DEFAULTS
+        .with(|defaults| defaults.borrow().min_score)
+        .unwrap_or(DEFAULT_MIN_SCORE)
+}
+
 /// A struct to batch multiple strings into a single allocation
 /// to reduce JS-WASM boundary crossings
 struct BatchedStrings {
@@ -48,81 +181,311 @@ impl BatchedStrings {
         &self.buffer[start..end]
     }
 }
+/// Fetch the word list at the given URL, feeding the response body to a
+/// `StreamingWordListParser` chunk by chunk as it arrives, so that the raw file never has to be
+/// held in memory all at once.
+async fn fetch_word_list(url: &str) -> Result<WordListSourceConfig, JsError> {
+    use wasm_bindgen::JsCast;
+    let fetch_error = |_| JsError::new(&format!("Failed to fetch word list: {url}"));
+
+    let window = web_sys::window().ok_or_else(|| JsError::new("No window available for fetch"))?;
+    let resp_value = wasm_bindgen_futures::JsFuture::from(window.fetch_with_str(url))
+        .await
+        .map_err(fetch_error)?;
+    let response: web_sys::Response = resp_value.dyn_into().map_err(fetch_error)?;
+    if !response.ok() {
+        return Err(JsError::new("Network response was not OK"));
+    }
+    let body = response
+        .body()
+        .ok_or_else(|| JsError::new("Word list response has no body"))?;
+    let reader = web_sys::ReadableStreamDefaultReader::new(&body).map_err(fetch_error)?;
+
+    let mut parser = StreamingWordListParser::new();
+    loop {
+        let result: web_sys::ReadableStreamReadResult =
+            wasm_bindgen_futures::JsFuture::from(reader.read())
+                .await
+                .map_err(fetch_error)?
+                .unchecked_into();
+        if result.get_done().unwrap_or(false) {
+            break;
+        }
+        parser.push(&js_sys::Uint8Array::new(&result.get_value()).to_vec());
+    }
+
+    let (source, errors) = parser.finish("0");
+    report_word_list_errors(&errors)?;
+    Ok(source)
+}
+
+/// Choose the word list source for a URL or a file path, falling back on the one given to
+/// `initialize` and then on the built-in word list.
+async fn load_word_list_source(
+    word_list_source: Option<String>,
+) -> Result<WordListSourceConfig, JsError> {
+    let word_list_source = word_list_source
+        .or_else(|| DEFAULTS.with(|defaults| defaults.borrow().word_list_source.clone()));
+    match word_list_source {
+        Some(src) if src.starts_with("http://") || src.starts_with("https://") => {
+            fetch_word_list(&src).await
+        }
+        Some(src) => Ok(WordListSourceConfig::File {
+            id: "0".into(),
+            enabled: true,
+            path: src.into(),
+        }),
+        None => Ok(WordListSourceConfig::FileContents {
+            id: "0".into(),
+            enabled: true,
+            contents: STWL_RAW.into(),
+        }),
+    }
+}
+
+/// Turn any problems found while parsing the word list into a single error.
+fn report_word_list_errors(errors: &[WordListError]) -> Result<(), JsError> {
+    #[allow(clippy::comparison_chain)]
+    if errors.len() == 1 {
+        return Err(JsError::new(&errors[0].to_string()));
+    } else if errors.len() > 1 {
+        let mut full_error = String::new();
+        for error in errors {
+            full_error.push_str(&format!("\n- {error}"));
+        }
+        return Err(JsError::new(&full_error));
+    }
+    Ok(())
+}
+
+/// Report any problems with the sources making up the given word list.
+fn check_word_list(word_list: &WordList) -> Result<(), JsError> {
+    if let Some(errors) = word_list.get_source_errors().get("0") {
+        report_word_list_errors(errors)?;
+    }
+
+    if word_list.word_id_by_string.is_empty() {
+        return Err(FillError::EmptyWordList.into());
+    }
+    Ok(())
+}
+
+/// Normalize a grid template passed in from JS. Unless `soft_uppercase` is set, letters are
+/// lowercased, since either case locks a letter in.
+fn normalize_template(grid_content: &str, soft_uppercase: bool) -> String {
+    let template = grid_content.trim().nfkd().collect::<String>();
+    if soft_uppercase {
+        template
+    } else {
+        template.to_lowercase()
+    }
+}
+
+/// Build a grid config from a template normalized by `normalize_template`, treating uppercase
+/// letters as suggestions if `soft_uppercase` is set.
+fn grid_config_from_template(
+    word_list: WordList,
+    template: &str,
+    min_score: u16,
+    soft_uppercase: bool,
+) -> Result<OwnedGridConfig, GridError> {
+    if soft_uppercase {
+        try_generate_grid_config_from_hinted_template_string(word_list, template, min_score)
+    } else {
+        try_generate_grid_config_from_template_string(word_list, template, min_score)
+    }
+}
+
+/// How often a Wasm fill stops to check whether it's been aborted, and whether it should hand
+/// control back to the browser while it's stopped.
+#[derive(Debug, Clone, Copy)]
+struct InterruptConfig {
+    /// The number of search states between checks.
+    frequency: usize,
+
+    /// If given, the search yields to the event loop at the first check after this many
+    /// milliseconds have passed since it last yielded, so that a fill running on the main thread
+    /// doesn't freeze the page.
+    yield_interval_ms: Option<f64>,
+}
+
+impl InterruptConfig {
+    fn new(frequency: Option<usize>, yield_interval_ms: Option<f64>) -> Result<InterruptConfig, JsError> {
+        let frequency = frequency.unwrap_or(INTERRUPT_FREQUENCY);
+        if frequency == 0 {
+            return Err(JsError::new("If given, interrupt frequency must be at least 1"));
+        }
+        Ok(InterruptConfig { frequency, yield_interval_ms })
+    }
+}
+
+impl Default for InterruptConfig {
+    fn default() -> Self {
+        InterruptConfig { frequency: INTERRUPT_FREQUENCY, yield_interval_ms: None }
+    }
+}
+
+/// The state of an `InterruptConfig` over the course of a fill.
+struct Interrupts {
+    config: InterruptConfig,
+    last_yield_ms: f64,
+}
+
+impl Interrupts {
+    fn new(config: InterruptConfig) -> Interrupts {
+        Interrupts { config, last_yield_ms: js_sys::Date::now() }
+    }
+
+    /// Yield to the event loop if it's been long enough since we last did.
+    async fn maybe_yield(&mut self) {
+        let Some(interval) = self.config.yield_interval_ms else {
+            return;
+        };
+        if js_sys::Date::now() - self.last_yield_ms >= interval {
+            yield_to_event_loop().await;
+            self.last_yield_ms = js_sys::Date::now();
+        }
+    }
+}
+
+/// Wait for a zero-length `setTimeout`, which lets the browser render and handle input before we
+/// continue. This works in both windows and workers.
+async fn yield_to_event_loop() {
+    use wasm_bindgen::JsCast;
+
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        let global = js_sys::global();
+        let set_timeout = js_sys::Reflect::get(&global, &JsValue::from_str("setTimeout"))
+            .and_then(JsCast::dyn_into::<js_sys::Function>);
+        let scheduled = set_timeout
+            .and_then(|set_timeout| set_timeout.call2(&global, &resolve, &JsValue::from(0)));
+        if scheduled.is_err() {
+            let _ = resolve.call0(&JsValue::UNDEFINED);
+        }
+    });
+    let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+}
+
 // Buffer pool removed for debugging WASM unreachable error
-/// WASM-compatible function to fill a crossword grid
+/// WASM-compatible function to fill a crossword grid. The search checks for interruptions every
+/// `interrupt_frequency` states (default `INTERRUPT_FREQUENCY`); if `yield_interval_ms` is given,
+/// it also yields to the event loop during those checks whenever that much time has passed since
+/// it last did. Small grids are fine with long intervals, but a big open grid filled on the main
+/// thread needs one short enough to keep the page responsive (e.g., 16ms for 60fps). Letters of
+/// either case are locked in unless `soft_uppercase` is true, in which case uppercase ones are
+/// only suggestions.
 #[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
 pub async fn fill_grid(
     grid_content: &str,
     min_score: Option<u16>,
     max_shared_substring: Option<usize>,
-    word_list_source: Option<String>
+    word_list_source: Option<String>,
+    forbidden_words: Option<Vec<String>>,
+    interrupt_frequency: Option<usize>,
+    yield_interval_ms: Option<f64>,
+    soft_uppercase: Option<bool>,
 ) -> Result<String, JsError> {
+    let (grid_config, result) = fill_grid_config(
+        grid_content,
+        min_score,
+        max_shared_substring,
+        word_list_source,
+        forbidden_words,
+        interrupt_frequency,
+        yield_interval_ms,
+        soft_uppercase,
+    )
+    .await?;
+
+    // Return the filled grid as a string
+    let rendered_grid = render_grid(&grid_config, &result.choices).replace('.', "#");
+    Ok(rendered_grid)
+}
+
+/// A fill returned by `fill_grid_with_stats`.
+#[cfg(feature = "serde")]
+#[wasm_bindgen(getter_with_clone)]
+pub struct FillResult {
+    /// The filled grid, as returned by `fill_grid`.
+    pub grid: String,
+    stats: String,
+}
+
+#[cfg(feature = "serde")]
+#[wasm_bindgen]
+impl FillResult {
+    /// The search's statistics, crossing weights, and per-slot details as a single JSON
+    /// document; see `fill_stats.rs`.
+    pub fn stats_json(&self) -> String {
+        self.stats.clone()
+    }
+}
+
+/// WASM-compatible function to fill a crossword grid like `fill_grid` (which see for the
+/// arguments), returning the search's statistics along with the filled grid.
+#[cfg(feature = "serde")]
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub async fn fill_grid_with_stats(
+    grid_content: &str,
+    min_score: Option<u16>,
+    max_shared_substring: Option<usize>,
+    word_list_source: Option<String>,
+    forbidden_words: Option<Vec<String>>,
+    interrupt_frequency: Option<usize>,
+    yield_interval_ms: Option<f64>,
+    soft_uppercase: Option<bool>,
+) -> Result<FillResult, JsError> {
+    let (grid_config, result) = fill_grid_config(
+        grid_content,
+        min_score,
+        max_shared_substring,
+        word_list_source,
+        forbidden_words,
+        interrupt_frequency,
+        yield_interval_ms,
+        soft_uppercase,
+    )
+    .await?;
+
+    Ok(FillResult {
+        grid: render_grid(&grid_config, &result.choices).replace('.', "#"),
+        stats: result.stats_json(&grid_config),
+    })
+}
+
+/// Build the grid config for `fill_grid` and fill it, returning both.
+#[allow(clippy::too_many_arguments)]
+async fn fill_grid_config(
+    grid_content: &str,
+    min_score: Option<u16>,
+    max_shared_substring: Option<usize>,
+    word_list_source: Option<String>,
+    forbidden_words: Option<Vec<String>>,
+    interrupt_frequency: Option<usize>,
+    yield_interval_ms: Option<f64>,
+    soft_uppercase: Option<bool>,
+) -> Result<(OwnedGridConfig, FillSuccess), JsError> {
+    let soft_uppercase = soft_uppercase.unwrap_or(false);
+    let interrupt_config = InterruptConfig::new(interrupt_frequency, yield_interval_ms)?;
+
     // Create a batched strings container to hold all strings with a single allocation
-    let mut batched_strings = BatchedStrings::with_capacity(
-        grid_content.len() + if word_list_source.is_none() { STWL_RAW.len() } else { 1024 * 1024 }
-    );
+    let mut batched_strings = BatchedStrings::with_capacity(grid_content.len());
 
     // Add grid content to the batch for normalization later
     let grid_content_idx = batched_strings.add(grid_content);
 
-    // Load the word list content from a URL or a file path, or use the built-in word list
-    let word_list_content = match word_list_source {
-        Some(src) => {
-            if src.starts_with("http://") || src.starts_with("https://") {
-                use wasm_bindgen::JsCast;
-                let window = web_sys::window().unwrap_throw();
-                let resp_value = wasm_bindgen_futures::JsFuture::from(window.fetch_with_str(&src))
-                    .await
-                    .unwrap_throw();
-                let response: web_sys::Response = resp_value.dyn_into().unwrap_throw();
-                if !response.ok() {
-                    wasm_bindgen::throw_str("Network response was not OK");
-                }
-                let text = wasm_bindgen_futures::JsFuture::from(response.text().unwrap_throw())
-                    .await
-                    .unwrap_throw();
-                text.as_string().unwrap_throw()
-            } else {
-                std::fs::read_to_string(&src)
-                    .map_err(|e| wasm_bindgen::throw_str(&format!("Failed to read file: {}", e)))
-                    .unwrap_throw()
-            }
-        }
-        None => {
-            // Use the built-in word list without extra allocation
-            STWL_RAW.to_string()
-        }
-    };
-
-    // Add the word list to our batched strings
-    let word_list_idx = batched_strings.add(&word_list_content);
+    let word_list_source = load_word_list_source(word_list_source).await?;
 
     // Get a pre-allocated buffer for string normalization from the pool
     let grid_content_for_normalization = batched_strings.get(grid_content_idx);
     
     // Buffer pool removed - creating buffer directly
     // Normalize grid content using the pre-allocated buffer
-    let raw_grid_content = grid_content_for_normalization
-        .trim()
-        .nfkd()
-        .collect::<String>()
-        .to_lowercase();
+    let raw_grid_content = normalize_template(grid_content_for_normalization, soft_uppercase);
     
-    let height = raw_grid_content.lines().count();
-
-    if height == 0 {
-        return Err(JsError::new("Grid must have at least one row"));
-    }
-
-    if raw_grid_content
-        .lines()
-        .map(|line| line.chars().count())
-        .collect::<HashSet<_>>()
-        .len()
-        != 1
-    {
-        return Err(JsError::new("Rows in grid must all be the same length"));
-    }
+    template_string_dimensions(&raw_grid_content)?;
 
     // Validate max_shared_substring
     if !max_shared_substring
@@ -133,105 +496,359 @@ pub async fn fill_grid(
         ));
     }
 
-    let min_score = min_score.unwrap_or(50);
+    let min_score = min_score.unwrap_or_else(default_min_score);
 
-    // Create the word list using the dynamically loaded content
-    let word_list_content_ref = batched_strings.get(word_list_idx);
-    
-    // Create WordList from the content
-    let word_list = WordList::new(
-        vec![WordListSourceConfig::FileContents {
-            id: "0".into(),
-            enabled: true,
-            contents: word_list_content_ref.to_string().into(), // Use .into() to convert String to Cow::Owned
-        }],
+    // Create WordList from the content, only indexing dupes for the lengths the grid needs
+    let slot_lengths = generate_slots_from_template_string(&raw_grid_content)
+        .iter()
+        .map(|slot_spec| slot_spec.length)
+        .collect();
+    let word_list = WordList::new_with_dupe_index_lengths(
+        vec![word_list_source],
         None,
         None,
         max_shared_substring,
+        Some(slot_lengths),
     );
     
-    #[allow(clippy::comparison_chain)]
-    if let Some(errors) = word_list.get_source_errors().get("0") {
-        if errors.len() == 1 {
-            return Err(JsError::new(&errors[0].to_string()));
-        } else if errors.len() > 1 {
-            let mut full_error = String::new();
-            for error in errors {
-                full_error.push_str(&format!("\n- {error}"));
+    check_word_list(&word_list)?;
+
+    let mut grid_config =
+        grid_config_from_template(word_list, &raw_grid_content, min_score, soft_uppercase)?;
+
+    // Drop any words banned for this request from the slot options
+    if let Some(forbidden_words) = forbidden_words {
+        grid_config.forbid_words(&forbidden_words);
+    }
+
+    let result = match find_fill_wasm(&grid_config, interrupt_config).await {
+        Ok(result) => result,
+        Err(error) => {
+            let mut message = error.to_string();
+            if let Some(summary) = error.partial().and_then(|partial| {
+                summarize_crossing_weights(&grid_config, &partial.crossing_weights, 5)
+            }) {
+                message.push_str(&format!("\nMost troublesome crossings:\n{summary}"));
             }
-            return Err(JsError::new(&full_error));
+            return Err(JsError::new(&message));
         }
+    };
+
+    // console::log_1(&JsValue::from_str("Solution found"));
+
+    Ok((grid_config, result))
+}
+
+// TypeScript declarations for the JSON passed to and returned from `fill_grid_json`, generated
+// from the Rust types (see `fill_schema::typescript_declarations`).
+#[cfg(feature = "serde")]
+#[wasm_bindgen(typescript_custom_section)]
+const FILL_SCHEMA_TYPES: &str = include_str!("../bindings/fill_schema.d.ts");
+
+/// WASM-compatible function to fill a crossword grid described by a JSON `FillRequest` (see
+/// `fill_schema.rs`), returning a JSON `FillResponse`. Problems with the request itself are
+/// reported in the response rather than thrown.
+#[cfg(feature = "serde")]
+#[wasm_bindgen]
+pub async fn fill_grid_json(
+    request_json: &str,
+    word_list_source: Option<String>,
+) -> Result<String, JsError> {
+    let request = match FillRequest::from_json(request_json) {
+        Ok(request) => request,
+        Err(error) => return Ok(FillResponse::invalid(&error).to_json()),
+    };
+    if let Err(error) = request.check_version() {
+        return Ok(FillResponse::invalid(&error).to_json());
+    }
+    // The Wasm search doesn't support global constraints yet.
+    if !request.constraints.required_words.is_empty() {
+        return Ok(FillResponse::invalid(&"Required words aren't supported in Wasm builds").to_json());
     }
 
-    if word_list.word_id_by_string.is_empty() {
-        return Err(JsError::new("Word list is empty"));
+    let raw_grid_content = normalize_template(&request.grid, request.options.soft_uppercase);
+    let word_list = WordList::new(
+        vec![load_word_list_source(word_list_source).await?],
+        None,
+        None,
+        None,
+    );
+    check_word_list(&word_list)?;
+
+    let mut grid_config = match grid_config_from_template(
+        word_list,
+        &raw_grid_content,
+        request.constraints.min_score.unwrap_or_else(default_min_score),
+        request.options.soft_uppercase,
+    ) {
+        Ok(grid_config) => grid_config,
+        Err(error) => return Ok(FillResponse::invalid(&error).to_json()),
+    };
+    grid_config.forbid_words(&request.constraints.forbidden_words);
+
+    let config = grid_config.borrowed();
+    Ok(FillResponse::from_result(&config, &find_fill_wasm(&config, InterruptConfig::default()).await).to_json())
+}
+
+/// A word suggested by `suggest_words`.
+#[wasm_bindgen(getter_with_clone)]
+pub struct WordSuggestion {
+    pub word: String,
+    pub display: String,
+    pub score: u16,
+}
+
+/// WASM-compatible function to list the best viable words for the slot running in the given
+/// direction ("across" or "down") through the given cell, taking the letters already in the grid
+/// and the options of every crossing slot into account. As with `fill_grid`, uppercase letters are
+/// only suggestions if `soft_uppercase` is true.
+#[wasm_bindgen]
+pub async fn suggest_words(
+    grid_content: &str,
+    x: usize,
+    y: usize,
+    direction: &str,
+    limit: Option<usize>,
+    min_score: Option<u16>,
+    word_list_source: Option<String>,
+    soft_uppercase: Option<bool>,
+) -> Result<Vec<WordSuggestion>, JsError> {
+    let soft_uppercase = soft_uppercase.unwrap_or(false);
+    let direction = match direction {
+        "across" => Direction::Across,
+        "down" => Direction::Down,
+        _ => return Err(JsError::new(&format!("Invalid direction: {direction}"))),
+    };
+
+    let raw_grid_content = normalize_template(grid_content, soft_uppercase);
+    let word_list = WordList::new(
+        vec![load_word_list_source(word_list_source).await?],
+        None,
+        None,
+        None,
+    );
+    check_word_list(&word_list)?;
+
+    let grid_config = grid_config_from_template(
+        word_list,
+        &raw_grid_content,
+        min_score.unwrap_or_else(default_min_score),
+        soft_uppercase,
+    )?;
+    let config = grid_config.borrowed();
+    let slot_id = find_slot_at(&config, (x, y), direction)
+        .ok_or_else(|| JsError::new(&format!("No {direction:?} slot at ({x}, {y})")))?;
+
+    let suggestions = ELIMINATION_SET_POOL.with(|pool| {
+        let mut pool = pool.borrow_mut();
+        let elimination_sets = pool.acquire(&config.slot_configs, &config.word_list);
+        crate::suggestions::suggest_words(&config, slot_id, limit.unwrap_or(100), Some(elimination_sets))
+    });
+
+    Ok(suggestions
+        .into_iter()
+        .map(|suggestion| WordSuggestion {
+            word: suggestion.normalized,
+            display: suggestion.display,
+            score: suggestion.score,
+        })
+        .collect())
+}
+
+/// The options left for each slot of a grid after `propagate`, as bitsets over the words of each
+/// slot's length. Each bitset is a `Uint32Array` in which bit `i % 32` of element `i / 32` is set if
+/// the word with ID `i` among the word list's words of that length is still an option; since the
+/// arrays are created fresh for each call, their buffers can be transferred to another thread with
+/// `postMessage`. Slots are identified by index; use `slot_start` and `slot_direction` to match
+/// them to the grid.
+#[wasm_bindgen]
+pub struct Propagation {
+    starts: Vec<(usize, usize)>,
+    directions: Vec<Direction>,
+    lengths: Vec<usize>,
+    remaining_counts: Vec<usize>,
+    bitsets: Vec<Vec<u32>>,
+}
+
+#[wasm_bindgen]
+impl Propagation {
+    #[wasm_bindgen(getter)]
+    pub fn slot_count(&self) -> usize {
+        self.bitsets.len()
+    }
+
+    /// The `[x, y]` coordinates of the slot's first cell.
+    pub fn slot_start(&self, slot_index: usize) -> Result<Vec<usize>, JsError> {
+        let (x, y) = self.starts[self.check_slot_index(slot_index)?];
+        Ok(vec![x, y])
+    }
+
+    /// The slot's direction, "across" or "down".
+    pub fn slot_direction(&self, slot_index: usize) -> Result<String, JsError> {
+        Ok(match self.directions[self.check_slot_index(slot_index)?] {
+            Direction::Across => "across".into(),
+            Direction::Down => "down".into(),
+        })
     }
 
-    let grid_config =
-        generate_grid_config_from_template_string(word_list, &raw_grid_content, min_score.into());
+    pub fn slot_length(&self, slot_index: usize) -> Result<usize, JsError> {
+        Ok(self.lengths[self.check_slot_index(slot_index)?])
+    }
 
-    let result = find_fill_wasm(&grid_config.to_config_ref())
-        .map_err(|_| {
-            JsError::new("Ingrid Wasm: Unfillable grid")
-        })?;
+    /// The number of options left for the slot, i.e. the number of bits set in its bitset.
+    pub fn remaining_count(&self, slot_index: usize) -> Result<usize, JsError> {
+        Ok(self.remaining_counts[self.check_slot_index(slot_index)?])
+    }
 
-    // console::log_1(&JsValue::from_str("Solution found"));
+    /// The bitset of options left for the slot; see the struct docs.
+    pub fn remaining_options(&self, slot_index: usize) -> Result<js_sys::Uint32Array, JsError> {
+        Ok(js_sys::Uint32Array::from(
+            &self.bitsets[self.check_slot_index(slot_index)?][..],
+        ))
+    }
+}
 
-    // Return the filled grid as a string
-    let rendered_grid = render_grid(&grid_config.to_config_ref(), &result.choices).replace('.', "#");
-    Ok(rendered_grid)
+impl Propagation {
+    fn check_slot_index(&self, slot_index: usize) -> Result<usize, JsError> {
+        if slot_index < self.bitsets.len() {
+            Ok(slot_index)
+        } else {
+            Err(JsError::new(&format!("No slot with index {slot_index}")))
+        }
+    }
+}
+
+/// WASM-compatible function to eliminate every option that can't fit with the letters already in
+/// the grid and the options of the crossing slots, without filling anything, and return the options
+/// left for each slot (see `Propagation`). Unfillable grids are reported as errors. As with
+/// `fill_grid`, uppercase letters are only suggestions if `soft_uppercase` is true.
+#[wasm_bindgen]
+pub async fn propagate(
+    grid_content: &str,
+    min_score: Option<u16>,
+    word_list_source: Option<String>,
+    soft_uppercase: Option<bool>,
+) -> Result<Propagation, JsError> {
+    let soft_uppercase = soft_uppercase.unwrap_or(false);
+    let raw_grid_content = normalize_template(grid_content, soft_uppercase);
+    let word_list = WordList::new(
+        vec![load_word_list_source(word_list_source).await?],
+        None,
+        None,
+        None,
+    );
+    check_word_list(&word_list)?;
+
+    let grid_config = grid_config_from_template(
+        word_list,
+        &raw_grid_content,
+        min_score.unwrap_or_else(default_min_score),
+        soft_uppercase,
+    )?;
+    let config = grid_config.borrowed();
+
+    let mut slots = build_slots(&config);
+    let mut crossing_weights = CrossingWeights::new(config.crossing_count);
+    let slot_weights =
+        crate::backtracking_search::calculate_slot_weights(&config, &slots, &crossing_weights);
+    let consistent = ELIMINATION_SET_POOL.with(|pool| {
+        let mut pool = pool.borrow_mut();
+        let elimination_sets = pool.acquire(&config.slot_configs, &config.word_list);
+        maintain_arc_consistency_wasm(
+            &config,
+            &mut slots,
+            &mut crossing_weights,
+            &slot_weights,
+            &ArcConsistencyMode::Initial,
+            elimination_sets,
+        )
+    });
+    if !consistent {
+        return Err(FillError::Unfillable(Box::default()).into());
+    }
+
+    let mut propagation = Propagation {
+        starts: vec![],
+        directions: vec![],
+        lengths: vec![],
+        remaining_counts: vec![],
+        bitsets: vec![],
+    };
+    for (slot_config, slot) in config.slot_configs.iter().zip(&slots) {
+        let mut bitset = vec![0u32; config.word_list.words[slot.length].len().div_ceil(32)];
+        let mut remaining_count = 0;
+        let remaining = slot.fixed_word_id.map_or_else(
+            || config.slot_options[slot.id.index()].clone(),
+            |word_id| vec![word_id],
+        );
+        for word_id in remaining {
+            if slot.fixed_word_id.is_none() && slot.eliminations[word_id.index()].is_some() {
+                continue;
+            }
+            bitset[word_id.index() / 32] |= 1 << (word_id.index() % 32);
+            remaining_count += 1;
+        }
+
+        propagation.starts.push(slot_config.start_cell);
+        propagation.directions.push(slot_config.direction);
+        propagation.lengths.push(slot.length);
+        propagation.remaining_counts.push(remaining_count);
+        propagation.bitsets.push(bitset);
+    }
+    Ok(propagation)
 }
 
 /// WASM-compatible wrapper for find_fill that avoids using std::time::Instant
-fn find_fill_wasm(config: &GridConfig) -> Result<crate::backtracking_search::FillSuccess, crate::backtracking_search::FillFailure> {
-    use crate::arc_consistency::EliminationSet;
+async fn find_fill_wasm(
+    config: &GridConfig<'_>,
+    interrupt_config: InterruptConfig,
+) -> Result<FillSuccess, FillError> {
+    // Split the search across workers if `init_parallel_fill` has started any
+    #[cfg(feature = "wasm-threads")]
+    {
+        let thread_count = PARALLEL_THREAD_COUNT.load(Ordering::Relaxed);
+        if thread_count > 1 {
+            return PARALLEL_ELIMINATION_SET_POOLS.with(|pools| {
+                find_fill_parallel_with_pools(
+                    config,
+                    None,
+                    thread_count,
+                    &SearchConfig::default(),
+                    &mut pools.borrow_mut(),
+                )
+                .map_err(FillError::from)
+            });
+        }
+    }
+
+    // Reuse the elimination sets' allocations from previous fills. We take the pool out for the
+    // duration of the fill, since another fill may start while this one is yielding.
+    let mut pool = ELIMINATION_SET_POOL.with(RefCell::take);
+    let elimination_sets = pool.acquire(&config.slot_configs, &config.word_list);
+    let result =
+        find_fill_wasm_with_elimination_sets(config, elimination_sets, interrupt_config).await;
+    ELIMINATION_SET_POOL.with(|shared_pool| *shared_pool.borrow_mut() = pool);
+    result
+}
+
+async fn find_fill_wasm_with_elimination_sets(
+    config: &GridConfig<'_>,
+    elimination_sets: &mut [EliminationSet],
+    interrupt_config: InterruptConfig,
+) -> Result<FillSuccess, FillError> {
     use crate::backtracking_search::*;
-    
-    // Create owned elimination sets
-    let mut owned_elimination_sets = Some(EliminationSet::build_all(
-        config.slot_configs,
-        config.word_list,
-    ));
-    let elimination_sets = owned_elimination_sets.as_mut().unwrap();
-
-    // Create basic Slot structs for the grid
-    let mut slots: Vec<Slot> = config
-        .slot_configs
-        .iter()
-        .map(|slot_config| {
-            let glyph_counts_by_cell = crate::util::build_glyph_counts_by_cell(
-                config.word_list,
-                slot_config.length,
-                &config.slot_options[slot_config.id],
-            );
-
-            let is_fixed = slot_config
-                .complete_fill(config.fill, config.width)
-                .is_some();
-
-            Slot {
-                id: slot_config.id,
-                length: slot_config.length,
-                eliminations: vec![None; config.word_list.words[slot_config.length].len()],
-                remaining_option_count: config.slot_options[slot_config.id].len(),
-                fixed_word_id: if is_fixed {
-                    assert_eq!(config.slot_options[slot_config.id].len(), 1);
-                    Some(config.slot_options[slot_config.id][0])
-                } else {
-                    None
-                },
-                fixed_glyph_counts_by_cell: if is_fixed {
-                    Some(glyph_counts_by_cell.clone())
-                } else {
-                    None
-                },
-                glyph_counts_by_cell,
-            }
-        })
-        .collect();
+    use rand::distributions::WeightedIndex;
+
+    // Build the random-choice distributions once, reporting bad weights instead of trapping
+    let slot_dist = WeightedIndex::new(RANDOM_SLOT_WEIGHTS)
+        .map_err(|err| FillError::Solver(format!("Invalid slot weights: {err}")))?;
+    let word_dist = WeightedIndex::new(RANDOM_WORD_WEIGHTS)
+        .map_err(|err| FillError::Solver(format!("Invalid word weights: {err}")))?;
+
+    let mut slots = build_slots(config);
 
     // Initialize crossing weights
-    let mut crossing_weights: Vec<f32> = (0..config.crossing_count).map(|_| 1.0).collect();
+    let mut crossing_weights = CrossingWeights::new(config.crossing_count);
 
     // Establish initial arc consistency without timing
     let slot_weights = calculate_slot_weights(config, &slots, &crossing_weights);
@@ -244,12 +861,19 @@ fn find_fill_wasm(config: &GridConfig) -> Result<crate::backtracking_search::Fil
         &ArcConsistencyMode::Initial,
         elimination_sets,
     ) {
-        return Err(FillFailure::HardFailure);
+        return Err(FillError::Unfillable(Box::new(PartialFill {
+            crossing_weights,
+            ..PartialFill::default()
+        })));
     }
 
     // Initial max_backtracks value
     let mut max_backtracks: usize = 500;
 
+    // Progress shared between retries, reported if the fill fails
+    let mut partial = PartialFill::default();
+    let mut interrupts = Interrupts::new(interrupt_config);
+
     // Try to fill the grid with a maximum number of retries
     const MAX_RETRIES: u64 = 100000;
     for retry_num in 0..MAX_RETRIES {
@@ -260,12 +884,23 @@ fn find_fill_wasm(config: &GridConfig) -> Result<crate::backtracking_search::Fil
             retry_num,
             &mut crossing_weights,
             elimination_sets,
-        ) {
+            &slot_dist,
+            &word_dist,
+            &mut partial,
+            &mut interrupts,
+        )
+        .await
+        {
             Ok(mut result) => {
                 result.statistics.retries = retry_num as usize;
+                result.crossing_weights = crossing_weights.clone();
+                result.record_inputs(config, None);
+
                 return Ok(result);
             }
-            Err(FillFailure::ExceededBacktrackLimit(_)) => {
+            Err(FillError::ExceededBacktrackLimit(_, _)) => {
+                partial.statistics.retries += 1;
+
                 // Increase max_backtracks for the next attempt
                 max_backtracks = (max_backtracks + 1)
                     .max((max_backtracks as f32 * RETRY_GROWTH_FACTOR) as usize);
@@ -276,15 +911,16 @@ fn find_fill_wasm(config: &GridConfig) -> Result<crate::backtracking_search::Fil
         }
     }
 
-    // If we've exhausted all retries, return a hard failure
-    Err(FillFailure::HardFailure)
+    // If we've exhausted all retries, give up without claiming that the grid is unfillable
+    partial.crossing_weights = crossing_weights;
+    Err(FillError::ExceededBacktrackLimit(max_backtracks, Box::new(partial)))
 }
 
 // WASM-compatible version of maintain_arc_consistency that doesn't use Instant
 fn maintain_arc_consistency_wasm(
     config: &GridConfig,
     slots: &mut [Slot],
-    crossing_weights: &mut [f32],
+    crossing_weights: &mut CrossingWeights,
     slot_weights: &[f32],
     mode: &ArcConsistencyMode,
     elimination_sets: &mut [EliminationSet],
@@ -301,14 +937,14 @@ fn maintain_arc_consistency_wasm(
 
     impl ArcConsistencyAdapter for Adapter<'_> {
         fn is_word_eliminated(&self, slot_id: SlotId, word_id: WordId) -> bool {
-            self.slots[slot_id].eliminations[word_id].is_some()
+            self.slots[slot_id.index()].eliminations[word_id.index()].is_some()
         }
 
         fn get_glyph_counts(&self, slot_id: SlotId) -> GlyphCountsByCell {
-            self.slots[slot_id]
+            self.slots[slot_id.index()]
                 .fixed_glyph_counts_by_cell
                 .clone()
-                .unwrap_or_else(|| self.slots[slot_id].glyph_counts_by_cell.clone())
+                .unwrap_or_else(|| self.slots[slot_id.index()].glyph_counts_by_cell.clone())
         }
 
         fn get_single_option(
@@ -316,11 +952,11 @@ fn maintain_arc_consistency_wasm(
             slot_id: SlotId,
             eliminations: &EliminationSet,
         ) -> Option<WordId> {
-            self.slots[slot_id].fixed_word_id.or_else(|| {
-                self.config.slot_options[slot_id]
+            self.slots[slot_id.index()].fixed_word_id.or_else(|| {
+                self.config.slot_options[slot_id.index()]
                     .iter()
                     .find(|&word_id| {
-                        self.slots[slot_id].eliminations[*word_id].is_none()
+                        self.slots[slot_id.index()].eliminations[word_id.index()].is_none()
                             && !eliminations.contains(*word_id)
                     })
                     .copied()
@@ -331,10 +967,10 @@ fn maintain_arc_consistency_wasm(
     // First, if we're testing a choice or elimination, update the relevant state provisionally
     match mode {
         ArcConsistencyMode::Choice(choice) => {
-            slots[choice.slot_id].choose_word(config, choice.word_id);
+            slots[choice.slot_id.index()].choose_word(config, choice.word_id);
         }
         ArcConsistencyMode::Elimination(choice, blamed_slot_id) => {
-            slots[choice.slot_id].add_elimination(config, choice.word_id, *blamed_slot_id);
+            slots[choice.slot_id.index()].add_elimination(config, choice.word_id, *blamed_slot_id);
         }
         ArcConsistencyMode::Initial => {}
     };
@@ -363,7 +999,7 @@ fn maintain_arc_consistency_wasm(
             // When maintaining consistency later on, we can treat all slots with exactly one option as fixed
             slots
                 .iter()
-                .map(|slot| remaining_option_counts[slot.id] == 1)
+                .map(|slot| remaining_option_counts[slot.id.index()] == 1)
                 .collect()
         }
     };
@@ -404,36 +1040,35 @@ fn maintain_arc_consistency_wasm(
         Err(crate::arc_consistency::ArcConsistencyFailure { weight_updates }) => {
             match mode {
                 ArcConsistencyMode::Choice(choice) => {
-                    slots[choice.slot_id].clear_choice();
+                    slots[choice.slot_id.index()].clear_choice();
                 }
                 ArcConsistencyMode::Elimination(choice, ..) => {
-                    slots[choice.slot_id].remove_elimination(config, choice.word_id);
+                    slots[choice.slot_id.index()].remove_elimination(config, choice.word_id);
                 }
                 ArcConsistencyMode::Initial => {}
             };
 
-            for (slot_id, weight) in crossing_weights.iter_mut().enumerate() {
-                *weight = 1.0
-                    + ((*weight - 1.0) * WEIGHT_AGE_FACTOR)
-                    + weight_updates.get(&slot_id).unwrap_or(&0.0);
-            }
+            crossing_weights.record_wipeout(&weight_updates);
             false
         }
     }
 }
 
 // WASM-compatible version of find_fill_for_seed that doesn't use Instant
-fn find_fill_for_seed_wasm(
-    config: &GridConfig,
+#[allow(clippy::too_many_arguments)]
+async fn find_fill_for_seed_wasm(
+    config: &GridConfig<'_>,
     slots: &Vec<Slot>,
     max_backtracks: usize,
     rng_seed: u64,
-    crossing_weights: &mut [f32],
+    crossing_weights: &mut CrossingWeights,
     elimination_sets: &mut [EliminationSet],
-) -> Result<FillSuccess, FillFailure> {
+    slot_dist: &rand::distributions::WeightedIndex<u8>,
+    word_dist: &rand::distributions::WeightedIndex<u8>,
+    partial: &mut crate::backtracking_search::PartialFill,
+    interrupts: &mut Interrupts,
+) -> Result<FillSuccess, FillError> {
     use rand::prelude::*;
-    use rand::distributions::WeightedIndex;
-    use std::sync::atomic::Ordering;
     use crate::grid_config::{Choice, SlotId};
     use crate::types::WordId;
     use crate::backtracking_search::*;
@@ -450,17 +1085,18 @@ fn find_fill_for_seed_wasm(
     let mut last_slot_id: Option<SlotId> = None;
     let mut last_starting_word_idx: Option<usize> = None;
 
-    let slot_dist = WeightedIndex::new(RANDOM_SLOT_WEIGHTS).unwrap();
-    let word_dist = WeightedIndex::new(RANDOM_WORD_WEIGHTS).unwrap();
-
     // Main loop
     loop {
         statistics.states += 1;
 
-        if statistics.states % INTERRUPT_FREQUENCY == 0 {
-            if let Some(abort) = config.abort {
-                if abort.load(Ordering::Relaxed) {
-                    return Err(FillFailure::Abort);
+        if statistics.states % interrupts.config.frequency == 0 {
+            interrupts.maybe_yield().await;
+
+            if let Some(cancel) = &config.cancel {
+                if cancel.is_cancelled() {
+                    partial.statistics.accumulate(&statistics);
+                    partial.crossing_weights = crossing_weights.clone();
+                    return Err(FillError::Abort(Box::new(std::mem::take(partial))));
                 }
             }
         }
@@ -471,8 +1107,9 @@ fn find_fill_for_seed_wasm(
             &slots,
             &slot_weights,
             last_slot_id,
+            None,
             &mut rng,
-            &slot_dist,
+            slot_dist,
             &mut statistics,
         ) else {
             // If no more slots to fill, we're done
@@ -480,14 +1117,17 @@ fn find_fill_for_seed_wasm(
             let choices = slots
                 .into_iter()
                 .map(|slot| {
-                    slot.get_choice(config)
-                        .expect("Failed to identify single choice for slot")
+                    slot.get_choice(config).ok_or_else(|| {
+                        FillError::Solver(format!("Failed to identify single choice for slot {}", slot.id))
+                    })
                 })
-                .collect();
+                .collect::<Result<Vec<_>, _>>()?;
 
             return Ok(FillSuccess {
                 statistics,
+                slot_stats: slot_fill_stats(config, &choices, &[], &[]),
                 choices,
+                ..FillSuccess::default()
             });
         };
 
@@ -499,16 +1139,18 @@ fn find_fill_for_seed_wasm(
         };
 
         // Get candidate words
-        let word_candidates: Vec<(usize, &WordId)> = config.slot_options[slot_id]
+        let word_candidates: Vec<(usize, &WordId)> = config.slot_options[slot_id.index()]
             .iter()
             .enumerate()
             .skip(starting_word_idx)
-            .filter(|&(_, &word_id)| slots[slot_id].eliminations[word_id].is_none())
+            .filter(|&(_, &word_id)| slots[slot_id.index()].eliminations[word_id.index()].is_none())
             .take(RANDOM_WORD_WEIGHTS.len())
             .collect();
 
         if word_candidates.is_empty() {
-            return Err(FillFailure::HardFailure);
+            partial.statistics.accumulate(&statistics);
+            partial.crossing_weights = crossing_weights.clone();
+            return Err(FillError::Unfillable(Box::new(std::mem::take(partial))));
         }
 
         // Choose one candidate at random
@@ -530,7 +1172,8 @@ fn find_fill_for_seed_wasm(
             &ArcConsistencyMode::Choice(choice.clone()),
             elimination_sets,
         ) {
-            // If successful, record choice and continue
+            // If successful, record choice (and how far we've gotten) and continue
+            partial.record_depth(config, &slots);
             choices.push(choice);
             continue;
         }
@@ -558,11 +1201,13 @@ fn find_fill_for_seed_wasm(
             // If unsuccessful, undo previous choice
             let Some(last_choice) = choices.pop() else {
                 // If no previous choices, grid is unsolvable
-                return Err(FillFailure::HardFailure);
+                partial.statistics.accumulate(&statistics);
+                partial.crossing_weights = crossing_weights.clone();
+                return Err(FillError::Unfillable(Box::new(std::mem::take(partial))));
             };
             undoing_choice = last_choice;
 
-            slots[undoing_choice.slot_id].clear_choice();
+            slots[undoing_choice.slot_id.index()].clear_choice();
 
             for slot in &mut slots {
                 if slot.id != undoing_choice.slot_id && slot.fixed_word_id.is_none() {
@@ -572,7 +1217,12 @@ fn find_fill_for_seed_wasm(
 
             // Check if we've exceeded backtrack limit
             if statistics.backtracks > max_backtracks {
-                return Err(FillFailure::ExceededBacktrackLimit(statistics.backtracks));
+                partial.statistics.accumulate(&statistics);
+                partial.crossing_weights = crossing_weights.clone();
+                return Err(FillError::ExceededBacktrackLimit(
+                    statistics.backtracks,
+                    Box::new(partial.clone()),
+                ));
             }
 
             // Reset cached position
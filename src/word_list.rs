@@ -7,12 +7,18 @@ use std::fmt::Debug;
 use std::fs::File;
 use std::hash::{DefaultHasher, Hash, Hasher};
 use std::io::Read;
+use std::sync::Arc;
 use std::time::SystemTime;
 use std::{fmt, fs, io, mem};
 use unicode_normalization::UnicodeNormalization;
 
-use crate::dupe_index::{AnyDupeIndex, BoxedDupeIndex, DupeIndex};
+use crate::cancel::{CancelReason, CancelToken};
+use crate::dupe_index::{AnyDupeIndex, BoxedDupeIndex, CompoundSplitting, DupeIndex};
+pub use crate::error::{SourceLineError, SourceLineErrorKind, WordListError};
+use crate::phonetic::PhoneticEncoding;
+use crate::score_overrides::{ScoreAdjustment, ScoreOverrides};
 use crate::types::{GlobalWordId, GlyphId, WordId};
+use crate::util::{build_glyph_counts_by_cell, StableHasher};
 use crate::MAX_SLOT_LENGTH;
 
 lazy_static! {
@@ -50,7 +56,7 @@ pub struct Word {
     /// The word's score, usually on a roughly 0 - 100 scale where 50 means average quality.
     pub score: u16,
 
-    /// The sum of the scores of the word's letters.
+    /// The sum of the scores of the word's letters, using `WordList::letter_points` if present.
     pub letter_score: u16,
 
     /// Is this word currently invisible to the user and unavailable for autofill? This will be
@@ -67,6 +73,30 @@ pub struct Word {
     pub personal_word_score: Option<u16>,
 }
 
+/// Optional information about how recently a word has appeared in published puzzles, which callers
+/// can attach to words with `WordList::set_word_recency`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WordRecency {
+    /// The word has never appeared in a published puzzle.
+    Debut,
+
+    /// The word last appeared on the given day, counted in days since the Unix epoch.
+    LastSeen(u32),
+}
+
+impl WordRecency {
+    /// A key ranking words from freshest to stalest: debuts first, then words with no recency
+    /// information, then previously-seen words in order from least to most recently used.
+    #[must_use]
+    pub fn freshness_key(recency: Option<WordRecency>) -> (u8, u32) {
+        match recency {
+            Some(WordRecency::Debut) => (0, 0),
+            None => (1, 0),
+            Some(WordRecency::LastSeen(day)) => (2, day),
+        }
+    }
+}
+
 /// Given a canonical word string from a dictionary file, turn it into the normalized form we'll
 /// use in the actual fill engine.
 #[must_use]
@@ -78,25 +108,212 @@ pub fn normalize_word(canonical: &str) -> String {
         .collect()
 }
 
-#[derive(Debug, Clone)]
-pub enum WordListError {
-    InvalidPath(String),
-    InvalidWord(String),
-    InvalidScore(String),
+/// A language-specific set of rules for folding normalized words down to the letters used in that
+/// language's grids, applied on top of `normalize_word`. Crossword conventions differ: Spanish
+/// grids drop accents but keep Ñ as its own letter, French grids drop all diacritics and split
+/// ligatures, and German grids spell out umlauts and ß (so STRASSE rather than keeping the ß).
+///
+/// Presets are applied when loading a bundled language sample (see `BundledList::normalization`);
+/// other input, like templates or words added by the user, should be folded with the same preset
+/// before it's passed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum NormalizationPreset {
+    /// No folding beyond `normalize_word`.
+    #[default]
+    Default,
+    Spanish,
+    French,
+    German,
 }
 
-impl fmt::Display for WordListError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let string = match self {
-            WordListError::InvalidPath(path) => format!("Can’t read file: “{path}”"),
-            WordListError::InvalidWord(word) => {
-                format!("Word list contains invalid word: “{word}”")
-            }
-            WordListError::InvalidScore(score) => {
-                format!("Word list contains invalid score: “{score}”")
+impl NormalizationPreset {
+    /// Normalize the given canonical word and fold it according to this preset.
+    #[must_use]
+    pub fn normalize(self, canonical: &str) -> String {
+        let normalized = normalize_word(canonical);
+        if self == NormalizationPreset::Default {
+            return normalized;
+        }
+
+        let mut folded = String::with_capacity(normalized.len());
+        for c in normalized.chars() {
+            match (self, c) {
+                (NormalizationPreset::German, 'ü') => folded.push_str("ue"),
+                (NormalizationPreset::German, 'ß') => folded.push_str("ss"),
+                (NormalizationPreset::Spanish, 'ñ') => folded.push('ñ'),
+                (NormalizationPreset::German, 'ä') | (_, 'æ') => folded.push_str("ae"),
+                (NormalizationPreset::German, 'ö') | (_, 'œ') => folded.push_str("oe"),
+                (_, 'à' | 'á' | 'â' | 'ä') => folded.push('a'),
+                (_, 'ç') => folded.push('c'),
+                (_, 'è' | 'é' | 'ê' | 'ë') => folded.push('e'),
+                (_, 'ì' | 'í' | 'î' | 'ï') => folded.push('i'),
+                (_, 'ñ') => folded.push('n'),
+                (_, 'ò' | 'ó' | 'ô' | 'ö') => folded.push('o'),
+                (_, 'ù' | 'ú' | 'û' | 'ü') => folded.push('u'),
+                (_, 'ý' | 'ÿ') => folded.push('y'),
+                (_, c) => folded.push(c),
             }
-        };
-        write!(f, "{string}")
+        }
+        folded
+    }
+}
+
+/// A single entry yielded by a `WordIterSource`: the word, its score, and its tags.
+pub type WordIterEntry = (String, u16, Vec<String>);
+
+/// A programmatic source of word list entries, such as a database query. Since sources are
+/// reloaded whenever the list is refreshed, this wraps a function that produces a fresh iterator
+/// each time it's called, rather than a single iterator.
+#[derive(Clone)]
+pub struct WordIterSource(Arc<dyn Fn() -> Box<dyn Iterator<Item = WordIterEntry>> + Send + Sync>);
+
+impl WordIterSource {
+    pub fn new<I, F>(make_iter: F) -> WordIterSource
+    where
+        I: Iterator<Item = WordIterEntry> + 'static,
+        F: Fn() -> I + Send + Sync + 'static,
+    {
+        WordIterSource(Arc::new(move || Box::new(make_iter())))
+    }
+
+    fn load(&self) -> Box<dyn Iterator<Item = WordIterEntry>> {
+        (self.0)()
+    }
+}
+
+impl Debug for WordIterSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("WordIterSource(..)")
+    }
+}
+
+/// A function for adjusting the scores of every entry in a source when it's loaded, given the
+/// source's id and its scores in order. Since public lists use incompatible scales, this can be
+/// used for things like percentile normalization, clamping, or rescaling specific sources. Scores
+/// from pending updates (i.e., edits made through this `WordList`) aren't transformed.
+#[derive(Clone)]
+pub struct ScoreTransform(Arc<ScoreTransformFn>);
+
+type ScoreTransformFn = dyn Fn(&str, &mut [u16]) + Send + Sync;
+
+impl ScoreTransform {
+    pub fn new<F>(transform: F) -> ScoreTransform
+    where
+        F: Fn(&str, &mut [u16]) + Send + Sync + 'static,
+    {
+        ScoreTransform(Arc::new(transform))
+    }
+
+    fn apply(&self, source_id: &str, entries: &mut [RawWordListEntry]) {
+        let mut scores: Vec<u16> = entries.iter().map(|entry| entry.score).collect();
+        (self.0)(source_id, &mut scores);
+        for (entry, score) in entries.iter_mut().zip(scores) {
+            entry.score = score;
+        }
+    }
+}
+
+impl Debug for ScoreTransform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ScoreTransform(..)")
+    }
+}
+
+#[cfg(feature = "bundled-xwi")]
+const XWI_CONTENTS: Option<&str> = Some(include_str!("../resources/XwiWordList.txt"));
+#[cfg(not(feature = "bundled-xwi"))]
+const XWI_CONTENTS: Option<&str> = None;
+
+#[cfg(feature = "bundled-stwl")]
+const STWL_CONTENTS: Option<&str> = Some(include_str!("../resources/spreadthewordlist.dict"));
+#[cfg(not(feature = "bundled-stwl"))]
+const STWL_CONTENTS: Option<&str> = None;
+
+#[cfg(feature = "lang-es-sample")]
+const SPANISH_SAMPLE_CONTENTS: Option<&str> = Some(include_str!("../resources/lang/es-sample.txt"));
+#[cfg(not(feature = "lang-es-sample"))]
+const SPANISH_SAMPLE_CONTENTS: Option<&str> = None;
+
+#[cfg(feature = "lang-fr-sample")]
+const FRENCH_SAMPLE_CONTENTS: Option<&str> = Some(include_str!("../resources/lang/fr-sample.txt"));
+#[cfg(not(feature = "lang-fr-sample"))]
+const FRENCH_SAMPLE_CONTENTS: Option<&str> = None;
+
+#[cfg(feature = "lang-de-sample")]
+const GERMAN_SAMPLE_CONTENTS: Option<&str> = Some(include_str!("../resources/lang/de-sample.txt"));
+#[cfg(not(feature = "lang-de-sample"))]
+const GERMAN_SAMPLE_CONTENTS: Option<&str> = None;
+
+/// A word list that can be compiled into the library, so that it's available without any setup
+/// (see `WordListSourceConfig::Bundled`). Each list is only included with its cargo feature, since
+/// the English lists add several megabytes to the binary. The Spanish, French, and German lists
+/// are only samples of a hundred or so common words (see `resources/lang/README.md`), enough to
+/// try out their normalization presets and fill small test grids but not real puzzles; real
+/// non-English construction needs a full list loaded from a file or URL. The lists come from
+/// different sources under different terms, so check each one's license before redistributing a
+/// build that includes it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum BundledList {
+    /// The list embedded in the CLI and Wasm builds; requires the `bundled-xwi` feature.
+    Xwi,
+
+    /// Spread the Wordlist; requires the `bundled-stwl` feature.
+    SpreadTheWordlist,
+
+    /// A small Spanish sample for testing; requires the `lang-es-sample` feature.
+    SpanishSample,
+
+    /// A small French sample for testing; requires the `lang-fr-sample` feature.
+    FrenchSample,
+
+    /// A small German sample for testing; requires the `lang-de-sample` feature.
+    GermanSample,
+}
+
+impl BundledList {
+    /// A human-readable name for the list.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            BundledList::Xwi => "XWI",
+            BundledList::SpreadTheWordlist => "Spread the Wordlist",
+            BundledList::SpanishSample => "Spanish sample",
+            BundledList::FrenchSample => "French sample",
+            BundledList::GermanSample => "German sample",
+        }
+    }
+
+    /// The preset applied to the list's entries when it's loaded.
+    #[must_use]
+    pub fn normalization(self) -> NormalizationPreset {
+        match self {
+            BundledList::Xwi | BundledList::SpreadTheWordlist => NormalizationPreset::Default,
+            BundledList::SpanishSample => NormalizationPreset::Spanish,
+            BundledList::FrenchSample => NormalizationPreset::French,
+            BundledList::GermanSample => NormalizationPreset::German,
+        }
+    }
+
+    /// The list's contents, in the usual `word;score` format, if it was compiled in.
+    #[must_use]
+    pub fn contents(self) -> Option<&'static str> {
+        match self {
+            BundledList::Xwi => XWI_CONTENTS,
+            BundledList::SpreadTheWordlist => STWL_CONTENTS,
+            BundledList::SpanishSample => SPANISH_SAMPLE_CONTENTS,
+            BundledList::FrenchSample => FRENCH_SAMPLE_CONTENTS,
+            BundledList::GermanSample => GERMAN_SAMPLE_CONTENTS,
+        }
     }
 }
 
@@ -118,6 +335,29 @@ pub enum WordListSourceConfig {
         enabled: bool,
         contents: Cow<'static, str>, // Changed from String to Cow
     },
+    Iter {
+        id: String,
+        enabled: bool,
+        words: WordIterSource,
+    },
+    /// A list fetched over HTTP each time the source is refreshed. If `cache_dir` is given, the
+    /// response is cached there so that unchanged lists can be revalidated cheaply using their
+    /// `ETag`, and so that the last fetched copy can be used if the server is unreachable. Fetching
+    /// requires the `http` feature and isn't available on Wasm (which fetches lists through the
+    /// browser instead); otherwise, loading this source just records a `FetchFailed` error.
+    Url {
+        id: String,
+        enabled: bool,
+        url: String,
+        cache_dir: Option<std::path::PathBuf>,
+    },
+    /// One of the lists bundled with the library. If the list's feature isn't enabled, loading
+    /// this source just records a `BundledListUnavailable` error.
+    Bundled {
+        id: String,
+        enabled: bool,
+        list: BundledList,
+    },
 }
 
 impl WordListSourceConfig {
@@ -127,7 +367,10 @@ impl WordListSourceConfig {
         match self {
             WordListSourceConfig::Memory { id, .. }
             | WordListSourceConfig::FileContents { id, .. }
-            | WordListSourceConfig::File { id, .. } => id.clone(),
+            | WordListSourceConfig::File { id, .. }
+            | WordListSourceConfig::Iter { id, .. }
+            | WordListSourceConfig::Url { id, .. }
+            | WordListSourceConfig::Bundled { id, .. } => id.clone(),
         }
     }
 
@@ -138,7 +381,22 @@ impl WordListSourceConfig {
         match self {
             WordListSourceConfig::Memory { enabled, .. }
             | WordListSourceConfig::FileContents { enabled, .. }
-            | WordListSourceConfig::File { enabled, .. } => *enabled,
+            | WordListSourceConfig::File { enabled, .. }
+            | WordListSourceConfig::Iter { enabled, .. }
+            | WordListSourceConfig::Url { enabled, .. }
+            | WordListSourceConfig::Bundled { enabled, .. } => *enabled,
+        }
+    }
+
+    /// Enable or disable this list (see `enabled`).
+    pub fn set_enabled(&mut self, is_enabled: bool) {
+        match self {
+            WordListSourceConfig::Memory { enabled, .. }
+            | WordListSourceConfig::FileContents { enabled, .. }
+            | WordListSourceConfig::File { enabled, .. }
+            | WordListSourceConfig::Iter { enabled, .. }
+            | WordListSourceConfig::Url { enabled, .. }
+            | WordListSourceConfig::Bundled { enabled, .. } => *enabled = is_enabled,
         }
     }
 
@@ -147,7 +405,11 @@ impl WordListSourceConfig {
     #[must_use]
     pub fn modified(&self) -> Option<SystemTime> {
         match self {
-            WordListSourceConfig::Memory { .. } | WordListSourceConfig::FileContents { .. } => None,
+            WordListSourceConfig::Memory { .. }
+            | WordListSourceConfig::FileContents { .. }
+            | WordListSourceConfig::Iter { .. }
+            | WordListSourceConfig::Url { .. }
+            | WordListSourceConfig::Bundled { .. } => None,
             WordListSourceConfig::File { path, .. } => fs::metadata(path).ok()?.modified().ok(),
         }
     }
@@ -201,6 +463,40 @@ impl WordListSourceState {
     }
 }
 
+/// One source's definition of a word that's defined by more than one source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateEntryDefinition {
+    pub source_id: String,
+    pub canonical: String,
+    pub score: u16,
+}
+
+/// A word that's defined with different scores by more than one enabled source. When sources
+/// conflict, the earliest source in the list takes priority, so the definition that's actually used
+/// for filling is always the first one in `definitions`. (If the personal list is one of the
+/// later sources, its score is still recorded as the word's `personal_word_score`.)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateEntry {
+    pub normalized: String,
+
+    /// Every enabled source's definition of the word, in priority order.
+    pub definitions: Vec<DuplicateEntryDefinition>,
+}
+
+impl DuplicateEntry {
+    /// The definition that won out over the others.
+    #[must_use]
+    pub fn used_definition(&self) -> &DuplicateEntryDefinition {
+        &self.definitions[0]
+    }
+
+    /// The definitions that were shadowed by `used_definition`.
+    #[must_use]
+    pub fn shadowed_definitions(&self) -> &[DuplicateEntryDefinition] {
+        &self.definitions[1..]
+    }
+}
+
 /// `WordListSourceState`s keyed by the `id` of the relevant source.
 pub type WordListSourceStates = HashMap<String, WordListSourceState>;
 
@@ -212,44 +508,204 @@ pub struct RawWordListEntry {
     pub normalized: String,
     pub canonical: String,
     pub score: u16,
+    pub tags: Vec<String>,
 }
 
+/// The maximum length of a word we'll accept from a word list source. Entries longer than
+/// `MAX_SLOT_LENGTH` can't be used in a fill, but published lists still include long phrases (the
+/// bundled XWI list goes up to 36 letters) that we want to keep around for editing, so we only
+/// reject entries that are several times longer than any slot; those are almost certainly mistakes,
+/// like a pasted paragraph.
+pub const MAX_WORD_LIST_ENTRY_LENGTH: usize = 3 * MAX_SLOT_LENGTH;
+
+/// The number of rejected entries after which we stop loading a source, since at that point it's
+/// probably not a word list at all.
+const MAX_SOURCE_ERRORS: usize = 100;
+
 fn parse_word_list_file_contents(
     file_contents: &str,
+    normalization: NormalizationPreset,
     index: &mut HashMap<String, usize>,
     errors: &mut Vec<WordListError>,
 ) -> Vec<RawWordListEntry> {
     let mut entries = Vec::with_capacity(file_contents.lines().count());
 
-    for line in file_contents.lines() {
-        if errors.len() > 100 {
+    for (line_idx, line) in file_contents.lines().enumerate() {
+        if errors.len() > MAX_SOURCE_ERRORS {
             break;
         }
+        parse_word_list_line(line_idx, line, normalization, index, &mut entries, errors);
+    }
 
-        let line_parts: Vec<_> = line.split(';').collect();
+    entries
+}
+
+/// Parse a single line of a word list file, adding it to `entries` unless it's blank, invalid, or
+/// a duplicate.
+fn parse_word_list_line(
+    line_idx: usize,
+    line: &str,
+    normalization: NormalizationPreset,
+    index: &mut HashMap<String, usize>,
+    entries: &mut Vec<RawWordListEntry>,
+    errors: &mut Vec<WordListError>,
+) {
+    let line_parts: Vec<_> = line.split(';').collect();
+    let mut push_error = |text: &str, kind: SourceLineErrorKind| {
+        errors.push(WordListError::InvalidLine(SourceLineError {
+            line_number: line_idx + 1,
+            text: text.into(),
+            kind,
+        }));
+    };
+
+    if line_parts[0].chars().any(|c| c == '�') {
+        push_error(line_parts[0], SourceLineErrorKind::InvalidCharacter);
+        return;
+    }
+
+    let canonical = line_parts[0].trim().to_string();
+    let normalized = normalization.normalize(&canonical);
+    if normalized.is_empty() {
+        return;
+    }
+    if normalized.chars().count() > MAX_WORD_LIST_ENTRY_LENGTH {
+        push_error(&canonical, SourceLineErrorKind::TooLong);
+        return;
+    }
+    if index.contains_key(&normalized) {
+        return;
+    }
+
+    let Ok(score) = (if line_parts.len() < 2 {
+        Ok(50)
+    } else {
+        line_parts[1].trim().parse::<u16>()
+    }) else {
+        push_error(line_parts[1], SourceLineErrorKind::InvalidScore);
+        return;
+    };
 
-        if line_parts[0].chars().any(|c| c == '�') {
-            errors.push(WordListError::InvalidWord(line_parts[0].into()));
+    index.insert(normalized.clone(), entries.len());
+    entries.push(RawWordListEntry {
+        length: normalized.chars().count(),
+        normalized,
+        canonical,
+        score,
+        tags: vec![],
+    });
+}
+
+/// A parser for word list file contents that arrive in chunks, such as the body of a network
+/// response, so that the whole file never has to be held in memory as a single string. Lines are
+/// handled exactly as in a `File` or `FileContents` source, and chunks can split lines (or even
+/// characters) at any point.
+#[derive(Debug, Default)]
+pub struct StreamingWordListParser {
+    /// The bytes of the current line that we've received so far.
+    partial_line: Vec<u8>,
+    line_count: usize,
+    index: HashMap<String, usize>,
+    entries: Vec<RawWordListEntry>,
+    errors: Vec<WordListError>,
+}
+
+impl StreamingWordListParser {
+    #[must_use]
+    pub fn new() -> StreamingWordListParser {
+        StreamingWordListParser::default()
+    }
+
+    /// Parse every line completed by the given chunk, holding on to any incomplete line at the end.
+    pub fn push(&mut self, chunk: &[u8]) {
+        let mut rest = chunk;
+        while let Some(newline_idx) = rest.iter().position(|&byte| byte == b'\n') {
+            self.partial_line.extend_from_slice(&rest[..newline_idx]);
+            self.parse_partial_line();
+            rest = &rest[newline_idx + 1..];
+        }
+        self.partial_line.extend_from_slice(rest);
+    }
+
+    /// Parse the final line, if any, and turn the results into an in-memory source with the given
+    /// id, along with any errors found along the way.
+    #[must_use]
+    pub fn finish(mut self, id: &str) -> (WordListSourceConfig, Vec<WordListError>) {
+        if !self.partial_line.is_empty() {
+            self.parse_partial_line();
+        }
+
+        let words = self
+            .entries
+            .into_iter()
+            .map(|entry| (entry.canonical, entry.score))
+            .collect();
+        let source = WordListSourceConfig::Memory {
+            id: id.into(),
+            enabled: true,
+            words,
+        };
+        (source, self.errors)
+    }
+
+    fn parse_partial_line(&mut self) {
+        let line_idx = self.line_count;
+        self.line_count += 1;
+
+        if self.errors.len() <= MAX_SOURCE_ERRORS {
+            let line = String::from_utf8_lossy(&self.partial_line);
+            parse_word_list_line(
+                line_idx,
+                line.strip_suffix('\r').unwrap_or(&line),
+                NormalizationPreset::Default,
+                &mut self.index,
+                &mut self.entries,
+                &mut self.errors,
+            );
+        }
+        self.partial_line.clear();
+    }
+}
+
+/// Build entries from words that were provided directly rather than parsed from a file, skipping
+/// blank and duplicate words. Words are checked the same way as lines in a file, with errors
+/// reporting each rejected word's 1-based position in `words` as its line number.
+fn collect_word_entries(
+    words: impl Iterator<Item = WordIterEntry>,
+    index: &mut HashMap<String, usize>,
+    errors: &mut Vec<WordListError>,
+) -> Vec<RawWordListEntry> {
+    let mut entries = Vec::with_capacity(words.size_hint().0);
+
+    for (word_idx, (canonical, score, tags)) in words.enumerate() {
+        if errors.len() > MAX_SOURCE_ERRORS {
+            break;
+        }
+
+        let mut push_error = |kind: SourceLineErrorKind| {
+            errors.push(WordListError::InvalidLine(SourceLineError {
+                line_number: word_idx + 1,
+                text: canonical.clone(),
+                kind,
+            }));
+        };
+
+        if canonical.chars().any(|c| c == '�') {
+            push_error(SourceLineErrorKind::InvalidCharacter);
             continue;
         }
 
-        let canonical = line_parts[0].trim().to_string();
         let normalized = normalize_word(&canonical);
         if normalized.is_empty() {
             continue;
         }
-        if index.contains_key(&normalized) {
+        if normalized.chars().count() > MAX_WORD_LIST_ENTRY_LENGTH {
+            push_error(SourceLineErrorKind::TooLong);
             continue;
         }
-
-        let Ok(score) = (if line_parts.len() < 2 {
-            Ok(50)
-        } else {
-            line_parts[1].trim().parse::<u16>()
-        }) else {
-            errors.push(WordListError::InvalidScore(line_parts[1].into()));
+        if index.contains_key(&normalized) {
             continue;
-        };
+        }
 
         index.insert(normalized.clone(), entries.len());
         entries.push(RawWordListEntry {
@@ -257,6 +713,7 @@ fn parse_word_list_file_contents(
             normalized,
             canonical,
             score,
+            tags,
         });
     }
 
@@ -284,41 +741,73 @@ pub fn load_words_from_source(source: &WordListSourceConfig) -> RawWordListConte
     let mut errors = vec![];
 
     let entries = match source {
-        WordListSourceConfig::Memory { words, .. } => {
-            let mut entries = Vec::with_capacity(words.len());
-
-            for (canonical, score) in words.iter().cloned() {
-                let normalized = normalize_word(&canonical);
-                if normalized.is_empty() {
-                    continue;
-                }
-                if index.contains_key(&normalized) {
-                    continue;
-                }
-
-                index.insert(normalized.clone(), entries.len());
-                entries.push(RawWordListEntry {
-                    length: normalized.chars().count(),
-                    normalized,
-                    canonical,
-                    score,
-                });
+        WordListSourceConfig::Memory { words, .. } => collect_word_entries(
+            words
+                .iter()
+                .map(|(canonical, score)| (canonical.clone(), *score, vec![])),
+            &mut index,
+            &mut errors,
+        ),
+
+        WordListSourceConfig::Iter { words, .. } => {
+            collect_word_entries(words.load(), &mut index, &mut errors)
+        }
+
+        #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+        WordListSourceConfig::Url { url, cache_dir, .. } => {
+            match crate::url_source::fetch_word_list(url, cache_dir.as_deref(), &mut errors) {
+                Some(contents) => parse_word_list_file_contents(
+                    &contents,
+                    NormalizationPreset::Default,
+                    &mut index,
+                    &mut errors,
+                ),
+                None => vec![],
             }
+        }
 
-            entries
+        #[cfg(not(all(feature = "http", not(target_arch = "wasm32"))))]
+        WordListSourceConfig::Url { url, .. } => {
+            errors.push(WordListError::FetchFailed {
+                url: url.clone(),
+                reason: "this build doesn’t support fetching word lists".into(),
+            });
+            vec![]
         }
 
         WordListSourceConfig::File { path, .. } => {
             if let Ok(contents) = read_file_tolerating_invalid_encoding(path) {
-                parse_word_list_file_contents(&contents, &mut index, &mut errors)
+                parse_word_list_file_contents(
+                    &contents,
+                    NormalizationPreset::Default,
+                    &mut index,
+                    &mut errors,
+                )
             } else {
                 errors.push(WordListError::InvalidPath(path.to_string_lossy().into()));
                 vec![]
             }
         }
 
-        WordListSourceConfig::FileContents { contents, .. } => {
-            parse_word_list_file_contents(contents, &mut index, &mut errors)
+        WordListSourceConfig::FileContents { contents, .. } => parse_word_list_file_contents(
+            contents,
+            NormalizationPreset::Default,
+            &mut index,
+            &mut errors,
+        ),
+
+        WordListSourceConfig::Bundled { list, .. } => {
+            if let Some(contents) = list.contents() {
+                parse_word_list_file_contents(
+                    contents,
+                    list.normalization(),
+                    &mut index,
+                    &mut errors,
+                )
+            } else {
+                errors.push(WordListError::BundledListUnavailable(list.name().into()));
+                vec![]
+            }
         }
     };
 
@@ -335,14 +824,19 @@ pub fn refresh_source(
     source: &WordListSourceConfig,
     source_index: u16,
     source_states: &mut HashMap<String, WordListSourceState>,
+    score_transform: Option<&ScoreTransform>,
 ) {
     let RawWordListContents {
-        entries,
+        mut entries,
         mtime,
         index,
         errors,
     } = load_words_from_source(source);
 
+    if let Some(score_transform) = score_transform {
+        score_transform.apply(&source.id(), &mut entries);
+    }
+
     let mut new_state = WordListSourceState {
         source_index,
         id: source.id(),
@@ -366,6 +860,7 @@ pub fn refresh_source_if_needed(
     source: &WordListSourceConfig,
     source_index: u16,
     source_states: &mut HashMap<String, WordListSourceState>,
+    score_transform: Option<&ScoreTransform>,
 ) {
     let old_state = source_states.get_mut(&source.id());
     if let Some(old_state) = old_state {
@@ -376,11 +871,59 @@ pub fn refresh_source_if_needed(
         }
     }
 
-    refresh_source(source, source_index, source_states);
+    refresh_source(source, source_index, source_states, score_transform);
 }
 
 type OnUpdateCallback = Box<dyn FnMut(&mut WordList, &[GlobalWordId]) + Send + Sync>;
 
+/// How many entries `WordList::new_interruptible` processes between progress reports (and checks
+/// for cancellation).
+pub const LOAD_PROGRESS_INTERVAL: usize = 10_000;
+
+/// How far `WordList::new_interruptible` has gotten through its sources.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WordListLoadProgress {
+    /// The index of the source being loaded, out of `source_count`.
+    pub source_index: usize,
+    pub source_count: usize,
+
+    /// How many of the current source's entries have been processed, out of `source_entry_count`.
+    pub source_entries_loaded: usize,
+    pub source_entry_count: usize,
+}
+
+/// Called with the progress of a load every `LOAD_PROGRESS_INTERVAL` entries and at the end of
+/// each source; returns false to stop loading.
+type LoadProgressCallback<'a> = &'a mut dyn FnMut(WordListLoadProgress) -> bool;
+
+/// Optional settings for constructing a `WordList` with `new_with_options`.
+#[derive(Debug, Clone, Default)]
+pub struct WordListOptions {
+    /// Omit entries longer than this.
+    pub max_length: Option<usize>,
+
+    /// The longest substring that two words in a fill can share (see `instantiate_dupe_index`).
+    pub max_shared_substring: Option<usize>,
+
+    /// If present, only words with these lengths are added to the dupe index.
+    pub dupe_index_lengths: Option<HashSet<usize>>,
+
+    /// If present, multi-word entries are treated as dupes of their components.
+    pub compound_splitting: Option<CompoundSplitting>,
+
+    /// If present, words that sound alike under this encoding are treated as dupes.
+    pub phonetic_encoding: Option<PhoneticEncoding>,
+
+    /// If present, applied to each source's scores whenever the source is loaded.
+    pub score_transform: Option<ScoreTransform>,
+
+    /// Personal adjustments applied on top of the merged list's scores.
+    pub score_overrides: ScoreOverrides,
+
+    /// If present, per-character weights used to calculate each word's `letter_score`.
+    pub letter_points: Option<HashMap<char, u16>>,
+}
+
 /// Errors that can arise when syncing to disk, keyed by the relevant source id.
 pub type SyncErrors = HashMap<String, io::Error>;
 
@@ -405,15 +948,62 @@ pub struct WordList {
     /// A map from a normalized string to the id of the Word representing it.
     pub word_id_by_string: HashMap<String, WordId>,
 
+    /// Arbitrary labels attached to words by the source they came from (e.g., "theme" or
+    /// "abbreviation"). Only programmatic sources can currently provide tags, so most words have
+    /// none; we store them here rather than on `Word` to avoid growing every entry.
+    pub tags_by_word_id: HashMap<GlobalWordId, Vec<String>>,
+
+    /// Recency information supplied by the caller, used to favor fresh entries when
+    /// `SearchConfig::favor_fresh_words` is set. Unlike tags, this isn't tied to any source, so it
+    /// survives refreshes and edits.
+    pub recency_by_word_id: HashMap<GlobalWordId, WordRecency>,
+
+    /// A static measure of how easy each word is to cross, indexed like `words`: the average, over
+    /// the word's positions, of the log of the number of visible words of the same length that
+    /// have the same glyph in that position. This is rebuilt whenever the list is loaded or
+    /// refreshed, so words added since then (e.g., by `optimistically_update_word`) have no score.
+    pub fillability_scores: Vec<Vec<f32>>,
+
     /// A dupe index reflecting the max substring length provided when configuring the `WordList`.
     pub dupe_index: BoxedDupeIndex,
 
+    /// If present, only words with these lengths are added to `dupe_index`. Building the substring
+    /// index for every length is a significant cost for small grids, which only need a few of them.
+    pub dupe_index_lengths: Option<HashSet<usize>>,
+
+    /// If present, multi-word entries are treated as dupes of their components in `dupe_index`.
+    /// Components are worked out whenever the list is loaded or refreshed, so words added since
+    /// then (e.g., by `optimistically_update_word`) aren't split or matched as components.
+    pub compound_splitting: Option<CompoundSplitting>,
+
+    /// If present, words with the same code under this encoding are treated as dupes in
+    /// `dupe_index`. Like compound components, the groups are worked out whenever the list is
+    /// loaded or refreshed.
+    pub phonetic_encoding: Option<PhoneticEncoding>,
+
     /// The maximum word length provided when configuring the `WordList`, if any.
     pub max_length: Option<usize>,
 
     /// Callback run after adding words.
     pub on_update: Option<OnUpdateCallback>,
 
+    /// If present, applied to each source's scores whenever the source is loaded.
+    pub score_transform: Option<ScoreTransform>,
+
+    /// Personal adjustments applied to each word's score after the sources are merged, so that
+    /// `Word::score` reflects them but the sources themselves (and `personal_word_score`) don't.
+    pub score_overrides: ScoreOverrides,
+
+    /// The number of times each normalized word has been rejected by the user in a fill during
+    /// this session (see `record_rejection`).
+    pub rejection_counts: HashMap<String, usize>,
+
+    /// If present, per-character weights that replace the default point values when calculating
+    /// each word's `letter_score`, which `sort_slot_options` uses to favor interesting fills.
+    /// Giving high weights to letters like J, Q, X, and Z and low weights to E and S will make
+    /// fills more "Scrabble-y". Characters missing from the map get the default point value.
+    pub letter_points: Option<HashMap<char, u16>>,
+
     /// The most recently-received word list sources, as an ordered list.
     pub source_configs: Vec<WordListSourceConfig>,
 
@@ -440,14 +1030,77 @@ impl WordList {
         max_length: Option<usize>,
         max_shared_substring: Option<usize>,
     ) -> WordList {
+        WordList::new_with_options(
+            source_configs,
+            personal_list_index,
+            WordListOptions {
+                max_length,
+                max_shared_substring,
+                ..WordListOptions::default()
+            },
+        )
+    }
+
+    /// Construct a new `WordList` like `new`, but only indexing dupes for words with the given
+    /// lengths (see `dupe_index_lengths`). This is useful when the lengths needed by the target
+    /// grid are known up front.
+    #[must_use]
+    pub fn new_with_dupe_index_lengths(
+        source_configs: Vec<WordListSourceConfig>,
+        personal_list_index: Option<u16>,
+        max_length: Option<usize>,
+        max_shared_substring: Option<usize>,
+        dupe_index_lengths: Option<HashSet<usize>>,
+    ) -> WordList {
+        WordList::new_with_options(
+            source_configs,
+            personal_list_index,
+            WordListOptions {
+                max_length,
+                max_shared_substring,
+                dupe_index_lengths,
+                ..WordListOptions::default()
+            },
+        )
+    }
+
+    /// Construct a new `WordList` using the given sources and any combination of the optional
+    /// settings in `WordListOptions`.
+    #[must_use]
+    pub fn new_with_options(
+        source_configs: Vec<WordListSourceConfig>,
+        personal_list_index: Option<u16>,
+        options: WordListOptions,
+    ) -> WordList {
+        let WordListOptions {
+            max_length,
+            max_shared_substring,
+            dupe_index_lengths,
+            compound_splitting,
+            phonetic_encoding,
+            score_transform,
+            score_overrides,
+            letter_points,
+        } = options;
+
         let mut instance = WordList {
             glyphs: vec![],
             glyph_id_by_char: HashMap::new(),
             words: vec![vec![]],
             word_id_by_string: HashMap::new(),
+            tags_by_word_id: HashMap::new(),
+            recency_by_word_id: HashMap::new(),
+            fillability_scores: vec![],
             dupe_index: WordList::instantiate_dupe_index(max_shared_substring),
+            dupe_index_lengths,
+            compound_splitting,
+            phonetic_encoding,
             max_length,
             on_update: None,
+            score_transform,
+            score_overrides,
+            rejection_counts: HashMap::new(),
+            letter_points,
             source_configs: vec![],
             personal_list_index,
             source_states: HashMap::new(),
@@ -459,6 +1112,106 @@ impl WordList {
         instance
     }
 
+    /// Construct a new `WordList` like `new_with_options`, but calling `on_progress` as the
+    /// sources' entries are processed (see `LOAD_PROGRESS_INTERVAL`) and giving up as soon as
+    /// possible once `cancel` is cancelled. This lets a UI show progress while loading a large list
+    /// and abandon it if the user picks another one. Reading and parsing each source happens in one
+    /// step before its entries are processed, so cancellation takes effect between those steps.
+    pub fn new_interruptible(
+        source_configs: Vec<WordListSourceConfig>,
+        personal_list_index: Option<u16>,
+        options: WordListOptions,
+        cancel: &CancelToken,
+        mut on_progress: impl FnMut(WordListLoadProgress),
+    ) -> Result<WordList, CancelReason> {
+        let max_length = options.max_length;
+        let mut instance = WordList::new_with_options(vec![], personal_list_index, options);
+        instance.update_list_with_progress(
+            source_configs,
+            personal_list_index,
+            max_length,
+            false,
+            true,
+            Some(&mut |progress| {
+                on_progress(progress);
+                !cancel.is_cancelled()
+            }),
+        );
+
+        match cancel.reason() {
+            Some(reason) => Err(reason),
+            None => Ok(instance),
+        }
+    }
+
+    /// Replace the score transform and reload every source so that it takes effect. Returns the
+    /// same information as `replace_list`.
+    pub fn set_score_transform(
+        &mut self,
+        score_transform: Option<ScoreTransform>,
+    ) -> (bool, HashSet<GlobalWordId>) {
+        self.score_transform = score_transform;
+        for (source_index, source) in self.source_configs.iter().enumerate() {
+            refresh_source(
+                source,
+                source_index as u16,
+                &mut self.source_states,
+                self.score_transform.as_ref(),
+            );
+        }
+        self.refresh_from_disk()
+    }
+
+    /// Replace the score overrides and recompute every word's score from the entries loaded last
+    /// time, without reloading any sources. Returns the same information as `replace_list`.
+    pub fn set_score_overrides(
+        &mut self,
+        score_overrides: ScoreOverrides,
+    ) -> (bool, HashSet<GlobalWordId>) {
+        self.score_overrides = score_overrides;
+        self.update_list(
+            self.source_configs.clone(),
+            self.personal_list_index,
+            self.max_length,
+            false,
+            false,
+        )
+    }
+
+    /// Record that the user rejected the given (canonical or normalized) word in a fill, returning
+    /// the number of times they've now rejected it this session. If `penalty` is given, the word's
+    /// score override is also lowered by that much (see `ScoreOverrides`), so that later fills
+    /// favor it less and less; since only the one word changes, its score is updated in place
+    /// rather than by recomputing the list.
+    pub fn record_rejection(&mut self, word: &str, penalty: Option<u16>) -> usize {
+        let normalized = normalize_word(word);
+        if normalized.is_empty() {
+            return 0;
+        }
+
+        if let Some(penalty) = penalty {
+            let adjustment = match self.score_overrides.get(&normalized) {
+                Some(ScoreAdjustment::Offset(offset)) => {
+                    ScoreAdjustment::Offset(offset - i32::from(penalty))
+                }
+                Some(ScoreAdjustment::Set(score)) => {
+                    ScoreAdjustment::Set(score.saturating_sub(penalty))
+                }
+                None => ScoreAdjustment::Offset(-i32::from(penalty)),
+            };
+            self.score_overrides.set(&normalized, adjustment);
+
+            if let Some(&word_id) = self.word_id_by_string.get(&normalized) {
+                let word = &mut self.words[normalized.chars().count()][word_id.index()];
+                word.score = word.score.saturating_sub(penalty);
+            }
+        }
+
+        let rejection_count = self.rejection_counts.entry(normalized).or_default();
+        *rejection_count += 1;
+        *rejection_count
+    }
+
     /// If the given normalized word is already in the list, return its id; if not, add it as a
     /// hidden entry and return the id of that.
     pub fn get_word_id_or_add_hidden(&mut self, normalized_word: &str) -> GlobalWordId {
@@ -474,7 +1227,127 @@ impl WordList {
     /// Borrow an existing word using its global id.
     #[must_use]
     pub fn get_word(&self, global_word_id: GlobalWordId) -> &Word {
-        &self.words[global_word_id.0][global_word_id.1]
+        &self.words[global_word_id.0][global_word_id.1.index()]
+    }
+
+    /// A hash of the effective contents of the list (every visible word, with its display form
+    /// and score), for recording which list produced a fill. Words are hashed in id order, so
+    /// loading the same sources in the same order always gives the same hash.
+    #[must_use]
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = StableHasher::new();
+        for word in self.words.iter().flatten().filter(|word| !word.hidden) {
+            hasher.write_str(&word.normalized_string);
+            hasher.write_str(&word.canonical_string);
+            hasher.write_u16(word.score);
+        }
+        hasher.finish()
+    }
+
+    /// The tags attached to an existing word by the source it came from, if any.
+    #[must_use]
+    pub fn get_word_tags(&self, global_word_id: GlobalWordId) -> &[String] {
+        self.tags_by_word_id
+            .get(&global_word_id)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Replace the tags attached to the given word, dropping its entry if there are none.
+    fn set_word_tags(&mut self, global_word_id: GlobalWordId, tags: &[String]) {
+        if tags.is_empty() {
+            self.tags_by_word_id.remove(&global_word_id);
+        } else {
+            self.tags_by_word_id.insert(global_word_id, tags.to_vec());
+        }
+    }
+
+    /// The recency information attached to an existing word, if any.
+    #[must_use]
+    pub fn get_word_recency(&self, global_word_id: GlobalWordId) -> Option<WordRecency> {
+        self.recency_by_word_id.get(&global_word_id).copied()
+    }
+
+    /// Attach recency information to an existing word, or clear it by passing `None`.
+    pub fn set_word_recency(&mut self, global_word_id: GlobalWordId, recency: Option<WordRecency>) {
+        match recency {
+            Some(recency) => {
+                self.recency_by_word_id.insert(global_word_id, recency);
+            }
+            None => {
+                self.recency_by_word_id.remove(&global_word_id);
+            }
+        }
+    }
+
+    /// The fillability score of the given word (see `fillability_scores`), or 0 if it was added
+    /// after the scores were last rebuilt.
+    #[must_use]
+    pub fn get_fillability_score(&self, global_word_id: GlobalWordId) -> f32 {
+        self.fillability_scores
+            .get(global_word_id.0)
+            .and_then(|scores| scores.get(global_word_id.1.index()))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// Recalculate `fillability_scores` based on the currently-visible words.
+    pub fn rebuild_fillability_scores(&mut self) {
+        self.fillability_scores = self
+            .words
+            .iter()
+            .enumerate()
+            .map(|(length, words)| {
+                let visible_word_ids: Vec<WordId> = WordId::range(words.len())
+                    .zip(words)
+                    .filter(|(_, word)| !word.hidden)
+                    .map(|(word_id, _)| word_id)
+                    .collect();
+                let glyph_counts_by_cell =
+                    build_glyph_counts_by_cell(self, length, &visible_word_ids);
+
+                words
+                    .iter()
+                    .map(|word| {
+                        let total: f32 = word
+                            .glyphs
+                            .iter()
+                            .zip(&glyph_counts_by_cell)
+                            .map(|(&glyph, counts)| (counts[glyph].max(1) as f32).log10())
+                            .sum();
+                        total / (length.max(1) as f32)
+                    })
+                    .collect()
+            })
+            .collect();
+    }
+
+    /// Sum the point values of the characters in the given normalized word.
+    fn calculate_letter_score(&self, normalized_word: &str) -> u16 {
+        normalized_word
+            .chars()
+            .map(|char| {
+                self.letter_points
+                    .as_ref()
+                    .and_then(|letter_points| letter_points.get(&char))
+                    .or_else(|| LETTER_POINTS.get(&char))
+                    .copied()
+                    .unwrap_or(3)
+            })
+            .fold(0, u16::saturating_add)
+    }
+
+    /// Replace the per-character weights used for `letter_score` (see `letter_points`) and
+    /// recalculate the letter scores of every word. Since this only changes option ordering, any
+    /// existing grid configs need to be regenerated to pick it up.
+    pub fn set_letter_points(&mut self, letter_points: Option<HashMap<char, u16>>) {
+        self.letter_points = letter_points;
+        for length in 0..self.words.len() {
+            for word_idx in 0..self.words[length].len() {
+                let letter_score =
+                    self.calculate_letter_score(&self.words[length][word_idx].normalized_string);
+                self.words[length][word_idx].letter_score = letter_score;
+            }
+        }
     }
 
     /// Add the given word to the list as a hidden entry and trigger the update callback. The word
@@ -486,6 +1359,7 @@ impl WordList {
                 normalized: normalized_word.to_string(),
                 canonical: normalized_word.to_string(),
                 score: 0,
+                tags: vec![],
             },
             None,
             true,
@@ -520,18 +1394,17 @@ impl WordList {
             self.words.push(vec![]);
         }
 
-        let word_id = self.words[word_length].len();
+        let word_id = WordId(self.words[word_length].len());
+        let letter_score = self.calculate_letter_score(&raw_entry.normalized);
 
         self.words[word_length].push(Word {
             normalized_string: raw_entry.normalized.clone(),
             canonical_string: raw_entry.canonical.clone(),
             glyphs,
-            score: raw_entry.score,
-            letter_score: raw_entry
-                .normalized
-                .chars()
-                .map(|char| LETTER_POINTS.get(&char).copied().unwrap_or(3))
-                .sum(),
+            score: self
+                .score_overrides
+                .apply(&raw_entry.normalized, raw_entry.score),
+            letter_score,
             hidden,
             source_index,
             personal_word_score: if self
@@ -543,12 +1416,15 @@ impl WordList {
                 None
             },
         });
+        self.set_word_tags((word_length, word_id), &raw_entry.tags);
 
         self.word_id_by_string
             .insert(raw_entry.normalized.clone(), word_id);
 
-        self.dupe_index
-            .add_word(word_id, &self.words[word_length][word_id]);
+        if self.should_index_dupes(word_length) {
+            self.dupe_index
+                .add_word(word_id, &self.words[word_length][word_id.index()]);
+        }
 
         (word_length, word_id)
     }
@@ -575,6 +1451,70 @@ impl WordList {
         personal_list_index: Option<u16>,
         max_length: Option<usize>,
         silent: bool,
+    ) -> (bool, HashSet<GlobalWordId>) {
+        self.update_list(
+            source_configs,
+            personal_list_index,
+            max_length,
+            silent,
+            true,
+        )
+    }
+
+    /// Enable or disable the source with the given id, recomputing the effective list from the
+    /// entries loaded last time instead of reloading any sources. Words that were only in sources
+    /// that are now disabled are hidden, not removed, so toggling a source back on is just as
+    /// cheap. Returns the same information as `replace_list`, or `None` if there's no source with
+    /// the given id.
+    pub fn set_source_enabled(
+        &mut self,
+        source_id: &str,
+        enabled: bool,
+    ) -> Option<(bool, HashSet<GlobalWordId>)> {
+        let source_index = self.find_source_index_for_id(source_id)?;
+        let mut source_configs = self.source_configs.clone();
+        source_configs[source_index as usize].set_enabled(enabled);
+
+        Some(self.update_list(
+            source_configs,
+            self.personal_list_index,
+            self.max_length,
+            false,
+            false,
+        ))
+    }
+
+    /// Replace the list as described in `replace_list`, reloading any sources that may have
+    /// changed if `reload_sources` is true, or otherwise only those we haven't loaded before.
+    fn update_list(
+        &mut self,
+        source_configs: Vec<WordListSourceConfig>,
+        personal_list_index: Option<u16>,
+        max_length: Option<usize>,
+        silent: bool,
+        reload_sources: bool,
+    ) -> (bool, HashSet<GlobalWordId>) {
+        self.update_list_with_progress(
+            source_configs,
+            personal_list_index,
+            max_length,
+            silent,
+            reload_sources,
+            None,
+        )
+    }
+
+    /// Like `update_list`, but reporting progress to the given callback, if any. If the callback
+    /// stops the load, the list is left partly updated.
+    #[allow(clippy::too_many_lines)]
+    fn update_list_with_progress(
+        &mut self,
+        source_configs: Vec<WordListSourceConfig>,
+        personal_list_index: Option<u16>,
+        max_length: Option<usize>,
+        silent: bool,
+        reload_sources: bool,
+        on_progress: Option<LoadProgressCallback>,
     ) -> (bool, HashSet<GlobalWordId>) {
         self.source_configs = source_configs;
         self.personal_list_index = personal_list_index;
@@ -588,13 +1528,15 @@ impl WordList {
             .iter()
             .enumerate()
             .flat_map(|(length, words)| {
-                words.iter().enumerate().filter_map(move |(word_id, word)| {
-                    if word.hidden {
-                        None
-                    } else {
-                        Some((length, word_id))
-                    }
-                })
+                WordId::range(words.len())
+                    .zip(words)
+                    .filter_map(move |(word_id, word)| {
+                        if word.hidden {
+                            None
+                        } else {
+                            Some((length, word_id))
+                        }
+                    })
             })
             .collect();
         let mut less_visible_words_set: HashSet<GlobalWordId> = HashSet::new();
@@ -612,21 +1554,26 @@ impl WordList {
 
         let mut hidden_personal_scores: HashMap<GlobalWordId, u16> = HashMap::new();
 
-        self.load_words_from_source_configs(
+        let completed = self.load_words_from_source_configs(
             max_length,
+            reload_sources,
+            on_progress,
             |word_list, raw_entry, source_index| {
                 let word_length = raw_entry.length;
                 let existing_word_id = word_list.word_id_by_string.get(&raw_entry.normalized);
 
                 if let Some(&existing_word_id) = existing_word_id {
-                    let word = &mut word_list.words[word_length][existing_word_id];
-                    if word.hidden || raw_entry.score > word.score {
+                    let score = word_list
+                        .score_overrides
+                        .apply(&raw_entry.normalized, raw_entry.score);
+                    let word = &mut word_list.words[word_length][existing_word_id.index()];
+                    if word.hidden || score > word.score {
                         any_more_visible = true;
                     }
-                    if !word.hidden && raw_entry.score < word.score {
+                    if !word.hidden && score < word.score {
                         less_visible_words_set.insert((word_length, existing_word_id));
                     }
-                    word.score = raw_entry.score;
+                    word.score = score;
                     word.hidden = false;
                     word.canonical_string.clone_from(&raw_entry.canonical);
                     word.source_index = Some(source_index);
@@ -636,6 +1583,7 @@ impl WordList {
                         } else {
                             None
                         };
+                    word_list.set_word_tags((word_length, existing_word_id), &raw_entry.tags);
                     removed_words_set.remove(&(word_length, existing_word_id));
                 } else if !silent {
                     any_more_visible = true;
@@ -652,12 +1600,15 @@ impl WordList {
                 hidden_personal_scores.insert(word_id, raw_entry.score);
             },
         );
+        if !completed {
+            return (any_more_visible, less_visible_words_set);
+        }
 
         // Hide any words that were in our existing list but aren't in the new one.
         for &(length, word_id) in &removed_words_set {
-            self.words[length][word_id].hidden = true;
-            self.words[length][word_id].source_index = None;
-            self.words[length][word_id].personal_word_score = None;
+            self.words[length][word_id.index()].hidden = true;
+            self.words[length][word_id.index()].source_index = None;
+            self.words[length][word_id.index()].personal_word_score = None;
             less_visible_words_set.insert((length, word_id));
         }
 
@@ -665,8 +1616,19 @@ impl WordList {
         // or the whole personal list is disabled, set the `personal_word_score` field on words
         // that weren't already updated.
         for ((length, word_id), score) in hidden_personal_scores {
-            self.words[length][word_id].personal_word_score = Some(score);
+            self.words[length][word_id.index()].personal_word_score = Some(score);
+        }
+
+        if self.compound_splitting.is_some() {
+            let components_by_compound = self.compound_components();
+            self.dupe_index
+                .set_compound_components(components_by_compound);
+        }
+        if self.phonetic_encoding.is_some() {
+            let phonetic_groups = self.phonetic_groups();
+            self.dupe_index.set_phonetic_groups(phonetic_groups);
         }
+        self.rebuild_fillability_scores();
 
         if let Some(mut on_update) = self.on_update.take() {
             on_update(self, &newly_added_words);
@@ -676,12 +1638,16 @@ impl WordList {
         (any_more_visible, less_visible_words_set)
     }
 
+    /// Process the entries of every source, returning false if `on_progress` stopped us early.
+    #[allow(clippy::too_many_lines)]
     fn load_words_from_source_configs(
         &mut self,
         max_length: Option<usize>,
+        reload_sources: bool,
+        mut on_progress: Option<LoadProgressCallback>,
         mut add_word: impl FnMut(&mut WordList, &RawWordListEntry, u16),
         mut handle_disabled_personal_entry: impl FnMut(&mut WordList, &RawWordListEntry),
-    ) {
+    ) -> bool {
         fn hash_str(str: &str) -> u64 {
             let mut hasher = DefaultHasher::new();
             str.hash(&mut hasher);
@@ -696,14 +1662,27 @@ impl WordList {
         );
 
         let mut seen_words: HashSet<u64> = HashSet::new();
+        let mut completed = true;
 
-        for (source_index, source) in source_configs.iter().enumerate() {
+        'sources: for (source_index, source) in source_configs.iter().enumerate() {
             let is_source_enabled = source.enabled();
             let is_personal_list = self
                 .personal_list_index
                 .map_or(false, |idx| idx == (source_index as u16));
 
-            refresh_source_if_needed(source, source_index as u16, &mut source_states);
+            if let Some(source_state) = source_states
+                .get_mut(&source.id())
+                .filter(|_| !reload_sources)
+            {
+                source_state.source_index = source_index as u16;
+            } else {
+                refresh_source_if_needed(
+                    source,
+                    source_index as u16,
+                    &mut source_states,
+                    self.score_transform.as_ref(),
+                );
+            }
 
             // If the source is disabled, none of its words (or pending updates) should affect the
             // actual wordlist. The exception is if this is the personal word list, in which case
@@ -732,6 +1711,7 @@ impl WordList {
                             normalized: normalized.clone(),
                             canonical: canonical.clone(),
                             score: *score,
+                            tags: vec![],
                         });
                     }
                 }
@@ -758,21 +1738,40 @@ impl WordList {
                 add_word(self, word, source_state.source_index);
                 seen_words.insert(hash);
             };
-            for word in &updated_words {
+            let words = updated_words
+                .iter()
+                .chain(source_state.entries.iter().filter(|word| {
+                    superseded_words.as_ref().map_or(true, |superseded_words| {
+                        !superseded_words.contains(&word.normalized)
+                    })
+                }));
+            let source_entry_count = updated_words.len() + source_state.entries.len();
+            let mut report_progress = |source_entries_loaded| {
+                on_progress.as_mut().map_or(true, |on_progress| {
+                    on_progress(WordListLoadProgress {
+                        source_index,
+                        source_count: source_configs.len(),
+                        source_entries_loaded,
+                        source_entry_count,
+                    })
+                })
+            };
+            for (word_idx, word) in words.enumerate() {
                 process_word(word);
-            }
-            for word in &source_state.entries {
-                if let Some(superseded_words) = superseded_words.as_ref() {
-                    if superseded_words.contains(&word.normalized) {
-                        continue;
-                    }
+                if (word_idx + 1) % LOAD_PROGRESS_INTERVAL == 0 && !report_progress(word_idx + 1) {
+                    completed = false;
+                    break 'sources;
                 }
-                process_word(word);
+            }
+            if !report_progress(source_entry_count) {
+                completed = false;
+                break;
             }
         }
 
         self.source_configs = source_configs;
         self.source_states = source_states;
+        completed
     }
 
     /// What's the unique glyph id for the given char? We do this lazily, instead of just mapping
@@ -787,8 +1786,45 @@ impl WordList {
         })
     }
 
-    /// Update the `max_shared_substring` config by regenerating the dupe index.
+    /// Should words of the given length be added to the dupe index?
+    fn should_index_dupes(&self, length: usize) -> bool {
+        self.dupe_index_lengths
+            .as_ref()
+            .map_or(true, |lengths| lengths.contains(&length))
+    }
+
+    /// Restrict the dupe index to words with the given lengths (or, if `None`, stop restricting
+    /// it), rebuilding it if the set of lengths has changed.
+    pub fn set_dupe_index_lengths(&mut self, dupe_index_lengths: Option<HashSet<usize>>) {
+        if dupe_index_lengths == self.dupe_index_lengths {
+            return;
+        }
+        self.dupe_index_lengths = dupe_index_lengths;
+        self.rebuild_dupe_index(self.max_shared_substring());
+    }
+
+    /// The `max_shared_substring` setting reflected by the current dupe index, or `None` if we
+    /// aren't checking for shared substrings.
+    #[must_use]
+    pub fn max_shared_substring(&self) -> Option<usize> {
+        let window_size = self.dupe_index.window_size();
+        (window_size > 0).then(|| window_size - 1)
+    }
+
+    /// Update the `max_shared_substring` config by regenerating the dupe index. Everything else
+    /// (glyphs, scores, and word ids) is left alone, as are any extra dupe pairs, so this is cheap
+    /// enough to expose as a live setting. Does nothing if the setting hasn't changed.
     pub fn update_max_shared_substring(&mut self, max_shared_substring: Option<usize>) {
+        let new_window_size = WordList::instantiate_dupe_index(max_shared_substring).window_size();
+        if new_window_size == self.dupe_index.window_size() {
+            return;
+        }
+        self.rebuild_dupe_index(max_shared_substring);
+    }
+
+    /// Replace the dupe index with a freshly-populated one for the given `max_shared_substring`,
+    /// carrying over any extra dupe pairs.
+    fn rebuild_dupe_index(&mut self, max_shared_substring: Option<usize>) {
         let extra_dupes = self.dupe_index.take_extra_dupes();
         let mut new_dupe_index = WordList::instantiate_dupe_index(max_shared_substring);
         self.populate_dupe_index(new_dupe_index.as_mut());
@@ -816,17 +1852,275 @@ impl WordList {
         }
     }
 
+    /// Treat multi-word entries as dupes of their components according to the given settings (or,
+    /// if `None`, stop doing so), updating the dupe index if the settings have changed.
+    pub fn set_compound_splitting(&mut self, compound_splitting: Option<CompoundSplitting>) {
+        if compound_splitting == self.compound_splitting {
+            return;
+        }
+        self.compound_splitting = compound_splitting;
+        let components_by_compound = self.compound_components();
+        self.dupe_index
+            .set_compound_components(components_by_compound);
+    }
+
+    /// For each visible multi-word entry that should be indexed, the visible words making it up,
+    /// according to `compound_splitting`.
+    fn compound_components(&self) -> HashMap<GlobalWordId, Vec<GlobalWordId>> {
+        let mut components_by_compound = HashMap::new();
+        let Some(compound_splitting) = &self.compound_splitting else {
+            return components_by_compound;
+        };
+
+        for (length, bucket) in self.words.iter().enumerate() {
+            if !self.should_index_dupes(length) {
+                continue;
+            }
+            for (word_id, word) in WordId::range(bucket.len()).zip(bucket) {
+                if word.hidden {
+                    continue;
+                }
+                let components = self.split_compound(word, compound_splitting);
+                if !components.is_empty() {
+                    components_by_compound.insert((length, word_id), components);
+                }
+            }
+        }
+
+        components_by_compound
+    }
+
+    /// The visible, indexed words with at least `min_component_length` letters that make up the
+    /// given word, or an empty vec if it isn't a compound. Display forms with spaces or hyphens
+    /// are split there; otherwise, if `segment_unspaced` is set, we look for the split into the
+    /// fewest words from the list.
+    fn split_compound(
+        &self,
+        word: &Word,
+        compound_splitting: &CompoundSplitting,
+    ) -> Vec<GlobalWordId> {
+        let min_length = compound_splitting.min_component_length.max(1);
+        let lookup = |normalized: &str| -> Option<GlobalWordId> {
+            let length = normalized.chars().count();
+            if length < min_length || !self.should_index_dupes(length) {
+                return None;
+            }
+            let word_id = *self.word_id_by_string.get(normalized)?;
+            (!self.words[length][word_id.index()].hidden).then_some((length, word_id))
+        };
+
+        let pieces: Vec<&str> = word
+            .canonical_string
+            .split(|c: char| c.is_whitespace() || c == '-')
+            .filter(|piece| !piece.is_empty())
+            .collect();
+        if pieces.len() > 1 {
+            let mut components: Vec<GlobalWordId> = vec![];
+            for component in pieces
+                .iter()
+                .filter_map(|piece| lookup(&normalize_word(piece)))
+            {
+                if !components.contains(&component) {
+                    components.push(component);
+                }
+            }
+            return components;
+        }
+        if !compound_splitting.segment_unspaced {
+            return vec![];
+        }
+
+        // `best_splits[i]` is the shortest split of the first `i` characters, as a list of the
+        // indices where its pieces start.
+        let chars: Vec<char> = word.normalized_string.chars().collect();
+        let mut best_splits: Vec<Option<Vec<usize>>> = vec![None; chars.len() + 1];
+        best_splits[0] = Some(vec![]);
+        for end in min_length..=chars.len() {
+            for start in 0..=(end - min_length) {
+                if start == 0 && end == chars.len() {
+                    continue;
+                }
+                let Some(prefix_split) = &best_splits[start] else {
+                    continue;
+                };
+                if best_splits[end]
+                    .as_ref()
+                    .map_or(false, |split| split.len() <= prefix_split.len() + 1)
+                {
+                    continue;
+                }
+                let piece: String = chars[start..end].iter().collect();
+                if lookup(&piece).is_some() {
+                    let mut split = prefix_split.clone();
+                    split.push(start);
+                    best_splits[end] = Some(split);
+                }
+            }
+        }
+
+        let Some(starts) = best_splits[chars.len()].take() else {
+            return vec![];
+        };
+        let mut components: Vec<GlobalWordId> = vec![];
+        for (i, &start) in starts.iter().enumerate() {
+            let end = starts.get(i + 1).copied().unwrap_or(chars.len());
+            let piece: String = chars[start..end].iter().collect();
+            let component = lookup(&piece).expect("split_compound: piece missing from list");
+            if !components.contains(&component) {
+                components.push(component);
+            }
+        }
+        components
+    }
+
+    /// Treat words that sound alike under the given encoding as dupes (or, if `None`, stop doing
+    /// so), updating the dupe index if the setting has changed.
+    pub fn set_phonetic_encoding(&mut self, phonetic_encoding: Option<PhoneticEncoding>) {
+        if phonetic_encoding == self.phonetic_encoding {
+            return;
+        }
+        self.phonetic_encoding = phonetic_encoding;
+        let phonetic_groups = self.phonetic_groups();
+        self.dupe_index.set_phonetic_groups(phonetic_groups);
+    }
+
+    /// Groups of two or more visible, indexed words sharing a code under `phonetic_encoding`.
+    fn phonetic_groups(&self) -> Vec<Vec<GlobalWordId>> {
+        let Some(phonetic_encoding) = self.phonetic_encoding else {
+            return vec![];
+        };
+
+        let mut groups_by_code: HashMap<String, Vec<GlobalWordId>> = HashMap::new();
+        for (length, bucket) in self.words.iter().enumerate() {
+            if !self.should_index_dupes(length) {
+                continue;
+            }
+            for (word_id, word) in WordId::range(bucket.len()).zip(bucket) {
+                if word.hidden {
+                    continue;
+                }
+                let code = phonetic_encoding.encode(&word.normalized_string);
+                if !code.is_empty() {
+                    groups_by_code
+                        .entry(code)
+                        .or_default()
+                        .push((length, word_id));
+                }
+            }
+        }
+
+        groups_by_code
+            .into_values()
+            .filter(|group| group.len() > 1)
+            .collect()
+    }
+
+    /// Add all of our words to the given dupe index, respecting `dupe_index_lengths`, along with
+    /// the components of any compounds (see `compound_splitting`) and groups of words that sound
+    /// alike (see `phonetic_encoding`).
     pub fn populate_dupe_index(&self, index: &mut dyn AnyDupeIndex) {
+        if self.compound_splitting.is_some() {
+            index.set_compound_components(self.compound_components());
+        }
+        if self.phonetic_encoding.is_some() {
+            index.set_phonetic_groups(self.phonetic_groups());
+        }
         if index.window_size() == 0 {
             return;
         }
-        for bucket in &self.words {
-            for (word_id, word) in bucket.iter().enumerate() {
+        for (length, bucket) in self.words.iter().enumerate() {
+            if !self.should_index_dupes(length) {
+                continue;
+            }
+            for (word_id, word) in WordId::range(bucket.len()).zip(bucket) {
                 index.add_word(word_id, word);
             }
         }
     }
 
+    /// Build a copy of this list containing only the visible words with one of the given lengths
+    /// and a score of at least `min_score`, for filling a specific grid. Against a large list, this
+    /// shrinks each slot's domain (and `EliminationSet`) considerably. Glyph ids are preserved, but
+    /// word ids aren't, and the copy has no sources, so it can't be refreshed or edited. Any words
+    /// later passed to `generate_slot_options` as `allowed_word_ids` need to meet the same criteria,
+    /// since everything else is dropped entirely.
+    #[must_use]
+    pub fn restricted_to(&self, lengths: &HashSet<usize>, min_score: u16) -> WordList {
+        let mut words: Vec<Vec<Word>> = vec![vec![]; self.words.len()];
+        let mut word_id_by_string = HashMap::new();
+        let mut new_ids: HashMap<GlobalWordId, GlobalWordId> = HashMap::new();
+
+        for &length in lengths {
+            let Some(bucket) = self.words.get(length) else {
+                continue;
+            };
+            for (word_id, word) in WordId::range(bucket.len()).zip(bucket) {
+                if word.hidden || word.score < min_score {
+                    continue;
+                }
+                let new_word_id = WordId(words[length].len());
+                words[length].push(word.clone());
+                word_id_by_string.insert(word.normalized_string.clone(), new_word_id);
+                new_ids.insert((length, word_id), (length, new_word_id));
+            }
+        }
+
+        let tags_by_word_id = self
+            .tags_by_word_id
+            .iter()
+            .filter_map(|(id, tags)| Some((*new_ids.get(id)?, tags.clone())))
+            .collect();
+        let recency_by_word_id = self
+            .recency_by_word_id
+            .iter()
+            .filter_map(|(id, &recency)| Some((*new_ids.get(id)?, recency)))
+            .collect();
+
+        let mut restricted = WordList {
+            glyphs: self.glyphs.clone(),
+            glyph_id_by_char: self.glyph_id_by_char.clone(),
+            words,
+            word_id_by_string,
+            tags_by_word_id,
+            recency_by_word_id,
+            fillability_scores: vec![],
+            dupe_index: WordList::instantiate_dupe_index(self.max_shared_substring()),
+            dupe_index_lengths: Some(lengths.clone()),
+            compound_splitting: self.compound_splitting.clone(),
+            phonetic_encoding: self.phonetic_encoding,
+            max_length: lengths.iter().max().copied(),
+            on_update: None,
+            score_transform: None,
+            score_overrides: self.score_overrides.clone(),
+            rejection_counts: self.rejection_counts.clone(),
+            letter_points: self.letter_points.clone(),
+            source_configs: vec![],
+            personal_list_index: None,
+            source_states: HashMap::new(),
+            needs_sync: false,
+        };
+
+        let mut dupe_index = mem::replace(
+            &mut restricted.dupe_index,
+            WordList::instantiate_dupe_index(None),
+        );
+        restricted.populate_dupe_index(dupe_index.as_mut());
+        for (id, dupes) in self.dupe_index.extra_dupes() {
+            let Some(&new_id) = new_ids.get(id) else {
+                continue;
+            };
+            for dupe in dupes {
+                if let Some(&new_dupe) = new_ids.get(dupe) {
+                    dupe_index.add_dupe_pair(new_id, new_dupe);
+                }
+            }
+        }
+        restricted.dupe_index = dupe_index;
+        restricted.rebuild_fillability_scores();
+
+        restricted
+    }
+
     /// Update the word list state to be consistent with the given word being upserted into the
     /// given source. Return the source's previous entry for that normalized word, if
     /// applicable.
@@ -847,10 +2141,12 @@ impl WordList {
             .personal_list_index
             .map_or(false, |idx| idx == source_index);
 
+        // Every configured source is refreshed (and so has a state) whenever the list is replaced.
         let source_id = source_config.id();
-        let Some(source_state) = self.source_states.get_mut(&source_id) else {
-            panic!("optimistically_update_word: no source state found for id");
-        };
+        let source_state = self
+            .source_states
+            .get_mut(&source_id)
+            .expect("optimistically_update_word: no source state found for id");
 
         let previous_entry = source_state.get_entry(&normalized);
 
@@ -870,13 +2166,13 @@ impl WordList {
         if !source_config.enabled() {
             if is_personal_list {
                 let (length, word_id) = self.get_word_id_or_add_hidden(&normalized);
-                self.words[length][word_id].personal_word_score = Some(score);
+                self.words[length][word_id.index()].personal_word_score = Some(score);
             }
             return previous_entry;
         }
 
         let (length, word_id) = self.get_word_id_or_add_hidden(&normalized);
-        let word = &mut self.words[length][word_id];
+        let word = &mut self.words[length][word_id.index()];
 
         if is_personal_list {
             word.personal_word_score = Some(score);
@@ -891,7 +2187,7 @@ impl WordList {
         }
 
         word.canonical_string = canonical.into();
-        word.score = score;
+        word.score = self.score_overrides.apply(&normalized, score);
         word.hidden = false;
         word.source_index = Some(source_index);
         previous_entry
@@ -913,10 +2209,12 @@ impl WordList {
 
         // Regardless of whether this change is visible in `words`, we need to buffer it
         // to be persisted to the file.
+        // Every configured source is refreshed (and so has a state) whenever the list is replaced.
         let source_id = source_config.id();
-        let Some(source_state) = self.source_states.get_mut(&source_id) else {
-            panic!("optimistically_delete_word: no source state found for id");
-        };
+        let source_state = self
+            .source_states
+            .get_mut(&source_id)
+            .expect("optimistically_delete_word: no source state found for id");
 
         let previous_entry = source_state.get_entry(normalized);
 
@@ -930,21 +2228,21 @@ impl WordList {
         if !source_config.enabled() {
             if is_personal_list {
                 if let Some(word_id) = self.word_id_by_string.get(normalized) {
-                    self.words[normalized.chars().count()][*word_id].personal_word_score = None;
+                    self.words[normalized.chars().count()][word_id.index()].personal_word_score =
+                        None;
                 }
             }
             return previous_entry;
         }
 
         let length = normalized.chars().count();
+        // Callers can ask to delete a word that was never loaded, in which case there's nothing to
+        // hide.
         let Some(&word_id) = self.word_id_by_string.get(normalized) else {
-            #[cfg(feature = "check_invariants")]
-            panic!("optimistically_delete_word_impl: word doesn't exist");
-            #[cfg(not(feature = "check_invariants"))]
             return previous_entry;
         };
 
-        let word = &mut self.words[length][word_id];
+        let word = &mut self.words[length][word_id.index()];
 
         // Whatever else happens, we should clear the personal score iff we're deleting from the
         // personal list. Otherwise it should stay as is.
@@ -973,7 +2271,7 @@ impl WordList {
                 continue;
             };
             let entry = &other_source_state.entries[entry_index];
-            word.score = entry.score;
+            word.score = self.score_overrides.apply(&entry.normalized, entry.score);
             word.canonical_string.clone_from(&entry.canonical);
             word.hidden = false;
             word.source_index = Some(other_source_index as u16);
@@ -1006,6 +2304,7 @@ impl WordList {
     /// be written (probably due to something like permissions issues or a drive not being
     /// mounted), return error info, reset `sync_state` to `Synced`, and keep the pending updates
     /// in place.
+    #[allow(clippy::too_many_lines)]
     pub fn sync_updates_to_disk(&mut self) -> (bool, SyncErrors) {
         let mut should_refresh_overall = false;
         let mut sync_errors: SyncErrors = HashMap::new();
@@ -1014,7 +2313,10 @@ impl WordList {
 
         // For each source file, write any pending updates.
         for source_id in source_ids {
-            let source_state = self.source_states.get_mut(&source_id).unwrap();
+            let source_state = self
+                .source_states
+                .get_mut(&source_id)
+                .expect("sync_updates_to_disk: source id must come from source_states");
 
             if source_state.pending_updates.is_empty() {
                 continue;
@@ -1031,7 +2333,11 @@ impl WordList {
             }
 
             let WordListSourceConfig::File { path, .. } = &source_config else {
-                panic!("sync_updates_to_disk: non-file config had pending changes");
+                sync_errors.insert(
+                    source_id.clone(),
+                    io::Error::other(WordListError::ReadOnlySource(source_id)),
+                );
+                continue;
             };
             let path = path.clone();
             let mut pending_updates = source_state.pending_updates.clone();
@@ -1139,7 +2445,12 @@ impl WordList {
             // from disk.
             source_state.pending_updates = HashMap::new();
             let source_index = source_state.source_index;
-            refresh_source(source_config, source_index, &mut self.source_states);
+            refresh_source(
+                source_config,
+                source_index,
+                &mut self.source_states,
+                self.score_transform.as_ref(),
+            );
         }
 
         // Regardless of whether we actually succeeded in syncing everything, we should
@@ -1174,6 +2485,55 @@ impl WordList {
         source_errors
     }
 
+    /// Find every word that's defined with different scores by more than one enabled source, taking
+    /// pending updates into account, so that users can audit how their layered lists are being
+    /// combined. Entries are sorted by their normalized form.
+    #[must_use]
+    pub fn duplicate_entry_report(&self) -> Vec<DuplicateEntry> {
+        let mut definitions_by_word: HashMap<&str, Vec<DuplicateEntryDefinition>> = HashMap::new();
+
+        for source_config in self.source_configs.iter().filter(|source| source.enabled()) {
+            let Some(source_state) = self.source_states.get(&source_config.id()) else {
+                continue;
+            };
+
+            let normalized_words: HashSet<&str> = source_state
+                .index
+                .keys()
+                .chain(source_state.pending_updates.keys())
+                .map(String::as_str)
+                .collect();
+
+            for normalized in normalized_words {
+                if let Some((canonical, score)) = source_state.get_entry(normalized) {
+                    definitions_by_word.entry(normalized).or_default().push(
+                        DuplicateEntryDefinition {
+                            source_id: source_state.id.clone(),
+                            canonical,
+                            score,
+                        },
+                    );
+                }
+            }
+        }
+
+        let mut report: Vec<DuplicateEntry> = definitions_by_word
+            .into_iter()
+            .filter(|(_, definitions)| {
+                definitions
+                    .iter()
+                    .any(|definition| definition.score != definitions[0].score)
+            })
+            .map(|(normalized, definitions)| DuplicateEntry {
+                normalized: normalized.to_string(),
+                definitions,
+            })
+            .collect();
+        report.sort_by(|a, b| a.normalized.cmp(&b.normalized));
+
+        report
+    }
+
     /// If any word lists have been modified since the last time we refreshed, return their ids.
     #[must_use]
     pub fn identify_stale_sources(&self) -> Vec<String> {
@@ -1213,13 +2573,19 @@ impl Debug for WordList {
 #[allow(clippy::too_many_lines)]
 #[allow(clippy::similar_names)]
 pub mod tests {
+    use crate::cancel::{CancelReason, CancelToken};
     use crate::dupe_index::{AnyDupeIndex, DupeIndex};
     use crate::types::GlobalWordId;
-    use crate::word_list::{WordList, WordListSourceConfig};
-    use std::collections::HashSet;
+    use crate::word_list::{
+        BundledList, NormalizationPreset, ScoreTransform, SourceLineError, SourceLineErrorKind,
+        StreamingWordListParser, WordIterSource, WordList, WordListError, WordListOptions,
+        WordListSourceConfig, MAX_WORD_LIST_ENTRY_LENGTH,
+    };
+    use std::collections::{HashMap, HashSet};
     use std::fs;
     use std::path;
     use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
     use std::sync::{Arc, Mutex};
 
     #[must_use]
@@ -1241,6 +2607,27 @@ pub mod tests {
         }]
     }
 
+    /// Entries for a `Memory` source with the given words, each scoring 50.
+    #[must_use]
+    pub fn memory_words(words: &[&str]) -> Vec<(String, u16)> {
+        words.iter().map(|&word| (word.into(), 50)).collect()
+    }
+
+    /// A word list with just the given words, each scoring 50.
+    #[must_use]
+    pub fn memory_word_list(words: &[&str]) -> WordList {
+        WordList::new(
+            vec![WordListSourceConfig::Memory {
+                id: "0".into(),
+                enabled: true,
+                words: memory_words(words),
+            }],
+            None,
+            None,
+            None,
+        )
+    }
+
     #[test]
     fn test_loads_words_up_to_max_length() {
         let word_list = WordList::new(word_list_source_config(), None, Some(5), None);
@@ -1253,7 +2640,7 @@ pub mod tests {
             .get("skate")
             .expect("word list should include 'skate'");
 
-        let word = &word_list.words[5][word_id];
+        let word = &word_list.words[5][word_id.index()];
         assert_eq!(word.normalized_string, "skate");
         assert_eq!(word.canonical_string, "skate");
         assert_eq!(word.score, 50);
@@ -1274,7 +2661,7 @@ pub mod tests {
             .get("skates")
             .expect("word list should include 'skates'");
 
-        let word = &word_list.words[6][word_id];
+        let word = &word_list.words[6][word_id.index()];
         assert_eq!(word.hidden, false);
         assert_eq!(word.source_index, Some(0));
 
@@ -1283,7 +2670,7 @@ pub mod tests {
         assert_eq!(word_list.max_length, Some(5));
         assert_eq!(word_list.words.len(), 16);
 
-        let word = &word_list.words[6][word_id];
+        let word = &word_list.words[6][word_id.index()];
         assert_eq!(word.hidden, true);
         assert_eq!(word.source_index, None);
     }
@@ -1313,6 +2700,569 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_source_line_errors() {
+        let word_list = WordList::new(
+            vec![WordListSourceConfig::FileContents {
+                id: "0".into(),
+                enabled: true,
+                contents: format!(
+                    "wolves;51\nsteev;5x\n\nbad�word;50\n{};50\n",
+                    "a".repeat(65)
+                )
+                .into(),
+            }],
+            None,
+            None,
+            None,
+        );
+
+        let errors: Vec<SourceLineError> = word_list.get_source_errors()["0"]
+            .iter()
+            .map(|error| match error {
+                WordListError::InvalidLine(line_error) => line_error.clone(),
+                other => panic!("Unexpected error: {other}"),
+            })
+            .collect();
+
+        assert_eq!(
+            errors
+                .iter()
+                .map(|error| (error.line_number, error.kind))
+                .collect::<Vec<_>>(),
+            vec![
+                (2, SourceLineErrorKind::InvalidScore),
+                (4, SourceLineErrorKind::InvalidCharacter),
+                (5, SourceLineErrorKind::TooLong),
+            ]
+        );
+        assert_eq!(errors[0].text, "5x");
+        assert_eq!(
+            errors[0].to_string(),
+            "Word list contains invalid score on line 2: “5x”"
+        );
+        assert!(word_list.word_id_by_string.contains_key("wolves"));
+    }
+
+    #[test]
+    fn test_streaming_word_list_parser() {
+        let contents = fs::read(dictionary_path()).unwrap();
+        let file_word_list = WordList::new(
+            vec![WordListSourceConfig::File {
+                id: "0".into(),
+                enabled: true,
+                path: dictionary_path().into(),
+            }],
+            None,
+            None,
+            None,
+        );
+
+        // Use an odd chunk size so that chunks split lines and multibyte characters.
+        let mut parser = StreamingWordListParser::new();
+        for chunk in contents.chunks(4093) {
+            parser.push(chunk);
+        }
+        let (source, errors) = parser.finish("0");
+        assert!(errors.is_empty());
+        let streamed_word_list = WordList::new(vec![source], None, None, None);
+
+        assert_eq!(
+            streamed_word_list.word_id_by_string.len(),
+            file_word_list.word_id_by_string.len()
+        );
+        for (length, words) in file_word_list.words.iter().enumerate() {
+            for (word, streamed_word) in words.iter().zip(&streamed_word_list.words[length]) {
+                assert_eq!(word.canonical_string, streamed_word.canonical_string);
+                assert_eq!(word.score, streamed_word.score);
+            }
+        }
+
+        // Errors are reported with the same line numbers as for a file, even across chunks, and
+        // a missing final newline doesn't lose the last word.
+        let mut parser = StreamingWordListParser::new();
+        for chunk in "wolves;51\r\nsteev;5x\n\nbad\u{fffd}word;50\ncats;4"
+            .as_bytes()
+            .chunks(3)
+        {
+            parser.push(chunk);
+        }
+        let (source, errors) = parser.finish("0");
+        let WordListSourceConfig::Memory { words, .. } = source else {
+            panic!("Expected an in-memory source");
+        };
+        assert_eq!(words, vec![("wolves".into(), 51), ("cats".into(), 4)]);
+        assert_eq!(
+            errors
+                .iter()
+                .map(|error| match error {
+                    WordListError::InvalidLine(line_error) =>
+                        (line_error.line_number, line_error.kind),
+                    other => panic!("Unexpected error: {other}"),
+                })
+                .collect::<Vec<_>>(),
+            vec![
+                (2, SourceLineErrorKind::InvalidScore),
+                (4, SourceLineErrorKind::InvalidCharacter),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_programmatic_source_errors() {
+        let word_list = WordList::new(
+            vec![WordListSourceConfig::Iter {
+                id: "0".into(),
+                enabled: true,
+                words: WordIterSource::new(|| {
+                    vec![
+                        ("wolves".into(), 51, vec![]),
+                        ("bad�word".into(), 50, vec![]),
+                        ("a".repeat(MAX_WORD_LIST_ENTRY_LENGTH + 1), 50, vec![]),
+                    ]
+                    .into_iter()
+                }),
+            }],
+            None,
+            None,
+            None,
+        );
+
+        let errors: Vec<(usize, SourceLineErrorKind)> = word_list.get_source_errors()["0"]
+            .iter()
+            .map(|error| match error {
+                WordListError::InvalidLine(line_error) => (line_error.line_number, line_error.kind),
+                other => panic!("Unexpected error: {other}"),
+            })
+            .collect();
+
+        assert_eq!(
+            errors,
+            vec![
+                (2, SourceLineErrorKind::InvalidCharacter),
+                (3, SourceLineErrorKind::TooLong),
+            ]
+        );
+        assert!(word_list.word_id_by_string.contains_key("wolves"));
+        assert_eq!(word_list.word_id_by_string.len(), 1);
+    }
+
+    #[test]
+    #[cfg(not(feature = "http"))]
+    fn test_url_source_without_http() {
+        let word_list = WordList::new(
+            vec![WordListSourceConfig::Url {
+                id: "0".into(),
+                enabled: true,
+                url: "https://example.com/words.txt".into(),
+                cache_dir: None,
+            }],
+            None,
+            None,
+            None,
+        );
+
+        assert!(matches!(
+            &word_list.get_source_errors()["0"][..],
+            [WordListError::FetchFailed { .. }]
+        ));
+        assert!(word_list.word_id_by_string.is_empty());
+    }
+
+    #[test]
+    fn test_bundled_source() {
+        let word_list = WordList::new(
+            vec![WordListSourceConfig::Bundled {
+                id: "0".into(),
+                enabled: true,
+                list: BundledList::SpreadTheWordlist,
+            }],
+            None,
+            Some(5),
+            None,
+        );
+
+        if BundledList::SpreadTheWordlist.contents().is_some() {
+            assert!(word_list.get_source_errors()["0"].is_empty());
+            assert!(word_list.word_id_by_string.contains_key("aaaaa"));
+        } else {
+            assert!(matches!(
+                &word_list.get_source_errors()["0"][..],
+                [WordListError::BundledListUnavailable(name)] if name == "Spread the Wordlist"
+            ));
+            assert!(word_list.word_id_by_string.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_language_samples() {
+        assert_eq!(
+            NormalizationPreset::Spanish.normalize("Mañana él"),
+            "mañanael"
+        );
+        assert_eq!(
+            NormalizationPreset::French.normalize("Cœur brûlé"),
+            "coeurbrule"
+        );
+        assert_eq!(NormalizationPreset::German.normalize("Straße"), "strasse");
+        assert_eq!(NormalizationPreset::German.normalize("Brücke"), "bruecke");
+        assert_eq!(NormalizationPreset::Default.normalize("Café"), "café");
+
+        for (list, word) in [
+            (BundledList::SpanishSample, "corazon"),
+            (BundledList::FrenchSample, "soeur"),
+            (BundledList::GermanSample, "strasse"),
+        ] {
+            let word_list = WordList::new(
+                vec![WordListSourceConfig::Bundled {
+                    id: "0".into(),
+                    enabled: true,
+                    list,
+                }],
+                None,
+                Some(10),
+                None,
+            );
+
+            if list.contents().is_some() {
+                assert!(word_list.get_source_errors()["0"].is_empty());
+                assert!(word_list.word_id_by_string.contains_key(word));
+            } else {
+                assert!(word_list.word_id_by_string.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_iter_source() {
+        let load_count = Arc::new(AtomicUsize::new(0));
+        let source_config = WordListSourceConfig::Iter {
+            id: "0".into(),
+            enabled: true,
+            words: WordIterSource::new({
+                let load_count = load_count.clone();
+                move || {
+                    load_count.fetch_add(1, Ordering::Relaxed);
+                    vec![
+                        ("Wolves".into(), 60, vec!["theme".into()]),
+                        ("steev".into(), 40, vec![]),
+                    ]
+                    .into_iter()
+                }
+            }),
+        };
+
+        let mut word_list = WordList::new(vec![source_config.clone()], None, None, None);
+        let wolves_id = word_list.get_word_id_or_add_hidden("wolves");
+        let steev_id = word_list.get_word_id_or_add_hidden("steev");
+
+        let wolves = word_list.get_word(wolves_id);
+        assert_eq!(wolves.canonical_string, "Wolves");
+        assert_eq!(wolves.score, 60);
+        assert_eq!(word_list.get_word_tags(wolves_id), ["theme".to_string()]);
+        assert!(word_list.get_word_tags(steev_id).is_empty());
+        assert_eq!(word_list.tags_by_word_id.len(), 1);
+
+        // Each refresh asks the source for a fresh iterator.
+        word_list.replace_list(vec![source_config], None, None, false);
+        assert_eq!(load_count.load(Ordering::Relaxed), 2);
+        assert!(!word_list.get_word(wolves_id).hidden);
+    }
+
+    #[test]
+    fn test_set_source_enabled() {
+        let load_count = Arc::new(AtomicUsize::new(0));
+        let mut word_list = WordList::new(
+            vec![
+                WordListSourceConfig::Iter {
+                    id: "0".into(),
+                    enabled: true,
+                    words: WordIterSource::new({
+                        let load_count = load_count.clone();
+                        move || {
+                            load_count.fetch_add(1, Ordering::Relaxed);
+                            vec![("wolves".into(), 60, vec![]), ("steev".into(), 40, vec![])]
+                                .into_iter()
+                        }
+                    }),
+                },
+                WordListSourceConfig::Memory {
+                    id: "1".into(),
+                    enabled: true,
+                    words: vec![("steev".into(), 50), ("golf".into(), 50)],
+                },
+            ],
+            None,
+            None,
+            None,
+        );
+        let wolves_id = word_list.get_word_id_or_add_hidden("wolves");
+        let steev_id = word_list.get_word_id_or_add_hidden("steev");
+
+        // Disabling the first source hides WOLVES and lets the second source's STEEV show through,
+        // without asking the first source for its words again.
+        let (any_more_visible, less_visible_words) =
+            word_list.set_source_enabled("0", false).unwrap();
+        assert!(any_more_visible);
+        assert!(less_visible_words.contains(&wolves_id));
+        assert!(word_list.get_word(wolves_id).hidden);
+        assert_eq!(word_list.get_word(steev_id).score, 50);
+        assert_eq!(word_list.get_word(steev_id).source_index, Some(1));
+        assert!(!word_list.source_configs[0].enabled());
+
+        // Re-enabling it restores the same word ids.
+        word_list.set_source_enabled("0", true).unwrap();
+        assert!(!word_list.get_word(wolves_id).hidden);
+        assert_eq!(word_list.get_word_id_or_add_hidden("wolves"), wolves_id);
+        assert_eq!(word_list.get_word(steev_id).score, 40);
+        assert_eq!(load_count.load(Ordering::Relaxed), 1);
+
+        assert!(word_list.set_source_enabled("2", false).is_none());
+    }
+
+    #[test]
+    fn test_duplicate_entry_report() {
+        fn source(id: &str, enabled: bool, words: &[(&str, u16)]) -> WordListSourceConfig {
+            WordListSourceConfig::Memory {
+                id: id.into(),
+                enabled,
+                words: words
+                    .iter()
+                    .map(|&(word, score)| (word.into(), score))
+                    .collect(),
+            }
+        }
+
+        let mut word_list = WordList::new(
+            vec![
+                source("0", true, &[("wolves", 60), ("steev", 50), ("Golf", 40)]),
+                source("1", false, &[("wolves", 10)]),
+                source("2", true, &[("wolves", 30), ("steev", 50), ("golf", 45)]),
+            ],
+            None,
+            None,
+            None,
+        );
+
+        let report = word_list.duplicate_entry_report();
+        assert_eq!(
+            report
+                .iter()
+                .map(|entry| entry.normalized.as_str())
+                .collect::<Vec<_>>(),
+            vec!["golf", "wolves"]
+        );
+        assert_eq!(report[0].used_definition().source_id, "0");
+        assert_eq!(report[0].used_definition().canonical, "Golf");
+        assert_eq!(report[1].definitions.len(), 2);
+        assert_eq!(report[1].shadowed_definitions()[0].score, 30);
+
+        // Pending updates count as the source's current definition.
+        word_list.optimistically_update_word("golf", 40, "2");
+        assert_eq!(
+            word_list
+                .duplicate_entry_report()
+                .iter()
+                .map(|entry| entry.normalized.as_str())
+                .collect::<Vec<_>>(),
+            vec!["wolves"]
+        );
+    }
+
+    #[test]
+    fn test_dupe_index_lengths() {
+        let mut word_list = WordList::new_with_dupe_index_lengths(
+            vec![WordListSourceConfig::Memory {
+                id: "0".into(),
+                enabled: true,
+                words: vec![("golf".into(), 50), ("golfy".into(), 50)],
+            }],
+            None,
+            None,
+            Some(3),
+            Some(HashSet::from([4])),
+        );
+        let golf_id = word_list.get_word_id_or_add_hidden("golf");
+
+        let dupes = word_list.dupe_index.get_dupes_by_length(golf_id);
+        assert!(!dupes.contains_key(&5));
+
+        word_list.set_dupe_index_lengths(None);
+        let dupes = word_list.dupe_index.get_dupes_by_length(golf_id);
+        assert_eq!(dupes[&5].len(), 1);
+    }
+
+    #[test]
+    fn test_score_transform() {
+        fn source(id: &str, words: &[(&str, u16)]) -> WordListSourceConfig {
+            WordListSourceConfig::Memory {
+                id: id.into(),
+                enabled: true,
+                words: words
+                    .iter()
+                    .map(|&(word, score)| (word.into(), score))
+                    .collect(),
+            }
+        }
+
+        // Rescale the second source from a 0-10 scale and clamp everything to 60.
+        let score_transform = ScoreTransform::new(|source_id, scores| {
+            for score in scores {
+                if source_id == "1" {
+                    *score *= 10;
+                }
+                *score = (*score).min(60);
+            }
+        });
+
+        let mut word_list = WordList::new_with_options(
+            vec![
+                source("0", &[("wolves", 80), ("steev", 40)]),
+                source("1", &[("golf", 5)]),
+            ],
+            None,
+            WordListOptions {
+                score_transform: Some(score_transform),
+                ..WordListOptions::default()
+            },
+        );
+        let score = |word_list: &mut WordList, word: &str| {
+            let word_id = word_list.get_word_id_or_add_hidden(word);
+            word_list.get_word(word_id).score
+        };
+        assert_eq!(score(&mut word_list, "wolves"), 60);
+        assert_eq!(score(&mut word_list, "steev"), 40);
+        assert_eq!(score(&mut word_list, "golf"), 50);
+
+        word_list.set_score_transform(None);
+        assert_eq!(score(&mut word_list, "wolves"), 80);
+        assert_eq!(score(&mut word_list, "golf"), 5);
+    }
+
+    #[test]
+    fn test_letter_points() {
+        let sources = vec![WordListSourceConfig::Memory {
+            id: "0".into(),
+            enabled: true,
+            words: vec![("jazz".into(), 50), ("ease".into(), 50)],
+        }];
+        let letter_score = |word_list: &WordList, word: &str| {
+            word_list.words[4][word_list.word_id_by_string[word].index()].letter_score
+        };
+
+        let mut word_list = WordList::new(sources.clone(), None, None, None);
+        assert_eq!(letter_score(&word_list, "jazz"), 29);
+        assert_eq!(letter_score(&word_list, "ease"), 4);
+
+        // Characters missing from the custom map keep their default values.
+        word_list.set_letter_points(Some(HashMap::from([('e', 0), ('s', 0), ('j', 20)])));
+        assert_eq!(letter_score(&word_list, "jazz"), 41);
+        assert_eq!(letter_score(&word_list, "ease"), 1);
+
+        let word_list = WordList::new_with_options(
+            sources,
+            None,
+            WordListOptions {
+                letter_points: Some(HashMap::from([('z', 1)])),
+                ..WordListOptions::default()
+            },
+        );
+        assert_eq!(letter_score(&word_list, "jazz"), 11);
+    }
+
+    #[test]
+    fn test_restricted_to() {
+        let mut word_list = WordList::new(
+            vec![WordListSourceConfig::Iter {
+                id: "0".into(),
+                enabled: true,
+                words: WordIterSource::new(|| {
+                    vec![
+                        ("golf".into(), 50, vec!["sport".into()]),
+                        ("golfs".into(), 60, vec![]),
+                        ("wolf".into(), 30, vec![]),
+                        ("golfer".into(), 70, vec![]),
+                        ("golfing".into(), 70, vec![]),
+                    ]
+                    .into_iter()
+                }),
+            }],
+            None,
+            None,
+            Some(3),
+        );
+        let golf_id = word_list.get_word_id_or_add_hidden("golf");
+        let golfer_id = word_list.get_word_id_or_add_hidden("golfer");
+        word_list.dupe_index.add_dupe_pair(golf_id, golfer_id);
+        word_list.get_word_id_or_add_hidden("gulf");
+
+        let restricted = word_list.restricted_to(&HashSet::from([4, 5, 6]), 40);
+        assert_eq!(
+            restricted.words.iter().map(Vec::len).collect::<Vec<_>>(),
+            vec![0, 0, 0, 0, 1, 1, 1, 0],
+            "drops low-scoring, hidden, and wrong-length words"
+        );
+        assert_eq!(restricted.glyphs, word_list.glyphs);
+        assert!(restricted.source_configs.is_empty());
+
+        let golf_id = (4, restricted.word_id_by_string["golf"]);
+        let golfer_id = (6, restricted.word_id_by_string["golfer"]);
+        assert_eq!(restricted.get_word_tags(golf_id), ["sport".to_string()]);
+
+        let dupes = restricted.dupe_index.get_dupes_by_length(golf_id);
+        assert!(dupes[&5].contains(&restricted.word_id_by_string["golfs"]));
+        assert!(
+            dupes[&6].contains(&golfer_id.1),
+            "extra dupes are carried over"
+        );
+    }
+
+    #[test]
+    fn test_update_max_shared_substring() {
+        let mut word_list = WordList::new(
+            vec![WordListSourceConfig::Memory {
+                id: "0".into(),
+                enabled: true,
+                words: vec![
+                    ("golf".into(), 50),
+                    ("golfy".into(), 50),
+                    ("wolf".into(), 50),
+                ],
+            }],
+            None,
+            None,
+            Some(3),
+        );
+        let golf_id = word_list.get_word_id_or_add_hidden("golf");
+        let wolf_id = word_list.get_word_id_or_add_hidden("wolf");
+        let golfy_id = word_list.get_word_id_or_add_hidden("golfy");
+        word_list.dupe_index.add_dupe_pair(golf_id, wolf_id);
+        let glyph_count = word_list.glyphs.len();
+        assert_eq!(word_list.max_shared_substring(), Some(3));
+        assert!(word_list.dupe_index.get_dupes_by_length(golf_id)[&5].contains(&golfy_id.1));
+
+        // "golf" and "golfy" only share four letters, but the extra pair is carried over.
+        word_list.update_max_shared_substring(Some(4));
+        assert_eq!(word_list.max_shared_substring(), Some(4));
+        let dupes = word_list.dupe_index.get_dupes_by_length(golf_id);
+        assert!(!dupes.contains_key(&5));
+        assert!(dupes[&4].contains(&wolf_id.1));
+
+        word_list.update_max_shared_substring(Some(2));
+        assert_eq!(
+            word_list.max_shared_substring(),
+            None,
+            "2 isn't a supported setting"
+        );
+
+        word_list.update_max_shared_substring(Some(3));
+        assert!(word_list.dupe_index.get_dupes_by_length(golf_id)[&5].contains(&golfy_id.1));
+
+        // Nothing but the dupe index is rebuilt.
+        assert_eq!(word_list.glyphs.len(), glyph_count);
+        assert_eq!(word_list.get_word_id_or_add_hidden("golfy"), golfy_id);
+    }
+
     #[test]
     fn test_soft_dupe_index() {
         let mut word_list = WordList::new(vec![], None, Some(6), Some(5));
@@ -1330,7 +3280,8 @@ pub mod tests {
                 let mut soft_dupe_index = soft_dupe_index.lock().unwrap();
 
                 for &(word_length, word_id) in added_word_ids {
-                    soft_dupe_index.add_word(word_id, &word_list.words[word_length][word_id]);
+                    soft_dupe_index
+                        .add_word(word_id, &word_list.words[word_length][word_id.index()]);
                 }
             }))
         };
@@ -2088,4 +4039,46 @@ pub mod tests {
 
         assert_eq!(fs::read_to_string(tmpfile.path()).unwrap(), "sT eev;51\n");
     }
+
+    #[test]
+    fn test_new_interruptible() {
+        let sources = vec![
+            WordListSourceConfig::Memory {
+                id: "0".into(),
+                enabled: true,
+                words: (0..25_000).map(|i| (format!("w{i:05}"), 50)).collect(),
+            },
+            WordListSourceConfig::Memory {
+                id: "1".into(),
+                enabled: true,
+                words: vec![("extra".into(), 50)],
+            },
+        ];
+
+        let mut reports = vec![];
+        let word_list = WordList::new_interruptible(
+            sources.clone(),
+            None,
+            WordListOptions::default(),
+            &CancelToken::new(),
+            |progress| reports.push((progress.source_index, progress.source_entries_loaded)),
+        )
+        .unwrap();
+        assert_eq!(word_list.word_id_by_string.len(), 25_001);
+        assert_eq!(reports, vec![(0, 10_000), (0, 20_000), (0, 25_000), (1, 1)]);
+
+        // Cancelling from the progress callback stops the load at the next check.
+        let cancel = CancelToken::new();
+        let mut report_count = 0;
+        let result =
+            WordList::new_interruptible(sources, None, WordListOptions::default(), &cancel, |_| {
+                report_count += 1;
+                cancel.cancel("switched lists");
+            });
+        assert_eq!(
+            result.err(),
+            Some(CancelReason::Cancelled("switched lists".into()))
+        );
+        assert_eq!(report_count, 1);
+    }
 }